@@ -1,5 +1,6 @@
 use regex::Regex;
 use std::str;
+use std::sync::LazyLock;
 
 #[derive(Debug, PartialEq)]
 pub enum AnsiEvent {
@@ -48,3 +49,51 @@ impl AnsiParser {
         events
     }
 }
+
+// OSC sequences end with BEL or ST (ESC \); CSI sequences end with a final byte in @..~
+static ESCAPE_SEQUENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b\[[0-?]*[ -/]*[@-~]").unwrap()
+});
+
+/// Remove CSI/SGR and OSC escape sequences, leaving only the printable text
+pub fn strip_ansi(input: &str) -> String {
+    ESCAPE_SEQUENCE.replace_all(input, "").into_owned()
+}
+
+/// Number of visible characters in `input`, ignoring escape sequences
+pub fn visible_width(input: &str) -> usize {
+    strip_ansi(input).chars().count()
+}
+
+/// Enable ANSI escape handling on the Windows console; no-op elsewhere
+#[cfg(windows)]
+pub fn enable_vt_mode() -> bool {
+    use winapi::um::consoleapi::{GetConsoleMode, SetConsoleMode};
+    use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+    use winapi::um::processenv::GetStdHandle;
+    use winapi::um::winbase::STD_OUTPUT_HANDLE;
+    use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return false;
+        }
+
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return false;
+        }
+        if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+            return true;
+        }
+
+        SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
+
+/// Enable ANSI escape handling on the Windows console; no-op elsewhere
+#[cfg(not(windows))]
+pub fn enable_vt_mode() -> bool {
+    true
+}
@@ -1,33 +1,475 @@
 use bytes::Bytes;
 use futures::stream::{self, Stream, StreamExt};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 use tokio::fs::File as TokioFile;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 
 // === Sync implementation ===
+
+/// Bytes read from each input at a time
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Open a file for reading, or stdin for `-`
+fn open_input(path: &Path) -> io::Result<Box<dyn Read>> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdin()))
+    } else {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
+/// Copy `reader` to `out` in `CHUNK_SIZE` pieces, turning `\r\n` into `\n` and
+/// terminating an unterminated last line. Returns the number of bytes written.
+pub fn copy_stream<R: Read, W: Write>(mut reader: R, out: &mut W) -> io::Result<u64> {
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut written = 0u64;
+    // A `\r` ending the previous chunk, held back until we see what follows it
+    let mut pending_cr = false;
+    let mut last = None;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let chunk = &buf[..n];
+
+        if pending_cr && chunk[0] != b'\n' {
+            out.write_all(b"\r")?;
+            written += 1;
+        }
+        pending_cr = false;
+
+        let mut start = 0;
+        for (i, _) in chunk.iter().enumerate().filter(|(_, b)| **b == b'\r') {
+            if i + 1 == n {
+                pending_cr = true;
+            } else if chunk[i + 1] != b'\n' {
+                continue;
+            }
+            out.write_all(&chunk[start..i])?;
+            written += (i - start) as u64;
+            start = i + 1;
+        }
+        out.write_all(&chunk[start..])?;
+        written += (n - start) as u64;
+        last = Some(chunk[n - 1]);
+    }
+
+    // A trailing lone `\r` is dropped like the `\r` of a CRLF ending
+    if last.is_some_and(|b| b != b'\n') {
+        out.write_all(b"\n")?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Lines of `reader` without their `\n` or `\r\n` endings, read one at a time
+pub fn byte_lines<R: Read>(reader: R) -> impl Iterator<Item = io::Result<Vec<u8>>> {
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, reader);
+    std::iter::from_fn(move || {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                Some(Ok(line))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    })
+}
+
+/// Count the lines `cat` will print for a regular file, or `None` for stdin,
+/// pipes and other inputs that can only be read once
+fn count_lines(path: &Path) -> io::Result<Option<usize>> {
+    if path == Path::new("-") || !path.metadata()?.is_file() {
+        return Ok(None);
+    }
+
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut count = 0;
+    let mut last = None;
+    loop {
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        count += buf[..n].iter().filter(|&&b| b == b'\n').count();
+        last = Some(buf[n - 1]);
+    }
+    if last.is_some_and(|b| b != b'\n') {
+        count += 1;
+    }
+    Ok(Some(count))
+}
+
 #[allow(dead_code)]
 pub fn cat<S: AsRef<Path>>(files: Vec<S>) -> io::Result<String> {
-    let mut result = String::new();
+    cat_with_options(files, &CatOptions::default())
+}
 
-    for file_path in files {
-        let file = File::open(&file_path)?;
-        let reader = BufReader::new(file);
+/// Output formatting applied by `cat_with_options`
+#[derive(Debug, Clone)]
+pub struct CatOptions {
+    /// Prefix each line with its line number
+    pub number: bool,
+    /// Minimum width of the line-number field; grows to fit the last line number
+    pub number_width: usize,
+    /// Expand tabs to the next multiple of this many columns
+    pub tabs: Option<usize>,
+    /// `-v`: render control and high bytes with `^` and `M-` notation
+    pub show_nonprinting: bool,
+    /// `-E`: end each line with `$`
+    pub show_ends: bool,
+    /// `-T`: show tabs as `^I`
+    pub show_tabs: bool,
+}
+
+impl Default for CatOptions {
+    fn default() -> Self {
+        CatOptions {
+            number: false,
+            number_width: 4,
+            tabs: None,
+            show_nonprinting: false,
+            show_ends: false,
+            show_tabs: false,
+        }
+    }
+}
+
+impl CatOptions {
+    /// Whether output has to be built line by line rather than copied through
+    fn transforms_lines(&self) -> bool {
+        self.number
+            || self.tabs.is_some()
+            || self.show_nonprinting
+            || self.show_ends
+            || self.show_tabs
+    }
+}
 
-        for line in reader.lines() {
-            let mut line = line?;
-            if line.ends_with('\r') {
-                line.pop();
+/// Concatenate files like `cat`, then apply numbering and tab expansion
+pub fn cat_with_options<S: AsRef<Path>>(files: Vec<S>, options: &CatOptions) -> io::Result<String> {
+    let mut out = Vec::new();
+    cat_to_writer(&files, options, &mut out)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Stream files to `out` like `cat`, never holding more than a chunk or a line
+pub fn cat_to_writer<S: AsRef<Path>, W: Write>(
+    files: &[S],
+    options: &CatOptions,
+    out: &mut W,
+) -> io::Result<()> {
+    if !options.transforms_lines() {
+        for file in files {
+            copy_stream(open_input(file.as_ref())?, out)?;
+        }
+        return Ok(());
+    }
+
+    // The number column fits the last line number when the total is known up front
+    let mut width = options.number_width;
+    if options.number {
+        let mut total = Some(0);
+        for file in files {
+            total = match (total, count_lines(file.as_ref())?) {
+                (Some(total), Some(count)) => Some(total + count),
+                _ => None,
+            };
+        }
+        if let Some(total) = total {
+            width = width.max(total.to_string().len());
+        }
+    }
+
+    let mut number = 0;
+    for file in files {
+        for line in byte_lines(open_input(file.as_ref())?) {
+            let line = line?;
+            number += 1;
+            if options.number {
+                write!(out, "{:>width$}\t", number, width = width)?;
             }
-            result.push_str(&line);
-            result.push('\n');
+            out.write_all(&render_line(&line, options))?;
+            out.write_all(b"\n")?;
         }
     }
+    Ok(())
+}
+
+/// Apply `-v`, `-T`/`--tabs` and `-E` to one line, without its ending
+fn render_line(line: &[u8], options: &CatOptions) -> Vec<u8> {
+    let mut rendered = if options.show_nonprinting {
+        show_nonprinting(line).into_bytes()
+    } else {
+        line.to_vec()
+    };
+    if options.show_tabs {
+        let mut shown = Vec::with_capacity(rendered.len());
+        for byte in rendered {
+            match byte {
+                b'\t' => shown.extend_from_slice(b"^I"),
+                _ => shown.push(byte),
+            }
+        }
+        rendered = shown;
+    } else if let Some(tab_width) = options.tabs {
+        rendered = expand_tabs(&String::from_utf8_lossy(&rendered), tab_width).into_bytes();
+    }
+    if options.show_ends {
+        rendered.push(b'$');
+    }
+    rendered
+}
+
+/// Replace each tab with spaces up to the next multiple of `tab_width` columns
+pub fn expand_tabs(line: &str, tab_width: usize) -> String {
+    if tab_width == 0 {
+        return line.replace('\t', "");
+    }
 
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+/// Render bytes like `cat -v`: control characters as `^X`, DEL as `^?`, and
+/// bytes above 127 with an `M-` prefix. Newlines and tabs are kept.
+pub fn show_nonprinting(bytes: &[u8]) -> String {
+    let mut rendered = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        let mut b = byte;
+        if b >= 128 {
+            rendered.push_str("M-");
+            b -= 128;
+        }
+        match b {
+            b'\n' | b'\t' if byte < 128 => rendered.push(b as char),
+            0..=31 => {
+                rendered.push('^');
+                rendered.push((b + 64) as char);
+            }
+            127 => rendered.push_str("^?"),
+            _ => rendered.push(b as char),
+        }
+    }
+    rendered
+}
+
+/// Set the options for one short flag; `-A`, `-e` and `-t` stand for `-vET`, `-vE` and `-vT`
+fn apply_short_flag(options: &mut CatOptions, flag: char) -> Result<(), String> {
+    let shortcut = match flag {
+        'A' => "vET",
+        'e' => "vE",
+        't' => "vT",
+        _ => "",
+    };
+    if !shortcut.is_empty() {
+        return shortcut
+            .chars()
+            .try_for_each(|flag| apply_short_flag(options, flag));
+    }
+    match flag {
+        'n' => options.number = true,
+        'v' => options.show_nonprinting = true,
+        'E' => options.show_ends = true,
+        'T' => options.show_tabs = true,
+        _ => return Err(format!("invalid option -- '{}'", flag)),
+    }
+    Ok(())
+}
+
+/// Parse `cat` flags, returning the options and the files to read.
+/// Short flags may be combined, as in `-nA`.
+pub fn parse_args(args: &[String]) -> Result<(CatOptions, Vec<String>), String> {
+    let mut options = CatOptions::default();
+    let mut files = Vec::new();
+
+    for arg in args {
+        let short = match arg.as_str() {
+            "--number" => "n",
+            "--show-all" => "A",
+            "--show-nonprinting" => "v",
+            "--show-ends" => "E",
+            "--show-tabs" => "T",
+            _ if arg.len() > 1 && arg.starts_with('-') && !arg.starts_with("--") => &arg[1..],
+            _ => "",
+        };
+        if !short.is_empty() {
+            for flag in short.chars() {
+                apply_short_flag(&mut options, flag)?;
+            }
+        } else if let Some(value) = arg.strip_prefix("--tabs=") {
+            // A width of 0 would swallow every tab
+            let tabs = value
+                .parse()
+                .ok()
+                .filter(|tabs| *tabs > 0)
+                .ok_or_else(|| format!("invalid tab size: '{}'", value))?;
+            options.tabs = Some(tabs);
+        } else if let Some(value) = arg.strip_prefix("--number-width=") {
+            options.number_width = value
+                .parse()
+                .map_err(|_| format!("invalid number width: '{}'", value))?;
+            options.number = true;
+        } else if arg.starts_with("--") {
+            return Err(format!("unrecognized option '{}'", arg));
+        } else {
+            files.push(arg.clone());
+        }
+    }
+
+    Ok((options, files))
+}
+
+/// Entry point for the `cat` shell command
+pub fn run(args: &[String]) {
+    let (options, files) = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("cat: {}", e);
+            return;
+        }
+    };
+
+    if files.is_empty() {
+        eprintln!("Usage: cat [-nvETAet] [--number-width=N] [--tabs=N] <file|->...");
+        return;
+    }
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if let Err(e) = cat_to_writer(&files, &options, &mut out).and_then(|_| out.flush()) {
+        eprintln!("cat: {}", e);
+    }
+}
+
+/// Separator handling for `tac`
+#[derive(Debug, Clone)]
+pub struct TacOptions {
+    /// Record separator, a newline unless `-s` is given
+    pub separator: String,
+    /// `-b`: the separator starts each record instead of ending it
+    pub before: bool,
+}
+
+impl Default for TacOptions {
+    fn default() -> Self {
+        TacOptions {
+            separator: "\n".to_string(),
+            before: false,
+        }
+    }
+}
+
+/// Reverse the records of `content`, keeping each separator attached to its record
+pub fn tac_str(content: &str, options: &TacOptions) -> String {
+    let sep = options.separator.as_str();
+    let mut records = Vec::new();
+    let mut start = 0;
+
+    for (index, _) in content.match_indices(sep) {
+        let end = if options.before {
+            index
+        } else {
+            index + sep.len()
+        };
+        if end > start {
+            records.push(&content[start..end]);
+            start = end;
+        }
+    }
+    if start < content.len() {
+        records.push(&content[start..]);
+    }
+
+    records.into_iter().rev().collect()
+}
+
+/// Print each file with its records in reverse order, like GNU `tac`
+pub fn tac<S: AsRef<Path>>(files: Vec<S>, options: &TacOptions) -> io::Result<String> {
+    let mut result = String::new();
+    for file_path in files {
+        let bytes = std::fs::read(file_path)?;
+        result.push_str(&tac_str(&String::from_utf8_lossy(&bytes), options));
+    }
     Ok(result)
 }
 
+/// Parse `tac [-b] [-s SEP]` flags, returning the options and the files to read
+pub fn parse_tac_args(args: &[String]) -> Result<(TacOptions, Vec<String>), String> {
+    let mut options = TacOptions::default();
+    let mut files = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "-b" || arg == "--before" {
+            options.before = true;
+        } else if arg == "-s" || arg == "--separator" {
+            options.separator = iter
+                .next()
+                .ok_or_else(|| format!("option requires an argument -- '{}'", arg))?
+                .clone();
+        } else if let Some(value) = arg.strip_prefix("--separator=") {
+            options.separator = value.to_string();
+        } else if arg.starts_with('-') && arg.len() > 1 {
+            return Err(format!("unrecognized option '{}'", arg));
+        } else {
+            files.push(arg.clone());
+        }
+    }
+
+    if options.separator.is_empty() {
+        return Err("separator cannot be empty".to_string());
+    }
+    Ok((options, files))
+}
+
+/// Entry point for the `tac` shell command
+pub fn run_tac(args: &[String]) {
+    let (options, files) = match parse_tac_args(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("tac: {}", e);
+            return;
+        }
+    };
+
+    if files.is_empty() {
+        eprintln!("Usage: tac [-b] [-s SEP] <file>...");
+        return;
+    }
+
+    match tac(files, &options) {
+        Ok(output) => print!("{}", output),
+        Err(e) => eprintln!("tac: {}", e),
+    }
+}
+
 #[allow(dead_code)]
 // === Async stream version ===
 pub async fn cat_async<S: AsRef<Path> + Send + 'static>(
@@ -1,10 +1,21 @@
 use colored::Colorize;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(windows)]
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
 
 #[cfg(windows)]
 use windows_acl::acl::ACL;
 #[cfg(windows)]
 use windows_acl::helper::string_to_sid;
 
+#[cfg(windows)]
+use winapi::shared::winerror::ERROR_SUCCESS;
+#[cfg(windows)]
+use winapi::um::accctrl::SE_FILE_OBJECT;
+#[cfg(windows)]
+use winapi::um::aclapi::{GetNamedSecurityInfoW, SetNamedSecurityInfoW};
 #[cfg(windows)]
 use winapi::um::handleapi::CloseHandle;
 #[cfg(windows)]
@@ -12,17 +23,87 @@ use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
 #[cfg(windows)]
 use winapi::um::securitybaseapi::GetTokenInformation;
 #[cfg(windows)]
+use winapi::um::winbase::LocalFree;
+#[cfg(windows)]
 use winapi::um::winnt::{
-    FILE_GENERIC_EXECUTE, FILE_GENERIC_READ, FILE_GENERIC_WRITE, PSID, TOKEN_USER,
+    DACL_SECURITY_INFORMATION, FILE_GENERIC_EXECUTE, FILE_GENERIC_READ, FILE_GENERIC_WRITE, PACL,
+    PSECURITY_DESCRIPTOR, PSID, TOKEN_USER,
 };
 
+/// Flags accepted alongside the mode operand
+#[derive(Debug, Default, Clone, Copy)]
+struct ChmodOptions {
+    recursive: bool,
+    verbose: bool,
+    changes: bool,
+}
+
+/// What the files are changed to: a mode operand or another file's permissions
+#[derive(Debug, Clone, Copy)]
+enum ModeSpec<'a> {
+    Mode(&'a str),
+    Reference(&'a str),
+}
+
 pub fn execute(args: &[&str]) {
-    if args.len() < 2 {
+    let mut options = ChmodOptions::default();
+    let mut reference = None;
+    let mut operands = Vec::new();
+
+    for arg in args {
+        match *arg {
+            "--recursive" => options.recursive = true,
+            "--verbose" => options.verbose = true,
+            "--changes" => options.changes = true,
+            _ if arg.starts_with("--reference=") => {
+                reference = Some(&arg["--reference=".len()..]);
+            }
+            // `-w` and friends are modes, so only clusters of known flags count as options
+            flag if flag.len() > 1
+                && flag.starts_with('-')
+                && flag[1..].chars().all(|c| "Rvc".contains(c)) =>
+            {
+                for c in flag[1..].chars() {
+                    match c {
+                        'R' => options.recursive = true,
+                        'v' => options.verbose = true,
+                        _ => options.changes = true,
+                    }
+                }
+            }
+            _ => operands.push(*arg),
+        }
+    }
+
+    let required = if reference.is_some() { 1 } else { 2 };
+    if operands.len() < required {
         println!(
             "{}",
             "Usage: chmod [OPTION]... MODE[,MODE]... FILE...".red()
         );
         println!("{}", "   or: chmod [OPTION]... OCTAL-MODE FILE...".red());
+        println!(
+            "{}",
+            "   or: chmod [OPTION]... --reference=RFILE FILE...".red()
+        );
+        println!();
+        println!("{}", "Options:".yellow());
+        println!(
+            "  {}",
+            "-R, --recursive  change files and directories recursively".dimmed()
+        );
+        println!(
+            "  {}",
+            "-v, --verbose    report every file processed".dimmed()
+        );
+        println!(
+            "  {}",
+            "-c, --changes    report only when a change is made".dimmed()
+        );
+        println!(
+            "  {}",
+            "--reference=RFILE  use RFILE's mode instead of a MODE value".dimmed()
+        );
         println!();
         println!("{}", "Examples:".yellow());
         println!("  {}", "chmod 755 myfile.txt".dimmed());
@@ -30,14 +111,29 @@ pub fn execute(args: &[&str]) {
         println!("  {}", "chmod g-w,o-w file.txt".dimmed());
         println!("  {}", "chmod a=r file.txt".dimmed());
         println!("  {}", "chmod u=rwx,g=rx,o=r file.txt".dimmed());
+        println!("  {}", "chmod -R -v a-w folder".dimmed());
+        println!("  {}", "chmod --reference=good.txt *.txt".dimmed());
         return;
     }
 
-    let mode = args[0];
-    let files = &args[1..];
+    let (spec, files) = match reference {
+        Some(rfile) => {
+            // Check the reference once so a typo fails before touching any file
+            if let Err(e) = std::fs::metadata(rfile) {
+                println!(
+                    "{}",
+                    format!("chmod: failed to get attributes of '{}': {}", rfile, e).red()
+                );
+                return;
+            }
+            (ModeSpec::Reference(rfile), &operands[..])
+        }
+        None => (ModeSpec::Mode(operands[0]), &operands[1..]),
+    };
 
     for filename in files {
-        if !std::path::Path::new(filename).exists() {
+        let path = Path::new(filename);
+        if !path.exists() {
             println!(
                 "{}",
                 format!(
@@ -49,12 +145,20 @@ pub fn execute(args: &[&str]) {
             continue;
         }
 
-        match parse_and_mode(filename, mode) {
+        let result = if options.recursive {
+            chmod_recursive(path, spec, &options)
+        } else {
+            chmod_entry(path, spec, &options)
+        };
+
+        match result {
             Ok(_) => {
-                println!(
-                    "{}",
-                    format!("Permissions changed for '{}'", filename).green()
-                );
+                if !options.verbose && !options.changes {
+                    println!(
+                        "{}",
+                        format!("Permissions changed for '{}'", filename).green()
+                    );
+                }
             }
             Err(e) => {
                 println!("{}", format!("chmod: {}", e).red());
@@ -63,124 +167,250 @@ pub fn execute(args: &[&str]) {
     }
 }
 
-fn parse_and_mode(filename: &str, mode: &str) -> Result<bool, String> {
-    if mode.chars().all(|c| c.is_ascii_digit()) {
-        parse_octal(filename, mode)
-    } else {
-        parse_symbolic(filename, mode)
+/// Apply `spec` to a single path, reporting according to `-v`/`-c`
+fn chmod_entry(path: &Path, spec: ModeSpec, options: &ChmodOptions) -> Result<(), String> {
+    let filename = path.to_string_lossy();
+    let (before, after) = match spec {
+        ModeSpec::Mode(mode) => parse_and_mode(&filename, mode)?,
+        ModeSpec::Reference(rfile) => copy_reference(&filename, rfile)?,
+    };
+    let changed = before != after;
+
+    if changed && (options.verbose || options.changes) {
+        println!(
+            "mode of '{}' changed from 0{} to 0{}",
+            filename,
+            before.to_octal(),
+            after.to_octal()
+        );
+    } else if options.verbose {
+        println!("mode of '{}' retained as 0{}", filename, after.to_octal());
     }
+
+    Ok(())
 }
 
-fn parse_octal(filename: &str, mode: &str) -> Result<bool, String> {
-    if mode.len() < 1 || mode.len() > 4 {
-        return Err("Invalid mode".to_string());
+/// Apply `spec` to `path` and everything below it without following symlinks
+fn chmod_recursive(path: &Path, spec: ModeSpec, options: &ChmodOptions) -> Result<(), String> {
+    chmod_entry(path, spec, options)?;
+
+    if !path.is_dir() {
+        return Ok(());
     }
 
-    if !mode.chars().all(|c| c.is_ascii_digit() && c <= '7') {
-        return Err("Invalid mode".to_string());
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| format!("cannot read directory '{}': {}", path.display(), e))?;
+
+    for entry in entries.flatten() {
+        if entry.file_type().map(|t| t.is_symlink()).unwrap_or(false) {
+            continue;
+        }
+
+        let child = entry.path();
+        if let Err(e) = chmod_recursive(&child, spec, options) {
+            println!(
+                "{}",
+                format!("chmod: skipping '{}': {}", child.display(), e).yellow()
+            );
+        }
     }
 
-    apply_win_perm(filename, mode)
+    Ok(())
 }
 
-fn parse_symbolic(filename: &str, mode: &str) -> Result<bool, String> {
-    let expressions: Vec<&str> = mode.split(',').collect();
+/// Returns the approximated permissions before and after the change
+fn parse_and_mode(
+    filename: &str,
+    mode: &str,
+) -> Result<(FilePermissions, FilePermissions), String> {
+    if mode.chars().all(|c| c.is_ascii_digit()) {
+        parse_octal(filename, mode)
+    } else {
+        parse_symbolic(filename, mode)
+    }
+}
 
-    for expr in expressions {
-        apply_symbolic_expression(filename, expr.trim())?;
+/// Give `filename` the permissions of `rfile`, returning the before and after modes
+fn copy_reference(
+    filename: &str,
+    rfile: &str,
+) -> Result<(FilePermissions, FilePermissions), String> {
+    let before = get_current_permissions(filename)?;
+    let after = get_current_permissions(rfile)?;
+
+    // The approximated mode would lose entries, so copy the real ACL and read-only flag
+    #[cfg(windows)]
+    {
+        copy_dacl(rfile, filename)?;
+        let mut attributes = std::fs::metadata(filename)
+            .map_err(|e| format!("cannot access '{}': {}", filename, e))?
+            .permissions();
+        attributes.set_readonly(!after.owner_write);
+        std::fs::set_permissions(filename, attributes)
+            .map_err(|e| format!("failed to update attributes of '{}': {}", filename, e))?;
     }
+    #[cfg(unix)]
+    apply_permissions_to_file(filename, &after)?;
 
-    Ok(true)
+    Ok((before, after))
 }
 
-fn apply_symbolic_expression(filename: &str, expr: &str) -> Result<(), String> {
-    if expr.is_empty() {
-        return Err("Empty expression".to_string());
+/// Validate a numeric mode of up to four octal digits, e.g. `644` or `4755`
+pub fn parse_octal_mode(mode: &str) -> Result<u32, String> {
+    if mode.is_empty() || mode.len() > 4 {
+        return Err(format!(
+            "invalid mode: '{}' (expected 1 to 4 octal digits)",
+            mode
+        ));
     }
+    if let Some(digit) = mode.chars().find(|c| !('0'..='7').contains(c)) {
+        return Err(format!(
+            "invalid mode: '{}' ('{}' is not an octal digit)",
+            mode, digit
+        ));
+    }
+    u32::from_str_radix(mode, 8).map_err(|e| format!("invalid mode: '{}' ({})", mode, e))
+}
 
-    let chars: Vec<char> = expr.chars().collect();
-    let mut i = 0;
+fn parse_octal(filename: &str, mode: &str) -> Result<(FilePermissions, FilePermissions), String> {
+    let value = parse_octal_mode(mode)?;
+    if cfg!(windows) && value & 0o7000 != 0 {
+        println!(
+            "{}",
+            "chmod: setuid, setgid and sticky bits have no meaning on Windows; ignoring them"
+                .yellow()
+        );
+    }
+
+    let before = get_current_permissions(filename)?;
+    let after = FilePermissions::from_octal(mode);
+    apply_permissions_to_file(filename, &after)?;
+    Ok((before, after))
+}
 
-    let mut who = String::new();
-    while i < chars.len() && "ugoa".contains(chars[i]) {
-        who.push(chars[i]);
-        i += 1;
+fn parse_symbolic(
+    filename: &str,
+    mode: &str,
+) -> Result<(FilePermissions, FilePermissions), String> {
+    // Parse every clause up front so an invalid mode never half-applies
+    let clauses = parse_symbolic_mode(mode)?;
+    let is_dir = Path::new(filename).is_dir();
+    if cfg!(windows) && clauses.iter().any(|c| c.perms.contains(['s', 't'])) {
+        println!(
+            "{}",
+            "chmod: setuid, setgid and sticky bits have no meaning on Windows; ignoring them"
+                .yellow()
+        );
     }
 
-    if who.is_empty() {
-        who = "a".to_string();
+    let before = get_current_permissions(filename)?;
+    let mut after = before.clone();
+    for clause in &clauses {
+        apply_symbolic_clause(&mut after, clause, is_dir)?;
     }
 
-    if i >= chars.len() {
-        return Err("Invalid symbolic expression: missing operation".to_string());
+    apply_permissions_to_file(filename, &after)?;
+    Ok((before, after))
+}
+
+/// Operator of a symbolic mode clause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolicOp {
+    Add,
+    Remove,
+    Set,
+}
+
+/// A single clause of a symbolic mode, e.g. `go-w`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolicClause {
+    pub targets: Vec<&'static str>,
+    pub op: SymbolicOp,
+    pub perms: String,
+}
+
+/// Parse a comma separated symbolic mode such as `u+x,go-w` or `a=r`
+pub fn parse_symbolic_mode(mode: &str) -> Result<Vec<SymbolicClause>, String> {
+    mode.split(',')
+        .map(|expr| parse_symbolic_clause(expr.trim()))
+        .collect()
+}
+
+fn parse_symbolic_clause(expr: &str) -> Result<SymbolicClause, String> {
+    if expr.is_empty() {
+        return Err("invalid mode: empty clause".to_string());
     }
 
-    let operation = chars[i];
-    if !"+-=".contains(operation) {
-        return Err("Invalid operation: must be +, -, or =".to_string());
+    let who_len = expr.find(|c| !"ugoa".contains(c)).unwrap_or(expr.len());
+    let (who, rest) = expr.split_at(who_len);
+
+    let mut chars = rest.chars();
+    let op = match chars.next() {
+        Some('+') => SymbolicOp::Add,
+        Some('-') => SymbolicOp::Remove,
+        Some('=') => SymbolicOp::Set,
+        Some(c) => {
+            return Err(format!(
+                "invalid mode '{}': unknown operator '{}', expected +, - or =",
+                expr, c
+            ));
+        }
+        None => return Err(format!("invalid mode '{}': missing operator", expr)),
+    };
+
+    let perms: String = chars.collect();
+    if let Some(c) = perms.chars().find(|c| !"rwxXst".contains(*c)) {
+        return Err(format!(
+            "invalid mode '{}': unknown permission '{}'",
+            expr, c
+        ));
     }
-    i += 1;
 
-    let mut permissions = String::new();
-    while i < chars.len() {
-        let ch = chars[i];
-        if "rwxXst".contains(ch) || "ugo".contains(ch) {
-            permissions.push(ch);
-        } else {
-            return Err(format!("Invalid permission character: '{}'", ch));
+    let mut targets = Vec::new();
+    let who = if who.is_empty() { "a" } else { who };
+    for who_char in who.chars() {
+        let expanded: &[&'static str] = match who_char {
+            'u' => &["owner"],
+            'g' => &["group"],
+            'o' => &["other"],
+            _ => &["owner", "group", "other"],
+        };
+        for target in expanded {
+            if !targets.contains(target) {
+                targets.push(*target);
+            }
         }
-        i += 1;
     }
 
-    apply_symbolic_to_file(filename, &who, operation, &permissions)
+    Ok(SymbolicClause { targets, op, perms })
 }
 
-fn apply_symbolic_to_file(
-    filename: &str,
-    who: &str,
-    operation: char,
-    permissions: &str,
+fn apply_symbolic_clause(
+    perms: &mut FilePermissions,
+    clause: &SymbolicClause,
+    is_dir: bool,
 ) -> Result<(), String> {
-    let current_perms = get_current_permissions(filename)?;
-
-    let mut new_perms = current_perms;
-
-    for who_char in who.chars() {
-        let targets = match who_char {
-            'u' => vec!["owner"],
-            'g' => vec!["group"],
-            'o' => vec!["other"],
-            'a' => vec!["owner", "group", "other"],
-            _ => continue,
-        };
+    // X only grants execute on directories or files that are already executable
+    let conditional_x = is_dir || perms.owner_execute || perms.group_execute || perms.other_execute;
 
-        for target in targets {
-            match operation {
-                '+' => {
-                    for perm in permissions.chars() {
-                        add_permission(&mut new_perms, target, perm, filename)?;
-                    }
-                }
-                '-' => {
-                    for perm in permissions.chars() {
-                        remove_permission(&mut new_perms, target, perm, filename)?;
-                    }
-                }
-                '=' => {
-                    clear_permissions(&mut new_perms, target);
-                    for perm in permissions.chars() {
-                        add_permission(&mut new_perms, target, perm, filename)?;
-                    }
+    for target in &clause.targets {
+        if clause.op == SymbolicOp::Set {
+            clear_permissions(perms, target, is_dir);
+        }
+        for perm in clause.perms.chars() {
+            match clause.op {
+                SymbolicOp::Add | SymbolicOp::Set => {
+                    add_permission(perms, target, perm, conditional_x)?
                 }
-                _ => return Err("Invalid operation".to_string()),
+                SymbolicOp::Remove => remove_permission(perms, target, perm)?,
             }
         }
     }
 
-    apply_permissions_to_file(filename, &new_perms)
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 struct FilePermissions {
     owner_read: bool,
     owner_write: bool,
@@ -191,33 +421,85 @@ struct FilePermissions {
     other_read: bool,
     other_write: bool,
     other_execute: bool,
+    /// Applied on Unix from octal or `s`/`t` modes; Windows keeps them only for reporting
+    setuid: bool,
+    setgid: bool,
+    sticky: bool,
 }
 
-impl Default for FilePermissions {
-    fn default() -> Self {
+impl FilePermissions {
+    /// Build from an already validated octal mode string such as `644` or `4755`
+    fn from_octal(mode: &str) -> Self {
+        let value = u32::from_str_radix(mode, 8).unwrap_or(0);
+        let bit = |shift: u32| value & (1 << shift) != 0;
+
         FilePermissions {
-            owner_read: false,
-            owner_write: false,
-            owner_execute: false,
-            group_read: false,
-            group_write: false,
-            group_execute: false,
-            other_read: false,
-            other_write: false,
-            other_execute: false,
+            owner_read: bit(8),
+            owner_write: bit(7),
+            owner_execute: bit(6),
+            group_read: bit(5),
+            group_write: bit(4),
+            group_execute: bit(3),
+            other_read: bit(2),
+            other_write: bit(1),
+            other_execute: bit(0),
+            setuid: bit(11),
+            setgid: bit(10),
+            sticky: bit(9),
         }
     }
+
+    /// Octal form, e.g. `644`, with a leading special-bits digit when any is set (`4755`)
+    fn to_octal(&self) -> String {
+        let special = permissions_to_octal(self.setuid, self.setgid, self.sticky);
+        format!(
+            "{}{}{}{}",
+            if special > 0 {
+                special.to_string()
+            } else {
+                String::new()
+            },
+            permissions_to_octal(self.owner_read, self.owner_write, self.owner_execute),
+            permissions_to_octal(self.group_read, self.group_write, self.group_execute),
+            permissions_to_octal(self.other_read, self.other_write, self.other_execute)
+        )
+    }
+}
+
+#[cfg(unix)]
+fn get_current_permissions(filename: &str) -> Result<FilePermissions, String> {
+    let metadata =
+        std::fs::metadata(filename).map_err(|e| format!("cannot access '{}': {}", filename, e))?;
+    Ok(FilePermissions::from_octal(&format!(
+        "{:o}",
+        metadata.permissions().mode() & 0o7777
+    )))
 }
 
-fn get_current_permissions(_filename: &str) -> Result<FilePermissions, String> {
-    Ok(FilePermissions::default())
+#[cfg(windows)]
+fn get_current_permissions(filename: &str) -> Result<FilePermissions, String> {
+    // Windows has no mode bits; approximate them from the read-only attribute
+    let metadata =
+        std::fs::metadata(filename).map_err(|e| format!("cannot access '{}': {}", filename, e))?;
+    let is_dir = metadata.is_dir();
+
+    Ok(FilePermissions {
+        owner_read: true,
+        owner_write: !metadata.permissions().readonly(),
+        owner_execute: is_dir,
+        group_read: true,
+        group_execute: is_dir,
+        other_read: true,
+        other_execute: is_dir,
+        ..FilePermissions::default()
+    })
 }
 
 fn add_permission(
     perms: &mut FilePermissions,
     target: &str,
     perm_char: char,
-    filename: &str,
+    conditional_x: bool,
 ) -> Result<(), String> {
     match (target, perm_char) {
         ("owner", 'r') => perms.owner_read = true,
@@ -230,8 +512,7 @@ fn add_permission(
         ("other", 'w') => perms.other_write = true,
         ("other", 'x') => perms.other_execute = true,
         (_, 'X') => {
-            let path = std::path::Path::new(filename);
-            if path.is_dir() || perm_char == 'x' {
+            if conditional_x {
                 match target {
                     "owner" => perms.owner_execute = true,
                     "group" => perms.group_execute = true,
@@ -240,9 +521,11 @@ fn add_permission(
                 }
             }
         }
-        (_, 's') | (_, 't') => {
-            return Ok(());
-        }
+        // `s` means setuid for u and setgid for g; `t` is the sticky bit, set through o
+        ("owner", 's') => perms.setuid = true,
+        ("group", 's') => perms.setgid = true,
+        ("other", 't') => perms.sticky = true,
+        (_, 's') | (_, 't') => {}
         _ => return Err(format!("Invalid permission: {} for {}", perm_char, target)),
     }
     Ok(())
@@ -252,7 +535,6 @@ fn remove_permission(
     perms: &mut FilePermissions,
     target: &str,
     perm_char: char,
-    _filename: &str,
 ) -> Result<(), String> {
     match (target, perm_char) {
         ("owner", 'r') => perms.owner_read = false,
@@ -270,45 +552,59 @@ fn remove_permission(
             "other" => perms.other_execute = false,
             _ => {}
         },
-        (_, 's') | (_, 't') => {
-            return Ok(());
-        }
+        ("owner", 's') => perms.setuid = false,
+        ("group", 's') => perms.setgid = false,
+        ("other", 't') => perms.sticky = false,
+        (_, 's') | (_, 't') => {}
         _ => return Err(format!("Invalid permission: {} for {}", perm_char, target)),
     }
     Ok(())
 }
 
-fn clear_permissions(perms: &mut FilePermissions, target: &str) {
+/// Reset `target` for `=`; like GNU chmod, directories keep setuid/setgid unless named
+fn clear_permissions(perms: &mut FilePermissions, target: &str, is_dir: bool) {
     match target {
         "owner" => {
             perms.owner_read = false;
             perms.owner_write = false;
             perms.owner_execute = false;
+            perms.setuid &= is_dir;
         }
         "group" => {
             perms.group_read = false;
             perms.group_write = false;
             perms.group_execute = false;
+            perms.setgid &= is_dir;
         }
         "other" => {
             perms.other_read = false;
             perms.other_write = false;
             perms.other_execute = false;
+            perms.sticky = false;
         }
         _ => {}
     }
 }
 
+#[cfg(unix)]
+fn apply_permissions_to_file(filename: &str, perms: &FilePermissions) -> Result<(), String> {
+    let mode = u32::from_str_radix(&perms.to_octal(), 8).unwrap_or(0);
+    std::fs::set_permissions(filename, std::fs::Permissions::from_mode(mode))
+        .map_err(|e| format!("changing permissions of '{}': {}", filename, e))
+}
+
+#[cfg(windows)]
 fn apply_permissions_to_file(filename: &str, perms: &FilePermissions) -> Result<(), String> {
-    let owner_octal =
-        permissions_to_octal(perms.owner_read, perms.owner_write, perms.owner_execute);
-    let group_octal =
-        permissions_to_octal(perms.group_read, perms.group_write, perms.group_execute);
-    let other_octal =
-        permissions_to_octal(perms.other_read, perms.other_write, perms.other_execute);
-
-    let octal_mode = format!("{}{}{}", owner_octal, group_octal, other_octal);
-    apply_win_perm(filename, &octal_mode).map(|_| ())
+    let octal_mode = perms.to_octal();
+    apply_win_perm(filename, &octal_mode)?;
+
+    // The read-only attribute is what most Windows tools honour for `-w`
+    let mut attributes = std::fs::metadata(filename)
+        .map_err(|e| format!("cannot access '{}': {}", filename, e))?
+        .permissions();
+    attributes.set_readonly(!perms.owner_write);
+    std::fs::set_permissions(filename, attributes)
+        .map_err(|e| format!("failed to update attributes of '{}': {}", filename, e))
 }
 
 fn permissions_to_octal(read: bool, write: bool, execute: bool) -> u8 {
@@ -325,6 +621,7 @@ fn permissions_to_octal(read: bool, write: bool, execute: bool) -> u8 {
     octal
 }
 
+#[cfg(windows)]
 fn apply_win_perm(filename: &str, octal_mode: &str) -> Result<bool, String> {
     let mut acl =
         ACL::from_file_path(filename, false).map_err(|e| format!("Failed to load ACL: {}", e))?;
@@ -423,6 +720,7 @@ fn apply_win_perm(filename: &str, octal_mode: &str) -> Result<bool, String> {
     Ok(true)
 }
 
+#[cfg(windows)]
 fn map_octal_to_permissions(octal_digit: u8) -> Vec<u32> {
     let mut permissions = Vec::new();
 
@@ -454,6 +752,7 @@ fn map_octal_to_permissions(octal_digit: u8) -> Vec<u32> {
     permissions
 }
 
+#[cfg(windows)]
 fn get_current_user_sid() -> Result<PSID, String> {
     unsafe {
         let mut token_handle = std::ptr::null_mut();
@@ -496,3 +795,224 @@ fn get_current_user_sid() -> Result<PSID, String> {
         Ok((*token_user).User.Sid)
     }
 }
+
+/// Replace the DACL of `target` with a copy of the one on `reference`
+#[cfg(windows)]
+fn copy_dacl(reference: &str, target: &str) -> Result<(), String> {
+    let wide = |path: &str| -> Vec<u16> {
+        std::ffi::OsStr::new(path)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    };
+    let reference_wide = wide(reference);
+    let mut target_wide = wide(target);
+
+    unsafe {
+        let mut dacl: PACL = std::ptr::null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+        let res = GetNamedSecurityInfoW(
+            reference_wide.as_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut dacl,
+            std::ptr::null_mut(),
+            &mut descriptor,
+        );
+        if res != ERROR_SUCCESS {
+            return Err(format!(
+                "failed to read the ACL of '{}' (error {})",
+                reference, res
+            ));
+        }
+
+        // The DACL points into the descriptor, so free it only once the copy is done
+        let res = SetNamedSecurityInfoW(
+            target_wide.as_mut_ptr(),
+            SE_FILE_OBJECT,
+            DACL_SECURITY_INFORMATION,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            dacl,
+            std::ptr::null_mut(),
+        );
+        LocalFree(descriptor as *mut _);
+
+        if res != ERROR_SUCCESS {
+            return Err(format!(
+                "failed to set the ACL of '{}' (error {})",
+                target, res
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbolic_mode_clauses() {
+        let clauses = parse_symbolic_mode("u+x,go-w").unwrap();
+        assert_eq!(
+            clauses,
+            vec![
+                SymbolicClause {
+                    targets: vec!["owner"],
+                    op: SymbolicOp::Add,
+                    perms: "x".to_string(),
+                },
+                SymbolicClause {
+                    targets: vec!["group", "other"],
+                    op: SymbolicOp::Remove,
+                    perms: "w".to_string(),
+                },
+            ]
+        );
+
+        let all = parse_symbolic_mode("=r").unwrap();
+        assert_eq!(all[0].targets, vec!["owner", "group", "other"]);
+        assert_eq!(all[0].op, SymbolicOp::Set);
+    }
+
+    #[test]
+    fn test_symbolic_clause_delta() {
+        let mut perms = FilePermissions {
+            owner_read: true,
+            owner_write: true,
+            group_read: true,
+            group_write: true,
+            other_read: true,
+            other_write: true,
+            ..FilePermissions::default()
+        };
+
+        for clause in parse_symbolic_mode("u+x,go-w").unwrap() {
+            apply_symbolic_clause(&mut perms, &clause, false).unwrap();
+        }
+
+        assert!(perms.owner_execute && perms.owner_write);
+        assert!(!perms.group_write && !perms.other_write);
+        assert!(perms.group_read && perms.other_read);
+    }
+
+    #[test]
+    fn test_octal_round_trip() {
+        let perms = FilePermissions::from_octal("0754");
+        assert!(perms.owner_execute && perms.group_read && !perms.group_write);
+        assert!(perms.other_read && !perms.other_execute);
+        assert_eq!(perms.to_octal(), "754");
+        assert_eq!(FilePermissions::from_octal("5").to_octal(), "005");
+    }
+
+    #[test]
+    fn test_special_bits() {
+        assert_eq!(parse_octal_mode("4755"), Ok(0o4755));
+        let perms = FilePermissions::from_octal("4755");
+        assert!(perms.setuid && !perms.setgid && !perms.sticky);
+        assert!(perms.owner_execute && perms.other_execute);
+        assert_eq!(perms.to_octal(), "4755");
+        assert_eq!(FilePermissions::from_octal("3777").to_octal(), "3777");
+
+        let mut symbolic = FilePermissions::from_octal("755");
+        for clause in parse_symbolic_mode("u+s,g+s,+t").unwrap() {
+            apply_symbolic_clause(&mut symbolic, &clause, false).unwrap();
+        }
+        assert_eq!(symbolic.to_octal(), "7755");
+        for clause in parse_symbolic_mode("g-s,o-t").unwrap() {
+            apply_symbolic_clause(&mut symbolic, &clause, false).unwrap();
+        }
+        assert_eq!(symbolic.to_octal(), "4755");
+        // `o+s` and `u+t` have no bit to set, as in GNU chmod
+        for clause in parse_symbolic_mode("o+s,u+t").unwrap() {
+            apply_symbolic_clause(&mut symbolic, &clause, false).unwrap();
+        }
+        assert_eq!(symbolic.to_octal(), "4755");
+        let clause = &parse_symbolic_mode("u=rwx").unwrap()[0];
+        let mut file = symbolic.clone();
+        apply_symbolic_clause(&mut file, clause, false).unwrap();
+        assert_eq!(file.to_octal(), "755");
+        let mut dir = symbolic.clone();
+        apply_symbolic_clause(&mut dir, clause, true).unwrap();
+        assert_eq!(dir.to_octal(), "4755");
+
+        let err = parse_octal_mode("8000").unwrap_err();
+        assert!(err.contains("'8' is not an octal digit"));
+        assert!(parse_octal_mode("47555").is_err());
+        assert!(parse_octal_mode("").is_err());
+    }
+
+    #[test]
+    fn test_recursive_remove_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        let files = [dir.path().join("top.txt"), nested.join("inner.txt")];
+        for file in &files {
+            std::fs::write(file, "data").unwrap();
+        }
+
+        execute(&["-R", "a-w", dir.path().to_str().unwrap()]);
+
+        for file in &files {
+            let readonly = std::fs::metadata(file).unwrap().permissions().readonly();
+            assert!(readonly, "{} should be read-only", file.display());
+        }
+
+        // Restore write access so the temp dir can be cleaned up
+        execute(&["-R", "u+w", dir.path().to_str().unwrap()]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symbolic_setuid_reaches_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tool");
+        std::fs::write(&file, "data").unwrap();
+        let file = file.to_str().unwrap();
+
+        execute(&["4755", file]);
+        let mode = std::fs::metadata(file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o4755);
+
+        execute(&["755", file]);
+        execute(&["u+s", file]);
+        let mode = std::fs::metadata(file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o4755);
+
+        execute(&["u-s", file]);
+        let mode = std::fs::metadata(file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o755);
+    }
+
+    #[test]
+    fn test_parse_symbolic_mode_rejects_invalid() {
+        assert!(parse_symbolic_mode("u+q").is_err());
+        assert!(parse_symbolic_mode("u").is_err());
+        assert!(parse_symbolic_mode("z+x").is_err());
+        assert!(parse_symbolic_mode("u+x,").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_reference_copies_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        std::fs::write(&a, "reference").unwrap();
+        std::fs::write(&b, "target").unwrap();
+        std::fs::set_permissions(&a, std::fs::Permissions::from_mode(0o640)).unwrap();
+        std::fs::set_permissions(&b, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let reference = format!("--reference={}", a.display());
+        execute(&[&reference, b.to_str().unwrap()]);
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&b), 0o640);
+        assert_eq!(mode(&b), mode(&a));
+    }
+}
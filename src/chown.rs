@@ -1,4 +1,4 @@
-#[cfg(windows)]
+#[cfg(any(windows, unix))]
 use colored::Colorize;
 #[cfg(windows)]
 use std::os::windows::ffi::OsStrExt;
@@ -10,10 +10,12 @@ use winapi::shared::winerror::ERROR_SUCCESS;
 #[cfg(windows)]
 use winapi::um::accctrl::SE_FILE_OBJECT;
 #[cfg(windows)]
-use winapi::um::aclapi::SetNamedSecurityInfoW;
+use winapi::um::aclapi::{GetNamedSecurityInfoW, SetNamedSecurityInfoW};
 #[cfg(windows)]
 use winapi::um::errhandlingapi::GetLastError;
 #[cfg(windows)]
+use winapi::um::securitybaseapi::GetLengthSid;
+#[cfg(windows)]
 use winapi::um::winbase::FormatMessageW;
 #[cfg(windows)]
 use winapi::um::winbase::LocalFree;
@@ -25,10 +27,36 @@ use winapi::um::winbase::{
 use winapi::um::winnt::*;
 // no OsStringExt needed when using from_utf16_lossy
 
-/// Main entry point for chown command (only works on Windows)
+/// Owner or group identity: SID bytes on Windows, a numeric uid/gid on Unix
 #[cfg(windows)]
+type AccountId = Vec<u8>;
+#[cfg(unix)]
+type AccountId = u32;
+
+/// Owner and group to set; `None` leaves that part unchanged
+#[cfg(any(windows, unix))]
+type Ownership = (Option<AccountId>, Option<AccountId>);
+
+/// Main entry point for chown command
+#[cfg(any(windows, unix))]
 pub fn execute(args: &[&str]) {
-    if args.len() < 2 {
+    let mut recursive = false;
+    let mut no_dereference = false;
+    let mut reference = None;
+    let mut operands = Vec::new();
+    for arg in args {
+        match *arg {
+            "-R" | "--recursive" => recursive = true,
+            "-h" | "--no-dereference" => no_dereference = true,
+            _ if arg.starts_with("--reference=") => {
+                reference = Some(&arg["--reference=".len()..]);
+            }
+            _ => operands.push(*arg),
+        }
+    }
+
+    let required = if reference.is_some() { 1 } else { 2 };
+    if operands.len() < required {
         println!(
             "{}",
             "Usage: chown [OPTION]... [OWNER][:[GROUP]] FILE...".red()
@@ -42,16 +70,26 @@ pub fn execute(args: &[&str]) {
         println!("  {}", "chown alice file.txt".dimmed());
         println!("  {}", "chown alice:developers file.txt".dimmed());
         println!("  {}", "chown :developers file.txt".dimmed());
+        println!("  {}", "chown 1000:1000 file.txt".dimmed());
+        println!("  {}", "chown -h alice link.txt".dimmed());
         println!("  {}", "chown --recursive alice:developers /mydir".dimmed());
         println!("  {}", "chown --reference=ref.txt file.txt".dimmed());
         return;
     }
 
-    let mode = args[0];
-    let files = &args[1..];
+    // Resolve accounts once up front so a typo fails before touching any file
+    let ((owner, group), files) = match target_ids(reference, &operands) {
+        Ok(target) => target,
+        Err(e) => {
+            println!("{}", format!("chown: {}", e).red());
+            return;
+        }
+    };
 
     for filename in files {
-        if !std::path::Path::new(filename).exists() {
+        let path = std::path::Path::new(filename);
+        // A dangling symlink still exists as far as -h is concerned
+        if path.symlink_metadata().is_err() || (!no_dereference && !path.exists()) {
             println!(
                 "{}",
                 format!(
@@ -63,7 +101,13 @@ pub fn execute(args: &[&str]) {
             continue;
         }
 
-        match parse_and_mode(filename, mode) {
+        let result = if recursive {
+            change_owner_recursive(path, owner.as_ref(), group.as_ref(), no_dereference)
+        } else {
+            change_owner(filename, owner.as_ref(), group.as_ref(), no_dereference)
+        };
+
+        match result {
             Ok(_) => {
                 println!("{}", format!("Owner changed for '{}'", filename).green());
             }
@@ -74,75 +118,259 @@ pub fn execute(args: &[&str]) {
     }
 }
 
-/// Parses user:group mode string and calls `change_owner_with_only_sid`
-#[cfg(windows)]
-fn parse_and_mode(file: &str, mode: &str) -> Result<bool, String> {
-    let (user, group) = if let Some(colon_pos) = mode.find(':') {
-        let user_part = &mode[..colon_pos];
-        let group_part = &mode[colon_pos + 1..];
-
-        let user = if user_part.is_empty() {
-            None
-        } else {
-            Some(user_part)
-        };
-        let group = if group_part.is_empty() {
-            None
-        } else {
-            Some(group_part)
-        };
+/// The owner and group to apply, taken from `--reference` or the first operand,
+/// along with the files they apply to
+#[cfg(any(windows, unix))]
+fn target_ids<'a>(
+    reference: Option<&str>,
+    operands: &'a [&'a str],
+) -> Result<(Ownership, &'a [&'a str]), String> {
+    match reference {
+        Some(rfile) => Ok((read_owner(rfile)?, operands)),
+        None => Ok((resolve_owner_spec(operands[0])?, &operands[1..])),
+    }
+}
 
-        (user, group)
-    } else {
-        (Some(mode), None)
+/// Splits an `OWNER[:GROUP]` spec into its non-empty parts
+#[cfg(any(windows, unix))]
+fn split_owner_spec(spec: &str) -> (Option<&str>, Option<&str>) {
+    let (user, group) = match spec.split_once(':') {
+        Some((user, group)) => (user, Some(group)),
+        None => (spec, None),
     };
 
-    if let Some(username) = user {
-        change_owner_with_only_sid(file, username)?;
-    }
+    (
+        Some(user).filter(|u| !u.is_empty()),
+        group.filter(|g| !g.is_empty()),
+    )
+}
 
-    if let Some(_groupname) = group {
-        println!("Note: Group ownership changes are not fully supported on Windows");
+/// Resolves the owner and group named by an `OWNER[:GROUP]` spec to SIDs
+#[cfg(windows)]
+fn resolve_owner_spec(spec: &str) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>), String> {
+    let (user, group) = split_owner_spec(spec);
+    if user.is_none() && group.is_none() {
+        return Err(format!("invalid spec: '{}'", spec));
     }
 
-    Ok(true)
+    let owner_sid = user
+        .map(|name| resolve_id(name).map_err(|e| format!("invalid user: {}", e)))
+        .transpose()?;
+    let group_sid = group
+        .map(|name| resolve_id(name).map_err(|e| format!("invalid group: {}", e)))
+        .transpose()?;
+
+    Ok((owner_sid, group_sid))
 }
 
-/// Changes file owner using SID derived from username
+/// Reads the owner and group SIDs of an existing file, as used by `--reference`
 #[cfg(windows)]
-fn change_owner_with_only_sid(file: &str, username: &str) -> Result<(), String> {
-    let user_sid = match name_to_sid(username, None) {
-        Ok(sid) => sid,
-        Err(0) => return Err(format!("invalid user: {}", username)),
-        Err(code) => {
+fn read_owner(file: &str) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>), String> {
+    let file_wide: Vec<u16> = OsStr::new(file)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    unsafe {
+        let mut owner: PSID = ptr::null_mut();
+        let mut group: PSID = ptr::null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+
+        let res = GetNamedSecurityInfoW(
+            file_wide.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | GROUP_SECURITY_INFORMATION,
+            &mut owner,
+            &mut group,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut descriptor,
+        );
+
+        if res != ERROR_SUCCESS {
             return Err(format!(
-                "error looking up user '{}': Error code '{}'",
-                username, code
+                "failed to get attributes of '{}': {}",
+                file,
+                format_error_message(res)
             ));
         }
-    };
+
+        // The SIDs point into the descriptor, so copy them out before freeing it
+        let copy_sid = |sid: PSID| {
+            (!sid.is_null()).then(|| {
+                std::slice::from_raw_parts(sid as *const u8, GetLengthSid(sid) as usize).to_vec()
+            })
+        };
+        let sids = (copy_sid(owner), copy_sid(group));
+        LocalFree(descriptor as *mut _);
+
+        if sids.0.is_none() {
+            return Err(format!("'{}' has no owner", file));
+        }
+        Ok(sids)
+    }
+}
+
+/// Resolves an account name, or a number taken as a RID in this machine's domain
+#[cfg(windows)]
+fn resolve_id(name: &str) -> Result<Vec<u8>, String> {
+    match name.parse() {
+        Ok(rid) => rid_to_sid(rid),
+        Err(_) => resolve_account_sid(name),
+    }
+}
+
+/// Builds the SID of a local account from its relative id (e.g. 500 for the
+/// built-in Administrator) by appending it to the machine's domain SID
+#[cfg(windows)]
+fn rid_to_sid(rid: u32) -> Result<Vec<u8>, String> {
+    use std::ptr::null_mut;
+    use winapi::shared::winerror::ERROR_NONE_MAPPED;
+    use winapi::um::winbase::LookupAccountSidW;
+
+    let computer = std::env::var("COMPUTERNAME")
+        .map_err(|_| format!("'{}' (cannot determine the local domain)", rid))?;
+    let mut sid = name_to_sid(&computer, None)
+        .map_err(|code| format!("'{}' ({})", rid, format_error_message(code)))?;
+
+    // Revision, sub-authority count, 6-byte authority, then 4 bytes per sub-authority
+    let count = sid[1] as usize;
+    sid.truncate(8 + 4 * count);
+    sid[1] += 1;
+    sid.extend_from_slice(&rid.to_le_bytes());
+
+    // Refuse ids that don't name an account rather than assigning an orphan SID
+    unsafe {
+        let mut name_len = 0u32;
+        let mut domain_len = 0u32;
+        let mut sid_name_use: SID_NAME_USE = std::mem::zeroed();
+        LookupAccountSidW(
+            null_mut(),
+            sid.as_mut_ptr() as PSID,
+            null_mut(),
+            &mut name_len,
+            null_mut(),
+            &mut domain_len,
+            &mut sid_name_use,
+        );
+        if GetLastError() == ERROR_NONE_MAPPED {
+            return Err(format!("'{}' (no such account)", rid));
+        }
+    }
+    Ok(sid)
+}
+
+/// Looks up a local or domain account name and returns its SID bytes
+#[cfg(windows)]
+pub fn resolve_account_sid(name: &str) -> Result<Vec<u8>, String> {
+    match name_to_sid(name, None) {
+        Ok(sid) => Ok(sid),
+        Err(0) | Err(winapi::shared::winerror::ERROR_NONE_MAPPED) => {
+            Err(format!("'{}' (no such account)", name))
+        }
+        Err(code) => Err(format!("'{}' ({})", name, format_error_message(code))),
+    }
+}
+
+/// Sets the owner and/or primary group of a single file, or of a symlink
+/// itself when `no_dereference` is set
+#[cfg(windows)]
+fn change_owner(
+    file: &str,
+    owner_sid: Option<&AccountId>,
+    group_sid: Option<&AccountId>,
+    no_dereference: bool,
+) -> Result<(), String> {
+    let mut info = 0;
+    if owner_sid.is_some() {
+        info |= OWNER_SECURITY_INFORMATION;
+    }
+    if group_sid.is_some() {
+        info |= GROUP_SECURITY_INFORMATION;
+    }
 
     let file_wide: Vec<u16> = OsStr::new(file)
         .encode_wide()
         .chain(std::iter::once(0))
         .collect();
 
+    if no_dereference {
+        return change_link_owner(file, &file_wide, info, owner_sid, group_sid);
+    }
+
     unsafe {
         let res = SetNamedSecurityInfoW(
             file_wide.as_ptr() as *mut u16,
             SE_FILE_OBJECT,
-            OWNER_SECURITY_INFORMATION,
-            user_sid.as_ptr() as *mut _,
+            info,
+            owner_sid.map_or(ptr::null_mut(), |sid| sid.as_ptr() as *mut _),
+            group_sid.map_or(ptr::null_mut(), |sid| sid.as_ptr() as *mut _),
             ptr::null_mut(),
             ptr::null_mut(),
+        );
+
+        // SetNamedSecurityInfoW returns the error code rather than setting the last error
+        if res != ERROR_SUCCESS {
+            return Err(format!(
+                "failed to change owner of '{}': {}",
+                file,
+                format_error_message(res)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the owner through a handle opened on the reparse point itself, since
+/// `SetNamedSecurityInfoW` always follows symlinks
+#[cfg(windows)]
+fn change_link_owner(
+    file: &str,
+    file_wide: &[u16],
+    info: SECURITY_INFORMATION,
+    owner_sid: Option<&AccountId>,
+    group_sid: Option<&AccountId>,
+) -> Result<(), String> {
+    use winapi::um::aclapi::SetSecurityInfo;
+    use winapi::um::fileapi::{CreateFileW, OPEN_EXISTING};
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::winbase::{FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT};
+
+    unsafe {
+        let handle = CreateFileW(
+            file_wide.as_ptr(),
+            WRITE_OWNER,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            ptr::null_mut(),
+            OPEN_EXISTING,
+            FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS,
             ptr::null_mut(),
         );
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(format!(
+                "failed to open '{}': {}",
+                file,
+                format_error_message(GetLastError())
+            ));
+        }
+
+        let res = SetSecurityInfo(
+            handle,
+            SE_FILE_OBJECT,
+            info,
+            owner_sid.map_or(ptr::null_mut(), |sid| sid.as_ptr() as *mut _),
+            group_sid.map_or(ptr::null_mut(), |sid| sid.as_ptr() as *mut _),
+            ptr::null_mut(),
+            ptr::null_mut(),
+        );
+        CloseHandle(handle);
 
         if res != ERROR_SUCCESS {
             return Err(format!(
                 "failed to change owner of '{}': {}",
                 file,
-                get_last_error_message()
+                format_error_message(res)
             ));
         }
     }
@@ -150,9 +378,113 @@ fn change_owner_with_only_sid(file: &str, username: &str) -> Result<(), String>
     Ok(())
 }
 
-/// Retrieves readable Windows error message from last error code
+/// Resolves one side of an `OWNER[:GROUP]` spec: numbers are used as-is, names are looked up
+#[cfg(unix)]
+fn resolve_id(name: &str, group: bool) -> Result<u32, String> {
+    if let Ok(id) = name.parse() {
+        return Ok(id);
+    }
+
+    let c_name = std::ffi::CString::new(name).map_err(|_| format!("'{}' (invalid name)", name))?;
+    unsafe {
+        if group {
+            let entry = libc::getgrnam(c_name.as_ptr());
+            (!entry.is_null())
+                .then(|| (*entry).gr_gid)
+                .ok_or_else(|| format!("'{}' (no such group)", name))
+        } else {
+            let entry = libc::getpwnam(c_name.as_ptr());
+            (!entry.is_null())
+                .then(|| (*entry).pw_uid)
+                .ok_or_else(|| format!("'{}' (no such user)", name))
+        }
+    }
+}
+
+/// Resolves the owner and group of an `OWNER[:GROUP]` spec to a uid and gid
+#[cfg(unix)]
+fn resolve_owner_spec(spec: &str) -> Result<(Option<u32>, Option<u32>), String> {
+    let (user, group) = split_owner_spec(spec);
+    if user.is_none() && group.is_none() {
+        return Err(format!("invalid spec: '{}'", spec));
+    }
+
+    let uid = user
+        .map(|name| resolve_id(name, false).map_err(|e| format!("invalid user: {}", e)))
+        .transpose()?;
+    let gid = group
+        .map(|name| resolve_id(name, true).map_err(|e| format!("invalid group: {}", e)))
+        .transpose()?;
+
+    Ok((uid, gid))
+}
+
+/// Reads the uid and gid of an existing file, as used by `--reference`
+#[cfg(unix)]
+fn read_owner(file: &str) -> Result<(Option<u32>, Option<u32>), String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(file)
+        .map_err(|e| format!("failed to get attributes of '{}': {}", file, e))?;
+    Ok((Some(metadata.uid()), Some(metadata.gid())))
+}
+
+/// Sets the owner and/or group of a single file, or of a symlink itself
+/// (`lchown`) when `no_dereference` is set
+#[cfg(unix)]
+fn change_owner(
+    file: &str,
+    uid: Option<&AccountId>,
+    gid: Option<&AccountId>,
+    no_dereference: bool,
+) -> Result<(), String> {
+    let (uid, gid) = (uid.copied(), gid.copied());
+    let result = if no_dereference {
+        std::os::unix::fs::lchown(file, uid, gid)
+    } else {
+        std::os::unix::fs::chown(file, uid, gid)
+    };
+    result.map_err(|e| format!("changing ownership of '{}': {}", file, e))
+}
+
+/// Applies `change_owner` to a path and everything below it without following
+/// symlinks; with `no_dereference` the links themselves are changed too
+#[cfg(any(windows, unix))]
+fn change_owner_recursive(
+    path: &std::path::Path,
+    owner: Option<&AccountId>,
+    group: Option<&AccountId>,
+    no_dereference: bool,
+) -> Result<(), String> {
+    change_owner(&path.to_string_lossy(), owner, group, no_dereference)?;
+
+    if !path.is_dir() || path.is_symlink() {
+        return Ok(());
+    }
+
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| format!("cannot read directory '{}': {}", path.display(), e))?;
+
+    for entry in entries.flatten() {
+        if entry.file_type().map(|t| t.is_symlink()).unwrap_or(false) && !no_dereference {
+            continue;
+        }
+
+        let child = entry.path();
+        if let Err(e) = change_owner_recursive(&child, owner, group, no_dereference) {
+            println!(
+                "{}",
+                format!("chown: skipping '{}': {}", child.display(), e).yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a Windows error code into its system message
 #[cfg(windows)]
-fn get_last_error_message() -> String {
+fn format_error_message(code: u32) -> String {
     unsafe {
         let mut buf: *mut u16 = ptr::null_mut();
         let len = FormatMessageW(
@@ -160,7 +492,7 @@ fn get_last_error_message() -> String {
                 | FORMAT_MESSAGE_ALLOCATE_BUFFER
                 | FORMAT_MESSAGE_IGNORE_INSERTS,
             ptr::null(),
-            GetLastError(),
+            code,
             0,
             &mut buf as *mut *mut u16 as *mut u16,
             0,
@@ -168,7 +500,7 @@ fn get_last_error_message() -> String {
         );
 
         if len == 0 {
-            return format!("Unknown error ({})", GetLastError());
+            return format!("Unknown error ({})", code);
         }
 
         let slice = std::slice::from_raw_parts(buf, len as usize);
@@ -233,8 +565,122 @@ fn name_to_sid(name: &str, _domain: Option<&str>) -> Result<Vec<u8>, u32> {
     }
 }
 
-/// Dummy stub for non-Windows platforms
-#[cfg(not(windows))]
+/// Dummy stub for platforms without an ownership model we support
+#[cfg(not(any(windows, unix)))]
 pub fn execute(_args: &[&str]) {
-    eprintln!("Error: `chown` command is only supported on Windows platforms.");
+    eprintln!("Error: `chown` command is only supported on Windows and Unix platforms.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(windows)]
+    #[ignore = "looks up the real logged-in account, which service and CI sessions may not have"]
+    fn test_resolve_current_user_sid() {
+        let user = std::env::var("USERNAME").expect("USERNAME is set on Windows");
+        let sid = resolve_account_sid(&user).unwrap();
+        assert!(!sid.is_empty());
+    }
+
+    #[test]
+    #[cfg(any(windows, unix))]
+    fn test_unknown_account_is_rejected() {
+        let err = resolve_owner_spec("winix-no-such-user-4f1c").unwrap_err();
+        assert!(err.starts_with("invalid user"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(any(windows, unix))]
+    fn test_reference_supplies_the_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        std::fs::write(&a, "a").unwrap();
+        let a = a.to_str().unwrap();
+
+        // Every operand is a target; none is taken as an owner spec
+        let (ids, files) = target_ids(Some(a), &["winix-no-such-user-4f1c", "b"]).unwrap();
+        assert_eq!(ids, read_owner(a).unwrap());
+        assert!(ids.0.is_some() && ids.1.is_some());
+        assert_eq!(files, ["winix-no-such-user-4f1c", "b"]);
+
+        let missing = dir.path().join("missing");
+        assert!(target_ids(Some(missing.to_str().unwrap()), &["b"]).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[ignore = "needs root to give files away"]
+    fn test_reference_copies_owner() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        std::fs::write(&a, "a").unwrap();
+        std::fs::write(&b, "b").unwrap();
+        std::os::unix::fs::chown(&a, Some(4242), Some(4343)).unwrap();
+        let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+        execute(&[&format!("--reference={}", a), b]);
+
+        let meta = std::fs::metadata(b).unwrap();
+        assert_eq!((meta.uid(), meta.gid()), (4242, 4343));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_numeric_id_maps_to_rid() {
+        // 500 is the built-in Administrator account, even when renamed
+        let sid = resolve_id("500").unwrap();
+        assert_eq!(&sid[sid.len() - 4..], &500u32.to_le_bytes());
+        assert!(resolve_id("4000000000").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_numeric_spec_parses_ids() {
+        assert_eq!(
+            resolve_owner_spec("1000:1000"),
+            Ok((Some(1000), Some(1000)))
+        );
+        assert_eq!(resolve_owner_spec("0"), Ok((Some(0), None)));
+        assert_eq!(resolve_owner_spec(":42"), Ok((None, Some(42))));
+        assert_eq!(resolve_owner_spec("root:0"), Ok((Some(0), Some(0))));
+        assert!(resolve_owner_spec(":").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    #[ignore = "needs root to give files away"]
+    fn test_no_dereference_changes_only_the_link() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        std::fs::write(&target, "data").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let before = std::fs::metadata(&target).unwrap();
+
+        execute(&["-h", "4242:4343", link.to_str().unwrap()]);
+
+        let link_meta = std::fs::symlink_metadata(&link).unwrap();
+        assert_eq!((link_meta.uid(), link_meta.gid()), (4242, 4343));
+        let after = std::fs::metadata(&target).unwrap();
+        assert_eq!((after.uid(), after.gid()), (before.uid(), before.gid()));
+
+        execute(&["4242", link.to_str().unwrap()]);
+        assert_eq!(std::fs::metadata(&target).unwrap().uid(), 4242);
+    }
+
+    #[test]
+    #[cfg(any(windows, unix))]
+    fn test_split_owner_spec() {
+        assert_eq!(split_owner_spec("alice"), (Some("alice"), None));
+        assert_eq!(split_owner_spec("alice:dev"), (Some("alice"), Some("dev")));
+        assert_eq!(split_owner_spec(":dev"), (None, Some("dev")));
+        assert_eq!(split_owner_spec("alice:"), (Some("alice"), None));
+    }
 }
@@ -1,23 +1,770 @@
 use std::env;
 use std::fs;
-use std::io;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// Bytes read and written per step of a `--verify` copy
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The digest as lowercase hex
+fn hex_digest(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// SHA-256 of everything `reader` yields
+fn hash_reader(mut reader: impl Read) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            return Ok(hex_digest(hasher));
+        }
+        hasher.update(&buf[..read]);
+    }
+}
+
+/// When to attempt a copy-on-write clone instead of copying the bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflink {
+    Never,
+    Auto,
+    Always,
+}
+
+/// Parse the value of `--reflink[=WHEN]`; a bare `--reflink` means `always`
+pub fn parse_reflink(value: Option<&str>) -> Result<Reflink, String> {
+    match value {
+        None | Some("always") => Ok(Reflink::Always),
+        Some("auto") => Ok(Reflink::Auto),
+        Some("never") => Ok(Reflink::Never),
+        Some(other) => Err(format!("invalid argument '{}' for '--reflink'", other)),
+    }
+}
+
+/// Clone `src` into `dest` with FICLONE (Btrfs, XFS and friends)
+#[cfg(target_os = "linux")]
+fn try_reflink(src: &Path, dest: &Path) -> io::Result<u64> {
+    use std::os::unix::io::AsRawFd;
+
+    // _IOW(0x94, 9, int) from linux/fs.h
+    const FICLONE: u32 = 0x4004_9409;
+
+    let source = fs::File::open(src)?;
+    let metadata = source.metadata()?;
+    let target = fs::File::create(dest)?;
+
+    if unsafe { libc::ioctl(target.as_raw_fd(), FICLONE as _, source.as_raw_fd()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    target.set_permissions(metadata.permissions())?;
+    Ok(metadata.len())
+}
+
+/// Clone `src` into `dest` with ReFS block cloning
+#[cfg(windows)]
+fn try_reflink(src: &Path, dest: &Path) -> io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::AsRawHandle;
+    use std::ptr;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::fileapi::{
+        GetDiskFreeSpaceW, GetVolumeInformationByHandleW, GetVolumePathNameW,
+    };
+    use winapi::um::ioapiset::DeviceIoControl;
+    use winapi::um::winnt::{FILE_SUPPORTS_BLOCK_REFCOUNTING, HANDLE};
+
+    // CTL_CODE(FILE_DEVICE_FILE_SYSTEM, 209, METHOD_BUFFERED, FILE_WRITE_ACCESS)
+    const FSCTL_DUPLICATE_EXTENTS_TO_FILE: DWORD = 0x0009_8344;
+    // Each request must stay under 4 GiB
+    const CHUNK: u64 = 1 << 30;
+
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    struct DUPLICATE_EXTENTS_DATA {
+        FileHandle: HANDLE,
+        SourceFileOffset: i64,
+        TargetFileOffset: i64,
+        ByteCount: i64,
+    }
+
+    let source = fs::File::open(src)?;
+    let len = source.metadata()?.len();
+
+    let mut flags: DWORD = 0;
+    let ok = unsafe {
+        GetVolumeInformationByHandleW(
+            source.as_raw_handle() as HANDLE,
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut flags,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if flags & FILE_SUPPORTS_BLOCK_REFCOUNTING == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "filesystem does not support block cloning",
+        ));
+    }
+
+    // Cloned ranges must be whole clusters
+    let wide: Vec<u16> = src.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut root = [0u16; 261];
+    let (mut sectors, mut bytes, mut free, mut total): (DWORD, DWORD, DWORD, DWORD) = (0, 0, 0, 0);
+    let ok = unsafe {
+        GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as DWORD) != 0
+            && GetDiskFreeSpaceW(
+                root.as_ptr(),
+                &mut sectors,
+                &mut bytes,
+                &mut free,
+                &mut total,
+            ) != 0
+    };
+    if !ok {
+        return Err(io::Error::last_os_error());
+    }
+    let cluster = (sectors as u64 * bytes as u64).max(1);
+
+    let target = fs::File::create(dest)?;
+    target.set_len(len)?;
+
+    let mut offset = 0;
+    while offset < len {
+        let count = (len - offset).min(CHUNK).div_ceil(cluster) * cluster;
+        let mut data = DUPLICATE_EXTENTS_DATA {
+            FileHandle: source.as_raw_handle() as HANDLE,
+            SourceFileOffset: offset as i64,
+            TargetFileOffset: offset as i64,
+            ByteCount: count as i64,
+        };
+        let mut returned: DWORD = 0;
+        let ok = unsafe {
+            DeviceIoControl(
+                target.as_raw_handle() as HANDLE,
+                FSCTL_DUPLICATE_EXTENTS_TO_FILE,
+                &mut data as *mut _ as *mut _,
+                std::mem::size_of::<DUPLICATE_EXTENTS_DATA>() as DWORD,
+                ptr::null_mut(),
+                0,
+                &mut returned,
+                ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        offset += count;
+    }
+
+    Ok(len)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn try_reflink(_src: &Path, _dest: &Path) -> io::Result<u64> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "copy-on-write clones are not supported on this platform",
+    ))
+}
+
+fn check_distinct(src: &str, dest: &str) -> Result<(), String> {
+    if let (Ok(a), Ok(b)) = (fs::canonicalize(src), fs::canonicalize(dest))
+        && a == b
+    {
+        return Err(format!("'{}' and '{}' are the same file", src, dest));
+    }
+    Ok(())
+}
+
+/// Copy `src` to `dest`, cloning the data first when `reflink` allows it
+pub fn copy_file(src: &str, dest: &str, reflink: Reflink) -> Result<u64, String> {
+    check_distinct(src, dest)?;
+
+    if reflink != Reflink::Never {
+        let existed = Path::new(dest).exists();
+        match try_reflink(Path::new(src), Path::new(dest)) {
+            Ok(bytes) => return Ok(bytes),
+            Err(e) => {
+                if !existed {
+                    let _ = fs::remove_file(dest);
+                }
+                if reflink == Reflink::Always {
+                    return Err(format!("failed to clone '{}' to '{}': {}", src, dest, e));
+                }
+            }
+        }
+    }
+
+    fs::copy(src, dest).map_err(|e| format!("Error copying file '{}': {}", src, e))
+}
+
+/// Copy `src` to `dest` in chunks, hashing the source on the way through.
+/// Returns the bytes copied and the source digest.
+fn copy_hashing(src: &str, dest: &str) -> Result<(u64, String), String> {
+    let read_err = |e: io::Error| format!("Error reading '{}': {}", src, e);
+    let write_err = |e: io::Error| format!("Error writing '{}': {}", dest, e);
+
+    let mut source = fs::File::open(src).map_err(read_err)?;
+    let permissions = source.metadata().map_err(read_err)?.permissions();
+    let mut target = fs::File::create(dest).map_err(write_err)?;
+
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut bytes = 0;
+    loop {
+        let read = source.read(&mut buf).map_err(read_err)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+        target.write_all(&buf[..read]).map_err(write_err)?;
+        bytes += read as u64;
+    }
+
+    // The check below must read what reached the disk, not the page cache's pending writes
+    target.sync_all().map_err(write_err)?;
+    target.set_permissions(permissions).map_err(write_err)?;
+    Ok((bytes, hex_digest(hasher)))
+}
+
+/// Hash `dest` and compare it with the source digest
+pub fn verify_copy(src_digest: &str, dest: &str) -> Result<(), String> {
+    let file = fs::File::open(dest).map_err(|e| format!("Error reading '{}': {}", dest, e))?;
+    let dest_digest = hash_reader(file).map_err(|e| format!("Error reading '{}': {}", dest, e))?;
+    if dest_digest != src_digest {
+        return Err(format!(
+            "verification of '{}' failed: source sha256 {}, destination sha256 {}",
+            dest, src_digest, dest_digest
+        ));
+    }
+    Ok(())
+}
+
+/// Copy like `copy_file`, then check the destination's SHA-256 against the source's.
+/// Plain copies hash the source while copying; clones re-read it.
+pub fn copy_verified(src: &str, dest: &str, reflink: Reflink) -> Result<u64, String> {
+    check_distinct(src, dest)?;
+    let (bytes, digest) = if reflink == Reflink::Never {
+        copy_hashing(src, dest)?
+    } else {
+        let bytes = copy_file(src, dest, reflink)?;
+        let file = fs::File::open(src).map_err(|e| format!("Error reading '{}': {}", src, e))?;
+        let digest = hash_reader(file).map_err(|e| format!("Error reading '{}': {}", src, e))?;
+        (bytes, digest)
+    };
+    verify_copy(&digest, dest)?;
+    Ok(bytes)
+}
+
+/// Whether `dest` is missing or older than `src`
+pub fn needs_update(src: &str, dest: &str) -> Result<bool, String> {
+    let src_time = fs::metadata(src)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Error reading '{}': {}", src, e))?;
+
+    match fs::metadata(dest).and_then(|m| m.modified()) {
+        Ok(dest_time) => Ok(src_time > dest_time),
+        Err(_) => Ok(true),
+    }
+}
+
+/// Copy like `copy_with_backup` unless `dest` is already up to date,
+/// returning `None` when skipped
+pub fn copy_if_newer(
+    src: &str,
+    dest: &str,
+    reflink: Reflink,
+    verify: bool,
+    backup: Backup,
+    suffix: &str,
+) -> Result<Option<u64>, String> {
+    if !needs_update(src, dest)? {
+        return Ok(None);
+    }
+    copy_with_backup(src, dest, reflink, verify, backup, suffix).map(Some)
+}
+
+/// Which backup to make of a destination before overwriting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backup {
+    None,
+    /// `dest~`
+    Simple,
+    /// `dest.~N~`, one past the highest existing number
+    Numbered,
+    /// Numbered if numbered backups of `dest` already exist, simple otherwise
+    Existing,
+}
+
+/// Parse the CONTROL of `--backup[=CONTROL]`, accepting GNU's names and abbreviations;
+/// a bare `--backup` falls back to `$VERSION_CONTROL`, then `existing`
+pub fn parse_backup(value: Option<&str>) -> Result<Backup, String> {
+    let control = match value {
+        Some(value) => value.to_string(),
+        None => match env::var("VERSION_CONTROL") {
+            Ok(value) if !value.is_empty() => value,
+            _ => return Ok(Backup::Existing),
+        },
+    };
+
+    const CHOICES: [(&str, Backup); 8] = [
+        ("none", Backup::None),
+        ("off", Backup::None),
+        ("simple", Backup::Simple),
+        ("never", Backup::Simple),
+        ("numbered", Backup::Numbered),
+        ("t", Backup::Numbered),
+        ("existing", Backup::Existing),
+        ("nil", Backup::Existing),
+    ];
+    let matches: Vec<Backup> = CHOICES
+        .iter()
+        .filter(|(name, _)| !control.is_empty() && name.starts_with(control.as_str()))
+        .map(|&(_, backup)| backup)
+        .collect();
+    match matches.as_slice() {
+        [first, rest @ ..] if rest.iter().all(|b| b == first) => Ok(*first),
+        [] => Err(format!("invalid argument '{}' for '--backup'", control)),
+        _ => Err(format!("ambiguous argument '{}' for '--backup'", control)),
+    }
+}
+
+/// Highest N among the `dest.~N~` files next to `dest`
+fn last_backup_number(dest: &Path) -> u64 {
+    let (Some(name), Some(dir)) = (dest.file_name(), dest.parent()) else {
+        return 0;
+    };
+    let prefix = format!("{}.~", name.to_string_lossy());
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.strip_prefix(&prefix)?
+                .strip_suffix('~')?
+                .parse::<u64>()
+                .ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Where `make_backup` would move `dest`, or `None` when no backup is wanted
+pub fn backup_path(dest: &Path, backup: Backup, suffix: &str) -> Option<PathBuf> {
+    let numbered = match backup {
+        Backup::None => return None,
+        Backup::Simple => false,
+        Backup::Numbered => true,
+        Backup::Existing => last_backup_number(dest) > 0,
+    };
+
+    let mut path = dest.as_os_str().to_owned();
+    if numbered {
+        path.push(format!(".~{}~", last_backup_number(dest) + 1));
+    } else {
+        path.push(suffix);
+    }
+    Some(PathBuf::from(path))
+}
+
+/// Move an existing `dest` out of the way, returning where it went
+pub fn make_backup(dest: &str, backup: Backup, suffix: &str) -> Result<Option<PathBuf>, String> {
+    let dest = Path::new(dest);
+    if fs::symlink_metadata(dest).is_err() {
+        return Ok(None);
+    }
+    let Some(path) = backup_path(dest, backup, suffix) else {
+        return Ok(None);
+    };
+    fs::rename(dest, &path).map_err(|e| {
+        format!(
+            "cannot back up '{}' to '{}': {}",
+            dest.display(),
+            path.display(),
+            e
+        )
+    })?;
+    Ok(Some(path))
+}
+
+/// Back up `dest` as `backup` asks, then copy like `copy_file`
+/// (or `copy_verified` with `verify`)
+pub fn copy_with_backup(
+    src: &str,
+    dest: &str,
+    reflink: Reflink,
+    verify: bool,
+    backup: Backup,
+    suffix: &str,
+) -> Result<u64, String> {
+    // Checked before any backup so `--backup` can't move a whole tree aside
+    if fs::metadata(dest).is_ok_and(|m| m.is_dir()) {
+        return Err(format!(
+            "cannot overwrite directory '{}' with non-directory",
+            dest
+        ));
+    }
+    if backup != Backup::None {
+        // Never move the source aside as a "backup" of itself
+        check_distinct(src, dest)?;
+        make_backup(dest, backup, suffix)?;
+    }
+    if verify {
+        copy_verified(src, dest, reflink)
+    } else {
+        copy_file(src, dest, reflink)
+    }
+}
 
 /// Run the `cp` command
-/// `args` should contain exactly 2 arguments: source and destination
+/// `args` should contain a source and destination, optionally with `--reflink[=WHEN]`,
+/// `-u`/`--update`, `-v`/`--verbose`, `--verify`, `-b`/`--backup[=CONTROL]`
+/// and `-S`/`--suffix SUFFIX`
 pub fn run(args: &[String]) -> io::Result<()> {
-    if args.len() != 2 {
-        eprintln!("Usage: cp <source> <destination>");
+    let mut reflink = Reflink::Never;
+    let mut update = false;
+    let mut verbose = false;
+    let mut verify = false;
+    let mut backup = None;
+    let mut suffix = env::var("SIMPLE_BACKUP_SUFFIX").unwrap_or_else(|_| "~".to_string());
+    let mut suffix_given = false;
+    let mut paths = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-u" || arg == "--update" {
+            update = true;
+        } else if arg == "-v" || arg == "--verbose" {
+            verbose = true;
+        } else if arg == "--verify" {
+            verify = true;
+        } else if arg == "--reflink" || arg.starts_with("--reflink=") {
+            match parse_reflink(arg.strip_prefix("--reflink=")) {
+                Ok(value) => reflink = value,
+                Err(e) => {
+                    eprintln!("cp: {}", e);
+                    return Ok(());
+                }
+            }
+        } else if arg == "-b" || arg == "--backup" || arg.starts_with("--backup=") {
+            match parse_backup(arg.strip_prefix("--backup=")) {
+                Ok(value) => backup = Some(value),
+                Err(e) => {
+                    eprintln!("cp: {}", e);
+                    return Ok(());
+                }
+            }
+        } else if arg == "-S" || arg == "--suffix" {
+            match iter.next() {
+                Some(value) => {
+                    suffix = value.clone();
+                    suffix_given = true;
+                }
+                None => {
+                    eprintln!("cp: option '{}' requires an argument", arg);
+                    return Ok(());
+                }
+            }
+        } else if let Some(value) = arg.strip_prefix("--suffix=") {
+            suffix = value.to_string();
+            suffix_given = true;
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    if paths.len() != 2 {
+        eprintln!(
+            "Usage: cp [-u] [-v] [--verify] [--reflink[=auto|always|never]] \
+             [--backup[=CONTROL]] [-S SUFFIX] <source> <destination>"
+        );
         return Ok(()); // Do not panic
     }
 
-    let src = &args[0];
-    let dest = &args[1];
+    let src = paths[0];
+    let dest = paths[1];
 
-    match fs::copy(src, dest) {
-        Ok(bytes) => println!("✅ Copied {} bytes from '{}' → '{}'", bytes, src, dest),
-        Err(e) => eprintln!(" Error copying file '{}': {}", src, e),
+    // As in GNU cp, a suffix alone turns backups on
+    let backup = match backup {
+        Some(backup) => backup,
+        None if suffix_given => parse_backup(None).unwrap_or(Backup::Existing),
+        None => Backup::None,
+    };
+
+    let result = if update {
+        copy_if_newer(src, dest, reflink, verify, backup, &suffix)
+    } else {
+        copy_with_backup(src, dest, reflink, verify, backup, &suffix).map(Some)
+    };
+
+    match result {
+        Ok(Some(bytes)) => println!("✅ Copied {} bytes from '{}' → '{}'", bytes, src, dest),
+        Ok(None) => {
+            if verbose {
+                println!("skipped '{}', '{}' is up to date", src, dest);
+            }
+        }
+        Err(e) => eprintln!(" {}", e),
     }
 
     Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reflink() {
+        assert_eq!(parse_reflink(None), Ok(Reflink::Always));
+        assert_eq!(parse_reflink(Some("auto")), Ok(Reflink::Auto));
+        assert_eq!(parse_reflink(Some("never")), Ok(Reflink::Never));
+        assert!(parse_reflink(Some("sometimes")).is_err());
+    }
+
+    #[test]
+    fn test_reflink_auto_copies_identical_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("big.bin");
+        let dest = dir.path().join("copy.bin");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&src, &data).unwrap();
+
+        let bytes = copy_file(
+            &src.to_string_lossy(),
+            &dest.to_string_lossy(),
+            Reflink::Auto,
+        )
+        .unwrap();
+
+        assert_eq!(bytes, data.len() as u64);
+        assert_eq!(fs::read(&dest).unwrap(), data);
+    }
+
+    #[test]
+    fn test_copy_onto_itself_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("same.txt");
+        fs::write(&src, "keep me").unwrap();
+
+        let path = src.to_string_lossy();
+        assert!(copy_file(&path, &path, Reflink::Auto).is_err());
+        assert_eq!(fs::read_to_string(&src).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn test_sha256_known_digests() {
+        let digest = |data: &[u8]| hash_reader(data).unwrap();
+        assert_eq!(
+            digest(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            digest(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_verify_detects_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("big.bin");
+        let dest = dir.path().join("copy.bin");
+        let data: Vec<u8> = (0..300_000u32).map(|i| (i % 253) as u8).collect();
+        fs::write(&src, &data).unwrap();
+        let (src_path, dest_path) = (src.to_string_lossy(), dest.to_string_lossy());
+
+        let bytes = copy_verified(&src_path, &dest_path, Reflink::Never).unwrap();
+        assert_eq!(bytes, data.len() as u64);
+        assert_eq!(fs::read(&dest).unwrap(), data);
+
+        let (_, digest) = copy_hashing(&src_path, &dest_path).unwrap();
+        let mut corrupted = data.clone();
+        corrupted[150_000] ^= 0xff;
+        fs::write(&dest, &corrupted).unwrap();
+
+        let err = verify_copy(&digest, &dest_path).unwrap_err();
+        let dest_digest = hash_reader(corrupted.as_slice()).unwrap();
+        assert!(err.contains(&digest));
+        assert!(err.contains(&dest_digest));
+    }
+
+    #[test]
+    fn test_update_skips_newer_destination() {
+        use filetime::{FileTime, set_file_mtime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        fs::write(&src, "new contents").unwrap();
+        fs::write(&dest, "old contents").unwrap();
+        let (src_path, dest_path) = (src.to_string_lossy(), dest.to_string_lossy());
+
+        // Destination newer than the source: left alone
+        set_file_mtime(&src, FileTime::from_unix_time(1_000_000, 0)).unwrap();
+        set_file_mtime(&dest, FileTime::from_unix_time(2_000_000, 0)).unwrap();
+        assert_eq!(
+            copy_if_newer(
+                &src_path,
+                &dest_path,
+                Reflink::Never,
+                false,
+                Backup::None,
+                "~"
+            ),
+            Ok(None)
+        );
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "old contents");
+
+        // Destination older than the source: copied
+        set_file_mtime(&dest, FileTime::from_unix_time(500_000, 0)).unwrap();
+        assert_eq!(
+            copy_if_newer(
+                &src_path,
+                &dest_path,
+                Reflink::Never,
+                false,
+                Backup::None,
+                "~"
+            ),
+            Ok(Some(12))
+        );
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new contents");
+
+        // Missing destination: copied
+        fs::remove_file(&dest).unwrap();
+        assert!(needs_update(&src_path, &dest_path).unwrap());
+    }
+
+    #[test]
+    fn test_parse_backup() {
+        assert_eq!(parse_backup(Some("numbered")), Ok(Backup::Numbered));
+        assert_eq!(parse_backup(Some("t")), Ok(Backup::Numbered));
+        assert_eq!(parse_backup(Some("simple")), Ok(Backup::Simple));
+        assert_eq!(parse_backup(Some("never")), Ok(Backup::Simple));
+        assert_eq!(parse_backup(Some("off")), Ok(Backup::None));
+        assert_eq!(parse_backup(Some("ex")), Ok(Backup::Existing));
+        assert!(parse_backup(Some("n")).is_err());
+        assert!(parse_backup(Some("sometimes")).is_err());
+    }
+
+    #[test]
+    fn test_backup_keeps_old_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        fs::write(&src, "new contents").unwrap();
+        fs::write(&dest, "old contents").unwrap();
+        let (src_path, dest_path) = (src.to_string_lossy(), dest.to_string_lossy());
+
+        copy_with_backup(
+            &src_path,
+            &dest_path,
+            Reflink::Never,
+            false,
+            Backup::Simple,
+            "~",
+        )
+        .unwrap();
+
+        let backup = dir.path().join("dest.txt~");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "old contents");
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "new contents");
+
+        // A custom suffix replaces the `~`
+        copy_with_backup(
+            &src_path,
+            &dest_path,
+            Reflink::Never,
+            false,
+            Backup::Simple,
+            ".bak",
+        )
+        .unwrap();
+        assert!(dir.path().join("dest.txt.bak").exists());
+    }
+
+    #[test]
+    fn test_backup_refuses_directory_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest");
+        fs::write(&src, "new contents").unwrap();
+        fs::create_dir(&dest).unwrap();
+        fs::write(dest.join("inside.txt"), "keep").unwrap();
+        let (src_path, dest_path) = (src.to_string_lossy(), dest.to_string_lossy());
+
+        let err = copy_with_backup(
+            &src_path,
+            &dest_path,
+            Reflink::Never,
+            false,
+            Backup::Simple,
+            "~",
+        )
+        .unwrap_err();
+        assert!(err.contains("cannot overwrite directory"));
+        assert_eq!(fs::read_to_string(dest.join("inside.txt")).unwrap(), "keep");
+        assert!(!dir.path().join("dest~").exists());
+    }
+
+    #[test]
+    fn test_numbered_backups_increment() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dest.txt");
+        let (src_path, dest_path) = (src.to_string_lossy(), dest.to_string_lossy());
+        fs::write(&dest, "v0").unwrap();
+
+        for version in 1..=3 {
+            fs::write(&src, format!("v{}", version)).unwrap();
+            copy_with_backup(
+                &src_path,
+                &dest_path,
+                Reflink::Never,
+                false,
+                Backup::Numbered,
+                "~",
+            )
+            .unwrap();
+        }
+
+        for n in 1..=3 {
+            let backup = dir.path().join(format!("dest.txt.~{}~", n));
+            assert_eq!(fs::read_to_string(backup).unwrap(), format!("v{}", n - 1));
+        }
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "v3");
+
+        // `existing` keeps numbering once numbered backups are there
+        assert_eq!(
+            backup_path(&dest, Backup::Existing, "~"),
+            Some(dir.path().join("dest.txt.~4~"))
+        );
+    }
+}
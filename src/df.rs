@@ -1,29 +1,722 @@
+use crate::watch;
+use colored::Colorize;
+use crossterm::{cursor, execute, terminal};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use sysinfo::Disks;
 
-pub fn execute() {
+/// Space usage of a single mounted filesystem, in bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskStats {
+    pub name: String,
+    pub file_system: String,
+    pub mount_point: String,
+    pub total: u64,
+    pub available: u64,
+    /// Served over the network (NFS, SMB, a mapped Windows drive, ...)
+    pub remote: bool,
+    /// Mounted read-only, so nothing on it can be written
+    pub read_only: bool,
+    /// Notable mount options such as `noexec`, or drive flags such as `removable` on Windows
+    pub mount_options: Vec<String>,
+}
+
+impl DiskStats {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Fraction of the filesystem in use, between 0.0 and 1.0
+    pub fn usage_ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64
+        }
+    }
+}
+
+/// Get disk usage for TUI display
+pub fn get_disks_for_tui() -> Vec<DiskStats> {
     let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .map(|disk| {
+            let file_system = disk.file_system().to_string_lossy().to_string();
+            let (read_only, mount_options) = mount_flags(disk.mount_point())
+                .unwrap_or_else(|| (disk.is_read_only(), Vec::new()));
+            DiskStats {
+                name: disk.name().to_string_lossy().to_string(),
+                remote: is_remote(disk.mount_point(), &file_system),
+                read_only,
+                mount_options,
+                file_system,
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                total: disk.total_space(),
+                available: disk.available_space(),
+            }
+        })
+        .collect()
+}
+
+/// Filesystem types whose data lives on another machine
+#[cfg(not(windows))]
+const REMOTE_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smbfs",
+    "smb3",
+    "afs",
+    "ncpfs",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "sshfs",
+    "davfs",
+];
+
+/// Whether a mount is remote, judged by its filesystem type; FUSE types such as
+/// `fuse.sshfs` are judged by the part after the dot
+#[cfg(not(windows))]
+fn is_remote(_mount_point: &Path, file_system: &str) -> bool {
+    let fs_type = file_system.strip_prefix("fuse.").unwrap_or(file_system);
+    REMOTE_FS_TYPES.contains(&fs_type.to_ascii_lowercase().as_str())
+}
+
+/// Whether a volume is a network drive, as `GetDriveType` reports it
+#[cfg(windows)]
+fn is_remote(mount_point: &Path, _file_system: &str) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDriveTypeW;
+    use winapi::um::winbase::DRIVE_REMOTE;
+
+    let wide: Vec<u16> = mount_point
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    unsafe { GetDriveTypeW(wide.as_ptr()) == DRIVE_REMOTE }
+}
+
+/// One line of `/proc/mounts`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountEntry {
+    pub source: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub read_only: bool,
+    /// Every option other than `ro`/`rw`, in the order listed
+    pub options: Vec<String>,
+}
+
+/// Parse one `/proc/mounts` line, undoing the octal escapes (`\040` for a space) in its paths
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_mount_line(line: &str) -> Option<MountEntry> {
+    let unescape = |field: &str| {
+        let mut out = String::with_capacity(field.len());
+        let mut rest = field;
+        while let Some(pos) = rest.find('\\') {
+            out.push_str(&rest[..pos]);
+            let code = rest.get(pos + 1..pos + 4);
+            match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+                Some(byte) => {
+                    out.push(byte as char);
+                    rest = &rest[pos + 4..];
+                }
+                None => {
+                    out.push('\\');
+                    rest = &rest[pos + 1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    };
+
+    let mut fields = line.split_whitespace();
+    let source = unescape(fields.next()?);
+    let mount_point = unescape(fields.next()?);
+    let file_system = fields.next()?.to_string();
+    let mut read_only = false;
+    let mut options = Vec::new();
+    for option in fields.next()?.split(',') {
+        match option {
+            "ro" => read_only = true,
+            "rw" => read_only = false,
+            _ => options.push(option.to_string()),
+        }
+    }
+    Some(MountEntry {
+        source,
+        mount_point,
+        file_system,
+        read_only,
+        options,
+    })
+}
+
+/// Generic mount options worth showing; filesystem-specific ones like `errors=` are left out
+#[cfg(target_os = "linux")]
+const KEY_MOUNT_OPTIONS: &[&str] = &[
+    "nosuid",
+    "nodev",
+    "noexec",
+    "noatime",
+    "nodiratime",
+    "relatime",
+    "sync",
+    "dirsync",
+    "nosymfollow",
+];
+
+/// Read-only status and notable options of the mount at `mount_point`, from `/proc/mounts`
+#[cfg(target_os = "linux")]
+fn mount_flags(mount_point: &Path) -> Option<(bool, Vec<String>)> {
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    // A later mount on the same point hides the earlier ones
+    let entry = mounts
+        .lines()
+        .rev()
+        .filter_map(parse_mount_line)
+        .find(|entry| Path::new(&entry.mount_point) == mount_point)?;
+    let options = entry
+        .options
+        .into_iter()
+        .filter(|option| KEY_MOUNT_OPTIONS.contains(&option.as_str()))
+        .collect();
+    Some((entry.read_only, options))
+}
+
+/// Read-only status from the volume flags, plus the drive type and compression
+#[cfg(windows)]
+fn mount_flags(mount_point: &Path) -> Option<(bool, Vec<String>)> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::um::fileapi::{GetDriveTypeW, GetVolumeInformationW};
+    use winapi::um::winbase::{DRIVE_CDROM, DRIVE_RAMDISK, DRIVE_REMOVABLE};
+    use winapi::um::winnt::{FILE_READ_ONLY_VOLUME, FILE_VOLUME_IS_COMPRESSED};
+
+    let wide: Vec<u16> = mount_point
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    let mut flags = 0;
+    let queried = unsafe {
+        GetVolumeInformationW(
+            wide.as_ptr(),
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut flags,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if queried == 0 {
+        return None;
+    }
+
+    let mut options = Vec::new();
+    match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+        DRIVE_REMOVABLE => options.push("removable".to_string()),
+        DRIVE_CDROM => options.push("cdrom".to_string()),
+        DRIVE_RAMDISK => options.push("ramdisk".to_string()),
+        _ => {}
+    }
+    if flags & FILE_VOLUME_IS_COMPRESSED != 0 {
+        options.push("compressed".to_string());
+    }
+    Some((flags & FILE_READ_ONLY_VOLUME != 0, options))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn mount_flags(_mount_point: &Path) -> Option<(bool, Vec<String>)> {
+    None
+}
+
+/// The mount point of the filesystem holding `path`: the highest ancestor on the same device
+#[cfg(unix)]
+fn mount_point(path: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = std::fs::canonicalize(path)?;
+    let dev = std::fs::metadata(&path)?.dev();
+    let mut mount = path.as_path();
+    while let Some(parent) = mount.parent() {
+        if std::fs::metadata(parent)?.dev() != dev {
+            break;
+        }
+        mount = parent;
+    }
+    Ok(mount.to_path_buf())
+}
+
+/// Total and available bytes of the filesystem holding `path`
+#[cfg(unix)]
+fn space(path: &Path) -> io::Result<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok((stat.f_blocks as u64 * block, stat.f_bavail as u64 * block))
+}
+
+/// The volume root holding `path`, as `GetVolumePathName` reports it
+#[cfg(windows)]
+fn mount_point(path: &Path) -> io::Result<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::fileapi::GetVolumePathNameW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut root = [0u16; 261];
+    if unsafe { GetVolumePathNameW(wide.as_ptr(), root.as_mut_ptr(), root.len() as DWORD) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let len = root.iter().position(|&c| c == 0).unwrap_or(root.len());
+    Ok(PathBuf::from(OsString::from_wide(&root[..len])))
+}
+
+/// Total and available bytes of the volume holding `path`
+#[cfg(windows)]
+fn space(path: &Path) -> io::Result<(u64, u64)> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    use winapi::um::winnt::ULARGE_INTEGER;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let (mut available, mut total, mut free): (ULARGE_INTEGER, ULARGE_INTEGER, ULARGE_INTEGER) =
+        unsafe { std::mem::zeroed() };
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { (*total.QuadPart(), *available.QuadPart()) })
+}
+
+#[cfg(not(any(unix, windows)))]
+fn mount_point(_path: &Path) -> io::Result<PathBuf> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "path lookup is not supported on this platform",
+    ))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn space(path: &Path) -> io::Result<(u64, u64)> {
+    mount_point(path).map(|_| (0, 0))
+}
+
+/// Usage of the filesystem that contains `path`
+pub fn stats_for_path(path: &str) -> Result<DiskStats, String> {
+    let mount = mount_point(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?;
+    let (total, available) = space(&mount).map_err(|e| format!("{}: {}", path, e))?;
+
+    // Name and type come from the mount list; pseudo filesystems may be missing from it
+    let listed = Disks::new_with_refreshed_list();
+    let disk = listed
+        .list()
+        .iter()
+        .find(|disk| disk.mount_point() == mount);
+    let file_system = disk.map_or_else(
+        || "-".to_string(),
+        |disk| disk.file_system().to_string_lossy().to_string(),
+    );
+    let (read_only, mount_options) = mount_flags(&mount)
+        .unwrap_or_else(|| (disk.is_some_and(|disk| disk.is_read_only()), Vec::new()));
+    Ok(DiskStats {
+        name: disk.map_or_else(
+            || mount.to_string_lossy().to_string(),
+            |disk| disk.name().to_string_lossy().to_string(),
+        ),
+        remote: is_remote(&mount, &file_system),
+        read_only,
+        mount_options,
+        file_system,
+        mount_point: mount.to_string_lossy().to_string(),
+        total,
+        available,
+    })
+}
+
+/// Every mounted filesystem, or only those holding `paths` when any are given
+pub fn collect(paths: &[String]) -> Result<Vec<DiskStats>, String> {
+    if paths.is_empty() {
+        return Ok(get_disks_for_tui());
+    }
+    paths.iter().map(|path| stats_for_path(path)).collect()
+}
+
+/// Drop the remote filesystems, as `--local` does
+pub fn local_only(disks: Vec<DiskStats>) -> Vec<DiskStats> {
+    disks.into_iter().filter(|disk| !disk.remote).collect()
+}
+
+/// The filesystems `options` asks for
+fn select(options: &DfOptions) -> Result<Vec<DiskStats>, String> {
+    let disks = collect(&options.paths)?;
+    Ok(if options.local {
+        local_only(disks)
+    } else {
+        disks
+    })
+}
+
+/// A column selectable with `--output`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfField {
+    Source,
+    Fstype,
+    Size,
+    Used,
+    Avail,
+    Pcent,
+    Target,
+    /// `ro` or `rw`, then the notable mount options
+    Options,
+}
+
+const VALID_FIELDS: &str = "source,fstype,size,used,avail,pcent,target,options";
+
+impl DfField {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "source" => Ok(DfField::Source),
+            "fstype" => Ok(DfField::Fstype),
+            "size" => Ok(DfField::Size),
+            "used" => Ok(DfField::Used),
+            "avail" => Ok(DfField::Avail),
+            "pcent" => Ok(DfField::Pcent),
+            "target" => Ok(DfField::Target),
+            "options" => Ok(DfField::Options),
+            _ => Err(format!(
+                "'{}': not a valid field for --output (valid fields: {})",
+                name, VALID_FIELDS
+            )),
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            DfField::Source => "Filesystem",
+            DfField::Fstype => "Type",
+            DfField::Size => "1K-blocks",
+            DfField::Used => "Used",
+            DfField::Avail => "Avail",
+            DfField::Pcent => "Use%",
+            DfField::Target => "Mounted on",
+            DfField::Options => "Options",
+        }
+    }
+
+    fn value(self, disk: &DiskStats) -> String {
+        match self {
+            DfField::Source => disk.name.clone(),
+            DfField::Fstype => disk.file_system.clone(),
+            DfField::Size => (disk.total / 1024).to_string(),
+            DfField::Used => (disk.used() / 1024).to_string(),
+            DfField::Avail => (disk.available / 1024).to_string(),
+            DfField::Pcent if disk.total == 0 => "-".to_string(),
+            // Like GNU df, round the percentage up
+            DfField::Pcent => format!("{}%", (disk.usage_ratio() * 100.0).ceil() as u64),
+            DfField::Target => disk.mount_point.clone(),
+            DfField::Options => {
+                let mode = if disk.read_only { "ro" } else { "rw" };
+                std::iter::once(mode)
+                    .chain(disk.mount_options.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            DfField::Size | DfField::Used | DfField::Avail | DfField::Pcent
+        )
+    }
+}
+
+/// Parse the comma separated field list given to `--output`
+pub fn parse_output_fields(spec: &str) -> Result<Vec<DfField>, String> {
+    spec.split(',')
+        .map(|name| DfField::parse(name.trim()))
+        .collect()
+}
 
-    // Print header
-    println!(
-        "{:<20} {:<15} {:<15} {:<15}",
+/// Render the selected columns, one row per disk, with a header line
+pub fn render_output(disks: &[DiskStats], fields: &[DfField]) -> String {
+    let mut rows = vec![
+        fields
+            .iter()
+            .map(|f| f.header().to_string())
+            .collect::<Vec<_>>(),
+    ];
+    rows.extend(
+        disks
+            .iter()
+            .map(|disk| fields.iter().map(|f| f.value(disk)).collect()),
+    );
+
+    let widths: Vec<usize> = (0..fields.len())
+        .map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0))
+        .collect();
+
+    let mut output = String::new();
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(fields)
+            .zip(&widths)
+            .map(|((cell, field), &width)| {
+                if field.is_numeric() {
+                    format!("{:>width$}", cell, width = width)
+                } else {
+                    format!("{:<width$}", cell, width = width)
+                }
+            })
+            .collect();
+        output.push_str(cells.join(" ").trim_end());
+        output.push('\n');
+    }
+    output
+}
+
+/// When to color filesystems that cross the `--warn` threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => std::io::stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Usage percentage `--warn` uses when no value is given
+pub const DEFAULT_WARN_PERCENT: u8 = 90;
+
+/// Options accepted by the `df` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct DfOptions {
+    pub fields: Option<Vec<DfField>>,
+    pub warn: Option<u8>,
+    pub color: ColorMode,
+    /// `--watch SECONDS`: redraw at this interval until interrupted
+    pub watch: Option<Duration>,
+    /// Report only the filesystems holding these paths
+    pub paths: Vec<String>,
+    /// `-l`/`--local`: leave out network filesystems
+    pub local: bool,
+}
+
+/// Parse `--output=FIELDS`, `--warn[=PCT]`, `--color[=WHEN]`, `--watch SECONDS`, `--local` and paths
+pub fn parse_args(args: &[String]) -> Result<DfOptions, String> {
+    let mut options = DfOptions {
+        fields: None,
+        warn: None,
+        color: ColorMode::Auto,
+        watch: None,
+        paths: Vec::new(),
+        local: false,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--watch" {
+            let value = iter.next().ok_or("option '--watch' requires an argument")?;
+            options.watch = Some(watch::parse_interval(value)?);
+        } else if let Some(value) = arg.strip_prefix("--watch=") {
+            options.watch = Some(watch::parse_interval(value)?);
+        } else if let Some(spec) = arg.strip_prefix("--output=") {
+            options.fields = Some(parse_output_fields(spec)?);
+        } else if arg == "--warn" {
+            options.warn = Some(DEFAULT_WARN_PERCENT);
+        } else if let Some(value) = arg.strip_prefix("--warn=") {
+            let percent = value
+                .trim_end_matches('%')
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= 100)
+                .ok_or_else(|| format!("invalid --warn percentage '{}'", value))?;
+            options.warn = Some(percent);
+        } else if arg == "-l" || arg == "--local" {
+            options.local = true;
+        } else if arg == "--color" {
+            options.color = ColorMode::Always;
+        } else if let Some(value) = arg.strip_prefix("--color=") {
+            options.color = match value {
+                "auto" => ColorMode::Auto,
+                "always" => ColorMode::Always,
+                "never" => ColorMode::Never,
+                _ => return Err(format!("invalid argument '{}' for '--color'", value)),
+            };
+        } else if arg.starts_with('-') && arg != "-" {
+            return Err(format!("unrecognized option '{}'", arg));
+        } else {
+            options.paths.push(arg.clone());
+        }
+    }
+
+    Ok(options)
+}
+
+/// Whether `disk` is more than `percent` full
+pub fn exceeds(disk: &DiskStats, percent: u8) -> bool {
+    disk.total > 0 && disk.usage_ratio() * 100.0 > percent as f64
+}
+
+/// Render the default Disk/Total/Available/Used table
+pub fn render_table(disks: &[DiskStats]) -> String {
+    let mut output = format!(
+        "{:<20} {:<15} {:<15} {:<15}\n",
         "Disk", "Total", "Available", "Used"
     );
-    println!("{:-<65}", "");
-
-    // Print disk information in rows
-    for disk in disks.list() {
-        let total = disk.total_space();
-        let available = disk.available_space();
-        let used = total - available;
-
-        println!(
-            "{:<20} {:<15} {:<15} {:<15}",
-            format!("{:?}", disk.name()),
-            format_memory(total),
-            format_memory(available),
-            format_memory(used)
-        );
+    output.push_str(&format!("{:-<65}\n", ""));
+
+    for disk in disks {
+        output.push_str(&format!(
+            "{:<20} {:<15} {:<15} {:<15}\n",
+            format!("{:?}", disk.name),
+            format_memory(disk.total),
+            format_memory(disk.available),
+            format_memory(disk.used())
+        ));
+    }
+    output
+}
+
+/// Render `disks` per `options`, highlighting rows over the `--warn` threshold and
+/// dimming read-only filesystems. Returns the text and the exit code, which is 1 if
+/// any filesystem is over the threshold.
+pub fn report(disks: &[DiskStats], options: &DfOptions, colorize: bool) -> (String, i32) {
+    let output = match &options.fields {
+        Some(fields) => render_output(disks, fields),
+        None => render_table(disks),
+    };
+    if options.warn.is_none() && !colorize {
+        return (output, 0);
+    }
+
+    let lines: Vec<&str> = output.lines().collect();
+    let header_lines = lines.len() - disks.len();
+    let mut code = 0;
+    let mut result = String::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let disk = i.checked_sub(header_lines).map(|row| &disks[row]);
+        let over = disk.is_some_and(|disk| options.warn.is_some_and(|pct| exceeds(disk, pct)));
+        if over {
+            code = 1;
+        }
+        if over && colorize {
+            result.push_str(&line.red().bold().to_string());
+        } else if colorize && disk.is_some_and(|disk| disk.read_only) {
+            result.push_str(&line.dimmed().to_string());
+        } else {
+            result.push_str(line);
+        }
+        result.push('\n');
+    }
+    (result, code)
+}
+
+/// One `--watch` redraw: a header, then the one-shot output
+pub fn watch_frame(
+    disks: &[DiskStats],
+    options: &DfOptions,
+    colorize: bool,
+    interval: Duration,
+) -> (String, i32) {
+    let (output, code) = report(disks, options, colorize);
+    let frame = format!(" Every {:.1}s: df\n\n{}", interval.as_secs_f64(), output);
+    (frame, code)
+}
+
+/// Redraw the report every `interval` until Ctrl+C or `q`, returning the last exit code.
+/// Raw mode turns Ctrl+C into a key press, so the cursor is always restored on the way out.
+fn watch(options: &DfOptions, colorize: bool, interval: Duration) -> i32 {
+    let mut stdout = io::stdout();
+    if let Err(e) = terminal::enable_raw_mode() {
+        eprintln!("df: cannot watch without a terminal: {}", e);
+        return 1;
+    }
+    let _ = execute!(stdout, cursor::Hide);
+
+    let result = loop {
+        let disks = match select(options) {
+            Ok(disks) => disks,
+            Err(e) => break Err(io::Error::other(e)),
+        };
+        let (frame, code) = watch_frame(&disks, options, colorize, interval);
+        if let Err(e) = watch::draw_frame(&mut stdout, &frame) {
+            break Err(e);
+        }
+        match watch::wait_for_quit(interval) {
+            Ok(false) => continue,
+            Ok(true) => break Ok(code),
+            Err(e) => break Err(e),
+        }
+    };
+
+    let _ = execute!(stdout, cursor::Show);
+    let _ = terminal::disable_raw_mode();
+    result.unwrap_or_else(|e| {
+        eprintln!("df: {}", e);
+        1
+    })
+}
+
+pub fn execute(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("df: {}", e);
+            return 1;
+        }
+    };
+
+    let colorize = options.color.enabled();
+    // `colored` has its own terminal detection; make it follow --color instead
+    colored::control::set_override(colorize);
+    if let Some(interval) = options.watch {
+        let code = watch(&options, colorize, interval);
+        colored::control::unset_override();
+        return code;
     }
+    let disks = match select(&options) {
+        Ok(disks) => disks,
+        Err(e) => {
+            colored::control::unset_override();
+            eprintln!("df: {}", e);
+            return 1;
+        }
+    };
+    let (output, code) = report(&disks, &options, colorize);
+    colored::control::unset_override();
+
+    print!("{}", output);
+    code
 }
 
 fn format_memory(bytes: u64) -> String {
@@ -41,3 +734,194 @@ fn format_memory(bytes: u64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_disks() -> Vec<DiskStats> {
+        vec![
+            DiskStats {
+                name: "disk0".to_string(),
+                file_system: "NTFS".to_string(),
+                mount_point: "C:\\".to_string(),
+                total: 100 * 1024,
+                available: 40 * 1024,
+                remote: false,
+                read_only: false,
+                mount_options: Vec::new(),
+            },
+            DiskStats {
+                name: "disk1".to_string(),
+                file_system: "exFAT".to_string(),
+                mount_point: "D:\\".to_string(),
+                total: 3 * 1024,
+                available: 2 * 1024,
+                remote: false,
+                read_only: false,
+                mount_options: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_output_target_pcent() {
+        let fields = parse_output_fields("target,pcent").unwrap();
+        let output = render_output(&sample_disks(), &fields);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            ["Mounted on Use%", "C:\\         60%", "D:\\         34%"]
+        );
+    }
+
+    #[test]
+    fn test_warn_threshold_sets_exit_code() {
+        let mut disks = sample_disks();
+        disks.push(DiskStats {
+            name: "disk2".to_string(),
+            file_system: "NTFS".to_string(),
+            mount_point: "E:\\".to_string(),
+            total: 100 * 1024,
+            available: 5 * 1024,
+            remote: false,
+            read_only: false,
+            mount_options: Vec::new(),
+        });
+
+        let options = parse_args(&["--warn=90".to_string(), "--color=never".to_string()]).unwrap();
+        assert_eq!(options.warn, Some(90));
+        assert_eq!(options.color, ColorMode::Never);
+
+        let (output, code) = report(&disks, &options, false);
+        assert_eq!(code, 1);
+        assert_eq!(output, render_table(&disks));
+
+        let (_, code) = report(&disks[..2], &options, false);
+        assert_eq!(code, 0);
+
+        let options = parse_args(&["--warn".to_string()]).unwrap();
+        assert_eq!(options.warn, Some(DEFAULT_WARN_PERCENT));
+        assert!(parse_args(&["--warn=150".to_string()]).is_err());
+        assert!(parse_args(&["--color=sometimes".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_watch_frame_matches_one_shot() {
+        let options = parse_args(&["--watch".to_string(), "2".to_string()]).unwrap();
+        assert_eq!(options.watch, Some(Duration::from_secs(2)));
+
+        let disks = sample_disks();
+        let (frame, code) = watch_frame(&disks, &options, false, Duration::from_secs(2));
+        let (one_shot, one_shot_code) = report(&disks, &options, false);
+        assert_eq!(code, one_shot_code);
+        assert!(frame.starts_with(" Every 2.0s: df"));
+        assert!(frame.ends_with(&one_shot));
+        assert_eq!(one_shot, render_table(&disks));
+
+        assert!(parse_args(&["--watch=0".to_string()]).is_err());
+        assert!(parse_args(&["--watch".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_path_reports_containing_mount() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("inside.txt");
+        std::fs::write(&file, "x").unwrap();
+
+        let options = parse_args(&[file.to_string_lossy().to_string()]).unwrap();
+        let disks = collect(&options.paths).unwrap();
+        assert_eq!(disks.len(), 1);
+
+        let mount = Path::new(&disks[0].mount_point);
+        assert!(std::fs::canonicalize(&file).unwrap().starts_with(mount));
+        assert!(disks[0].total > 0);
+
+        // The mount point itself reports the same filesystem
+        let own = stats_for_path(&disks[0].mount_point).unwrap();
+        assert_eq!(own.mount_point, disks[0].mount_point);
+        assert_eq!(own.total, disks[0].total);
+        if let Some(listed) = get_disks_for_tui()
+            .into_iter()
+            .find(|disk| disk.mount_point == disks[0].mount_point)
+        {
+            assert_eq!(listed.total, disks[0].total);
+        }
+
+        assert!(stats_for_path(&dir.path().join("missing").to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn test_local_filters_remote_mounts() {
+        let mut disks = sample_disks();
+        disks.push(DiskStats {
+            name: "fileserver:/export".to_string(),
+            file_system: "nfs4".to_string(),
+            mount_point: "/mnt/share".to_string(),
+            total: 10 * 1024,
+            available: 1024,
+            remote: true,
+            read_only: false,
+            mount_options: Vec::new(),
+        });
+
+        let options = parse_args(&["--local".to_string()]).unwrap();
+        assert!(options.local);
+        assert!(parse_args(&["-l".to_string()]).unwrap().local);
+
+        let local = local_only(disks.clone());
+        assert_eq!(local, sample_disks());
+        assert!(!render_table(&local).contains("fileserver"));
+        assert!(render_table(&disks).contains("fileserver"));
+
+        #[cfg(not(windows))]
+        {
+            assert!(is_remote(Path::new("/mnt/share"), "nfs4"));
+            assert!(is_remote(Path::new("/mnt/box"), "fuse.sshfs"));
+            assert!(!is_remote(Path::new("/"), "ext4"));
+        }
+    }
+
+    #[test]
+    fn test_parse_mount_line_and_options_field() {
+        let entry = parse_mount_line(
+            "/dev/sdb1 /mnt/backup\\040disk ext4 ro,nosuid,nodev,relatime,errors=remount-ro 0 0",
+        )
+        .unwrap();
+        assert_eq!(entry.source, "/dev/sdb1");
+        assert_eq!(entry.mount_point, "/mnt/backup disk");
+        assert_eq!(entry.file_system, "ext4");
+        assert!(entry.read_only);
+        assert_eq!(
+            entry.options,
+            ["nosuid", "nodev", "relatime", "errors=remount-ro"]
+        );
+
+        let entry = parse_mount_line("proc /proc proc rw,nosuid,nodev,noexec 0 0").unwrap();
+        assert!(!entry.read_only);
+        assert_eq!(entry.options, ["nosuid", "nodev", "noexec"]);
+        assert!(parse_mount_line("truncated /mnt").is_none());
+
+        let mut disks = sample_disks();
+        disks[1].read_only = true;
+        disks[1].mount_options = vec!["removable".to_string()];
+        let fields = parse_output_fields("target,options").unwrap();
+        let output = render_output(&disks, &fields);
+        assert_eq!(
+            output.lines().collect::<Vec<_>>(),
+            [
+                "Mounted on Options",
+                "C:\\        rw",
+                "D:\\        ro,removable"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_rejects_unknown_field() {
+        let err = parse_output_fields("target,bogus").unwrap_err();
+        assert!(err.contains("'bogus'"));
+        assert!(err.contains(VALID_FIELDS));
+    }
+}
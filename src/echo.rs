@@ -1,6 +1,69 @@
 use std::io::{self, Write};
 
+/// Write `args` separated by spaces and a trailing newline, then flush
+/// so the text lands before anything written after it
+pub fn echo_to<W: Write>(writer: &mut W, args: &[String]) -> io::Result<()> {
+    writeln!(writer, "{}", args.join(" "))?;
+    writer.flush()
+}
+
+/// `text` `n` times, joined by `sep`
+pub fn repeat(text: &str, n: usize, sep: &str) -> String {
+    vec![text; n].join(sep)
+}
+
+/// Echo `args` to `writer`, honoring leading `--repeat N` and `--sep SEP` options.
+/// `--repeat 0` writes nothing at all, not even the newline.
+pub fn echo_with_options<W: Write>(writer: &mut W, args: &[String]) -> Result<(), String> {
+    let mut count = None;
+    let mut separator = " ".to_string();
+    let mut rest = args;
+
+    while let Some(arg) = rest.first() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        if name != "--repeat" && name != "--sep" {
+            break;
+        }
+        let value = match inline {
+            Some(value) => value,
+            None => {
+                rest = &rest[1..];
+                rest.first()
+                    .cloned()
+                    .ok_or_else(|| format!("option '{}' requires an argument", name))?
+            }
+        };
+        if name == "--repeat" {
+            count = Some(
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid repeat count '{}'", value))?,
+            );
+        } else {
+            separator = value;
+        }
+        rest = &rest[1..];
+    }
+
+    let result = match count {
+        Some(0) => Ok(()),
+        Some(n) => echo_to(writer, &[repeat(&rest.join(" "), n, &separator)]),
+        None => echo_to(writer, rest),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Entry point for the `echo` shell command; a leading `--stderr` writes to stderr
 pub fn run(args: &[String]) {
-    print!("{}", args.join(" "));
-    io::stdout().flush().unwrap();
+    let result = match args.first().map(String::as_str) {
+        Some("--stderr") => echo_with_options(&mut io::stderr().lock(), &args[1..]),
+        _ => echo_with_options(&mut io::stdout().lock(), args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("echo: {}", e);
+    }
 }
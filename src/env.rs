@@ -1,15 +1,53 @@
 use std::collections::HashMap;
 use std::env as std_env;
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use colored::*;
 
+/// Shell syntax for `--export` output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `export KEY="VALUE"` for sh, bash, zsh and `.env` loaders
+    #[default]
+    Sh,
+    /// `set "KEY=VALUE"` for cmd.exe batch files
+    Cmd,
+    /// `$env:KEY = 'VALUE'` for PowerShell
+    PowerShell,
+}
+
+/// Separator between entries of PATH-like variables
+#[cfg(windows)]
+pub const PATH_SEPARATOR: char = ';';
+/// Separator between entries of PATH-like variables
+#[cfg(not(windows))]
+pub const PATH_SEPARATOR: char = ':';
+
+/// An `--append` or `--prepend` to a PATH-like variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListEdit {
+    name: String,
+    value: String,
+    prepend: bool,
+}
+
 /// Configuration for the env command
 #[derive(Debug, Default)]
 struct EnvConfig {
     ignore_environment: bool,
+    /// Variables carried over even with `ignore_environment`
+    keep_vars: Vec<String>,
     unset_vars: Vec<String>,
     set_vars: HashMap<String, String>,
+    /// Applied in order after `set_vars`
+    list_edits: Vec<ListEdit>,
     null_terminate: bool,
+    /// Trace what is about to run on stderr
+    debug: bool,
+    /// Print the environment as a script instead of `KEY=VALUE` lines
+    export: Option<ExportFormat>,
     command_args: Vec<String>,
 }
 
@@ -61,10 +99,44 @@ fn parse_arguments(args: &[String]) -> EnvResult<EnvConfig> {
                     return Err("env: option requires an argument -- 'u'".to_string());
                 }
             }
+            "--append" | "--prepend" => {
+                let Some(assignment) = args.get(i + 1) else {
+                    return Err(format!("env: option '{}' requires an argument", arg));
+                };
+                config.list_edits.push(parse_list_edit(assignment, arg == "--prepend")?);
+                i += 2;
+            }
+            arg if arg.starts_with("--append=") || arg.starts_with("--prepend=") => {
+                let (option, assignment) = arg.split_once('=').unwrap();
+                config.list_edits.push(parse_list_edit(assignment, option == "--prepend")?);
+                i += 1;
+            }
             "-0" | "--null" => {
                 config.null_terminate = true;
                 i += 1;
             }
+            "-v" | "--debug" => {
+                config.debug = true;
+                i += 1;
+            }
+            "--export" => {
+                config.export.get_or_insert_default();
+                i += 1;
+            }
+            arg if arg.starts_with("--format=") => {
+                config.export = Some(match &arg["--format=".len()..] {
+                    "sh" | "bash" => ExportFormat::Sh,
+                    "cmd" => ExportFormat::Cmd,
+                    "powershell" | "pwsh" => ExportFormat::PowerShell,
+                    other => {
+                        return Err(format!(
+                            "env: invalid format '{}' (expected sh, cmd or powershell)",
+                            other
+                        ));
+                    }
+                });
+                i += 1;
+            }
             "--help" => {
                 show_help();
                 return Err("".to_string()); // Special case: help shown, exit cleanly
@@ -112,8 +184,41 @@ fn parse_variable_assignment(arg: &str, set_vars: &mut HashMap<String, String>)
     }
 }
 
+/// Parse the VAR=VALUE of `--append` or `--prepend`
+fn parse_list_edit(arg: &str, prepend: bool) -> EnvResult<ListEdit> {
+    let Some((name, value)) = arg.split_once('=') else {
+        return Err(format!("env: invalid assignment: '{}'", arg));
+    };
+    if !is_valid_var_name(name) {
+        return Err(format!("env: invalid variable name: '{}'", name));
+    }
+    if value.is_empty() {
+        return Err(format!("env: empty value to add to '{}'", name));
+    }
+    Ok(ListEdit {
+        name: name.to_string(),
+        value: value.to_string(),
+        prepend,
+    })
+}
+
+/// Add `value` to the front or back of a PATH-like list, dropping the separator
+/// when there is nothing to join with
+pub fn join_list(existing: Option<&str>, value: &str, prepend: bool) -> String {
+    match existing {
+        Some(existing) if !existing.is_empty() => {
+            if prepend {
+                format!("{}{}{}", value, PATH_SEPARATOR, existing)
+            } else {
+                format!("{}{}{}", existing, PATH_SEPARATOR, value)
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
 /// Check if a variable name is valid
-fn is_valid_var_name(name: &str) -> bool {
+pub fn is_valid_var_name(name: &str) -> bool {
     if name.is_empty() {
         return false;
     }
@@ -147,7 +252,51 @@ fn display_modified_environment(config: &EnvConfig) {
     let env_vars = build_modified_environment(config);
     let mut sorted_vars: Vec<_> = env_vars.into_iter().collect();
     sorted_vars.sort_by(|a, b| a.0.cmp(&b.0));
-    print_env_vars(&sorted_vars, config.null_terminate);
+    match config.export {
+        Some(format) => {
+            for (key, value) in &sorted_vars {
+                if let Some(line) = export_line(key, value, format) {
+                    println!("{}", line);
+                }
+            }
+        }
+        None => print_env_vars(&sorted_vars, config.null_terminate),
+    }
+}
+
+/// One variable as a line of `format` script that sets it when sourced.
+/// Names a POSIX shell can't assign, such as `ProgramFiles(x86)`, give `None` for `Sh`.
+pub fn export_line(key: &str, value: &str, format: ExportFormat) -> Option<String> {
+    match format {
+        ExportFormat::Sh => {
+            if !is_valid_var_name(key) {
+                return None;
+            }
+            let mut quoted = String::with_capacity(value.len());
+            for c in value.chars() {
+                if matches!(c, '"' | '\\' | '$' | '`') {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            Some(format!("export {}=\"{}\"", key, quoted))
+        }
+        // Quoting the whole assignment keeps & | < > ^ literal; % still expands in batch files
+        ExportFormat::Cmd => Some(format!(
+            "set \"{}={}\"",
+            key.replace('%', "%%"),
+            value.replace('%', "%%")
+        )),
+        // Single quotes expand nothing, so only the quote itself needs doubling
+        ExportFormat::PowerShell => {
+            let value = value.replace('\'', "''");
+            if is_valid_var_name(key) {
+                Some(format!("$env:{} = '{}'", key, value))
+            } else {
+                Some(format!("${{env:{}}} = '{}'", key.replace('}', "`}"), value))
+            }
+        }
+    }
 }
 
 /// Build the modified environment based on configuration
@@ -159,6 +308,12 @@ fn build_modified_environment(config: &EnvConfig) -> HashMap<String, String> {
         for (key, value) in std_env::vars() {
             env_vars.insert(key, value);
         }
+    } else {
+        for key in &config.keep_vars {
+            if let Ok(value) = std_env::var(key) {
+                env_vars.insert(key.clone(), value);
+            }
+        }
     }
 
     // Remove unset variables
@@ -171,9 +326,31 @@ fn build_modified_environment(config: &EnvConfig) -> HashMap<String, String> {
         env_vars.insert(key.clone(), value.clone());
     }
 
+    for edit in &config.list_edits {
+        // Windows keeps PATH as `Path`; edit it under the name it already has
+        let key = env_vars
+            .keys()
+            .find(|key| same_var_name(key, &edit.name))
+            .cloned()
+            .unwrap_or_else(|| edit.name.clone());
+        let existing = env_vars.get(&key).map(String::as_str);
+        let joined = join_list(existing, &edit.value, edit.prepend);
+        env_vars.insert(key, joined);
+    }
+
     env_vars
 }
 
+/// The current environment, or with `keep` only the listed variables that are set
+pub fn inherited_environment(keep: Option<&[String]>) -> HashMap<String, String> {
+    let config = EnvConfig {
+        ignore_environment: keep.is_some(),
+        keep_vars: keep.unwrap_or_default().to_vec(),
+        ..EnvConfig::default()
+    };
+    build_modified_environment(&config)
+}
+
 /// Print environment variables
 fn print_env_vars(vars: &[(String, String)], null_terminate: bool) {
     for (key, value) in vars {
@@ -185,6 +362,57 @@ fn print_env_vars(vars: &[(String, String)], null_terminate: bool) {
     }
 }
 
+/// Write the `-v` trace of the environment changes and the command about to run,
+/// in the same layout as GNU env
+fn write_debug_trace<W: Write>(writer: &mut W, config: &EnvConfig) -> io::Result<()> {
+    if config.ignore_environment {
+        writeln!(writer, "cleaning environ")?;
+    }
+    for var in &config.unset_vars {
+        writeln!(writer, "unset:    {}", var)?;
+    }
+    let mut set_vars: Vec<_> = config.set_vars.iter().collect();
+    set_vars.sort();
+    for (key, value) in set_vars {
+        writeln!(writer, "setenv:   {}={}", key, value)?;
+    }
+    for edit in &config.list_edits {
+        let option = if edit.prepend { "prepend" } else { "append" };
+        writeln!(writer, "{}:  {}={}", option, edit.name, edit.value)?;
+    }
+
+    if let Some(program) = config.command_args.first() {
+        match resolve_program(program, &build_modified_environment(config)) {
+            Some(path) => writeln!(writer, "executing: {} ({})", program, path.display())?,
+            None => writeln!(writer, "executing: {} (not found in PATH)", program)?,
+        }
+        for (i, arg) in config.command_args.iter().enumerate() {
+            writeln!(writer, "   arg[{}]= '{}'", i, arg)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether two variable names refer to the same variable; Windows ignores case
+fn same_var_name(a: &str, b: &str) -> bool {
+    if cfg!(windows) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Find `program` the way GNU env does: a bare name is searched for in the PATH of `env`,
+/// which may differ from ours when PATH is set on the same line
+fn resolve_program(program: &str, env: &HashMap<String, String>) -> Option<PathBuf> {
+    let path = env
+        .iter()
+        .find(|(key, _)| same_var_name(key, "PATH"))
+        .map(|(_, value)| value);
+    let cwd = std_env::current_dir().ok()?;
+    which::which_in(program, path, cwd).ok()
+}
+
 /// Run a command with modified environment
 /// Returns the exit code of the executed command
 fn run_command_with_env(config: &EnvConfig) -> i32 {
@@ -196,8 +424,16 @@ fn run_command_with_env(config: &EnvConfig) -> i32 {
     let program = &config.command_args[0];
     let args = &config.command_args[1..];
 
-    // Try to run directly first
-    let status = run_directly(program, args, config);
+    if config.debug {
+        let _ = write_debug_trace(&mut io::stderr(), config);
+    }
+
+    // Try to run directly first, resolved against the child's PATH rather than ours
+    let resolved = resolve_program(program, &build_modified_environment(config));
+    let target = resolved
+        .as_deref()
+        .map_or(OsStr::new(program), Path::as_os_str);
+    let status = run_directly(target, args, config);
 
     match status {
         Ok(exit_status) => {
@@ -206,6 +442,12 @@ fn run_command_with_env(config: &EnvConfig) -> i32 {
         Err(e) => {
             // If direct execution fails, it might be a shell built-in or need shell expansion
             // Try with shell
+            if config.debug {
+                eprintln!(
+                    "env: cannot run '{}' directly ({}), retrying through the shell",
+                    program, e
+                );
+            }
             match run_with_shell(program, args, config) {
                 Ok(exit_status) => exit_status.code().unwrap_or(1),
                 Err(_shell_err) => {
@@ -218,7 +460,7 @@ fn run_command_with_env(config: &EnvConfig) -> i32 {
 }
 
 /// Run command directly without shell
-fn run_directly(program: &str, args: &[String], config: &EnvConfig) -> Result<std::process::ExitStatus, std::io::Error> {
+fn run_directly(program: &OsStr, args: &[String], config: &EnvConfig) -> Result<std::process::ExitStatus, std::io::Error> {
     let mut cmd = Command::new(program);
     cmd.args(args);
     apply_environment_to_command(&mut cmd, config);
@@ -323,7 +565,12 @@ fn run_with_shell(program: &str, args: &[String], config: &EnvConfig) -> Result<
 #[allow(dead_code)]
 /// Expand environment variables in a string
 fn expand_env_vars(input: &str, config: &EnvConfig) -> String {
-    let env_map = build_modified_environment(config);
+    expand_vars(input, &build_modified_environment(config))
+}
+
+/// Expand `$VAR`, `${VAR}` (and `%VAR%` on Windows) against the given variables,
+/// leaving unknown variables as written
+pub fn expand_vars(input: &str, env_map: &HashMap<String, String>) -> String {
     let mut result = String::new();
     let mut chars = input.chars().peekable();
 
@@ -436,6 +683,11 @@ fn expand_env_vars(input: &str, config: &EnvConfig) -> String {
 fn apply_environment_to_command(cmd: &mut Command, config: &EnvConfig) {
     if config.ignore_environment {
         cmd.env_clear();
+        for key in &config.keep_vars {
+            if let Ok(value) = std_env::var(key) {
+                cmd.env(key, value);
+            }
+        }
     }
 
     // Remove unset variables
@@ -447,6 +699,16 @@ fn apply_environment_to_command(cmd: &mut Command, config: &EnvConfig) {
     for (key, value) in &config.set_vars {
         cmd.env(key, value);
     }
+
+    if !config.list_edits.is_empty() {
+        let env_vars = build_modified_environment(config);
+        for edit in &config.list_edits {
+            let edited = env_vars.iter().find(|(key, _)| same_var_name(key, &edit.name));
+            if let Some((key, value)) = edited {
+                cmd.env(key, value);
+            }
+        }
+    }
 }
 
 /// Show help information
@@ -459,7 +721,12 @@ fn show_help() {
     println!("{}", "OPTIONS:".bold());
     println!("    -i, --ignore-environment    Start with an empty environment");
     println!("    -u, --unset NAME            Remove variable NAME from the environment");
+    println!("    --append VAR=VALUE          Add VALUE to the end of the list in VAR");
+    println!("    --prepend VAR=VALUE         Add VALUE to the start of the list in VAR");
     println!("    -0, --null                  End each output line with NUL, not newline");
+    println!("    -v, --debug                 Print verbose information for each processing step");
+    println!("    --export                    Print the environment as a script that can be sourced");
+    println!("    --format=FORMAT             Script syntax for --export: sh (default), cmd or powershell");
     println!("    --version                   Output version information and exit");
     println!("    --help                      Display this help and exit");
     println!();
@@ -471,6 +738,8 @@ fn show_help() {
     println!("    env                         Display all environment variables");
     println!("    env -i                      Display empty environment");
     println!("    env -u PATH                 Display environment without PATH");
+    println!("    env --export > vars.env     Save the environment as a sourceable script");
+    println!("    env --prepend PATH=/opt/bin prog  Run prog with /opt/bin searched first");
 
     #[cfg(windows)]
     {
@@ -613,6 +882,153 @@ mod tests {
         let env = build_modified_environment(&config);
         assert_eq!(env.len(), 1);
         assert_eq!(env.get("TEST_VAR"), Some(&"test_value".to_string()));
+
+        // Kept variables survive an ignored environment
+        config.keep_vars.push("PATH".to_string());
+        let env = build_modified_environment(&config);
+        assert_eq!(env.get("PATH"), std_env::var("PATH").ok().as_ref());
+    }
+
+    #[test]
+    fn test_append_keeps_existing_path() {
+        let parse = |list: &[&str]| {
+            parse_arguments(&list.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+        };
+        let config = parse(&["--append", "PATH=/extra", "--prepend=PATH=/opt/bin"]).unwrap();
+        let env = build_modified_environment(&config);
+        let existing = std_env::var("PATH").unwrap();
+        let sep = PATH_SEPARATOR.to_string();
+        assert_eq!(env["PATH"], ["/opt/bin", existing.as_str(), "/extra"].join(&sep));
+
+        // Nothing to join with on an empty environment
+        let config = parse(&["-i", "--append", "WINIX_LIST=a"]).unwrap();
+        assert_eq!(build_modified_environment(&config)["WINIX_LIST"], "a");
+
+        assert!(parse(&["--append", "PATH"]).is_err());
+        assert!(parse(&["--prepend", "1BAD=x"]).is_err());
+        assert!(parse(&["--append"]).is_err());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_prepend_finds_path_regardless_of_case() {
+        let (key, existing) = std_env::vars()
+            .find(|(key, _)| key.eq_ignore_ascii_case("PATH"))
+            .expect("PATH should be set");
+
+        let args = vec!["--prepend".to_string(), r"PATH=C:\winix\bin".to_string()];
+        let env = build_modified_environment(&parse_arguments(&args).unwrap());
+        assert_eq!(env[&key], format!(r"C:\winix\bin;{}", existing));
+        let paths = env.keys().filter(|name| name.eq_ignore_ascii_case("PATH"));
+        assert_eq!(paths.count(), 1);
+    }
+
+    #[test]
+    fn test_path_separator_matches_platform() {
+        assert_eq!(PATH_SEPARATOR, if cfg!(windows) { ';' } else { ':' });
+        let joined = join_list(Some("a"), "b", false);
+        let split: Vec<_> = std_env::split_paths(&joined).collect();
+        assert_eq!(split, [PathBuf::from("a"), PathBuf::from("b")]);
+    }
+
+    #[test]
+    fn test_export_line_quoting() {
+        let value = r#"say "hi" to $USER's `pc` \ 100% & more"#;
+
+        assert_eq!(
+            export_line("GREETING", value, ExportFormat::Sh).unwrap(),
+            r#"export GREETING="say \"hi\" to \$USER's \`pc\` \\ 100% & more""#
+        );
+        assert_eq!(
+            export_line("GREETING", value, ExportFormat::Cmd).unwrap(),
+            r#"set "GREETING=say "hi" to $USER's `pc` \ 100%% & more""#
+        );
+        assert_eq!(
+            export_line("GREETING", value, ExportFormat::PowerShell).unwrap(),
+            r#"$env:GREETING = 'say "hi" to $USER''s `pc` \ 100% & more'"#
+        );
+
+        assert_eq!(export_line("ProgramFiles(x86)", "C:\\x", ExportFormat::Sh), None);
+        assert_eq!(
+            export_line("ProgramFiles(x86)", "C:\\x", ExportFormat::PowerShell).unwrap(),
+            r"${env:ProgramFiles(x86)} = 'C:\x'"
+        );
+    }
+
+    #[test]
+    fn test_export_format_option() {
+        let parse = |list: &[&str]| {
+            parse_arguments(&list.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(parse(&["--export"]).unwrap().export, Some(ExportFormat::Sh));
+        assert_eq!(
+            parse(&["--export", "--format=powershell"]).unwrap().export,
+            Some(ExportFormat::PowerShell)
+        );
+        assert_eq!(parse(&["--format=cmd"]).unwrap().export, Some(ExportFormat::Cmd));
+        assert_eq!(parse(&["A=1"]).unwrap().export, None);
+        assert!(parse(&["--format=fish"]).is_err());
+    }
+
+    #[test]
+    fn test_export_round_trips_through_sh() {
+        let value = r#"two words, "quotes" and $dollars"#;
+        let line = export_line("WINIX_EXPORT_TEST", value, ExportFormat::Sh).unwrap();
+        let script = format!("{}\nprintf '%s' \"$WINIX_EXPORT_TEST\"", line);
+        let Ok(output) = Command::new("sh").arg("-c").arg(&script).output() else {
+            return; // no POSIX shell to check against
+        };
+        assert_eq!(String::from_utf8_lossy(&output.stdout), value);
+    }
+
+    #[test]
+    fn test_debug_trace() {
+        let args: Vec<String> = ["-v", "-u", "OLD", "GREETING=hello", "echo", "hi"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let config = parse_arguments(&args).unwrap();
+        assert!(config.debug);
+
+        let mut stderr = Vec::new();
+        write_debug_trace(&mut stderr, &config).unwrap();
+        let trace = String::from_utf8(stderr).unwrap();
+
+        assert!(trace.contains("unset:    OLD"));
+        assert!(trace.contains("setenv:   GREETING=hello"));
+        assert!(trace.contains("executing: echo"));
+        assert!(trace.contains("arg[0]= 'echo'"));
+        assert!(trace.contains("arg[1]= 'hi'"));
+    }
+
+    #[test]
+    fn test_custom_path_resolves_program() {
+        let dir = tempfile::tempdir().unwrap();
+        #[cfg(windows)]
+        let script = {
+            let script = dir.path().join("winix-env-probe.bat");
+            std::fs::write(&script, "@exit /b 42\r\n").unwrap();
+            script
+        };
+        #[cfg(not(windows))]
+        let script = {
+            use std::os::unix::fs::PermissionsExt;
+            let script = dir.path().join("winix-env-probe");
+            std::fs::write(&script, "#!/bin/sh\nexit 42\n").unwrap();
+            std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+            script
+        };
+
+        let args = vec![
+            format!("PATH={}", dir.path().display()),
+            "winix-env-probe".to_string(),
+        ];
+        let config = parse_arguments(&args).unwrap();
+        assert_eq!(
+            resolve_program("winix-env-probe", &build_modified_environment(&config)),
+            Some(script)
+        );
+        assert_eq!(run_command_with_env(&config), 42);
     }
 
     #[test]
@@ -1,13 +1,262 @@
+use serde::{Deserialize, Serialize};
 use sysinfo::System;
 
-pub fn execute() {
+/// Memory and swap usage in bytes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryStats {
+    pub total: u64,
+    pub used: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+}
+
+impl MemoryStats {
+    /// Fraction of physical memory in use, between 0.0 and 1.0
+    pub fn usage_ratio(&self) -> f64 {
+        ratio(self.used, self.total)
+    }
+
+    /// Fraction of swap in use, between 0.0 and 1.0
+    pub fn swap_ratio(&self) -> f64 {
+        ratio(self.used_swap, self.total_swap)
+    }
+}
+
+fn ratio(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (used as f64 / total as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// Get memory usage for TUI display
+pub fn get_memory_for_tui() -> MemoryStats {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    println!("Used memory : {}", format_memory(sys.used_memory()));
-    println!("Total memory: {}", format_memory(sys.total_memory()));
-    println!("Total swap  : {}", format_memory(sys.total_swap()));
-    println!("Used swap   : {}", format_memory(sys.used_swap()));
+    MemoryStats {
+        total: sys.total_memory(),
+        used: sys.used_memory(),
+        total_swap: sys.total_swap(),
+        used_swap: sys.used_swap(),
+    }
+}
+
+/// Breakdown shown by the tabular `free` output, in bytes.
+/// Fields `/proc/meminfo` alone provides are `None` on other platforms.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub shared: Option<u64>,
+    pub buffers: Option<u64>,
+    pub cache: Option<u64>,
+    pub total_swap: u64,
+    pub free_swap: u64,
+}
+
+impl MemInfo {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    pub fn used_swap(&self) -> u64 {
+        self.total_swap.saturating_sub(self.free_swap)
+    }
+}
+
+/// Parse the contents of `/proc/meminfo`; cache counts `Cached` plus `SReclaimable` like procps
+pub fn parse_meminfo(text: &str) -> MemInfo {
+    let mut fields = std::collections::HashMap::new();
+    for line in text.lines() {
+        if let Some((key, rest)) = line.split_once(':') {
+            let kib = rest.trim().trim_end_matches("kB").trim();
+            if let Ok(value) = kib.parse::<u64>() {
+                fields.insert(key.trim(), value * 1024);
+            }
+        }
+    }
+    let get = |key: &str| fields.get(key).copied().unwrap_or(0);
+
+    MemInfo {
+        total: get("MemTotal"),
+        free: get("MemFree"),
+        available: get("MemAvailable"),
+        shared: Some(get("Shmem")),
+        buffers: Some(get("Buffers")),
+        cache: Some(get("Cached") + get("SReclaimable")),
+        total_swap: get("SwapTotal"),
+        free_swap: get("SwapFree"),
+    }
+}
+
+/// Read the current memory breakdown, from `/proc/meminfo` where available
+pub(crate) fn collect_meminfo() -> MemInfo {
+    #[cfg(target_os = "linux")]
+    if let Ok(text) = std::fs::read_to_string("/proc/meminfo") {
+        return parse_meminfo(&text);
+    }
+
+    let mut sys = System::new();
+    sys.refresh_memory();
+    MemInfo {
+        total: sys.total_memory(),
+        free: sys.free_memory(),
+        available: sys.available_memory(),
+        shared: None,
+        buffers: None,
+        cache: None,
+        total_swap: sys.total_swap(),
+        free_swap: sys.free_swap(),
+    }
+}
+
+/// How `free` scales the numbers in its table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Units {
+    /// Powers of 1024 (the default)
+    #[default]
+    Binary,
+    /// `--si`: powers of 1000
+    Si,
+    /// `--bytes`: exact byte counts
+    Bytes,
+}
+
+/// One-shot `--json` snapshot of every memory and swap field, in bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+    pub shared: Option<u64>,
+    pub buffers: Option<u64>,
+    pub cache: Option<u64>,
+    pub available: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    pub free_swap: u64,
+}
+
+impl From<&MemInfo> for MemorySnapshot {
+    fn from(info: &MemInfo) -> Self {
+        MemorySnapshot {
+            total: info.total,
+            used: info.used(),
+            free: info.free,
+            shared: info.shared,
+            buffers: info.buffers,
+            cache: info.cache,
+            available: info.available,
+            total_swap: info.total_swap,
+            used_swap: info.used_swap(),
+            free_swap: info.free_swap,
+        }
+    }
+}
+
+/// Render the procps-style table; `wide` splits buff/cache into two columns
+pub fn render_table(info: &MemInfo, wide: bool) -> String {
+    render_table_with(info, wide, Units::Binary)
+}
+
+/// Like `render_table`, scaling every cell according to `units`
+pub fn render_table_with(info: &MemInfo, wide: bool, units: Units) -> String {
+    let cell =
+        |value: Option<u64>| value.map_or("-".to_string(), |bytes| format_size(bytes, units));
+    let buff_cache = match (info.buffers, info.cache) {
+        (Some(buffers), Some(cache)) => Some(buffers + cache),
+        _ => None,
+    };
+
+    let mut headers = vec!["total", "used", "free", "shared"];
+    let mut mem = vec![
+        cell(Some(info.total)),
+        cell(Some(info.used())),
+        cell(Some(info.free)),
+        cell(info.shared),
+    ];
+    if wide {
+        headers.extend(["buffers", "cache"]);
+        mem.extend([cell(info.buffers), cell(info.cache)]);
+    } else {
+        headers.push("buff/cache");
+        mem.push(cell(buff_cache));
+    }
+    headers.push("available");
+    mem.push(cell(Some(info.available)));
+
+    let swap = [
+        cell(Some(info.total_swap)),
+        cell(Some(info.used_swap())),
+        cell(Some(info.free_swap)),
+    ];
+
+    let row = |label: &str, cells: &[String]| {
+        let mut line = format!("{:<7}", label);
+        for value in cells {
+            line.push_str(&format!("{:>12}", value));
+        }
+        line.trim_end().to_string()
+    };
+    let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+
+    format!(
+        "{}\n{}\n{}\n",
+        row("", &header_cells),
+        row("Mem:", &mem),
+        row("Swap:", &swap)
+    )
+}
+
+pub fn execute(args: &[String]) {
+    let mut wide = false;
+    let mut json = false;
+    let mut units = Units::Binary;
+    for arg in args {
+        match arg.as_str() {
+            "-w" | "--wide" => wide = true,
+            "--si" => units = Units::Si,
+            "-b" | "--bytes" => units = Units::Bytes,
+            "--json" => json = true,
+            _ => {
+                eprintln!("free: invalid option '{}'", arg);
+                return;
+            }
+        }
+    }
+
+    let info = collect_meminfo();
+    if json {
+        match serde_json::to_string(&MemorySnapshot::from(&info)) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("free: {}", e),
+        }
+        return;
+    }
+
+    print!("{}", render_table_with(&info, wide, units));
+}
+
+fn format_size(bytes: u64, units: Units) -> String {
+    match units {
+        Units::Binary => format_memory(bytes),
+        Units::Bytes => bytes.to_string(),
+        Units::Si => {
+            let value = bytes as f64;
+            if value >= 1e9 {
+                format!("{:.2} GB", value / 1e9)
+            } else if value >= 1e6 {
+                format!("{:.2} MB", value / 1e6)
+            } else if value >= 1e3 {
+                format!("{:.2} kB", value / 1e3)
+            } else {
+                format!("{} bytes", bytes)
+            }
+        }
+    }
 }
 
 fn format_memory(bytes: u64) -> String {
@@ -25,3 +274,81 @@ fn format_memory(bytes: u64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:       16000 kB
+MemFree:         4000 kB
+MemAvailable:   10000 kB
+Buffers:          500 kB
+Cached:          3000 kB
+SwapCached:         0 kB
+Shmem:            200 kB
+SReclaimable:     250 kB
+SwapTotal:       2048 kB
+SwapFree:        1024 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let info = parse_meminfo(MEMINFO);
+        assert_eq!(info.total, 16000 * 1024);
+        assert_eq!(info.buffers, Some(500 * 1024));
+        assert_eq!(info.cache, Some(3250 * 1024));
+        assert_eq!(info.used(), 6000 * 1024);
+        assert_eq!(info.used_swap(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_wide_splits_buff_cache() {
+        let info = parse_meminfo(MEMINFO);
+
+        let wide = render_table(&info, true);
+        let header = wide.lines().next().unwrap();
+        assert!(header.contains("buffers") && header.contains("cache"));
+        assert!(!header.contains("buff/cache"));
+        assert!(wide.lines().nth(1).unwrap().contains("3.17 MB"));
+
+        let narrow = render_table(&info, false);
+        assert!(narrow.lines().next().unwrap().contains("buff/cache"));
+    }
+
+    #[test]
+    fn test_bytes_and_si_units() {
+        let info = parse_meminfo(MEMINFO);
+
+        let bytes = render_table_with(&info, false, Units::Bytes);
+        let mem_row: Vec<&str> = bytes.lines().nth(1).unwrap().split_whitespace().collect();
+        assert_eq!(mem_row[..3], ["Mem:", "16384000", "6144000"]);
+
+        let si = render_table_with(&info, false, Units::Si);
+        assert!(si.lines().nth(1).unwrap().contains("16.38 MB"));
+    }
+
+    #[test]
+    fn test_json_snapshot_round_trips() {
+        let snapshot = MemorySnapshot::from(&parse_meminfo(MEMINFO));
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains("\"used\":6144000"));
+
+        let parsed: MemorySnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn test_missing_fields_render_as_dash() {
+        let info = MemInfo {
+            total: 1024,
+            available: 1024,
+            ..MemInfo::default()
+        };
+        let mem_row = render_table(&info, true)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .to_string();
+        assert_eq!(mem_row.matches(" -").count(), 3);
+    }
+}
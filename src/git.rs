@@ -1,5 +1,6 @@
 use colored::Colorize;
 use std::io::{self, Write};
+use std::path::Path;
 use std::process::Command;
 
 /// Execute git commands by shelling out to the system git
@@ -262,3 +263,661 @@ pub fn get_repo_status() -> Option<String> {
         Err(_) => None,
     }
 }
+
+/// One line of `git blame` output
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameLine {
+    pub commit: String,
+    pub author: String,
+    pub line_number: usize,
+    pub content: String,
+}
+
+/// Parse the output of `git blame --line-porcelain`
+pub fn parse_line_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<BlameLine> = None;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some(mut entry) = current.take() {
+                entry.content = content.to_string();
+                lines.push(entry);
+            }
+        } else if let Some(entry) = current.as_mut() {
+            if let Some(author) = line.strip_prefix("author ") {
+                entry.author = author.to_string();
+            }
+        } else {
+            // Header: <commit> <original line> <final line> [<group size>]
+            let mut fields = line.split_whitespace();
+            if let (Some(commit), Some(_), Some(final_line)) =
+                (fields.next(), fields.next(), fields.next())
+            {
+                current = Some(BlameLine {
+                    commit: commit.to_string(),
+                    author: String::new(),
+                    line_number: final_line.parse().unwrap_or(0),
+                    content: String::new(),
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+/// Number of lines attributed to each author, most lines first
+pub fn blame_summary(lines: &[BlameLine]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for line in lines {
+        match counts.iter_mut().find(|(author, _)| *author == line.author) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((line.author.clone(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Run `git blame --line-porcelain` on a tracked file, given relative to `repo`.
+/// Git runs in the file's own directory, so it finds the repository the file belongs to.
+pub fn blame(repo: &Path, file: &str) -> Result<Vec<BlameLine>, String> {
+    let path = repo.join(file);
+    if !path.exists() {
+        return Err(format!("{}: No such file or directory", file));
+    }
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{}: not a file", file))?;
+
+    git_in(dir, &["ls-files", "--error-unmatch", "--", name])
+        .map_err(|_| format!("{}: not tracked by git", file))?;
+    let output = git_in(dir, &["blame", "--line-porcelain", "--", name])?;
+    Ok(parse_line_porcelain(&output))
+}
+
+/// One entry of `git stash list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StashEntry {
+    /// The `N` in `stash@{N}`
+    pub index: usize,
+    /// Branch the stash was made on, `(no branch)` for a detached HEAD
+    pub branch: String,
+    pub message: String,
+}
+
+/// Parse the default output of `git stash list`, e.g.
+/// `stash@{0}: WIP on main: 1a2b3c4 Fix parser` or `stash@{1}: On main: my message`
+pub fn parse_stash_list(output: &str) -> Vec<StashEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("stash@{")?;
+            let (index, rest) = rest.split_once("}: ")?;
+            let rest = rest
+                .strip_prefix("WIP on ")
+                .or_else(|| rest.strip_prefix("On "))?;
+            let (branch, message) = rest.split_once(": ").unwrap_or((rest, ""));
+            Some(StashEntry {
+                index: index.parse().ok()?,
+                branch: branch.to_string(),
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// List the stashes of `repo`, newest first
+pub fn stash_list(repo: &Path) -> Result<Vec<StashEntry>, String> {
+    Ok(parse_stash_list(&git_in(repo, &["stash", "list"])?))
+}
+
+/// How applying a stash went
+#[derive(Debug, Clone, PartialEq)]
+pub enum StashApply {
+    Clean,
+    /// Applied with conflicts in these files; a popped stash is kept
+    Conflicts(Vec<String>),
+}
+
+/// Files named in `CONFLICT (...): Merge conflict in <file>` lines
+pub fn parse_conflicts(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with("CONFLICT"))
+        .filter_map(|line| line.split_once("Merge conflict in "))
+        .map(|(_, file)| file.trim().to_string())
+        .collect()
+}
+
+/// Apply stash `index` to the working tree of `repo`, dropping it afterwards with `pop`
+pub fn stash_apply(repo: &Path, index: usize, pop: bool) -> Result<StashApply, String> {
+    let stash = format!("stash@{{{}}}", index);
+    // Not `git_in`: the conflicting files are only listed on stdout
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["stash", if pop { "pop" } else { "apply" }, &stash])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        return Ok(StashApply::Clean);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let conflicts = parse_conflicts(&stdout);
+    if conflicts.is_empty() {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    } else {
+        Ok(StashApply::Conflicts(conflicts))
+    }
+}
+
+/// What `commit` did, or with `dry_run` would do
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommitOutcome {
+    /// The full hash of the new commit
+    Committed(String),
+    /// Dry run: the paths that would go into the commit
+    WouldCommit(Vec<String>),
+    /// Nothing staged and nothing new among the given paths
+    NothingToCommit,
+}
+
+/// Run git in `repo`, returning stdout or the trimmed stderr as the error
+fn git_in(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Paths named in `git add --dry-run` output, e.g. `add 'src/new.rs'`
+fn parse_add_dry_run(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line
+                .strip_prefix("add '")
+                .or_else(|| line.strip_prefix("remove '"))?;
+            Some(rest.strip_suffix('\'').unwrap_or(rest).to_string())
+        })
+        .collect()
+}
+
+/// Stage `paths` in `repo` and commit them with `message`, without an editor.
+/// With `dry_run` nothing is staged or committed; the paths that would be are listed.
+pub fn commit(
+    repo: &Path,
+    paths: &[&str],
+    message: &str,
+    dry_run: bool,
+) -> Result<CommitOutcome, String> {
+    if message.trim().is_empty() {
+        return Err("Aborting commit due to empty commit message".to_string());
+    }
+
+    let mut add = vec!["add"];
+    if dry_run {
+        add.push("--dry-run");
+    }
+    add.push("--");
+    add.extend(paths);
+
+    if dry_run {
+        let mut files: Vec<String> = git_in(repo, &["diff", "--cached", "--name-only"])?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if !paths.is_empty() {
+            for file in parse_add_dry_run(&git_in(repo, &add)?) {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        return Ok(if files.is_empty() {
+            CommitOutcome::NothingToCommit
+        } else {
+            CommitOutcome::WouldCommit(files)
+        });
+    }
+
+    if !paths.is_empty() {
+        git_in(repo, &add)?;
+    }
+    if git_in(repo, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(CommitOutcome::NothingToCommit);
+    }
+    git_in(repo, &["commit", "--quiet", "-m", message])?;
+    let hash = git_in(repo, &["rev-parse", "HEAD"])?;
+    Ok(CommitOutcome::Committed(hash.trim().to_string()))
+}
+
+/// What a `BranchInfo` names
+#[derive(Debug, Clone, PartialEq)]
+pub enum BranchKind {
+    Local,
+    /// A remote-tracking branch such as `origin/main`
+    Remote,
+    /// Not a branch: HEAD is detached, and `name` is the commit it points at
+    Detached,
+}
+
+/// One entry of the branch list
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchInfo {
+    pub name: String,
+    pub kind: BranchKind,
+    /// Checked out right now
+    pub current: bool,
+    /// Abbreviated hash of the commit the branch points at
+    pub commit: String,
+}
+
+/// `git branch --all --format` fields: HEAD marker, refname, short hash, symref target
+const BRANCH_FORMAT: &str = "--format=%(HEAD)%09%(refname)%09%(objectname:short)%09%(symref)";
+
+/// Parse `git branch --all` output in `BRANCH_FORMAT`, leaving out symbolic refs
+/// like `origin/HEAD`
+pub fn parse_branches(output: &str) -> Vec<BranchInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let current = fields.next()? == "*";
+            let refname = fields.next()?;
+            let commit = fields.next()?.to_string();
+            if fields.next().is_some_and(|symref| !symref.is_empty()) {
+                return None;
+            }
+
+            let (name, kind) = if let Some(name) = refname.strip_prefix("refs/heads/") {
+                (name.to_string(), BranchKind::Local)
+            } else if let Some(name) = refname.strip_prefix("refs/remotes/") {
+                (name.to_string(), BranchKind::Remote)
+            } else if refname.starts_with('(') {
+                // `(HEAD detached at 1a2b3c4)`, or `(no branch, rebasing main)` mid-rebase
+                (commit.clone(), BranchKind::Detached)
+            } else {
+                return None;
+            };
+            Some(BranchInfo {
+                name,
+                kind,
+                current,
+                commit,
+            })
+        })
+        .collect()
+}
+
+/// Local and remote-tracking branches of `repo`, plus a detached HEAD if there is one
+pub fn list_branches(repo: &Path) -> Result<Vec<BranchInfo>, String> {
+    Ok(parse_branches(&git_in(
+        repo,
+        &["branch", "--all", "--no-color", BRANCH_FORMAT],
+    )?))
+}
+
+/// How a `checkout` went
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckoutOutcome {
+    Switched,
+    /// Refused: local changes to these files would be overwritten
+    DirtyTree(Vec<String>),
+}
+
+/// The files git lists under "Your local changes to the following files would be overwritten"
+pub fn parse_overwritten(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .skip_while(|line| !line.contains("would be overwritten by checkout"))
+        .skip(1)
+        .map_while(|line| line.strip_prefix('\t'))
+        .map(|file| file.trim().to_string())
+        .collect()
+}
+
+/// Switch `repo` to branch `name`; a remote branch's short name creates a tracking branch.
+/// Uses `git switch` so a file name is never taken as a pathspec to restore.
+pub fn checkout(repo: &Path, name: &str) -> Result<CheckoutOutcome, String> {
+    if name.is_empty() || name.starts_with('-') {
+        return Err(format!("invalid branch name '{}'", name));
+    }
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["switch", "--quiet", name])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        return Ok(CheckoutOutcome::Switched);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let files = parse_overwritten(&stderr);
+    if files.is_empty() {
+        Err(stderr.trim().to_string())
+    } else {
+        Ok(CheckoutOutcome::DirtyTree(files))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE_PORCELAIN: &str = "\
+5e4cb65a3f1c2d9e8b7a6f5e4d3c2b1a09f8e7d6 1 1 2
+author Alice
+author-mail <alice@example.com>
+author-time 1700000000
+author-tz +0000
+committer Alice
+committer-mail <alice@example.com>
+committer-time 1700000000
+committer-tz +0000
+summary Initial commit
+boundary
+filename src/lib.rs
+\tpub mod ansi;
+5e4cb65a3f1c2d9e8b7a6f5e4d3c2b1a09f8e7d6 2 2
+author Alice
+author-mail <alice@example.com>
+author-time 1700000000
+author-tz +0000
+committer Alice
+committer-mail <alice@example.com>
+committer-time 1700000000
+committer-tz +0000
+summary Initial commit
+boundary
+filename src/lib.rs
+\tpub mod cat;
+7152223b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f 3 3 1
+author Bob Smith
+author-mail <bob@example.com>
+author-time 1710000000
+author-tz +0100
+committer Bob Smith
+committer-mail <bob@example.com>
+committer-time 1710000000
+committer-tz +0100
+summary Add free
+previous 5e4cb65a3f1c2d9e8b7a6f5e4d3c2b1a09f8e7d6 src/lib.rs
+filename src/lib.rs
+\t\tpub mod free;
+";
+
+    #[test]
+    fn test_parse_line_porcelain() {
+        let lines = parse_line_porcelain(LINE_PORCELAIN);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].author, "Alice");
+        assert_eq!(lines[0].commit, "5e4cb65a3f1c2d9e8b7a6f5e4d3c2b1a09f8e7d6");
+        assert_eq!(lines[1].line_number, 2);
+        assert_eq!(lines[1].content, "pub mod cat;");
+        assert_eq!(lines[2].author, "Bob Smith");
+        assert_eq!(lines[2].content, "\tpub mod free;");
+
+        assert_eq!(
+            blame_summary(&lines),
+            vec![("Alice".to_string(), 2), ("Bob Smith".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = "\
+stash@{0}: WIP on main: 5e4cb65 Add free
+stash@{1}: On feature/tree: half-done filelimit: needs tests
+stash@{2}: WIP on (no branch): 7152223 Detached work
+";
+        let entries = parse_stash_list(output);
+        assert_eq!(
+            entries,
+            vec![
+                StashEntry {
+                    index: 0,
+                    branch: "main".to_string(),
+                    message: "5e4cb65 Add free".to_string(),
+                },
+                StashEntry {
+                    index: 1,
+                    branch: "feature/tree".to_string(),
+                    message: "half-done filelimit: needs tests".to_string(),
+                },
+                StashEntry {
+                    index: 2,
+                    branch: "(no branch)".to_string(),
+                    message: "7152223 Detached work".to_string(),
+                },
+            ]
+        );
+        assert!(parse_stash_list("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_conflicts() {
+        let output = "\
+Auto-merging src/lib.rs
+CONFLICT (content): Merge conflict in src/lib.rs
+CONFLICT (content): Merge conflict in src/main.rs
+The stash entry is kept in case you need it again.
+";
+        assert_eq!(parse_conflicts(output), ["src/lib.rs", "src/main.rs"]);
+        assert!(parse_conflicts("Dropped refs/stash@{0}").is_empty());
+    }
+
+    /// An empty repository on branch `main` that can commit without global config
+    fn temp_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git_in(repo, &["init", "--quiet"]).unwrap();
+        git_in(repo, &["symbolic-ref", "HEAD", "refs/heads/main"]).unwrap();
+        for (key, value) in [
+            ("user.name", "Winix Test"),
+            ("user.email", "winix@example.com"),
+            ("commit.gpgsign", "false"),
+        ] {
+            git_in(repo, &["config", key, value]).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_commit_in_temp_repo() {
+        if !is_git_available() {
+            return;
+        }
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::write(repo.join("new.txt"), "hello\n").unwrap();
+
+        assert_eq!(
+            commit(repo, &["new.txt"], "Add new.txt", true),
+            Ok(CommitOutcome::WouldCommit(vec!["new.txt".to_string()]))
+        );
+        assert!(!git_in(repo, &["status", "--porcelain"]).unwrap().is_empty());
+
+        let CommitOutcome::Committed(hash) =
+            commit(repo, &["new.txt"], "Add new.txt", false).unwrap()
+        else {
+            panic!("expected a commit");
+        };
+        assert!(hash.len() >= 40 && hash.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(git_in(repo, &["status", "--porcelain"]).unwrap(), "");
+
+        assert_eq!(
+            commit(repo, &["new.txt"], "Again", false),
+            Ok(CommitOutcome::NothingToCommit)
+        );
+        assert!(commit(repo, &["new.txt"], "  ", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_branches() {
+        let output = "\
+*\t(HEAD detached at af9c432)\taf9c432\t
+ \trefs/heads/feature/tree\t5e4cb65\t
+ \trefs/heads/main\taf9c432\t
+ \trefs/remotes/origin/HEAD\taf9c432\trefs/remotes/origin/main
+ \trefs/remotes/origin/main\taf9c432\t
+";
+        let branches = parse_branches(output);
+        assert_eq!(branches.len(), 4);
+        assert_eq!(
+            branches[0],
+            BranchInfo {
+                name: "af9c432".to_string(),
+                kind: BranchKind::Detached,
+                current: true,
+                commit: "af9c432".to_string(),
+            }
+        );
+        assert_eq!(branches[1].name, "feature/tree");
+        assert_eq!(branches[1].kind, BranchKind::Local);
+        assert!(!branches[1].current);
+        assert_eq!(branches[3].name, "origin/main");
+        assert_eq!(branches[3].kind, BranchKind::Remote);
+
+        let stderr = "\
+error: Your local changes to the following files would be overwritten by checkout:
+\tnotes.txt
+\tsrc/lib.rs
+Please commit your changes or stash them before you switch branches.
+Aborting
+";
+        assert_eq!(parse_overwritten(stderr), ["notes.txt", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_branches_and_checkout_in_temp_repo() {
+        if !is_git_available() {
+            return;
+        }
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::write(repo.join("notes.txt"), "main\n").unwrap();
+        commit(repo, &["notes.txt"], "Add notes", false).unwrap();
+        git_in(repo, &["branch", "feature"]).unwrap();
+
+        let current = |repo: &Path| -> Vec<String> {
+            list_branches(repo)
+                .unwrap()
+                .into_iter()
+                .filter(|branch| branch.current)
+                .map(|branch| branch.name)
+                .collect()
+        };
+        let branches = list_branches(repo).unwrap();
+        let names: Vec<&str> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["feature", "main"]);
+        assert!(branches.iter().all(|b| b.kind == BranchKind::Local));
+        assert_eq!(current(repo), ["main"]);
+
+        assert_eq!(checkout(repo, "feature"), Ok(CheckoutOutcome::Switched));
+        assert_eq!(current(repo), ["feature"]);
+
+        // Diverge, then try to leave with a conflicting local edit
+        std::fs::write(repo.join("notes.txt"), "feature\n").unwrap();
+        commit(repo, &["notes.txt"], "Change notes", false).unwrap();
+        std::fs::write(repo.join("notes.txt"), "uncommitted\n").unwrap();
+        assert_eq!(
+            checkout(repo, "main"),
+            Ok(CheckoutOutcome::DirtyTree(vec!["notes.txt".to_string()]))
+        );
+        assert_eq!(current(repo), ["feature"]);
+        git_in(repo, &["checkout", "--", "notes.txt"]).unwrap();
+
+        git_in(repo, &["checkout", "--quiet", "--detach"]).unwrap();
+        let detached: Vec<BranchInfo> = list_branches(repo)
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == BranchKind::Detached)
+            .collect();
+        assert_eq!(detached.len(), 1);
+        assert!(detached[0].current);
+
+        assert!(checkout(repo, "no-such-branch").is_err());
+        assert!(checkout(repo, "--force").is_err());
+
+        // A tracked file's name is not a branch, and its edits must survive
+        std::fs::write(repo.join("notes.txt"), "keep me\n").unwrap();
+        assert!(checkout(repo, "notes.txt").is_err());
+        assert_eq!(
+            std::fs::read_to_string(repo.join("notes.txt")).unwrap(),
+            "keep me\n"
+        );
+    }
+
+    #[test]
+    fn test_blame_untracked_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("untracked.txt");
+        std::fs::write(&path, "hello\n").unwrap();
+
+        let err = blame(dir.path(), "untracked.txt").unwrap_err();
+        assert!(err.contains("not tracked by git"));
+        assert!(blame(dir.path(), "definitely-missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_stash_in_temp_repo() {
+        if !is_git_available() {
+            return;
+        }
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::write(repo.join("notes.txt"), "first\n").unwrap();
+        commit(repo, &["notes.txt"], "Add notes", false).unwrap();
+        assert_eq!(stash_list(repo), Ok(Vec::new()));
+
+        std::fs::write(repo.join("notes.txt"), "stashed\n").unwrap();
+        git_in(repo, &["stash", "push", "--quiet", "-m", "half done"]).unwrap();
+        let stashes = stash_list(repo).unwrap();
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].branch, "main");
+        assert_eq!(stashes[0].message, "half done");
+
+        assert_eq!(stash_apply(repo, 0, true), Ok(StashApply::Clean));
+        assert_eq!(
+            std::fs::read_to_string(repo.join("notes.txt")).unwrap(),
+            "stashed\n"
+        );
+        assert_eq!(stash_list(repo), Ok(Vec::new()));
+        assert!(stash_apply(repo, 0, false).is_err());
+    }
+
+    #[test]
+    fn test_blame_file_in_another_repo() {
+        if !is_git_available() {
+            return;
+        }
+        let dir = temp_repo();
+        let repo = dir.path();
+        std::fs::create_dir(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        commit(repo, &["src/lib.rs"], "Add lib", false).unwrap();
+
+        // The test runs outside `repo`, so plain `git blame` would look in the wrong place
+        let lines = blame(repo, "src/lib.rs").unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].content, "fn b() {}");
+        assert_eq!(blame_summary(&lines), [("Winix Test".to_string(), 2)]);
+    }
+}
@@ -1,36 +1,408 @@
 use bytes::Bytes;
 use futures::stream::{self, Stream, StreamExt};
 use regex::Regex;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 use tokio::fs::File as TokioFile;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 
+/// Output controls for `grep_with`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GrepOptions {
+    /// `-H`/`-h`; `None` shows file names only when searching several files
+    pub with_filename: Option<bool>,
+    /// `-b`: prefix each match with the byte offset of its line
+    pub byte_offset: bool,
+    /// `-m NUM`: stop reading each file after this many matching lines
+    pub max_count: Option<usize>,
+    /// `-c`: print a count of matching lines per file instead of the lines
+    pub count: bool,
+    /// `--count-matches`: like `-c`, but count every match rather than each matching line
+    pub count_matches: bool,
+    /// `-l`: print only the names of files with a match
+    pub files_with_matches: bool,
+    /// `-L`: print only the names of files without a match
+    pub files_without_match: bool,
+    /// `-Z`: end file names with a NUL byte instead of `:` or a newline
+    pub null: bool,
+    /// `--line-buffered`: flush after every output line so pipelines see matches immediately
+    pub line_buffered: bool,
+    /// `-P`: Perl-compatible patterns with lookaround and backreferences.
+    /// These may backtrack, so they can be much slower than the default engine.
+    pub perl: bool,
+    /// `-v`: select the lines that do not match
+    pub invert: bool,
+    /// `-q`: print nothing and stop at the first selected line; only the exit status matters
+    pub quiet: bool,
+}
+
+/// What `grep_to` found across its files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GrepStatus {
+    /// Some line was selected (or, with `-L`, some file listed)
+    pub selected: bool,
+    /// Some file couldn't be searched; it was reported on stderr and skipped
+    pub failed: bool,
+}
+
+/// The compiled pattern: `regex` by default, `fancy-regex` with `-P`
+enum Matcher {
+    Default(Regex),
+    Perl(fancy_regex::Regex),
+}
+
+impl Matcher {
+    fn new(pattern: &str, perl: bool) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+        if perl {
+            fancy_regex::Regex::new(pattern)
+                .map(Matcher::Perl)
+                .map_err(|e| invalid(e.to_string()))
+        } else {
+            Regex::new(pattern)
+                .map(Matcher::Default)
+                .map_err(|e| invalid(e.to_string()))
+        }
+    }
+
+    /// A line that exhausts the backtracking limit counts as not matching
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Default(regex) => regex.is_match(line),
+            Matcher::Perl(regex) => regex.is_match(line).unwrap_or(false),
+        }
+    }
+
+    /// Non-empty matches in `line`, as `-o` would print them
+    fn count_matches(&self, line: &str) -> usize {
+        match self {
+            Matcher::Default(regex) => regex.find_iter(line).filter(|m| !m.is_empty()).count(),
+            Matcher::Perl(regex) => regex
+                .find_iter(line)
+                .filter(|m| m.as_ref().is_ok_and(|m| m.start() < m.end()))
+                .count(),
+        }
+    }
+}
+
 // Sync version for benchmarking
 pub fn grep_sync<S: AsRef<Path>>(pattern: &str, files: Vec<S>) -> io::Result<String> {
-    let regex = Regex::new(pattern).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-    let mut result = String::new();
+    let options = GrepOptions {
+        with_filename: Some(true),
+        ..Default::default()
+    };
+    grep_with(pattern, files, &options)
+}
 
-    for file_path in files {
-        let file = std::fs::File::open(&file_path)?;
-        let reader = std::io::BufReader::new(file);
-
-        for (line_num, line) in reader.lines().enumerate() {
-            let line = line?;
-            if regex.is_match(&line) {
-                result.push_str(&format!(
-                    "{}:{}",
-                    file_path.as_ref().display(),
-                    line_num + 1
-                ));
-                result.push_str(": ");
-                result.push_str(&line);
-                result.push('\n');
+/// Search `files` for `pattern`, formatting matches as `[file:]line[:offset]: text`
+pub fn grep_with<S: AsRef<Path>>(
+    pattern: &str,
+    files: Vec<S>,
+    options: &GrepOptions,
+) -> io::Result<String> {
+    let mut out = Vec::new();
+    grep_to(pattern, files, options, &mut out)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Like `grep_with`, but write each match to `out` as it is found; `-` reads standard input.
+/// A file that can't be opened is reported as `grep: FILE: reason` and the rest are still searched.
+pub fn grep_to<S: AsRef<Path>, W: Write>(
+    pattern: &str,
+    files: Vec<S>,
+    options: &GrepOptions,
+    out: &mut W,
+) -> io::Result<GrepStatus> {
+    let regex = Matcher::new(pattern, options.perl)?;
+    let show_filename = options.with_filename.unwrap_or(files.len() > 1);
+    let mut status = GrepStatus::default();
+
+    for file_path in &files {
+        // One hit decides the exit status, so later files are never opened
+        if status.selected && options.quiet {
+            break;
+        }
+        let file_path = file_path.as_ref();
+        status.selected |= if file_path == Path::new("-") {
+            let stdin = io::stdin();
+            search(
+                &regex,
+                "(standard input)",
+                stdin.lock(),
+                options,
+                show_filename,
+                out,
+            )?
+        } else {
+            let name = file_path.display().to_string();
+            match open_file(file_path) {
+                Ok(file) => search(&regex, &name, file, options, show_filename, out)?,
+                Err(reason) => {
+                    eprintln!("grep: {}: {}", name, reason);
+                    status.failed = true;
+                    false
+                }
+            }
+        };
+    }
+
+    Ok(status)
+}
+
+/// Open one FILE operand, describing the failure the way grep reports it
+fn open_file(path: &Path) -> Result<io::BufReader<std::fs::File>, String> {
+    if path.is_dir() {
+        return Err("Is a directory".to_string());
+    }
+    std::fs::File::open(path)
+        .map(io::BufReader::new)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => "No such file or directory".to_string(),
+            io::ErrorKind::PermissionDenied => "Permission denied".to_string(),
+            _ => e.to_string(),
+        })
+}
+
+/// Search a single stream, such as the read end of a pipe, writing matches to `out`
+pub fn grep_reader<R: BufRead, W: Write>(
+    pattern: &str,
+    reader: R,
+    options: &GrepOptions,
+    out: &mut W,
+) -> io::Result<bool> {
+    let regex = Matcher::new(pattern, options.perl)?;
+    let show_filename = options.with_filename.unwrap_or(false);
+    search(
+        &regex,
+        "(standard input)",
+        reader,
+        options,
+        show_filename,
+        out,
+    )
+}
+
+/// Write the selected lines of one input labelled `name`, returning whether there were any
+fn search<R: BufRead, W: Write>(
+    regex: &Matcher,
+    name: &str,
+    mut reader: R,
+    options: &GrepOptions,
+    show_filename: bool,
+    out: &mut W,
+) -> io::Result<bool> {
+    let (prefix_end, name_end) = if options.null {
+        ('\0', '\0')
+    } else {
+        (':', '\n')
+    };
+
+    // A single match is enough to decide whether -l or -L lists a file, or what -q reports
+    let max_count = if options.files_with_matches || options.files_without_match || options.quiet {
+        Some(options.max_count.unwrap_or(1).min(1))
+    } else {
+        options.max_count
+    };
+
+    let mut offset = 0;
+    let mut matches = 0;
+    let mut occurrences = 0;
+    let mut buf = Vec::new();
+
+    for line_num in 1.. {
+        if max_count.is_some_and(|max| matches >= max) {
+            break;
+        }
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches('\n').trim_end_matches('\r');
+        let matched = regex.is_match(line) != options.invert;
+        if matched {
+            matches += 1;
+            if options.count_matches {
+                // Inverted lines have no matches to count, so count the lines themselves
+                occurrences += if options.invert {
+                    1
+                } else {
+                    regex.count_matches(line)
+                };
+            }
+        }
+        if matched
+            && !options.quiet
+            && !options.count
+            && !options.count_matches
+            && !options.files_with_matches
+            && !options.files_without_match
+        {
+            if show_filename {
+                write!(out, "{}{}", name, prefix_end)?;
+            }
+            write!(out, "{}", line_num)?;
+            if options.byte_offset {
+                write!(out, ":{}", offset)?;
+            }
+            writeln!(out, ": {}", line)?;
+            if options.line_buffered {
+                out.flush()?;
             }
         }
+        offset += read;
     }
 
-    Ok(result)
+    if options.quiet {
+        return Ok(matches > 0);
+    }
+    // With -L, the file being listed is what counts as success
+    let mut selected = matches > 0;
+    if options.files_with_matches || options.files_without_match {
+        selected = (matches > 0) == options.files_with_matches;
+        if selected {
+            write!(out, "{}{}", name, name_end)?;
+        }
+    } else if options.count || options.count_matches {
+        if show_filename {
+            write!(out, "{}{}", name, prefix_end)?;
+        }
+        let total = if options.count_matches {
+            occurrences
+        } else {
+            matches
+        };
+        writeln!(out, "{}", total)?;
+    }
+    if options.line_buffered {
+        out.flush()?;
+    }
+
+    Ok(selected)
+}
+
+fn parse_max_count(value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid max count '{}'", value))
+}
+
+/// Set the option for a flag that takes no value, returning false if it isn't one
+fn apply_flag(options: &mut GrepOptions, flag: &str) -> bool {
+    match flag {
+        "-b" | "--byte-offset" => options.byte_offset = true,
+        "-c" | "--count" => options.count = true,
+        "--count-matches" => options.count_matches = true,
+        "--line-buffered" => options.line_buffered = true,
+        // -l and -L override each other, so the last one given wins
+        "-l" | "--files-with-matches" => {
+            options.files_with_matches = true;
+            options.files_without_match = false;
+        }
+        "-L" | "--files-without-match" => {
+            options.files_without_match = true;
+            options.files_with_matches = false;
+        }
+        "-P" | "--perl-regexp" => options.perl = true,
+        "-v" | "--invert-match" => options.invert = true,
+        "-q" | "--quiet" | "--silent" => options.quiet = true,
+        "-Z" | "--null" => options.null = true,
+        "-H" | "--with-filename" => options.with_filename = Some(true),
+        "-h" | "--no-filename" => options.with_filename = Some(false),
+        _ => return false,
+    }
+    true
+}
+
+/// Parse `grep [-b] [-c|--count-matches] [-l|-L] [-P] [-q] [-v] [-Z] [--line-buffered] [-m NUM] [-H|-h] PATTERN FILE...` into options, pattern and files.
+/// Value-less short flags may be combined, as in `-lZ`.
+pub fn parse_args(args: &[String]) -> Result<(GrepOptions, String, Vec<String>), String> {
+    let mut options = GrepOptions::default();
+    let mut operands = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if apply_flag(&mut options, arg) {
+            continue;
+        }
+        match arg.as_str() {
+            "-m" | "--max-count" => {
+                let value = iter.next().ok_or("option requires an argument -- 'm'")?;
+                options.max_count = Some(parse_max_count(value)?);
+            }
+            _ if arg.starts_with("--max-count=") => {
+                options.max_count = Some(parse_max_count(&arg["--max-count=".len()..])?);
+            }
+            _ if arg.len() > 2
+                && arg.starts_with('-')
+                && !arg.starts_with("--")
+                && operands.is_empty() =>
+            {
+                let mut combined = options.clone();
+                if !arg[1..]
+                    .chars()
+                    .all(|c| apply_flag(&mut combined, &format!("-{}", c)))
+                {
+                    return Err(format!("invalid option '{}'", arg));
+                }
+                options = combined;
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 && operands.is_empty() => {
+                return Err(format!("invalid option '{}'", arg));
+            }
+            _ => operands.push(arg.clone()),
+        }
+    }
+
+    if operands.len() < 2 {
+        return Err(
+            "Usage: grep [-b] [-c|--count-matches] [-l|-L] [-P] [-q] [-v] [-Z] [--line-buffered] [-m NUM] [-H|-h] <pattern> <file>...".to_string(),
+        );
+    }
+    let pattern = operands.remove(0);
+    Ok((options, pattern, operands))
+}
+
+/// Entry point for the `grep` shell command.
+/// Exits 0 if a line was selected, 1 if none was, and 2 on a bad pattern, option or file.
+/// As in GNU grep, `-q` still exits 0 on a match even if some file couldn't be read.
+pub fn run(args: &[String]) -> i32 {
+    let stdout = io::stdout();
+    run_with(args, &mut stdout.lock())
+}
+
+/// `run`, writing results to `out`
+pub fn run_with<W: Write>(args: &[String], out: &mut W) -> i32 {
+    let (options, pattern, files) = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("grep: {}", e);
+            return 2;
+        }
+    };
+
+    // Block buffering keeps large searches fast unless matches must show up right away
+    let result = if options.line_buffered {
+        grep_to(&pattern, files, &options, out)
+    } else {
+        let mut out = io::BufWriter::new(out);
+        grep_to(&pattern, files, &options, &mut out).and_then(|status| {
+            out.flush()?;
+            Ok(status)
+        })
+    };
+    match result {
+        Ok(status) if status.selected && options.quiet => 0,
+        Ok(status) if status.failed => 2,
+        Ok(status) if status.selected => 0,
+        Ok(_) => 1,
+        Err(e) => {
+            eprintln!("grep: {}", e);
+            2
+        }
+    }
 }
 
 // Async version that returns a Stream<Bytes>
@@ -142,6 +514,392 @@ mod tests {
         std::fs::remove_file(file_path).unwrap();
     }
 
+    #[test]
+    fn test_grep_byte_offset_and_filename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("offsets.txt");
+        std::fs::write(&path, "first line\nsecond match\r\nthird match\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let options = GrepOptions {
+            byte_offset: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_with("second", vec![&file], &options).unwrap(),
+            "2:11: second match\n"
+        );
+        assert_eq!(
+            grep_with("third", vec![&file], &options).unwrap(),
+            "3:25: third match\n"
+        );
+
+        let options = GrepOptions {
+            with_filename: Some(true),
+            byte_offset: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_with("second", vec![&file], &options).unwrap(),
+            format!("{}:2:11: second match\n", file)
+        );
+
+        let options = GrepOptions {
+            with_filename: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_with("first", vec![&file, &file], &options).unwrap(),
+            "1: first line\n1: first line\n"
+        );
+    }
+
+    #[test]
+    fn test_grep_max_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("many.txt");
+        std::fs::write(&path, "hit 1\nmiss\nhit 2\nhit 3\nhit 4\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let args: Vec<String> = ["-m", "2", "hit", &file]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (options, pattern, files) = parse_args(&args).unwrap();
+        assert_eq!(options.max_count, Some(2));
+        assert_eq!(
+            grep_with(&pattern, files.clone(), &options).unwrap(),
+            "1: hit 1\n3: hit 2\n"
+        );
+
+        // -c reports min(count, NUM); without -m it counts every match
+        let counted = GrepOptions {
+            count: true,
+            ..options.clone()
+        };
+        assert_eq!(grep_with(&pattern, files.clone(), &counted).unwrap(), "2\n");
+        let all = GrepOptions {
+            count: true,
+            ..Default::default()
+        };
+        assert_eq!(grep_with(&pattern, files.clone(), &all).unwrap(), "4\n");
+
+        let listed = GrepOptions {
+            files_with_matches: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_with(&pattern, files.clone(), &listed).unwrap(),
+            format!("{}\n", file)
+        );
+        assert_eq!(grep_with("absent", files, &listed).unwrap(), "");
+    }
+
+    #[test]
+    fn test_grep_count_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("twice.txt");
+        std::fs::write(&path, "foo and foo\nbar\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let args = |flag: &str| -> Vec<String> {
+            [flag, "foo", &file].iter().map(|s| s.to_string()).collect()
+        };
+        let (options, pattern, files) = parse_args(&args("-c")).unwrap();
+        assert_eq!(grep_with(&pattern, files, &options).unwrap(), "1\n");
+
+        let (options, pattern, files) = parse_args(&args("--count-matches")).unwrap();
+        assert!(options.count_matches);
+        assert_eq!(grep_with(&pattern, files.clone(), &options).unwrap(), "2\n");
+
+        let perl = GrepOptions {
+            perl: true,
+            ..options
+        };
+        assert_eq!(grep_with("fo(?=o)", files, &perl).unwrap(), "2\n");
+    }
+
+    /// Reports what has been written each time it is flushed
+    struct FlushRecorder {
+        written: Vec<u8>,
+        flushes: std::sync::mpsc::Sender<String>,
+    }
+
+    impl Write for FlushRecorder {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            let _ = self
+                .flushes
+                .send(String::from_utf8_lossy(&self.written).into_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_grep_line_buffered() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let (options, _, _) = parse_args(&[
+            "--line-buffered".to_string(),
+            "hit".to_string(),
+            "-".to_string(),
+        ])
+        .unwrap();
+        assert!(options.line_buffered);
+
+        let (reader, mut writer) = io::pipe().unwrap();
+        let (flush_tx, flushes) = mpsc::channel();
+        let (resume_tx, resume) = mpsc::channel::<()>();
+
+        // The second match is only written once the first has been seen
+        let producer = std::thread::spawn(move || {
+            writer.write_all(b"hit one\nmiss\n").unwrap();
+            let _ = resume.recv_timeout(Duration::from_secs(5));
+            writer.write_all(b"hit two\n").unwrap();
+        });
+        let searcher = std::thread::spawn(move || {
+            let mut out = FlushRecorder {
+                written: Vec::new(),
+                flushes: flush_tx,
+            };
+            grep_reader("hit", io::BufReader::new(reader), &options, &mut out).unwrap();
+        });
+
+        let first = flushes.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(first, "1: hit one\n");
+        resume_tx.send(()).unwrap();
+        let second = flushes.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(second, "1: hit one\n3: hit two\n");
+
+        producer.join().unwrap();
+        searcher.join().unwrap();
+    }
+
+    #[test]
+    fn test_grep_files_without_match() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+        let files = [
+            (
+                dir.path().join("licensed.rs"),
+                "// SPDX-License-Identifier: MIT\nfn a() {}\n",
+            ),
+            (dir.path().join("bare.rs"), "fn b() {}\n"),
+            (
+                nested.join("late.rs"),
+                "fn c() {}\n// SPDX-License-Identifier: MIT\n",
+            ),
+            (nested.join("empty.rs"), ""),
+        ];
+        for (path, content) in &files {
+            std::fs::write(path, content).unwrap();
+        }
+        let names: Vec<String> = files
+            .iter()
+            .map(|(path, _)| path.to_string_lossy().to_string())
+            .collect();
+
+        let mut args = vec!["-L".to_string(), "SPDX-License-Identifier".to_string()];
+        args.extend(names.iter().cloned());
+        let (options, pattern, files) = parse_args(&args).unwrap();
+        assert!(options.files_without_match && !options.files_with_matches);
+        let output = grep_with(&pattern, files, &options).unwrap();
+        assert_eq!(output, format!("{}\n{}\n", names[1], names[3]));
+
+        // The later of -l and -L wins
+        let (options, _, _) =
+            parse_args(&["-L".into(), "-l".into(), "x".into(), "f".into()]).unwrap();
+        assert!(options.files_with_matches && !options.files_without_match);
+        let (options, _, _) = parse_args(&["-lL".into(), "x".into(), "f".into()]).unwrap();
+        assert!(options.files_without_match && !options.files_with_matches);
+    }
+
+    #[test]
+    fn test_grep_perl_lookahead() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("look.txt");
+        std::fs::write(&path, "foobar\nfoobaz\nbarfoo\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        // The default engine has no lookaround
+        assert!(grep_with("foo(?=bar)", vec![&file], &GrepOptions::default()).is_err());
+
+        let args: Vec<String> = ["-P", "foo(?=bar)", &file]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (options, pattern, files) = parse_args(&args).unwrap();
+        assert!(options.perl);
+        assert_eq!(
+            grep_with(&pattern, files.clone(), &options).unwrap(),
+            "1: foobar\n"
+        );
+
+        let counted = GrepOptions {
+            count: true,
+            ..options
+        };
+        assert_eq!(grep_with("(?<!bar)foo", files, &counted).unwrap(), "2\n");
+    }
+
+    #[test]
+    fn test_grep_null_separated_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("has space.txt");
+        let second = dir.path().join("second one.txt");
+        let third = dir.path().join("none.txt");
+        std::fs::write(&first, "needle\n").unwrap();
+        std::fs::write(&second, "hay\nneedle\n").unwrap();
+        std::fs::write(&third, "hay\n").unwrap();
+        let names: Vec<String> = [&first, &second, &third]
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        let mut args = vec!["-lZ".to_string(), "needle".to_string()];
+        args.extend(names.iter().cloned());
+        let (options, pattern, files) = parse_args(&args).unwrap();
+        assert!(options.files_with_matches && options.null);
+        assert_eq!(
+            grep_with(&pattern, files.clone(), &options).unwrap(),
+            format!("{}\0{}\0", names[0], names[1])
+        );
+
+        let prefixed = GrepOptions {
+            null: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            grep_with(&pattern, files, &prefixed).unwrap(),
+            format!("{}\01: needle\n{}\02: needle\n", names[0], names[1])
+        );
+        assert!(parse_args(&["-lX".to_string(), "a".to_string(), "b".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args: Vec<String> = ["-b", "-h", "foo", "a.txt", "b.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (options, pattern, files) = parse_args(&args).unwrap();
+        assert!(options.byte_offset);
+        assert_eq!(options.with_filename, Some(false));
+        assert_eq!(pattern, "foo");
+        assert_eq!(files, ["a.txt", "b.txt"]);
+
+        assert!(parse_args(&["foo".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_exit_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status.txt");
+        std::fs::write(&path, "alpha\nbeta\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+        let run = |args: &[&str]| {
+            let mut args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+            args.push(file.clone());
+            let mut out = Vec::new();
+            let code = run_with(&args, &mut out);
+            (code, String::from_utf8(out).unwrap())
+        };
+
+        assert_eq!(run(&["beta"]), (0, "2: beta\n".to_string()));
+        assert_eq!(run(&["gamma"]), (1, String::new()));
+        assert_eq!(run(&["(unclosed"]).0, 2);
+        assert_eq!(run(&["-X", "alpha"]).0, 2);
+
+        // With -v, success means some line was printed
+        assert_eq!(run(&["-v", "alpha"]), (0, "2: beta\n".to_string()));
+        assert_eq!(run(&["-v", "a"]), (1, String::new()));
+        assert_eq!(run(&["-vc", "alpha"]), (0, "1\n".to_string()));
+
+        let missing = dir.path().join("missing.txt").to_string_lossy().to_string();
+        let mut out = Vec::new();
+        assert_eq!(
+            run_with(&["alpha".to_string(), missing.clone()], &mut out),
+            2
+        );
+
+        // A missing file is skipped; the files after it are still searched
+        let mut out = Vec::new();
+        let args = ["beta".to_string(), missing.clone(), file.clone()];
+        assert_eq!(run_with(&args, &mut out), 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}:2: beta\n", file)
+        );
+        let status = grep_to(
+            "beta",
+            vec![&missing, &file],
+            &GrepOptions::default(),
+            &mut io::sink(),
+        );
+        assert_eq!(
+            status.unwrap(),
+            GrepStatus {
+                selected: true,
+                failed: true
+            }
+        );
+        let mut out = Vec::new();
+        let args = ["beta".to_string(), dir.path().to_string_lossy().to_string()];
+        assert_eq!(run_with(&args, &mut out), 2);
+    }
+
+    #[test]
+    fn test_quiet_stops_at_first_match() {
+        /// Fails the search if grep reads past the first match
+        struct Unreadable;
+        impl io::Read for Unreadable {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("read past the first match"))
+            }
+        }
+
+        let quiet = GrepOptions {
+            quiet: true,
+            ..Default::default()
+        };
+        let reader = io::BufReader::new(io::Read::chain(&b"miss\nhit\n"[..], Unreadable));
+        let mut out = Vec::new();
+        assert!(grep_reader("hit", reader, &quiet, &mut out).unwrap());
+        assert!(out.is_empty());
+
+        let reader = io::BufReader::new(io::Read::chain(&b"miss\nhit\n"[..], Unreadable));
+        assert!(grep_reader("hit", reader, &GrepOptions::default(), &mut out).is_err());
+
+        // Later files are skipped once one matches, even unreadable ones
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("quiet.txt");
+        std::fs::write(&path, "alpha\nbeta\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+        let missing = dir.path().join("missing.txt").to_string_lossy().to_string();
+        let args = |flags: &[&str]| -> Vec<String> {
+            let mut args: Vec<String> = flags.iter().map(|s| s.to_string()).collect();
+            args.extend([file.clone(), missing.clone()]);
+            args
+        };
+
+        let mut out = Vec::new();
+        assert_eq!(run_with(&args(&["-q", "beta"]), &mut out), 0);
+        assert_eq!(run_with(&args(&["--quiet", "-c", "alpha"]), &mut out), 0);
+        assert!(out.is_empty());
+        assert_eq!(run_with(&args(&["--silent", "gamma"]), &mut out), 2);
+        assert_eq!(
+            run_with(&["-q".to_string(), "gamma".to_string(), file], &mut out),
+            1
+        );
+        assert!(out.is_empty());
+    }
+
     #[tokio::test]
     async fn test_grep_async() {
         let file_path = "test_grep_async.txt";
@@ -1,6 +1,6 @@
 use bytes::Bytes;
 use futures::stream::{self, Stream, StreamExt};
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read, Write};
 use std::path::Path;
 use tokio::fs::File as TokioFile;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
@@ -36,6 +36,228 @@ pub fn head_sync<S: AsRef<Path>>(files: Vec<S>, lines: usize) -> io::Result<Stri
     Ok(result)
 }
 
+/// Like `head_sync`, but with `zero_terminated` records are NUL-delimited on input and output
+pub fn head_sync_with<S: AsRef<Path>>(
+    files: Vec<S>,
+    lines: usize,
+    zero_terminated: bool,
+) -> io::Result<String> {
+    if !zero_terminated {
+        return head_sync(files, lines);
+    }
+
+    let mut result = String::new();
+    let mut total_records = 0;
+
+    for file_path in files {
+        if total_records >= lines {
+            break;
+        }
+
+        let file = std::fs::File::open(&file_path)?;
+        let reader = std::io::BufReader::new(file);
+
+        for record in reader.split(b'\0') {
+            if total_records >= lines {
+                break;
+            }
+            result.push_str(&String::from_utf8_lossy(&record?));
+            result.push('\0');
+            total_records += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+/// The first `bytes` bytes of each file
+pub fn head_bytes<S: AsRef<Path>>(files: Vec<S>, bytes: usize) -> io::Result<Vec<u8>> {
+    let mut result = Vec::new();
+    for file_path in files {
+        let file = std::fs::File::open(&file_path)?;
+        file.take(bytes as u64).read_to_end(&mut result)?;
+    }
+    Ok(result)
+}
+
+/// Options shared by the `head` and `tail` commands
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineArgs {
+    pub lines: usize,
+    /// `-c NUM`: count bytes instead of lines
+    pub bytes: Option<usize>,
+    /// `-v`/`-q`; `None` prints `==> file <==` headers only for several files
+    pub headers: Option<bool>,
+    pub zero_terminated: bool,
+    /// `-f`, only meaningful for `tail`
+    pub follow: bool,
+    /// `--follow=name`: reopen the file by name when it is rotated
+    pub follow_name: bool,
+    /// `--retry`: wait for files that don't exist yet
+    pub retry: bool,
+    pub files: Vec<String>,
+}
+
+impl LineArgs {
+    /// Whether to print a header before each file
+    pub fn show_headers(&self) -> bool {
+        self.headers.unwrap_or(self.files.len() > 1)
+    }
+}
+
+/// The value of `-X NUM`, `-XNUM`, `--long NUM` or `--long=NUM`, if `arg` is that option
+fn option_value<'a>(
+    arg: &'a str,
+    short: &str,
+    long: &str,
+    rest: &mut impl Iterator<Item = &'a String>,
+) -> Result<Option<&'a str>, String> {
+    if arg == short || arg == long {
+        return rest
+            .next()
+            .map(|value| Some(value.as_str()))
+            .ok_or_else(|| format!("option requires an argument -- '{}'", &short[1..]));
+    }
+    if let Some(value) = arg.strip_prefix(long).and_then(|v| v.strip_prefix('=')) {
+        return Ok(Some(value));
+    }
+    Ok(arg.strip_prefix(short).filter(|v| !v.is_empty()))
+}
+
+/// Count suffixes and what they multiply by, as GNU `head` and `tail` accept them
+const SUFFIXES: [(&str, u64); 20] = [
+    ("b", 512),
+    ("kB", 1000),
+    ("k", 1 << 10),
+    ("K", 1 << 10),
+    ("KiB", 1 << 10),
+    ("MB", 1000 * 1000),
+    ("M", 1 << 20),
+    ("MiB", 1 << 20),
+    ("GB", 1000 * 1000 * 1000),
+    ("G", 1 << 30),
+    ("GiB", 1 << 30),
+    ("TB", 1000 * 1000 * 1000 * 1000),
+    ("T", 1 << 40),
+    ("TiB", 1 << 40),
+    ("PB", 1000 * 1000 * 1000 * 1000 * 1000),
+    ("P", 1 << 50),
+    ("PiB", 1 << 50),
+    ("EB", 1000 * 1000 * 1000 * 1000 * 1000 * 1000),
+    ("E", 1 << 60),
+    ("EiB", 1 << 60),
+];
+
+/// Parse a count like `20`, `10K` or `1MiB`. Counts too big for `usize` saturate,
+/// since no file can outlast them anyway.
+fn parse_count(value: &str, unit: &str) -> Result<usize, String> {
+    let invalid = || format!("invalid number of {}: '{}'", unit, value);
+
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, suffix) = value.split_at(split);
+    let number = match digits.parse::<u64>() {
+        Ok(number) => number,
+        Err(e) if *e.kind() == std::num::IntErrorKind::PosOverflow => u64::MAX,
+        Err(_) => return Err(invalid()),
+    };
+    let multiplier = match suffix {
+        "" => 1,
+        suffix => {
+            SUFFIXES
+                .iter()
+                .find(|(name, _)| *name == suffix)
+                .ok_or_else(invalid)?
+                .1
+        }
+    };
+
+    Ok(usize::try_from(number.saturating_mul(multiplier)).unwrap_or(usize::MAX))
+}
+
+/// Parse `-n NUM`/`--lines=NUM`, `-c NUM`/`--bytes=NUM`, `-q`/`--quiet`, `-v`/`--verbose`,
+/// `-z`/`--zero-terminated`, `-f`/`--follow[=name|descriptor]`, `-F`, `--retry` and file operands
+pub fn parse_line_args(args: &[String]) -> Result<LineArgs, String> {
+    let mut parsed = LineArgs {
+        lines: 10,
+        bytes: None,
+        headers: None,
+        zero_terminated: false,
+        follow: false,
+        follow_name: false,
+        retry: false,
+        files: Vec::new(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if let Some(value) = option_value(arg, "-n", "--lines", &mut iter)? {
+            parsed.lines = parse_count(value, "lines")?;
+            continue;
+        }
+        if let Some(value) = option_value(arg, "-c", "--bytes", &mut iter)? {
+            parsed.bytes = Some(parse_count(value, "bytes")?);
+            continue;
+        }
+
+        match arg.as_str() {
+            "-z" | "--zero-terminated" => parsed.zero_terminated = true,
+            "-f" | "--follow" | "--follow=descriptor" => parsed.follow = true,
+            "--follow=name" => {
+                parsed.follow = true;
+                parsed.follow_name = true;
+            }
+            "-F" => {
+                parsed.follow = true;
+                parsed.follow_name = true;
+                parsed.retry = true;
+            }
+            "--retry" => parsed.retry = true,
+            "-q" | "--quiet" | "--silent" => parsed.headers = Some(false),
+            "-v" | "--verbose" => parsed.headers = Some(true),
+            _ => parsed.files.push(arg.clone()),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Entry point for the `head` shell command
+pub fn run(args: &[String]) {
+    let parsed = match parse_line_args(args) {
+        Ok(parsed) if !parsed.files.is_empty() => parsed,
+        Ok(_) => {
+            eprintln!("Usage: head [-n NUM | -c NUM] [-q | -v] [-z] <file>...");
+            return;
+        }
+        Err(e) => {
+            eprintln!("head: {}", e);
+            return;
+        }
+    };
+
+    let show_headers = parsed.show_headers();
+    let mut stdout = io::stdout();
+    for (i, file) in parsed.files.iter().enumerate() {
+        if show_headers {
+            println!("{}==> {} <==", if i == 0 { "" } else { "\n" }, file);
+        }
+        let output = match parsed.bytes {
+            Some(bytes) => head_bytes(vec![file], bytes),
+            None => head_sync_with(vec![file], parsed.lines, parsed.zero_terminated)
+                .map(String::into_bytes),
+        };
+        match output {
+            Ok(output) => {
+                let _ = stdout.write_all(&output);
+            }
+            Err(e) => eprintln!("head: {}: {}", file, e),
+        }
+    }
+    let _ = stdout.flush();
+}
+
 // Async version that returns a Stream<Bytes>
 pub async fn head_async<S: AsRef<Path> + Send + 'static>(
     files: Vec<S>,
@@ -145,4 +367,99 @@ mod tests {
 
         tokio::fs::remove_file(file_path).await.unwrap();
     }
+
+    #[test]
+    fn test_head_zero_terminated() {
+        let file_path = "test_head_zero.txt";
+        std::fs::write(file_path, "a\nb\0c\0d\0").unwrap();
+
+        let result = head_sync_with(vec![file_path], 2, true).unwrap();
+        assert_eq!(result, "a\nb\0c\0");
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_line_args() {
+        let args: Vec<String> = ["-n", "3", "-z", "-f", "f.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let parsed = parse_line_args(&args).unwrap();
+        assert_eq!(parsed.lines, 3);
+        assert!(parsed.zero_terminated);
+        assert!(parsed.follow);
+        assert_eq!(parsed.files, ["f.txt"]);
+
+        assert!(parse_line_args(&["-n".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_lines_option_spellings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("five.txt");
+        std::fs::write(&path, "1\n2\n3\n4\n5\n").unwrap();
+        let file = path.to_string_lossy().to_string();
+
+        let spellings: [&[&str]; 4] = [
+            &["--lines=3", &file],
+            &["-n3", &file],
+            &["-n", "3", &file],
+            &["--lines", "3", &file],
+        ];
+        for spelling in spellings {
+            let args: Vec<String> = spelling.iter().map(|s| s.to_string()).collect();
+            let parsed = parse_line_args(&args).unwrap();
+            assert_eq!(parsed.lines, 3);
+            assert_eq!(parsed.files, std::slice::from_ref(&file));
+            assert_eq!(head_sync(parsed.files, parsed.lines).unwrap(), "1\n2\n3\n");
+        }
+
+        let args: Vec<String> = ["--bytes=4", "-q", "--verbose", "--zero-terminated", &file]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let parsed = parse_line_args(&args).unwrap();
+        assert_eq!(parsed.bytes, Some(4));
+        assert_eq!(parsed.headers, Some(true));
+        assert!(parsed.zero_terminated);
+        assert_eq!(head_bytes(parsed.files, 4).unwrap(), b"1\n2\n");
+
+        assert_eq!(
+            parse_line_args(&["-nx".to_string()]).unwrap_err(),
+            "invalid number of lines: 'x'"
+        );
+        assert_eq!(
+            parse_line_args(&["--bytes=many".to_string()]).unwrap_err(),
+            "invalid number of bytes: 'many'"
+        );
+    }
+
+    #[test]
+    fn test_count_suffixes() {
+        assert_eq!(parse_count("10K", "bytes"), Ok(10240));
+        assert_eq!(parse_count("10k", "bytes"), Ok(10240));
+        assert_eq!(parse_count("1KiB", "bytes"), Ok(1024));
+        assert_eq!(parse_count("2kB", "bytes"), Ok(2000));
+        assert_eq!(parse_count("3b", "bytes"), Ok(1536));
+        assert_eq!(parse_count("1MiB", "bytes"), Ok(1 << 20));
+        assert_eq!(parse_count("5M", "lines"), Ok(5 << 20));
+        assert!(parse_count("K", "bytes").is_err());
+        assert!(parse_count("10Q", "bytes").is_err());
+        assert!(parse_count("-1", "bytes").is_err());
+
+        // Far past usize and u64: clamped instead of overflowing
+        assert_eq!(parse_count("20E", "bytes"), Ok(usize::MAX));
+        assert_eq!(
+            parse_count("99999999999999999999999999999999G", "bytes"),
+            Ok(usize::MAX)
+        );
+
+        let args: Vec<String> = ["-c", "10G", "f.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let expected = usize::try_from(10u64 << 30).unwrap_or(usize::MAX);
+        assert_eq!(parse_line_args(&args).unwrap().bytes, Some(expected));
+    }
 }
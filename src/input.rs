@@ -1,57 +1,848 @@
-use rustyline::completion::{Completer, Pair};
-use rustyline::highlight::Highlighter;
-use rustyline::hint::Hinter;
-use rustyline::history::DefaultHistory;
-use rustyline::validate::Validator;
-use rustyline::{Config, Context, Editor, Helper, error::ReadlineError};
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+use rustyline::error::ReadlineError;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-#[derive(Clone)]
-pub struct MyHelper;
+const PROMPT: &str = ">> ";
+const HISTORY_FILE: &str = ".winix_history";
+/// First line of a history file whose entries escape backslashes and newlines, as rustyline did
+const HISTORY_HEADER: &str = "#V2";
+/// History lines kept when `HISTSIZE` is unset
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
 
-impl Completer for MyHelper {
-    type Candidate = Pair;
+/// Keys the line editor understands, independent of the terminal backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKey {
+    Char(char),
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    Backspace,
+    Delete,
+    Enter,
+    Interrupt,
+    Eof,
+}
+
+/// Raw-mode terminal the line editor reads keys from and draws on
+pub trait Terminal {
+    /// Whether keys come from a console rather than piped or scripted input
+    fn is_interactive(&self) -> bool;
+    fn enable_raw_mode(&mut self) -> io::Result<()>;
+    fn disable_raw_mode(&mut self) -> io::Result<()>;
+    fn read_key(&mut self) -> io::Result<EditKey>;
+    /// Redraw the prompt and line, leaving the cursor at `cursor` chars into the line
+    fn redraw(&mut self, prompt: &str, line: &str, cursor: usize) -> io::Result<()>;
+    fn newline(&mut self) -> io::Result<()>;
+}
+
+/// Terminal backend built on crossterm, which works on both Windows and Unix consoles
+#[derive(Debug, Default)]
+pub struct CrosstermTerminal;
+
+impl Terminal for CrosstermTerminal {
+    fn is_interactive(&self) -> bool {
+        // crossterm falls back to /dev/tty, which would ignore what was piped to stdin
+        io::stdin().is_terminal()
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    fn read_key(&mut self) -> io::Result<EditKey> {
+        loop {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            // Windows reports releases too; only act on presses
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            let edit_key = match key.code {
+                KeyCode::Char('c') if ctrl => EditKey::Interrupt,
+                KeyCode::Char('d') if ctrl => EditKey::Eof,
+                KeyCode::Char('a') if ctrl => EditKey::Home,
+                KeyCode::Char('e') if ctrl => EditKey::End,
+                KeyCode::Char(_) if ctrl => continue,
+                KeyCode::Char(c) => EditKey::Char(c),
+                KeyCode::Left => EditKey::Left,
+                KeyCode::Right => EditKey::Right,
+                KeyCode::Home => EditKey::Home,
+                KeyCode::End => EditKey::End,
+                KeyCode::Up => EditKey::Up,
+                KeyCode::Down => EditKey::Down,
+                KeyCode::Backspace => EditKey::Backspace,
+                KeyCode::Delete => EditKey::Delete,
+                KeyCode::Enter => EditKey::Enter,
+                _ => continue,
+            };
+            return Ok(edit_key);
+        }
+    }
+
+    fn redraw(&mut self, prompt: &str, line: &str, cursor: usize) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        let column = prompt.chars().count() + cursor;
+        queue!(
+            stdout,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(prompt),
+            Print(line),
+            MoveToColumn(column as u16)
+        )?;
+        stdout.flush()
+    }
 
-    fn complete(
-        &self,
-        _line: &str,
-        _pos: usize,
-        _ctx: &Context<'_>,
-    ) -> Result<(usize, Vec<Pair>), ReadlineError> {
-        Ok((0, vec![])) // Stub for tab-completion
+    fn newline(&mut self) -> io::Result<()> {
+        execute!(io::stdout(), Print("\r\n"))
     }
 }
 
-impl Hinter for MyHelper {
-    type Hint = String;
+/// Editing state for one line plus the history it can recall
+#[derive(Debug, Default, Clone)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    /// Position while browsing history; `None` means editing the draft
+    history_index: Option<usize>,
+    draft: String,
 }
-impl Highlighter for MyHelper {}
-impl Validator for MyHelper {}
-impl Helper for MyHelper {}
 
-pub struct LineEditor {
-    rl: Editor<MyHelper, DefaultHistory>, // Corrected type and name
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_history(history: Vec<String>) -> Self {
+        LineBuffer {
+            history,
+            ..Self::default()
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Remove the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    /// Remove the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    /// Recall the previous (older) history entry
+    pub fn history_prev(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.text();
+                self.history.len() - 1
+            }
+            Some(0) => return,
+            Some(i) => i - 1,
+        };
+        self.history_index = Some(index);
+        self.set_text(&self.history[index].clone());
+    }
+
+    /// Move towards newer history entries, ending back at the draft line
+    pub fn history_next(&mut self) {
+        match self.history_index {
+            None => {}
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_index = Some(i + 1);
+                self.set_text(&self.history[i + 1].clone());
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.set_text(&self.draft.clone());
+            }
+        }
+    }
+
+    /// Record a submitted line, skipping blanks and immediate duplicates
+    pub fn push_history(&mut self, line: &str) {
+        if line.trim().is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+    }
+
+    /// Return the current line and reset for the next one
+    pub fn take_line(&mut self) -> String {
+        let line = self.text();
+        self.chars.clear();
+        self.cursor = 0;
+        self.history_index = None;
+        self.draft.clear();
+        line
+    }
+
+    /// Apply an editing key; submit and cancel keys are left to the caller
+    pub fn apply(&mut self, key: EditKey) {
+        match key {
+            EditKey::Char(c) => self.insert(c),
+            EditKey::Left => self.move_left(),
+            EditKey::Right => self.move_right(),
+            EditKey::Home => self.home(),
+            EditKey::End => self.end(),
+            EditKey::Up => self.history_prev(),
+            EditKey::Down => self.history_next(),
+            EditKey::Backspace => self.backspace(),
+            EditKey::Delete | EditKey::Eof => self.delete(),
+            EditKey::Enter | EditKey::Interrupt => {}
+        }
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+}
+
+/// Command history persisted to a file, one entry per line
+#[derive(Debug)]
+pub struct HistoryFile {
+    path: PathBuf,
+    max_len: usize,
+    entries: Vec<String>,
+    /// Whether the file on disk starts with `HISTORY_HEADER`, so escaped lines can be appended
+    escaped: bool,
+}
+
+/// Escape `entry` onto one line: `\` becomes `\\` and a newline `\n`
+fn escape_history(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Undo `escape_history`; a backslash before any other character is kept as is
+fn unescape_history(line: &str) -> String {
+    let mut entry = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.peek() {
+                Some('n') => {
+                    entry.push('\n');
+                    chars.next();
+                    continue;
+                }
+                Some('\\') => {
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        entry.push(c);
+    }
+    entry
+}
+
+impl HistoryFile {
+    /// `~/.winix_history`, or the current directory when there is no home
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir().unwrap_or_default().join(HISTORY_FILE)
+    }
+
+    /// Maximum entries from `HISTSIZE`, falling back to `DEFAULT_HISTORY_SIZE`
+    pub fn max_len_from_env() -> usize {
+        std::env::var("HISTSIZE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_HISTORY_SIZE)
+    }
+
+    /// Read the last `max_len` entries of `path`; a missing file is an empty history
+    pub fn load(path: &Path, max_len: usize) -> Self {
+        let mut history = HistoryFile {
+            path: path.to_path_buf(),
+            max_len,
+            entries: Vec::new(),
+            escaped: false,
+        };
+
+        if let Ok(contents) = fs::read_to_string(path) {
+            // Files without the header predate escaping and are read verbatim
+            let mut lines = contents.lines().peekable();
+            history.escaped = lines.next_if_eq(&HISTORY_HEADER).is_some();
+            for line in lines {
+                if history.escaped {
+                    history.push(&unescape_history(line));
+                } else {
+                    history.push(line);
+                }
+            }
+        }
+        history.truncate();
+        history
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record `line` unless it is blank or repeats the previous entry
+    fn push(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() || self.entries.last().map(String::as_str) == Some(line) {
+            return false;
+        }
+        self.entries.push(line.to_string());
+        true
+    }
+
+    /// Drop the oldest entries beyond `max_len`, returning whether any were dropped
+    fn truncate(&mut self) -> bool {
+        let excess = self.entries.len().saturating_sub(self.max_len);
+        self.entries.drain(..excess);
+        excess > 0
+    }
+
+    /// Add `line` and save it, appending unless the file needs trimming to `max_len`
+    /// or rewriting with the header. Multi-line entries are saved escaped onto one line.
+    pub fn append(&mut self, line: &str) -> io::Result<()> {
+        if !self.push(line) {
+            return Ok(());
+        }
+
+        if self.truncate() || !self.escaped {
+            let mut contents = format!("{}\n", HISTORY_HEADER);
+            for entry in &self.entries {
+                contents.push_str(&escape_history(entry));
+                contents.push('\n');
+            }
+            fs::write(&self.path, contents)?;
+            self.escaped = true;
+            return Ok(());
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", escape_history(line))
+    }
+}
+
+pub struct LineEditor<T: Terminal = CrosstermTerminal> {
+    buffer: LineBuffer,
+    terminal: T,
+    history_file: Option<HistoryFile>,
+    /// Set once saving history has failed, so the warning is shown only once
+    history_warned: bool,
 }
 
 impl LineEditor {
     pub fn new() -> Self {
-        let config = Config::builder().history_ignore_dups(true).unwrap().build();
+        let history_file = HistoryFile::load(
+            &HistoryFile::default_path(),
+            HistoryFile::max_len_from_env(),
+        );
+
+        LineEditor {
+            buffer: LineBuffer::with_history(history_file.entries().to_vec()),
+            terminal: CrosstermTerminal,
+            history_file: Some(history_file),
+            history_warned: false,
+        }
+    }
+}
 
-        let helper = MyHelper;
-        let mut rl = Editor::with_config(config).expect("Failed to create Editor");
-        rl.set_helper(Some(helper));
-        rl.load_history(".history.txt").ok(); // Optional: load history
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        LineEditor { rl }
+impl<T: Terminal> LineEditor<T> {
+    pub fn with_terminal(terminal: T) -> Self {
+        LineEditor {
+            buffer: LineBuffer::new(),
+            terminal,
+            history_file: None,
+            history_warned: false,
+        }
     }
 
     pub fn read_line(&mut self) -> Result<String, ReadlineError> {
-        self.rl.readline(">> ")
+        // Not a console (e.g. piped input): fall back to plain line reads
+        if !self.terminal.is_interactive() || self.terminal.enable_raw_mode().is_err() {
+            return read_plain_line();
+        }
+
+        let result = self.edit_line();
+        self.terminal.disable_raw_mode()?;
+        result
+    }
+
+    fn edit_line(&mut self) -> Result<String, ReadlineError> {
+        self.terminal.redraw(PROMPT, "", 0)?;
+
+        loop {
+            match self.terminal.read_key()? {
+                EditKey::Enter => {
+                    self.terminal.newline()?;
+                    return Ok(self.buffer.take_line());
+                }
+                EditKey::Interrupt => {
+                    self.buffer.take_line();
+                    self.terminal.newline()?;
+                    return Err(ReadlineError::Interrupted);
+                }
+                EditKey::Eof if self.buffer.is_empty() => {
+                    self.terminal.newline()?;
+                    return Err(ReadlineError::Eof);
+                }
+                key => self.buffer.apply(key),
+            }
+
+            self.terminal
+                .redraw(PROMPT, &self.buffer.text(), self.buffer.cursor())?;
+        }
     }
 
     pub fn add_history_entry(&mut self, line: &str) {
-        if let Err(e) = self.rl.add_history_entry(line) {
-            eprintln!("Failed to add history entry: {}", e);
+        self.buffer.push_history(line);
+
+        // A read-only home shouldn't stop the shell; warn once and keep history in memory
+        if let Some(file) = &mut self.history_file
+            && let Err(e) = file.append(line)
+            && !self.history_warned
+        {
+            eprintln!(
+                "warning: cannot save history to {}: {}",
+                file.path.display(),
+                e
+            );
+            self.history_warned = true;
+        }
+    }
+}
+
+fn read_plain_line() -> Result<String, ReadlineError> {
+    print!("{}", PROMPT);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(ReadlineError::Eof);
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Disables terminal echo while alive and restores the previous mode on drop
+#[cfg(unix)]
+pub struct EchoGuard {
+    fd: std::os::unix::io::RawFd,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl EchoGuard {
+    pub fn new(fd: std::os::unix::io::RawFd) -> io::Result<Self> {
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut original) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut silent = original;
+            silent.c_lflag &= !libc::ECHO;
+            if libc::tcsetattr(fd, libc::TCSANOW, &silent) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(EchoGuard { fd, original })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Disables console echo while alive and restores the previous mode on drop
+#[cfg(windows)]
+pub struct EchoGuard {
+    handle: winapi::um::winnt::HANDLE,
+    original: u32,
+}
+
+#[cfg(windows)]
+impl EchoGuard {
+    /// # Safety
+    /// `handle` must be a console input handle that stays open while the guard lives.
+    pub unsafe fn new(handle: winapi::um::winnt::HANDLE) -> io::Result<Self> {
+        use winapi::um::consoleapi::{GetConsoleMode, SetConsoleMode};
+        use winapi::um::wincon::ENABLE_ECHO_INPUT;
+
+        unsafe {
+            let mut original = 0;
+            if GetConsoleMode(handle, &mut original) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if SetConsoleMode(handle, original & !ENABLE_ECHO_INPUT) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(EchoGuard { handle, original })
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EchoGuard {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::consoleapi::SetConsoleMode(self.handle, self.original);
+        }
+    }
+}
+
+/// Prompt for a secret without echoing what the user types
+pub fn read_password(prompt: &str) -> io::Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    // Piped input has no echo to hide, so carry on without a guard
+    #[cfg(unix)]
+    let guard = EchoGuard::new(libc::STDIN_FILENO).ok();
+    #[cfg(windows)]
+    let guard = unsafe {
+        EchoGuard::new(winapi::um::processenv::GetStdHandle(
+            winapi::um::winbase::STD_INPUT_HANDLE,
+        ))
+    }
+    .ok();
+
+    let mut line = String::new();
+    let result = io::stdin().lock().read_line(&mut line);
+
+    if guard.is_some() {
+        drop(guard);
+        // The user's Enter was not echoed either
+        println!();
+    }
+
+    result?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn buffer_with(text: &str) -> LineBuffer {
+        let mut buffer = LineBuffer::new();
+        text.chars().for_each(|c| buffer.insert(c));
+        buffer
+    }
+
+    #[test]
+    fn test_insert_at_cursor() {
+        let mut buffer = buffer_with("helo");
+        buffer.move_left();
+        buffer.insert('l');
+        assert_eq!(buffer.text(), "hello");
+        assert_eq!(buffer.cursor(), 4);
+
+        buffer.home();
+        buffer.insert('>');
+        buffer.end();
+        buffer.insert('!');
+        assert_eq!(buffer.text(), ">hello!");
+    }
+
+    #[test]
+    fn test_backspace_and_delete_mid_line() {
+        let mut buffer = buffer_with("abcd");
+        buffer.move_left();
+        buffer.move_left();
+        buffer.backspace();
+        assert_eq!(buffer.text(), "acd");
+        buffer.delete();
+        assert_eq!(buffer.text(), "ad");
+        assert_eq!(buffer.cursor(), 1);
+
+        buffer.end();
+        buffer.delete();
+        buffer.home();
+        buffer.backspace();
+        assert_eq!(buffer.text(), "ad");
+    }
+
+    #[test]
+    fn test_history_navigation_restores_draft() {
+        let mut buffer = LineBuffer::with_history(vec!["ls".into(), "pwd".into()]);
+        buffer.insert('c');
+
+        buffer.history_prev();
+        assert_eq!(buffer.text(), "pwd");
+        buffer.history_prev();
+        assert_eq!(buffer.text(), "ls");
+        buffer.history_prev();
+        assert_eq!(buffer.text(), "ls");
+
+        buffer.history_next();
+        assert_eq!(buffer.text(), "pwd");
+        buffer.history_next();
+        assert_eq!(buffer.text(), "c");
+        assert_eq!(buffer.cursor(), 1);
+    }
+
+    #[test]
+    fn test_push_history_skips_blank_and_duplicates() {
+        let mut buffer = LineBuffer::new();
+        buffer.push_history("ls");
+        buffer.push_history("ls");
+        buffer.push_history("  ");
+        buffer.push_history("pwd");
+        assert_eq!(buffer.history(), ["ls", "pwd"]);
+    }
+
+    #[test]
+    fn test_history_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+
+        let mut history = HistoryFile::load(&path, 10);
+        assert!(history.entries().is_empty());
+        for line in ["ls", "ls", "", "pwd", "ls"] {
+            history.append(line).unwrap();
+        }
+        assert_eq!(history.entries(), ["ls", "pwd", "ls"]);
+
+        let reloaded = HistoryFile::load(&path, 10);
+        assert_eq!(reloaded.entries(), ["ls", "pwd", "ls"]);
+    }
+
+    #[test]
+    fn test_history_file_dedup_and_max_len() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+        fs::write(&path, "#V2\na\na\nb\nc\nc\nd\n").unwrap();
+
+        let mut history = HistoryFile::load(&path, 3);
+        assert_eq!(history.entries(), ["b", "c", "d"]);
+
+        history.append("e").unwrap();
+        assert_eq!(history.entries(), ["c", "d", "e"]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "#V2\nc\nd\ne\n");
+    }
+
+    #[test]
+    fn test_history_file_multi_line_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+        // A file from before escaping: backslashes are literal
+        fs::write(&path, "cd C:\\new\n").unwrap();
+
+        let heredoc = "cat <<EOF\nfirst\\nsecond\nEOF";
+        let mut history = HistoryFile::load(&path, 10);
+        history.append(heredoc).unwrap();
+        history.append("echo done").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "#V2\ncd C:\\\\new\ncat <<EOF\\nfirst\\\\nsecond\\nEOF\necho done\n"
+        );
+
+        let reloaded = HistoryFile::load(&path, 10);
+        assert_eq!(reloaded.entries(), ["cd C:\\new", heredoc, "echo done"]);
+        assert_eq!(unescape_history("a\\tb\\"), "a\\tb\\");
+    }
+
+    #[test]
+    fn test_history_file_unwritable_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = HistoryFile::load(&dir.path().join("missing").join("history"), 10);
+        assert!(history.append("ls").is_err());
+        // The entry is still remembered for this session
+        assert_eq!(history.entries(), ["ls"]);
+    }
+
+    struct ScriptedTerminal {
+        keys: VecDeque<EditKey>,
+    }
+
+    impl Terminal for ScriptedTerminal {
+        fn is_interactive(&self) -> bool {
+            true
+        }
+
+        fn enable_raw_mode(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn read_key(&mut self) -> io::Result<EditKey> {
+            self.keys
+                .pop_front()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "out of keys"))
+        }
+
+        fn redraw(&mut self, _prompt: &str, _line: &str, _cursor: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn newline(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_line_with_scripted_keys() {
+        use EditKey::*;
+        let keys = [
+            Char('e'),
+            Char('o'),
+            Left,
+            Char('h'),
+            Home,
+            Delete,
+            Enter,
+            Up,
+            Enter,
+            Eof,
+        ];
+        let mut editor = LineEditor::with_terminal(ScriptedTerminal {
+            keys: keys.into_iter().collect(),
+        });
+
+        let line = editor.read_line().unwrap();
+        assert_eq!(line, "ho");
+
+        editor.buffer.push_history(&line);
+        assert_eq!(editor.read_line().unwrap(), "ho");
+        assert!(matches!(editor.read_line(), Err(ReadlineError::Eof)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_echo_guard_restores_mode_on_drop() {
+        let echo_enabled = |fd| unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            assert_eq!(libc::tcgetattr(fd, &mut termios), 0);
+            termios.c_lflag & libc::ECHO != 0
+        };
+
+        // A fresh pseudo-terminal stands in for the user's console
+        let (master, slave) = unsafe {
+            let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(master >= 0);
+            assert_eq!(libc::grantpt(master), 0);
+            assert_eq!(libc::unlockpt(master), 0);
+            let slave = libc::open(libc::ptsname(master), libc::O_RDWR | libc::O_NOCTTY);
+            assert!(slave >= 0);
+            (master, slave)
+        };
+
+        assert!(echo_enabled(slave));
+        {
+            let _guard = EchoGuard::new(slave).unwrap();
+            assert!(!echo_enabled(slave));
+        }
+        assert!(echo_enabled(slave));
+
+        unsafe {
+            libc::close(slave);
+            libc::close(master);
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_echo_guard_restores_mode_on_drop() {
+        use std::os::windows::io::AsRawHandle;
+        use winapi::um::consoleapi::GetConsoleMode;
+        use winapi::um::wincon::ENABLE_ECHO_INPUT;
+
+        // Without an attached console there is nothing to toggle
+        let Ok(console) = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("CONIN$")
+        else {
+            return;
+        };
+        let handle = console.as_raw_handle() as winapi::um::winnt::HANDLE;
+        let mode = || unsafe {
+            let mut mode = 0;
+            GetConsoleMode(handle, &mut mode);
+            mode
+        };
+
+        let before = mode();
+        {
+            let _guard = unsafe { EchoGuard::new(handle) }.unwrap();
+            assert_eq!(mode() & ENABLE_ECHO_INPUT, 0);
         }
-        self.rl.save_history(".history.txt").ok(); // Optional: save history
+        assert_eq!(mode(), before);
     }
 }
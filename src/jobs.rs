@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::io;
+use std::process::Child;
+use std::sync::{Mutex, OnceLock};
+
+/// Shell job number, written as `%N`
+pub type JobId = usize;
+
+/// A background command started by the shell
+#[derive(Debug)]
+pub struct JobEntry {
+    pub pid: u32,
+    pub command: String,
+    child: Child,
+}
+
+impl JobEntry {
+    /// Whether the process is still alive
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Terminate the process and wait for it to exit
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.child.kill()?;
+        self.child.wait().map(|_| ())
+    }
+}
+
+/// Registry of background jobs the shell kills on exit.
+///
+/// Windows cannot remove a process from a Job object once assigned, so jobs
+/// are killed one by one at cleanup and disowning simply drops the entry.
+#[derive(Debug, Default)]
+pub struct JobTable {
+    jobs: Mutex<HashMap<JobId, JobEntry>>,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The table used by the interactive shell
+    pub fn global() -> &'static JobTable {
+        static TABLE: OnceLock<JobTable> = OnceLock::new();
+        TABLE.get_or_init(JobTable::new)
+    }
+
+    /// Track a spawned child and return its job number
+    pub fn register(&self, child: Child, command: &str) -> JobId {
+        let mut jobs = self.jobs.lock().unwrap();
+        let id = jobs.keys().max().map_or(1, |max| max + 1);
+        jobs.insert(
+            id,
+            JobEntry {
+                pid: child.id(),
+                command: command.to_string(),
+                child,
+            },
+        );
+        id
+    }
+
+    /// Tracked jobs as `(id, pid, command)`, dropping any that have exited
+    pub fn list(&self) -> Vec<(JobId, u32, String)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(|_, entry| entry.is_running());
+
+        let mut list: Vec<_> = jobs
+            .iter()
+            .map(|(id, entry)| (*id, entry.pid, entry.command.clone()))
+            .collect();
+        list.sort_by_key(|(id, _, _)| *id);
+        list
+    }
+
+    /// Stop tracking a job given as `%N`, a PID, or empty for the most recent job
+    pub fn disown(&self, spec: &str) -> Result<(JobId, JobEntry), String> {
+        let mut jobs = self.jobs.lock().unwrap();
+
+        let id = if spec.is_empty() {
+            *jobs.keys().max().ok_or("current: no such job")?
+        } else if let Some(number) = spec.strip_prefix('%') {
+            number
+                .parse()
+                .map_err(|_| format!("{}: no such job", spec))?
+        } else {
+            let pid: u32 = spec
+                .parse()
+                .map_err(|_| format!("{}: not a job spec or pid", spec))?;
+            let found = jobs.iter().find(|(_, entry)| entry.pid == pid);
+            match found {
+                Some((id, _)) => *id,
+                None if crate::process::is_running(pid) => {
+                    return Err(format!("{}: not a job of this shell", spec));
+                }
+                None => return Err(format!("{}: no such process", spec)),
+            }
+        };
+
+        jobs.remove(&id)
+            .map(|entry| (id, entry))
+            .ok_or(format!("{}: no such job", spec))
+    }
+
+    /// Kill every job still tracked, as the shell does on exit
+    pub fn cleanup(&self) -> Vec<JobId> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut killed = Vec::new();
+
+        for (id, mut entry) in jobs.drain() {
+            if entry.is_running() && entry.kill().is_ok() {
+                killed.push(id);
+            }
+        }
+
+        killed.sort();
+        killed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn spawn_sleeper() -> Child {
+        #[cfg(windows)]
+        let mut command = Command::new("powershell");
+        #[cfg(windows)]
+        command.args(["-Command", "Start-Sleep -Seconds 30"]);
+
+        #[cfg(not(windows))]
+        let mut command = Command::new("sleep");
+        #[cfg(not(windows))]
+        command.arg("30");
+
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to spawn sleep process")
+    }
+
+    #[test]
+    fn test_disowned_job_survives_cleanup() {
+        let table = JobTable::new();
+        let kept = table.register(spawn_sleeper(), "sleep 30");
+        let detached = table.register(spawn_sleeper(), "sleep 30");
+        assert_eq!((kept, detached), (1, 2));
+
+        let (id, mut entry) = table.disown("%2").unwrap();
+        assert_eq!(id, detached);
+        assert_eq!(table.list().len(), 1);
+
+        assert_eq!(table.cleanup(), vec![kept]);
+        assert!(entry.is_running());
+        entry.kill().unwrap();
+    }
+
+    #[test]
+    fn test_disown_by_pid_and_unknown() {
+        let table = JobTable::new();
+        table.register(spawn_sleeper(), "sleep 30");
+        let pid = table.list()[0].1;
+
+        let (_, mut entry) = table.disown(&pid.to_string()).unwrap();
+        entry.kill().unwrap();
+
+        assert!(table.disown("%1").is_err());
+        assert!(table.disown("").is_err());
+        assert!(table.disown("abc").is_err());
+    }
+}
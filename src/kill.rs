@@ -1,6 +1,7 @@
 /*
 This file tries to mimic the behavior of the Unix `kill` command
 for Windows, allowing users to terminate processes by PID or name.
+On Unix the same options map directly onto kill(2) and killpg(3).
 
 The Unix definition for 'kill' is:
 kill [-signal|-s signal|-p] [-q value] [-a] [--timeout milliseconds signal] [--] pid|name...
@@ -10,8 +11,11 @@ Arguments breakdown:
 - -signal: Specify signal number or name
 - -s signal: Alternative signal specification
 - -p: Print PID only, don't send signal
+- -n, --dry-run: List the processes that would be signaled, without signaling them
 - -q value: Send signal with additional data
 - -a: Apply to all processes with given name
+- -g pgid: Signal a whole process group (also written as a negative PID)
+- --port PORT: Signal whatever is listening on a TCP port
 - --timeout ms signal: Send signal, wait, then send second signal
 - --: End of options marker
 - pid|name...: Process IDs or names to target
@@ -28,29 +32,37 @@ Key things addressed:
     - Support for reasonable signals like -2, -3, -9, -15 (INT, QUIT, KILL, TERM)
     - Ensures -a tag is used with only names processes
 */
-#![cfg(windows)]
-
 use colored::Colorize;
-use std::thread;
-use std::time::Duration;
+#[cfg(windows)]
+use std::time::{Duration, Instant};
+#[cfg(windows)]
 use winapi::shared::minwindef::{BOOL, DWORD, LPARAM, TRUE};
+#[cfg(windows)]
 use winapi::shared::windef::HWND;
+#[cfg(windows)]
 use winapi::um::errhandlingapi::GetLastError;
+#[cfg(windows)]
 use winapi::um::handleapi::CloseHandle;
+#[cfg(windows)]
 use winapi::um::processthreadsapi::{GetCurrentProcessId, OpenProcess, TerminateProcess};
+#[cfg(windows)]
 use winapi::um::tlhelp32::{
     CreateToolhelp32Snapshot, PROCESSENTRY32, Process32First, Process32Next, TH32CS_SNAPPROCESS,
 };
+#[cfg(windows)]
 use winapi::um::wincon::{CTRL_BREAK_EVENT, CTRL_C_EVENT};
+#[cfg(windows)]
 use winapi::um::winnt::{PROCESS_QUERY_INFORMATION, PROCESS_TERMINATE};
+#[cfg(windows)]
 use winapi::um::winuser::{EnumWindows, GetWindowThreadProcessId, PostMessageW, WM_CLOSE};
 
 // Simple debug macro replacement
+#[cfg(windows)]
 macro_rules! debug {
     ($($arg:tt)*) => {};
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct KillOptions {
     pub signal: Option<String>,          // Signal
     pub signal_explicit: Option<String>, // Signal from -s flag
@@ -61,8 +73,12 @@ pub struct KillOptions {
     pub timeout_signal: Option<String>,  // Signal to send after timeout
     pub end_of_options: bool,            // -- encountered
     pub targets: Vec<String>,            // PIDs or process names
+    pub process_groups: Vec<u32>,        // -g pgid or negative PIDs
+    pub dry_run: bool,                   // -n / --dry-run
+    pub ports: Vec<u16>,                 // --port PORT
 }
 
+#[cfg(windows)]
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub enum WindowsKillMethod {
@@ -72,27 +88,12 @@ pub enum WindowsKillMethod {
     WindowClose,       // For GUI applications
 }
 
-impl Default for KillOptions {
-    fn default() -> Self {
-        KillOptions {
-            signal: None,
-            signal_explicit: None,
-            print_only: false,
-            queue_value: None,
-            all_processes: false,
-            timeout_ms: None,
-            timeout_signal: None,
-            end_of_options: false,
-            targets: Vec::new(),
-        }
-    }
-}
-
+#[cfg(windows)]
 pub fn execute(args: &[&str]) -> Result<(), String> {
     if args.is_empty() {
         return Err(format!(
             "{}",
-            "Usage: kill [-signal|-s signal|-p] [-q value] [-a] [--timeout milliseconds signal] [--] pid|name...\n\
+            "Usage: kill [-signal|-s signal|-p|-n] [-q value] [-a] [-g pgid] [--port port] [--timeout milliseconds signal] [--] pid|name|-pgid...\n\
             \n\
             Supported signals on Windows:\n\
             -2, -INT    Interrupt (Ctrl+C)\n\
@@ -104,16 +105,24 @@ pub fn execute(args: &[&str]) -> Result<(), String> {
             kill 1234           # Force terminate process 1234\n\
             kill -TERM 1234     # Graceful terminate\n\
             kill -9 1234        # Force terminate\n\
-            kill -a notepad     # Kill all notepad processes"
+            kill -a notepad     # Kill all notepad processes\n\
+            kill -n -a notepad  # List the notepad processes that would be killed\n\
+            kill -g 1234        # Kill process 1234 and all of its descendants\n\
+            kill --port 8080    # Kill whatever is listening on port 8080\n\
+            kill --timeout 5000 KILL 1234  # TERM, then KILL after 5 s if still running"
         ));
     }
 
     let options = parse_arguments(args)?;
     validate_options(&options)?;
     debug!("Parsed options: {:?}", options);
+    if options.dry_run {
+        return report_dry_run(&options).map(|_| ());
+    }
     handle_kill(&options)
 }
 
+#[cfg(windows)]
 fn handle_kill(options: &KillOptions) -> Result<(), String> {
     debug!("Starting kill operation");
 
@@ -128,7 +137,12 @@ fn handle_kill(options: &KillOptions) -> Result<(), String> {
         .as_ref()
         .or(options.signal_explicit.as_ref())
         .map(|s| s.as_str())
-        .unwrap_or("9"); // Default to SIGKILL if no signal specified
+        // Default to SIGKILL, or SIGTERM when --timeout will escalate anyway
+        .unwrap_or(if options.timeout_ms.is_some() {
+            "TERM"
+        } else {
+            "9"
+        });
 
     let kill_method = signal_to_windows_method(signal)?;
     debug!("Using kill method: {:?}", kill_method);
@@ -149,6 +163,18 @@ fn handle_kill(options: &KillOptions) -> Result<(), String> {
         results.push((target.clone(), result));
     }
 
+    // Windows has no process groups, so a group is the process tree rooted at its id
+    for &pgid in &options.process_groups {
+        results.push((format!("-{}", pgid), kill_process_tree(pgid, &kill_method)));
+    }
+
+    for &port in &options.ports {
+        let result = kill_port(port, requested_signal(options), |pid| {
+            kill_process_by_pid(pid, &kill_method, options)
+        });
+        results.push((format!("port {}", port), result));
+    }
+
     // Handle timeout logic if specified
     if let Some(timeout_ms) = options.timeout_ms {
         handle_timeout_kill(&results, timeout_ms, options)?;
@@ -161,6 +187,7 @@ fn handle_kill(options: &KillOptions) -> Result<(), String> {
 }
 
 // Handle -p flag: just print PIDs without killing
+#[cfg(windows)]
 fn handle_print_only_mode(options: &KillOptions) -> Result<(), String> {
     debug!("Print-only mode activated");
     for target in &options.targets {
@@ -182,22 +209,42 @@ fn handle_print_only_mode(options: &KillOptions) -> Result<(), String> {
             }
         }
     }
+    for &port in &options.ports {
+        for pid in port_owners(port)? {
+            println!("{}", pid);
+        }
+    }
 
     Ok(())
 }
 
 // Kill a specific process by PID
+#[cfg(windows)]
 fn kill_process_by_pid(
     pid: u32,
     method: &WindowsKillMethod,
-    _options: &KillOptions,
+    options: &KillOptions,
 ) -> Result<(), String> {
     debug!("Attempting to kill PID {} using method {:?}", pid, method);
-    validate_pid_safety(pid)?;
-    if !process_exists(pid) {
-        return Err(format!("No such process: {}", pid));
-    }
+    check_target_pid(pid)?;
     match method {
+        // With --timeout the escalation handles stragglers, so don't force-terminate yet
+        WindowsKillMethod::GracefulCtrlC | WindowsKillMethod::GracefulCtrlBreak
+            if options.timeout_ms.is_some() =>
+        {
+            if let Err(e) = window_close_process(pid) {
+                debug!("No graceful close for {}: {}", pid, e);
+                println!(
+                    "{}",
+                    format!(
+                        "Process {} has no window to close, waiting for the timeout",
+                        pid
+                    )
+                    .yellow()
+                );
+            }
+            Ok(())
+        }
         WindowsKillMethod::ForceTerminate => force_terminate_process(pid),
         WindowsKillMethod::GracefulCtrlC => graceful_terminate_process(pid, false),
         WindowsKillMethod::GracefulCtrlBreak => graceful_terminate_process(pid, true),
@@ -206,6 +253,7 @@ fn kill_process_by_pid(
 }
 
 // Kill processes by name
+#[cfg(windows)]
 fn kill_process_by_name(
     name: &str,
     method: &WindowsKillMethod,
@@ -216,15 +264,7 @@ fn kill_process_by_name(
         name, method
     );
 
-    let pids = find_processes_by_name(name)?;
-    if pids.is_empty() {
-        return Err(format!("No processes found with name: {}", name));
-    }
-    let targets = if options.all_processes {
-        pids
-    } else {
-        vec![pids[0]]
-    };
+    let targets = pids_for_name(name, options)?;
 
     let mut errors = Vec::new();
     let mut success_count = 0;
@@ -251,6 +291,7 @@ fn kill_process_by_name(
 }
 
 // Handle timeout logic: send initial signal, wait, then send final signal
+#[cfg(windows)]
 fn handle_timeout_kill(
     results: &[(String, Result<(), String>)],
     timeout_ms: u64,
@@ -319,12 +360,13 @@ fn handle_timeout_kill(
         )
         .cyan()
     );
-    thread::sleep(Duration::from_millis(timeout_ms));
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
 
-    // Check which processes are still alive and kill them with the timeout signal
+    // Wait for each process until the shared deadline; whatever is left gets the timeout signal
     let mut still_alive = Vec::new();
     for (pid, target_name) in target_pids {
-        if process_exists(pid) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !crate::process::wait_timeout(pid, remaining) {
             still_alive.push((pid, target_name));
         } else {
             println!(
@@ -408,6 +450,7 @@ fn handle_timeout_kill(
 }
 
 // Helper function to kill a process with a specific method
+#[cfg(windows)]
 fn kill_process_with_method(pid: u32, method: &WindowsKillMethod) -> Result<(), String> {
     match method {
         WindowsKillMethod::ForceTerminate => force_terminate_process(pid),
@@ -417,6 +460,140 @@ fn kill_process_with_method(pid: u32, method: &WindowsKillMethod) -> Result<(),
     }
 }
 
+/// Every process `options` selects as (pid, name), resolved with the same checks
+/// the real kill applies, so a dry run fails exactly where the kill would
+fn resolve_targets(options: &KillOptions) -> Result<Vec<(u32, String)>, String> {
+    let mut pids = Vec::new();
+    for target in &options.targets {
+        match target.parse::<u32>() {
+            Ok(pid) => {
+                check_target_pid(pid)?;
+                pids.push(pid);
+            }
+            Err(_) => pids.extend(pids_for_name(target, options)?),
+        }
+    }
+    for &pgid in &options.process_groups {
+        pids.extend(group_members(pgid)?);
+    }
+    for &port in &options.ports {
+        pids.extend(port_owners(port)?);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    pids.retain(|pid| seen.insert(*pid));
+    Ok(pids
+        .into_iter()
+        .map(|pid| {
+            let name = crate::process::name_of(pid).unwrap_or_else(|| "?".to_string());
+            (pid, name)
+        })
+        .collect())
+}
+
+/// The signal a kill with `options` starts with, as the user would write it
+fn requested_signal(options: &KillOptions) -> &str {
+    let default = if cfg!(windows) && options.timeout_ms.is_none() {
+        "KILL"
+    } else {
+        "TERM"
+    };
+    options
+        .signal
+        .as_deref()
+        .or(options.signal_explicit.as_deref())
+        .unwrap_or(default)
+}
+
+/// The processes listening on `port`, which must be at least one
+fn port_owners(port: u16) -> Result<Vec<u32>, String> {
+    let pids = crate::process::pids_for_port(port);
+    if pids.is_empty() {
+        return Err(format!("No process is listening on port {}", port));
+    }
+    Ok(pids)
+}
+
+/// Signal everything listening on `port` with `signal_one`, naming each process
+fn kill_port(
+    port: u16,
+    signal: &str,
+    signal_one: impl FnMut(u32) -> Result<(), String>,
+) -> Result<(), String> {
+    let pids = port_owners(port)?;
+    signal_port_owners(port, &pids, signal, signal_one)
+}
+
+/// Signal each of `pids`, carrying on past failures so one protected process
+/// doesn't shield the rest, then report how many could not be signaled
+fn signal_port_owners(
+    port: u16,
+    pids: &[u32],
+    signal: &str,
+    mut signal_one: impl FnMut(u32) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut failed = 0;
+    for &pid in pids {
+        // Look the name up first, while the process still exists
+        let name = crate::process::name_of(pid).unwrap_or_else(|| "?".to_string());
+        match signal_one(pid) {
+            Ok(()) => println!("Sent {} to {} ({}) on port {}", signal, pid, name, port),
+            Err(e) => {
+                println!("{}", format!("{} ({}): {}", pid, name, e).red());
+                failed += 1;
+            }
+        }
+    }
+    if pids.len() > 1 {
+        println!("{} processes were listening on port {}", pids.len(), port);
+    }
+    if failed > 0 {
+        return Err(format!(
+            "failed to signal {} of {} process(es) on port {}",
+            failed,
+            pids.len(),
+            port
+        ));
+    }
+    Ok(())
+}
+
+// Handle -n/--dry-run: list what would be signaled and how many processes that is
+fn report_dry_run(options: &KillOptions) -> Result<usize, String> {
+    let targets = resolve_targets(options)?;
+    let signal = requested_signal(options);
+    for (pid, name) in &targets {
+        println!("Would send {} to {} ({})", signal, pid, name);
+    }
+    println!(
+        "{}",
+        format!("{} process(es) would be signaled", targets.len()).yellow()
+    );
+    Ok(targets.len())
+}
+
+/// Parse `args` like `execute` and report what it would signal without sending
+/// anything, returning the number of processes found
+pub fn dry_run(args: &[&str]) -> Result<usize, String> {
+    let mut options = parse_arguments(args)?;
+    options.dry_run = true;
+    validate_options(&options)?;
+    report_dry_run(&options)
+}
+
+/// Stop `pid` the way a bare `kill PID` does, without printing anything:
+/// SIGTERM on Unix, forced termination on Windows
+pub fn terminate(pid: u32) -> Result<(), String> {
+    #[cfg(unix)]
+    return send_signal(pid, libc::SIGTERM);
+
+    #[cfg(windows)]
+    {
+        check_target_pid(pid)?;
+        force_terminate_process(pid)
+    }
+}
+
 // Report the results of kill operations
 fn report_kill_results(results: &[(String, Result<(), String>)]) -> Result<(), String> {
     let mut has_errors = false;
@@ -452,7 +629,10 @@ fn parse_arguments(args: &[&str]) -> Result<KillOptions, String> {
         let arg = args[i];
         // If we've seen --, everything else is a target
         if options.end_of_options {
-            options.targets.push(arg.to_string());
+            match parse_group(arg) {
+                Some(pgid) => options.process_groups.push(pgid?),
+                None => options.targets.push(arg.to_string()),
+            }
             i += 1;
             continue;
         }
@@ -465,10 +645,36 @@ fn parse_arguments(args: &[&str]) -> Result<KillOptions, String> {
             "-p" => {
                 options.print_only = true;
             }
+            // Resolve targets but don't signal them
+            "-n" | "--dry-run" => {
+                options.dry_run = true;
+            }
+            // Whatever is listening on a TCP port
+            "--port" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Option --port requires a port number".to_string());
+                }
+                options.ports.push(parse_port(args[i])?);
+            }
+            arg if arg.starts_with("--port=") => {
+                options.ports.push(parse_port(&arg["--port=".len()..])?);
+            }
             // All processes flag
             "-a" => {
                 options.all_processes = true;
             }
+            // Explicit process group
+            "-g" => {
+                i += 1;
+                if i >= args.len() {
+                    return Err("Option -g requires a process group ID".to_string());
+                }
+                match args[i].parse::<u32>() {
+                    Ok(pgid) => options.process_groups.push(pgid),
+                    Err(_) => return Err(format!("Invalid process group ID: {}", args[i])),
+                }
+            }
             // Explicit signal flag
             "-s" => {
                 i += 1;
@@ -519,6 +725,14 @@ fn parse_arguments(args: &[&str]) -> Result<KillOptions, String> {
                     options.timeout_signal = Some(args[i].to_string());
                 }
             }
+            // Once a signal is known, a negative number is a process group (kill -9 -1234)
+            arg if (options.signal.is_some() || options.signal_explicit.is_some())
+                && parse_group(arg).is_some() =>
+            {
+                if let Some(pgid) = parse_group(arg) {
+                    options.process_groups.push(pgid?);
+                }
+            }
             // Signal arguments (start with -)
             arg if arg.starts_with('-') && arg.len() > 1 => {
                 let signal = &arg[1..]; // Remove the leading -
@@ -545,12 +759,45 @@ fn parse_arguments(args: &[&str]) -> Result<KillOptions, String> {
     Ok(options)
 }
 
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("Invalid port: {}", value)),
+    }
+}
+
+/// A negative PID such as `-1234` names process group 1234
+fn parse_group(arg: &str) -> Option<Result<u32, String>> {
+    let digits = arg.strip_prefix('-')?;
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(
+        digits
+            .parse()
+            .map_err(|_| format!("Invalid process group ID: {}", arg)),
+    )
+}
+
 fn validate_options(options: &KillOptions) -> Result<(), String> {
     // Must have at least one target unless using -p with no targets
-    if options.targets.is_empty() && !options.print_only {
+    if options.targets.is_empty()
+        && options.process_groups.is_empty()
+        && options.ports.is_empty()
+        && !options.print_only
+    {
         return Err("No process ID or name specified".to_string());
     }
 
+    if options.dry_run && options.print_only {
+        return Err("Cannot use -n with -p".to_string());
+    }
+
+    // Group 0 means "my own group" and 1 is init's, neither of which we want to hit
+    if let Some(pgid) = options.process_groups.iter().find(|&&pgid| pgid <= 1) {
+        return Err(format!("Cannot signal process group {}", pgid));
+    }
+
     // Cannot use both -s signal and -signal
     if options.signal.is_some() && options.signal_explicit.is_some() {
         return Err("Cannot specify signal with both -signal and -s options".to_string());
@@ -559,7 +806,7 @@ fn validate_options(options: &KillOptions) -> Result<(), String> {
     // Validate signal if specified
     let signal_to_check = options.signal.as_ref().or(options.signal_explicit.as_ref());
     if let Some(signal) = signal_to_check {
-        signal_to_windows_method(signal)?; // This will return an error for unsupported signals
+        check_signal(signal)?; // This will return an error for unsupported signals
     }
 
     // -a flag only makes sense with process names, not PIDs
@@ -578,12 +825,23 @@ fn validate_options(options: &KillOptions) -> Result<(), String> {
 
     // Validate timeout signal if specified
     if let Some(timeout_signal) = &options.timeout_signal {
-        signal_to_windows_method(timeout_signal)?;
+        check_signal(timeout_signal)?;
     }
 
     Ok(())
 }
 
+#[cfg(windows)]
+fn check_signal(signal: &str) -> Result<(), String> {
+    signal_to_windows_method(signal).map(|_| ())
+}
+
+#[cfg(unix)]
+fn check_signal(signal: &str) -> Result<(), String> {
+    signal_number(signal).map(|_| ())
+}
+
+#[cfg(windows)]
 fn is_valid_signal_name(signal: &str) -> bool {
     matches!(
         signal.to_uppercase().as_str(),
@@ -594,6 +852,183 @@ fn is_valid_signal_name(signal: &str) -> bool {
     )
 }
 
+#[cfg(unix)]
+fn is_valid_signal_name(signal: &str) -> bool {
+    signal_number(signal).is_ok()
+}
+
+// Map a signal name (TERM or SIGTERM) or number to its value
+#[cfg(unix)]
+fn signal_number(signal: &str) -> Result<libc::c_int, String> {
+    let upper = signal.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    let number = match name {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "PIPE" => libc::SIGPIPE,
+        "ALRM" => libc::SIGALRM,
+        "TERM" => libc::SIGTERM,
+        "CHLD" => libc::SIGCHLD,
+        "CONT" => libc::SIGCONT,
+        "STOP" => libc::SIGSTOP,
+        "TSTP" => libc::SIGTSTP,
+        "TTIN" => libc::SIGTTIN,
+        "TTOU" => libc::SIGTTOU,
+        "WINCH" => libc::SIGWINCH,
+        _ => match name.parse::<libc::c_int>() {
+            Ok(number) if (0..=64).contains(&number) => number,
+            _ => return Err(format!("Invalid signal: {}", signal)),
+        },
+    };
+    Ok(number)
+}
+
+#[cfg(unix)]
+pub fn execute(args: &[&str]) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(
+            "Usage: kill [-signal|-s signal|-p|-n] [-g pgid] [--port port] [--] pid|-pgid...\n\
+            \n\
+            Examples:\n\
+            kill 1234           # Terminate process 1234 (SIGTERM)\n\
+            kill -9 1234        # Kill process 1234\n\
+            kill -g 1234        # Terminate every process in group 1234\n\
+            kill -n -g 1234     # List the members of group 1234 without signaling\n\
+            kill --port 8080    # Terminate whatever is listening on port 8080\n\
+            kill -TERM -- -1234 # Same, with a negative PID"
+                .to_string(),
+        );
+    }
+
+    let options = parse_arguments(args)?;
+    validate_options(&options)?;
+    if options.all_processes || options.queue_value.is_some() || options.timeout_ms.is_some() {
+        return Err("-a, -q and --timeout are only supported on Windows".to_string());
+    }
+    if options.dry_run {
+        return report_dry_run(&options).map(|_| ());
+    }
+
+    let signal = signal_number(
+        options
+            .signal
+            .as_deref()
+            .or(options.signal_explicit.as_deref())
+            .unwrap_or("TERM"),
+    )?;
+
+    let mut results = Vec::new();
+    for target in &options.targets {
+        let result = match target.parse::<u32>() {
+            Ok(pid) if options.print_only => {
+                println!("{}", pid);
+                continue;
+            }
+            Ok(pid) => send_signal(pid, signal),
+            Err(_) => pids_for_name(target, &options).map(|_| ()),
+        };
+        results.push((target.clone(), result));
+    }
+    if options.print_only {
+        for &port in &options.ports {
+            for pid in port_owners(port)? {
+                println!("{}", pid);
+            }
+        }
+    } else {
+        for &pgid in &options.process_groups {
+            results.push((format!("-{}", pgid), signal_group(pgid, signal)));
+        }
+        for &port in &options.ports {
+            let result = kill_port(port, requested_signal(&options), |pid| {
+                send_signal(pid, signal)
+            });
+            results.push((format!("port {}", port), result));
+        }
+    }
+
+    report_kill_results(&results)
+}
+
+// Refuse init, ourselves and processes that don't exist
+#[cfg(unix)]
+fn check_target_pid(pid: u32) -> Result<libc::pid_t, String> {
+    let target = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid PID: {}", pid))?;
+    if pid <= 1 {
+        return Err(format!("Cannot kill system process with PID {}", pid));
+    }
+    if pid == std::process::id() {
+        return Err("Cannot kill current process".to_string());
+    }
+    if !crate::process::is_running(pid) {
+        return Err(format!("No such process: {}", pid));
+    }
+    Ok(target)
+}
+
+#[cfg(unix)]
+fn pids_for_name(name: &str, _options: &KillOptions) -> Result<Vec<u32>, String> {
+    Err(format!(
+        "Process names are not supported, use a PID: {}",
+        name
+    ))
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> Result<(), String> {
+    let target = check_target_pid(pid)?;
+    if unsafe { libc::kill(target, signal) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+// A process group other than our own, which holds the shell and would take it down
+#[cfg(unix)]
+fn check_group(pgid: u32) -> Result<libc::pid_t, String> {
+    let group =
+        libc::pid_t::try_from(pgid).map_err(|_| format!("Invalid process group ID: {}", pgid))?;
+    if group == unsafe { libc::getpgrp() } {
+        return Err("Cannot signal the current process group".to_string());
+    }
+    Ok(group)
+}
+
+// The processes killpg would reach
+#[cfg(unix)]
+fn group_members(pgid: u32) -> Result<Vec<u32>, String> {
+    let group = check_group(pgid)?;
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    let mut members: Vec<u32> = system
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind().is_none())
+        .map(|(pid, _)| pid.as_u32())
+        .filter(|&pid| unsafe { libc::getpgid(pid as libc::pid_t) } == group)
+        .collect();
+    if members.is_empty() {
+        return Err(format!("No such process group: {}", pgid));
+    }
+    members.sort_unstable();
+    Ok(members)
+}
+
+// Signal every member of a process group with killpg
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) -> Result<(), String> {
+    let group = check_group(pgid)?;
+    if unsafe { libc::killpg(group, signal) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
 fn signal_to_windows_method(signal: &str) -> Result<WindowsKillMethod, String> {
     match signal.to_uppercase().as_str() {
         "KILL" | "9" => Ok(WindowsKillMethod::ForceTerminate),
@@ -607,29 +1042,14 @@ fn signal_to_windows_method(signal: &str) -> Result<WindowsKillMethod, String> {
     }
 }
 
+#[cfg(windows)]
 fn process_exists(pid: u32) -> bool {
     debug!("Checking if process {} exists", pid);
-    unsafe {
-        // Try to open the process with minimal rights just to check existence
-        let process_handle = OpenProcess(
-            PROCESS_QUERY_INFORMATION,
-            0, // bInheritHandle = FALSE
-            pid,
-        );
-
-        if process_handle.is_null() {
-            debug!("Process {} does not exist or cannot be accessed", pid);
-            return false;
-        }
-
-        // Process exists and we can access it
-        CloseHandle(process_handle);
-        debug!("Process {} exists and is accessible", pid);
-        true
-    }
+    crate::process::is_running(pid)
 }
 
 // Safety validation for PIDs
+#[cfg(windows)]
 fn validate_pid_safety(pid: u32) -> Result<(), String> {
     // Protect against killing critical system processes
     const PROTECTED_PIDS: &[u32] = &[0, 4, 8]; // System, System Idle, etc.
@@ -646,7 +1066,101 @@ fn validate_pid_safety(pid: u32) -> Result<(), String> {
     Ok(())
 }
 
+// Refuse protected processes, ourselves and processes that don't exist
+#[cfg(windows)]
+fn check_target_pid(pid: u32) -> Result<(), String> {
+    validate_pid_safety(pid)?;
+    if !process_exists(pid) {
+        return Err(format!("No such process: {}", pid));
+    }
+    Ok(())
+}
+
+// The processes matching `name`: all of them with -a, otherwise the first
+#[cfg(windows)]
+fn pids_for_name(name: &str, options: &KillOptions) -> Result<Vec<u32>, String> {
+    let pids = find_processes_by_name(name)?;
+    if pids.is_empty() {
+        return Err(format!("No processes found with name: {}", name));
+    }
+    if options.all_processes {
+        Ok(pids)
+    } else {
+        Ok(vec![pids[0]])
+    }
+}
+
+// Kill a process and everything descended from it, the closest Windows has to a group
+#[cfg(windows)]
+fn kill_process_tree(root: u32, method: &WindowsKillMethod) -> Result<(), String> {
+    let tree = group_members(root)?;
+    let errors: Vec<String> = tree
+        .into_iter()
+        .filter_map(|pid| {
+            validate_pid_safety(pid)
+                .and_then(|_| kill_process_with_method(pid, method))
+                .err()
+                .map(|e| format!("{}: {}", pid, e))
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+// `root` and all of its descendants
+#[cfg(windows)]
+fn group_members(root: u32) -> Result<Vec<u32>, String> {
+    validate_pid_safety(root)?;
+    if !process_exists(root) {
+        return Err(format!("No such process group: {}", root));
+    }
+
+    let parents = process_parents()?;
+    let mut tree = vec![root];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        for &(pid, ppid) in &parents {
+            if ppid == parent && pid != parent && !tree.contains(&pid) {
+                tree.push(pid);
+            }
+        }
+        i += 1;
+    }
+    debug!("Process tree of {}: {:?}", root, tree);
+    Ok(tree)
+}
+
+// Every running process as (pid, parent pid)
+#[cfg(windows)]
+fn process_parents() -> Result<Vec<(u32, u32)>, String> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == winapi::um::handleapi::INVALID_HANDLE_VALUE {
+            return Err(format!(
+                "Failed to create process snapshot: Windows error code {}",
+                GetLastError()
+            ));
+        }
+
+        let mut entry: PROCESSENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32>() as DWORD;
+        let mut pairs = Vec::new();
+        let mut more = Process32First(snapshot, &mut entry) != 0;
+        while more {
+            pairs.push((entry.th32ProcessID, entry.th32ParentProcessID));
+            more = Process32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+        Ok(pairs)
+    }
+}
+
 // Find processes by name
+#[cfg(windows)]
 fn find_processes_by_name(name: &str) -> Result<Vec<u32>, String> {
     debug!("Finding processes with name: {}", name);
     let mut matching_pids = Vec::new();
@@ -715,6 +1229,7 @@ fn find_processes_by_name(name: &str) -> Result<Vec<u32>, String> {
 }
 
 // Force terminate a process (SIGKILL equivalent)
+#[cfg(windows)]
 fn force_terminate_process(pid: u32) -> Result<(), String> {
     debug!("Force terminating process {} using TerminateProcess", pid);
     unsafe {
@@ -772,6 +1287,7 @@ fn force_terminate_process(pid: u32) -> Result<(), String> {
 }
 
 // Graceful terminate using console control events (SIGINT/SIGQUIT)
+#[cfg(windows)]
 fn graceful_terminate_process(pid: u32, use_ctrl_break: bool) -> Result<(), String> {
     let signal_type = if use_ctrl_break {
         ("Ctrl+Break (SIGQUIT)", CTRL_BREAK_EVENT)
@@ -813,6 +1329,7 @@ fn graceful_terminate_process(pid: u32, use_ctrl_break: bool) -> Result<(), Stri
 }
 
 // Fallback graceful termination for non-console applications
+#[cfg(windows)]
 fn graceful_terminate_fallback(pid: u32, use_ctrl_break: bool) -> Result<(), String> {
     let signal_type = if use_ctrl_break {
         "Ctrl+Break"
@@ -848,6 +1365,7 @@ fn graceful_terminate_fallback(pid: u32, use_ctrl_break: bool) -> Result<(), Str
 }
 
 // Close process by sending WM_CLOSE to its windows
+#[cfg(windows)]
 fn window_close_process(pid: u32) -> Result<(), String> {
     debug!("Attempting to close windows for process {}", pid);
     unsafe {
@@ -893,6 +1411,7 @@ fn window_close_process(pid: u32) -> Result<(), String> {
 }
 
 // Structure to pass data to the window enumeration callback
+#[cfg(windows)]
 #[repr(C)]
 struct EnumWindowsData {
     target_pid: DWORD,
@@ -901,6 +1420,7 @@ struct EnumWindowsData {
 }
 
 // Callback function for EnumWindows
+#[cfg(windows)]
 unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
     unsafe {
         let data = &mut *(lparam as *mut EnumWindowsData);
@@ -925,3 +1445,28 @@ unsafe extern "system" fn enum_windows_proc(hwnd: HWND, lparam: LPARAM) -> BOOL
         TRUE // Continue enumeration
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_kill_continues_past_failures() {
+        let mut signaled = Vec::new();
+        let result = signal_port_owners(8080, &[101, 102, 103], "SIGTERM", |pid| {
+            signaled.push(pid);
+            if pid == 102 {
+                Err("Operation not permitted".to_string())
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(signaled, vec![101, 102, 103]);
+        assert_eq!(
+            result,
+            Err("failed to signal 1 of 3 process(es) on port 8080".to_string())
+        );
+        assert!(signal_port_owners(8080, &[101], "SIGTERM", |_| Ok(())).is_ok());
+    }
+}
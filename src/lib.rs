@@ -1,6 +1,6 @@
 pub mod ansi;
 pub mod cat;
-#[cfg(windows)]
+#[cfg(any(windows, unix))]
 pub mod chmod;
 pub mod chown;
 pub mod df;
@@ -12,6 +12,7 @@ pub mod git;
 pub mod grep;
 pub mod head;
 pub mod input;
+pub mod jobs;
 pub mod kill;
 pub mod nproc;
 pub mod pipeline;
@@ -23,9 +24,11 @@ pub mod sensors;
 pub mod sudo;
 pub mod tail;
 pub mod touch;
+pub mod tree;
 pub mod tui;
 pub mod uname;
 pub mod uptime;
+pub mod watch;
 
 #[cfg(test)]
 mod tests {
@@ -4,38 +4,34 @@ use rustyline::error::ReadlineError;
 use std::env as std_env;
 use std::fs;
 use std::io::{self};
-use winix::{echo, touch, env, nproc};
+use winix::kill;
+use winix::jobs::JobTable;
+use winix::{
+    ansi, cat, echo, env, free, git, grep, head, input, nproc, pipeline, ps, sudo, tail, touch,
+    tree, tui,
+};
 
-mod cat;
 mod cd;
 mod mkdir;
 mod rmdir;
-mod tree;
 mod cp;
 mod traceroute;
 mod sysinfo;
-#[cfg(windows)]
+#[cfg(any(windows, unix))]
 mod chmod;
-#[cfg(windows)]
 mod chown;
 mod df;
 mod disown;
-mod free;
-mod git;
-mod input;
-#[cfg(windows)]
-mod kill;
 mod powershell;
-mod ps;
 mod rm;
 mod sensors;
-mod sudo;
-mod tui;
 mod uname;
 mod uptime;
+mod watch;
 
 fn main() {
     let args: Vec<String> = std_env::args().collect();
+    ansi::enable_vt_mode();
     if args.contains(&"--interactive".to_string()) {
         git::interactive_mode();
     }
@@ -58,7 +54,17 @@ fn run_cli() {
     loop {
         let readline = editor.read_line();
         match readline {
-            Ok(line) => {
+            Ok(mut line) => {
+                // A here-document's body follows on the next lines, up to its delimiter
+                while pipeline::heredoc_pending(&line) {
+                    match editor.read_line() {
+                        Ok(more) => {
+                            line.push('\n');
+                            line.push_str(&more);
+                        }
+                        Err(_) => break,
+                    }
+                }
                 editor.add_history_entry(line.as_str());
 
                 if line.trim() == "exit" || line.trim() == "quit" {
@@ -87,14 +93,40 @@ fn run_cli() {
             }
         }
     }
+
+    // Background jobs die with the shell unless they were disowned
+    for id in JobTable::global().cleanup() {
+        println!("[{}] Killed", id);
+    }
 }
 
 fn handle_command(line: &str) {
-    let parts: Vec<&str> = line.trim().split_whitespace().collect();
+    // Pipes, `&&`/`||` and groups run as external programs; a lone command keeps the builtins
+    let words = match pipeline::parse(line) {
+        Ok(pipeline::Ast::Command(words)) => words,
+        Ok(ast) => {
+            match pipeline::run_ast(&ast) {
+                Ok(0) => {}
+                Ok(code) => eprintln!("exited with code {}", code),
+                Err(e) => println!("{}", format!("winix: {}", e).red()),
+            }
+            return;
+        }
+        Err(e) => {
+            println!("{}", format!("winix: {}", e).red());
+            return;
+        }
+    };
+    let parts: Vec<&str> = words.iter().map(String::as_str).collect();
     if parts.is_empty() {
         return;
     }
 
+    if parts.len() > 1 && parts[parts.len() - 1] == "&" {
+        spawn_background(&parts[..parts.len() - 1]);
+        return;
+    }
+
     let command = parts[0].to_lowercase();
     let args: Vec<String> = parts[1..].iter().map(|s| s.to_string()).collect();
 
@@ -120,16 +152,28 @@ fn handle_command(line: &str) {
             }
         }
 
+        "cat" => cat::run(&args),
+        "tac" => cat::run_tac(&args),
         "echo" => echo::run(&args),
+        "head" => head::run(&args),
+        "grep" => {
+            // 1 only means nothing matched, and errors are already reported
+            grep::run(&args);
+        }
+        "tail" => tail::run(&args),
         "touch" => touch::run(&args),
-        "uname" => uname::execute(),
-        "ps" => ps::execute(),
-        "sensors" => sensors::execute(),
-        "free" => free::execute(),
-        "uptime" => uptime::execute(),
-        "df" => df::execute(),
-
-        #[cfg(windows)]
+        "uname" => uname::execute(&args),
+        "ps" => ps::execute(&args),
+        "sensors" => sensors::execute(&args),
+        "free" => free::execute(&args),
+        "uptime" => uptime::execute(&args),
+        "df" => {
+            let code = df::execute(&args);
+            if code != 0 {
+                eprintln!("df exited with code {}", code);
+            }
+        }
+
         "kill" => {
             if args.is_empty() {
                 println!("{}", "Usage: kill <pid|name> [options]".red());
@@ -140,24 +184,14 @@ fn handle_command(line: &str) {
             }
         }
 
-        #[cfg(windows)]
+        #[cfg(any(windows, unix))]
         "chmod" => {
             if args.is_empty() {
-                println!("{}", "Usage: chmod <mode> <file>...".red());
+                println!("{}", "Usage: chmod [-R] [-v|-c] <mode|--reference=RFILE> <file>...".red());
             } else {
-                let mode = &args[0];
-                let files: Vec<&str> = args[1..].iter().map(String::as_str).collect();
-                if files.is_empty() {
-                    println!("{}", "Usage: chmod <mode> <file>...".red());
-                } else {
-                    // Call into library implementation for each file
-                    for f in files {
-                        let _ = chmod::execute(&[mode, f]);
-                    }
-                }
+                chmod::execute(&args.iter().map(String::as_str).collect::<Vec<_>>());
             }
         }
-        #[cfg(windows)]
         "chown" => {
             if args.is_empty() {
                 println!("{}", "Usage: chown <owner>[:group] <file>...".red());
@@ -169,7 +203,7 @@ fn handle_command(line: &str) {
                 } else {
                     chown::execute(
                         &std::iter::once(mode.as_str())
-                            .chain(files.into_iter())
+                            .chain(files)
                             .collect::<Vec<&str>>(),
                     );
                 }
@@ -178,13 +212,15 @@ fn handle_command(line: &str) {
 
         "rm" => {
             if args.is_empty() {
-                println!("{}", "Usage: rm <file1> [file2] ...".red());
+                println!(
+                    "{}",
+                    "Usage: rm [-r] [-d] [-i|-I] [--trash] [--one-file-system] <file1> [file2] ..."
+                        .red()
+                );
             } else {
-                for file in &args {
-                    match fs::remove_file(file) {
-                        Ok(_) => println!("Deleted {}", file),
-                        Err(e) => eprintln!("Failed to delete {}: {}", file, e),
-                    }
+                let code = rm::run(&args);
+                if code != 0 {
+                    eprintln!("rm exited with code {}", code);
                 }
             }
         }
@@ -200,6 +236,29 @@ fn handle_command(line: &str) {
                 eprintln!("nproc exited with code {}", code);
             }
         }
+        "sudo" => {
+            let code = sudo::execute(&args);
+            if code != 0 {
+                eprintln!("sudo exited with code {}", code);
+            }
+        }
+        "jobs" => {
+            for (id, pid, command) in JobTable::global().list() {
+                println!("[{}] {} Running    {}", id, pid, command);
+            }
+        }
+        "disown" => {
+            let specs = if args.is_empty() {
+                vec![String::new()]
+            } else {
+                args.clone()
+            };
+            for spec in &specs {
+                if let Err(e) = JobTable::global().disown(spec) {
+                    println!("{}", format!("disown: {}", e).red());
+                }
+            }
+        }
         "git" => {
             let git_args = &["status"]; // Replace with real input
             git::execute(git_args);
@@ -224,7 +283,9 @@ fn handle_command(line: &str) {
     }
 
     "tree" => {
-        tree::run(&args);
+        if let Err(e) = tree::run(&args) {
+            eprintln!("tree: {}", e);
+        }
     }
 
     "cp" => {
@@ -242,6 +303,11 @@ fn handle_command(line: &str) {
         // let probes: u32 = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(3);
         // let timeout_ms: u64 = args.get(4).and_then(|s| s.parse().ok()).unwrap_or(2000);
         // let start_port: u16 = args.get(5).and_then(|s| s.parse().ok()).unwrap_or(33434u16);
+        let (options, args) = traceroute::parse_options(&args);
+        if args.is_empty() {
+            traceroute::print_usage("traceroute");
+            return;
+        }
         let host = &args[0];
         let max_hops: u32 = args.get(1).and_then(|s| s.parse().ok()).unwrap_or(30);
         let probes: u32 = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(3);
@@ -251,20 +317,20 @@ fn handle_command(line: &str) {
 
         #[cfg(target_os = "windows")]
         {
-            traceroute::windows_traceroute(host, max_hops, probes, timeout_ms);
+            traceroute::windows_traceroute(host, max_hops, probes, timeout_ms, &options);
             return;
         }
 
         #[cfg(not(target_os = "windows"))]
         {
-            if let Err(e) = traceroute::run_traceroute_unix(host, max_hops, probes, timeout_ms, start_port) {
+            if let Err(e) = traceroute::run_traceroute_unix(host, max_hops, probes, timeout_ms, start_port, &options) {
                 eprintln!("Traceroute failed: {}", e);
             }
         }
     }
 
     "sysinfo" =>{
-        sysinfo::run();
+        sysinfo::run(&args);
     }
 
         _ => {
@@ -274,6 +340,22 @@ fn handle_command(line: &str) {
     }
 }
 
+/// Launch an external command without waiting and track it in the job table
+fn spawn_background(parts: &[&str]) {
+    let child = std::process::Command::new(parts[0])
+        .args(&parts[1..])
+        .stdin(std::process::Stdio::null())
+        .spawn();
+
+    match child {
+        Ok(child) => {
+            let pid = child.id();
+            let id = JobTable::global().register(child, &parts.join(" "));
+            println!("[{}] {}", id, pid);
+        }
+        Err(e) => println!("{}", format!("{}: {}", parts[0], e).red()),
+    }
+}
 
 fn show_splash_screen() {
     println!(
@@ -1,9 +1,39 @@
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Create `path`, and with `parents` any missing ancestors.
+/// Returns the directories actually created, outermost first.
+pub fn create_dirs(path: &Path, parents: bool) -> io::Result<Vec<PathBuf>> {
+    if !parents {
+        fs::create_dir(path)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut missing = Vec::new();
+    let mut current = path;
+    while !current.as_os_str().is_empty() && !current.is_dir() {
+        missing.push(current);
+        match current.parent() {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+
+    let mut created = Vec::new();
+    for dir in missing.into_iter().rev() {
+        match fs::create_dir(dir) {
+            Ok(()) => created.push(dir.to_path_buf()),
+            // Someone else may have made it in the meantime
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && dir.is_dir() => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(created)
+}
 
 /// Run the mkdir command
-/// `args` should be the arguments passed to mkdir, e.g., ["-p", "dir1", "dir2"]
+/// `args` should be the arguments passed to mkdir, e.g., ["-p", "-v", "dir1", "dir2"]
 pub fn run(args: &[String]) -> io::Result<()> {
     if args.is_empty() {
         eprintln!("mkdir: missing operand");
@@ -11,27 +41,56 @@ pub fn run(args: &[String]) -> io::Result<()> {
     }
 
     let mut recursive = false;
+    let mut verbose = false;
     let mut dirs = Vec::new();
 
     for arg in args {
-        if arg == "-p" {
-            recursive = true;
-        } else {
-            dirs.push(arg);
+        match arg.as_str() {
+            "-p" | "--parents" => recursive = true,
+            "-v" | "--verbose" => verbose = true,
+            "-pv" | "-vp" => {
+                recursive = true;
+                verbose = true;
+            }
+            _ => dirs.push(arg),
         }
     }
 
     for dir in dirs {
-        let path = Path::new(dir);
-        let result = if recursive {
-            fs::create_dir_all(path)
-        } else {
-            fs::create_dir(path)
-        };
-
-        if let Err(e) = result {
-            eprintln!("mkdir: cannot create directory '{}': {}", dir, e);
+        match create_dirs(Path::new(dir), recursive) {
+            Ok(created) => {
+                if verbose {
+                    for path in created {
+                        println!("mkdir: created directory '{}'", path.display());
+                    }
+                }
+            }
+            Err(e) => eprintln!("mkdir: cannot create directory '{}': {}", dir, e),
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parents_reports_only_new_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        fs::create_dir(&a).unwrap();
+
+        let created = create_dirs(&a.join("b").join("c"), true).unwrap();
+        assert_eq!(created, vec![a.join("b"), a.join("b").join("c")]);
+        assert!(a.join("b").join("c").is_dir());
+
+        // Everything exists now: nothing to report, and no error with -p
+        assert!(
+            create_dirs(&a.join("b").join("c"), true)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(create_dirs(&a, false).is_err());
+    }
+}
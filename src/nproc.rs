@@ -16,6 +16,7 @@ use winapi::{
 struct NprocConfig {
     show_all: bool,
     ignore_count: usize,
+    explain: bool,
 }
 
 #[derive(Debug)]
@@ -51,6 +52,13 @@ impl std::fmt::Display for CpuInfo {
 /// Execute the nproc command to display number of processing units
 pub fn execute(args: &[String]) -> i32 {
     match parse_arguments(args) {
+        Ok(NprocAction::Run(config)) if config.explain => {
+            let constraints = Constraints::detect(config.ignore_count);
+            let explanation = explain(&constraints, config.show_all);
+            println!("{}", explanation.count.to_string().green());
+            println!("{}", explanation.text);
+            0
+        }
         Ok(NprocAction::Run(config)) => {
             let count = get_processor_count(&config);
             println!("{}", count.to_string().green());
@@ -84,6 +92,10 @@ fn parse_arguments(args: &[String]) -> Result<NprocAction, String> {
                 config.show_all = true;
                 i += 1;
             }
+            "--explain" | "--verbose" => {
+                config.explain = true;
+                i += 1;
+            }
             "--ignore" => {
                 if i + 1 < args.len() {
                     match args[i + 1].parse::<usize>() {
@@ -134,7 +146,10 @@ fn get_processor_count(config: &NprocConfig) -> usize {
     let count = if config.show_all {
         get_total_cpus()
     } else {
-        get_available_cpus()
+        let (threads, limit) = omp_overrides();
+        threads
+            .unwrap_or_else(get_available_cpus)
+            .min(limit.unwrap_or(usize::MAX))
     };
 
     // Apply ignore count, but ensure at least 1 processor
@@ -164,6 +179,213 @@ pub fn get_available_cpus() -> usize {
     }
 }
 
+/// Which input decided the reported processor count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitingFactor {
+    Installed,
+    Affinity,
+    CgroupQuota,
+    OmpOverride,
+    Ignore,
+}
+
+impl LimitingFactor {
+    fn describe(self) -> &'static str {
+        match self {
+            LimitingFactor::Installed => "installed processors (no constraint applies)",
+            LimitingFactor::Affinity => "CPU affinity mask",
+            LimitingFactor::CgroupQuota => "cgroup CPU quota",
+            LimitingFactor::OmpOverride => "OMP_NUM_THREADS/OMP_THREAD_LIMIT override",
+            LimitingFactor::Ignore => "--ignore",
+        }
+    }
+}
+
+/// Raw values of everything that can lower the processor count
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Constraints {
+    pub installed: usize,
+    pub affinity: Option<usize>,
+    /// CPUs allowed by the cgroup quota, e.g. 1.5 for `150000 100000`
+    pub cgroup_quota: Option<f64>,
+    pub omp_threads: Option<usize>,
+    pub omp_limit: Option<usize>,
+    pub ignore: usize,
+}
+
+impl Constraints {
+    /// Read the constraints that apply to the current process
+    pub fn detect(ignore: usize) -> Self {
+        let (omp_threads, omp_limit) = omp_overrides();
+        Constraints {
+            installed: get_total_cpus(),
+            affinity: get_affinity_cpus(),
+            cgroup_quota: get_cgroup_quota(),
+            omp_threads,
+            omp_limit,
+            ignore,
+        }
+    }
+}
+
+/// Result of working out the processor count step by step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub count: usize,
+    pub limiting: LimitingFactor,
+    pub text: String,
+}
+
+/// Apply each constraint in turn and describe which one decided the count
+pub fn explain(constraints: &Constraints, show_all: bool) -> Explanation {
+    let mut count = constraints.installed.max(1);
+    let mut limiting = LimitingFactor::Installed;
+
+    if !show_all {
+        if let Some(affinity) = constraints.affinity
+            && affinity > 0
+            && affinity < count
+        {
+            count = affinity;
+            limiting = LimitingFactor::Affinity;
+        }
+        if let Some(quota) = constraints.cgroup_quota {
+            // Round down like `available_parallelism`, so the count matches plain nproc
+            let allowed = (quota.floor() as usize).max(1);
+            if allowed < count {
+                count = allowed;
+                limiting = LimitingFactor::CgroupQuota;
+            }
+        }
+        if let Some(threads) = constraints.omp_threads {
+            count = threads;
+            limiting = LimitingFactor::OmpOverride;
+        }
+        if let Some(limit) = constraints.omp_limit
+            && limit < count
+        {
+            count = limit;
+            limiting = LimitingFactor::OmpOverride;
+        }
+    }
+
+    if constraints.ignore > 0 {
+        count = count.saturating_sub(constraints.ignore).max(1);
+        limiting = LimitingFactor::Ignore;
+    }
+
+    let or_unset = |value: Option<usize>| value.map_or("unset".to_string(), |v| v.to_string());
+    let mut text = String::new();
+    text.push_str(&format!(
+        "  installed processors: {}\n",
+        constraints.installed
+    ));
+    text.push_str(&format!(
+        "  affinity mask:        {}\n",
+        or_unset(constraints.affinity)
+    ));
+    text.push_str(&format!(
+        "  cgroup quota:         {}\n",
+        constraints
+            .cgroup_quota
+            .map_or("none".to_string(), |q| format!("{:.2} CPUs", q))
+    ));
+    text.push_str(&format!(
+        "  OMP_NUM_THREADS:      {}\n",
+        or_unset(constraints.omp_threads)
+    ));
+    text.push_str(&format!(
+        "  OMP_THREAD_LIMIT:     {}\n",
+        or_unset(constraints.omp_limit)
+    ));
+    text.push_str(&format!("  --ignore:             {}\n", constraints.ignore));
+    if show_all {
+        text.push_str("  (--all: affinity, cgroup and OMP limits are not applied)\n");
+    }
+    text.push_str(&format!("limited by: {}", limiting.describe()));
+
+    Explanation {
+        count,
+        limiting,
+        text,
+    }
+}
+
+/// `OMP_NUM_THREADS` (first entry of the list) and `OMP_THREAD_LIMIT`, as GNU nproc honours them
+fn omp_overrides() -> (Option<usize>, Option<usize>) {
+    let parse = |name: &str| {
+        std::env::var(name)
+            .ok()?
+            .split(',')
+            .next()?
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0)
+    };
+    (parse("OMP_NUM_THREADS"), parse("OMP_THREAD_LIMIT"))
+}
+
+/// Parse a cgroup v2 `cpu.max` line such as `200000 100000` into a CPU count
+pub fn parse_cpu_max(contents: &str) -> Option<f64> {
+    let mut fields = contents.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next().unwrap_or("100000").parse().ok()?;
+    (quota > 0.0 && period > 0.0).then(|| quota / period)
+}
+
+/// CPUs allowed by the cgroup the process runs in, if a quota is set
+#[cfg(target_os = "linux")]
+fn get_cgroup_quota() -> Option<f64> {
+    let cgroups = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+
+    // cgroup v2: a single "0::/path" entry
+    if let Some(path) = cgroups.lines().find_map(|line| line.strip_prefix("0::")) {
+        let file = format!("/sys/fs/cgroup{}/cpu.max", path.trim_end_matches('/'));
+        if let Ok(contents) = std::fs::read_to_string(file) {
+            return parse_cpu_max(&contents);
+        }
+    }
+
+    // cgroup v1: separate quota and period files
+    let read = |name: &str| {
+        std::fs::read_to_string(format!("/sys/fs/cgroup/cpu/{}", name))
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+    };
+    let (quota, period) = (read("cpu.cfs_quota_us")?, read("cpu.cfs_period_us")?);
+    (quota > 0.0 && period > 0.0).then(|| quota / period)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_cgroup_quota() -> Option<f64> {
+    None
+}
+
+/// Number of CPUs in the process affinity mask
+fn get_affinity_cpus() -> Option<usize> {
+    #[cfg(windows)]
+    unsafe {
+        let mut process_mask: DWORD_PTR = 0;
+        let mut system_mask: DWORD_PTR = 0;
+        if GetProcessAffinityMask(GetCurrentProcess(), &mut process_mask, &mut system_mask) != 0 {
+            return Some(process_mask.count_ones() as usize).filter(|n| *n > 0);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) == 0 {
+            return Some(libc::CPU_COUNT(&set) as usize).filter(|n| *n > 0);
+        }
+    }
+
+    None
+}
+
 /// Get total number of CPUs in the system
 pub fn get_total_cpus() -> usize {
     #[cfg(windows)]
@@ -304,6 +526,22 @@ pub fn get_build_cpu_count(leave_free: usize) -> usize {
     }
 }
 
+/// Number of parallel jobs that fit both the available CPUs and the available RAM,
+/// for build tools that would otherwise run out of memory (`make -j`)
+pub fn get_jobs_within_memory(mem_per_job_mb: usize) -> usize {
+    let available = crate::free::collect_meminfo().available;
+    jobs_within_memory(get_available_cpus(), available, mem_per_job_mb)
+}
+
+/// `min(cpus, available_bytes / mem_per_job)`, never below 1; a zero job size means no memory cap
+pub fn jobs_within_memory(cpus: usize, available_bytes: u64, mem_per_job_mb: usize) -> usize {
+    let per_job = (mem_per_job_mb as u64).saturating_mul(1024 * 1024);
+    let by_memory = available_bytes
+        .checked_div(per_job)
+        .map_or(usize::MAX, |jobs| usize::try_from(jobs).unwrap_or(usize::MAX));
+    cpus.min(by_memory).max(1)
+}
+
 #[allow(dead_code)]
 #[cfg(unix)]
 fn get_load_adjusted_cpu_count(available: usize) -> usize {
@@ -332,6 +570,7 @@ fn show_help() {
     println!("    --all          Print the number of installed processors");
     println!("    --ignore=N     If possible, exclude N processing units");
     println!("    --ignore N     Same as --ignore=N");
+    println!("    --explain      Show which constraint limits the count, and why");
     println!("    --version      Output version information and exit");
     println!("    --help         Display this help and exit");
     println!();
@@ -490,22 +729,38 @@ mod tests {
     #[test]
     fn test_get_processor_count() {
         // default (available)
-        let cfg = NprocConfig { show_all: false, ignore_count: 0 };
+        let cfg = NprocConfig {
+            show_all: false,
+            ignore_count: 0,
+            explain: false,
+        };
         let count = get_processor_count(&cfg);
         assert!(count > 0);
 
         // ignore 1
-        let cfg = NprocConfig { show_all: false, ignore_count: 1 };
+        let cfg = NprocConfig {
+            show_all: false,
+            ignore_count: 1,
+            explain: false,
+        };
         let count = get_processor_count(&cfg);
         assert!(count > 0); // always at least 1
 
         // large ignore -> clamped to 1
-        let cfg = NprocConfig { show_all: false, ignore_count: 1000 };
+        let cfg = NprocConfig {
+            show_all: false,
+            ignore_count: 1000,
+            explain: false,
+        };
         let count = get_processor_count(&cfg);
         assert_eq!(count, 1);
 
         // show all
-        let cfg = NprocConfig { show_all: true, ignore_count: 0 };
+        let cfg = NprocConfig {
+            show_all: true,
+            ignore_count: 0,
+            explain: false,
+        };
         let count = get_processor_count(&cfg);
         assert!(count > 0);
     }
@@ -556,6 +811,110 @@ mod tests {
         let _ = is_hyperthreading_likely();
     }
 
+    #[test]
+    fn test_explain_names_dominant_constraint() {
+        let base = Constraints {
+            installed: 16,
+            affinity: Some(16),
+            ..Default::default()
+        };
+
+        let quota = Constraints {
+            cgroup_quota: Some(1.5),
+            ..base.clone()
+        };
+        let explanation = explain(&quota, false);
+        assert_eq!(explanation.count, 1);
+        assert_eq!(explanation.limiting, LimitingFactor::CgroupQuota);
+        assert!(explanation.text.contains("1.50 CPUs"));
+        assert!(explanation.text.contains("limited by: cgroup CPU quota"));
+
+        let affinity = Constraints {
+            affinity: Some(4),
+            cgroup_quota: Some(8.0),
+            ..base.clone()
+        };
+        let explanation = explain(&affinity, false);
+        assert_eq!(explanation.count, 4);
+        assert!(explanation.text.contains("limited by: CPU affinity mask"));
+
+        let fractional = Constraints {
+            cgroup_quota: Some(2.9),
+            ..base.clone()
+        };
+        assert_eq!(explain(&fractional, false).count, 2);
+
+        let omp = Constraints {
+            omp_threads: Some(3),
+            ..base.clone()
+        };
+        let explanation = explain(&omp, false);
+        assert_eq!(explanation.count, 3);
+        assert!(explanation.text.contains("OMP_NUM_THREADS:      3"));
+        assert!(explanation.text.contains("limited by: OMP_NUM_THREADS"));
+
+        let ignore = Constraints {
+            ignore: 2,
+            ..quota.clone()
+        };
+        let explanation = explain(&ignore, false);
+        assert_eq!(explanation.count, 1);
+        assert!(explanation.text.contains("limited by: --ignore"));
+
+        let explanation = explain(&quota, true);
+        assert_eq!(explanation.count, 16);
+        assert_eq!(explanation.limiting, LimitingFactor::Installed);
+    }
+
+    #[test]
+    fn test_explain_reads_omp_variables() {
+        unsafe {
+            std::env::set_var("OMP_NUM_THREADS", "3,2");
+            std::env::set_var("OMP_THREAD_LIMIT", "2");
+        }
+        let constraints = Constraints::detect(0);
+        let config = NprocConfig {
+            show_all: false,
+            ignore_count: 0,
+            explain: true,
+        };
+        let plain = get_processor_count(&config);
+        unsafe {
+            std::env::remove_var("OMP_NUM_THREADS");
+            std::env::remove_var("OMP_THREAD_LIMIT");
+        }
+
+        assert_eq!(constraints.omp_threads, Some(3));
+        assert_eq!(constraints.omp_limit, Some(2));
+        let explanation = explain(&constraints, false);
+        assert_eq!(explanation.count, 2);
+        assert_eq!(explanation.limiting, LimitingFactor::OmpOverride);
+        assert!(explanation.text.contains("OMP_THREAD_LIMIT:     2"));
+        // --explain and plain nproc agree
+        assert_eq!(plain, explanation.count);
+        assert_eq!(explain(&constraints, true).count, constraints.installed);
+    }
+
+    #[test]
+    fn test_parse_cpu_max() {
+        assert_eq!(parse_cpu_max("200000 100000\n"), Some(2.0));
+        assert_eq!(parse_cpu_max("50000 100000"), Some(0.5));
+        assert_eq!(parse_cpu_max("max 100000"), None);
+    }
+
+    #[test]
+    fn test_jobs_within_memory() {
+        let cpus = get_available_cpus();
+        assert_eq!(get_jobs_within_memory(1), cpus);
+        assert_eq!(get_jobs_within_memory(usize::MAX), 1);
+
+        let gib = 1024 * 1024 * 1024;
+        assert_eq!(jobs_within_memory(16, 8 * gib, 2048), 4);
+        assert_eq!(jobs_within_memory(4, 64 * gib, 2048), 4);
+        assert_eq!(jobs_within_memory(8, 0, 512), 1);
+        assert_eq!(jobs_within_memory(8, 8 * gib, 0), 8);
+    }
+
     #[test]
     fn test_help_display() {
         show_help();
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
 use std::future::Future;
-use std::io;
+use std::io::{self, Write};
 use std::pin::Pin;
+use std::process::{Command, Stdio};
+
+use crate::env::expand_vars;
 
 // Pipeline command trait
 pub trait AsyncCommand {
@@ -118,10 +122,693 @@ pub async fn execute_pipeline<C: AsyncCommand<Input = ()>>(command: C) -> io::Re
     command.execute(()).await
 }
 
+/// Split a command line into words, expanding `~` and environment variables.
+///
+/// Single quotes keep their contents literal; double quotes group words but
+/// still expand variables. Quote characters themselves are removed.
+pub fn expand_words(line: &str) -> Vec<String> {
+    let vars: HashMap<String, String> = std::env::vars().collect();
+    let home = dirs::home_dir().map(|path| path.to_string_lossy().to_string());
+    expand_words_with(line, &vars, home.as_deref())
+}
+
+/// Like `expand_words`, with explicit variables and home directory
+pub fn expand_words_with(
+    line: &str,
+    vars: &HashMap<String, String>,
+    home: Option<&str>,
+) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Text waiting for variable expansion
+    let mut pending = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                word.push_str(&expand_vars(&pending, vars));
+                pending.clear();
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    word.push(c);
+                }
+                in_word = true;
+            }
+            '"' => {
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                    pending.push(c);
+                }
+                in_word = true;
+            }
+            '~' if !in_word && matches!(chars.peek(), None | Some('/') | Some(' ')) => {
+                match home {
+                    Some(home) => word.push_str(home),
+                    None => word.push('~'),
+                }
+                in_word = true;
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    word.push_str(&expand_vars(&pending, vars));
+                    pending.clear();
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                pending.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if in_word {
+        word.push_str(&expand_vars(&pending, vars));
+        words.push(word);
+    }
+    words
+}
+
+/// A parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ast {
+    /// A simple command and its expanded arguments
+    Command(Vec<String>),
+    /// `( list )`
+    Group(Box<Ast>),
+    /// `a | b | c`
+    Pipe(Vec<Ast>),
+    /// `a && b`
+    And(Box<Ast>, Box<Ast>),
+    /// `a || b`
+    Or(Box<Ast>, Box<Ast>),
+    /// `cmd <<< word` or `cmd <<EOF`: `cmd` reads the text on its stdin
+    Here(Box<Ast>, String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Unexpanded text of one simple command
+    Text(String),
+    Open,
+    Close,
+    Pipe,
+    And,
+    Or,
+    /// `<<< word`, with the word still quoted
+    HereString(String),
+    /// `<<DELIM`; the body arrives with the lines after the command
+    HereDoc {
+        delimiter: String,
+        body: Option<String>,
+        expand: bool,
+    },
+}
+
+impl Token {
+    fn as_str(&self) -> &str {
+        match self {
+            Token::Text(text) => text,
+            Token::Open => "(",
+            Token::Close => ")",
+            Token::Pipe => "|",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::HereString(_) => "<<<",
+            Token::HereDoc { .. } => "<<",
+        }
+    }
+}
+
+/// Read the word after `<<<` or `<<`, keeping its quotes
+fn here_word(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+
+    let mut word = String::new();
+    let mut quote = None;
+    while let Some(&c) = chars.peek() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c.is_whitespace() || "()|&<".contains(c) => break,
+            None => {}
+        }
+        word.push(c);
+        chars.next();
+    }
+
+    if quote.is_some() {
+        return Err("unexpected end of line while looking for matching quote".to_string());
+    }
+    if word.is_empty() {
+        return Err("syntax error near unexpected token `newline'".to_string());
+    }
+    Ok(word)
+}
+
+/// Fill in the bodies of here-documents still waiting for one, from the lines in `chars`.
+/// A body missing its delimiter line stays `None`.
+fn read_heredocs(tokens: &mut [Token], chars: &mut std::iter::Peekable<std::str::Chars>) {
+    for token in tokens.iter_mut() {
+        let Token::HereDoc {
+            delimiter,
+            body: body @ None,
+            ..
+        } = token
+        else {
+            continue;
+        };
+
+        let mut text = String::new();
+        while chars.peek().is_some() {
+            let line: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+            if line.trim_end_matches('\r') == delimiter {
+                *body = Some(text);
+                break;
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+}
+
+/// Split a line into command text and operators, leaving quoted operators alone.
+/// Here-document bodies are taken from the lines that follow the command.
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            text.push(c);
+            continue;
+        }
+
+        let token = match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                text.push(c);
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            '|' => Token::Pipe,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '<' if chars.next_if_eq(&'<').is_some() => {
+                if chars.next_if_eq(&'<').is_some() {
+                    Token::HereString(here_word(&mut chars)?)
+                } else {
+                    let word = here_word(&mut chars)?;
+                    Token::HereDoc {
+                        delimiter: word.replace(['\'', '"'], ""),
+                        body: None,
+                        // Quoting any part of the delimiter keeps the body literal
+                        expand: !word.contains(['\'', '"']),
+                    }
+                }
+            }
+            '\n' if tokens
+                .iter()
+                .any(|t| matches!(t, Token::HereDoc { body: None, .. })) =>
+            {
+                read_heredocs(&mut tokens, &mut chars);
+                text.push(' ');
+                continue;
+            }
+            c => {
+                text.push(c);
+                continue;
+            }
+        };
+
+        if !text.trim().is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+        text.clear();
+        tokens.push(token);
+    }
+
+    if quote.is_some() {
+        return Err("unexpected end of line while looking for matching quote".to_string());
+    }
+    if !text.trim().is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("syntax error near unexpected token `{}'", token.as_str()),
+            None => "syntax error: unexpected end of line".to_string(),
+        }
+    }
+
+    /// list := pipeline (('&&' | '||') pipeline)*
+    fn list(&mut self) -> Result<Ast, String> {
+        let mut node = self.pipeline()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.pos += 1;
+                    node = Ast::And(Box::new(node), Box::new(self.pipeline()?));
+                }
+                Some(Token::Or) => {
+                    self.pos += 1;
+                    node = Ast::Or(Box::new(node), Box::new(self.pipeline()?));
+                }
+                _ => return Ok(node),
+            }
+        }
+    }
+
+    /// pipeline := unit ('|' unit)*
+    fn pipeline(&mut self) -> Result<Ast, String> {
+        let mut stages = vec![self.unit()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.pos += 1;
+            stages.push(self.unit()?);
+        }
+        Ok(if stages.len() == 1 {
+            stages.remove(0)
+        } else {
+            Ast::Pipe(stages)
+        })
+    }
+
+    /// unit := '(' list ')' here* | (text | here)+
+    fn unit(&mut self) -> Result<Ast, String> {
+        let (node, input) = match self.peek() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let inner = self.list()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(self.unexpected());
+                }
+                self.pos += 1;
+                (Ast::Group(Box::new(inner)), self.here_input()?)
+            }
+            Some(Token::Text(_) | Token::HereString(_) | Token::HereDoc { .. }) => {
+                // Words and redirections may interleave: `cat <<< hi -n`
+                let mut text = String::new();
+                let mut input = None;
+                loop {
+                    if let Some(Token::Text(more)) = self.peek() {
+                        text.push(' ');
+                        text.push_str(more);
+                        self.pos += 1;
+                    } else if let Some(here) = self.here_input()? {
+                        input = Some(here);
+                    } else {
+                        break;
+                    }
+                }
+                (Ast::Command(expand_words(&text)), input)
+            }
+            _ => return Err(self.unexpected()),
+        };
+
+        Ok(match input {
+            Some(input) => Ast::Here(Box::new(node), input),
+            None => node,
+        })
+    }
+
+    /// Consume the here-strings and here-documents at the cursor; the last one wins
+    fn here_input(&mut self) -> Result<Option<String>, String> {
+        let mut input = None;
+        loop {
+            match self.peek() {
+                Some(Token::HereString(word)) => {
+                    input = Some(format!("{}\n", expand_words(word).join(" ")));
+                }
+                Some(Token::HereDoc {
+                    body: Some(body),
+                    expand,
+                    ..
+                }) => {
+                    input = Some(if *expand {
+                        let vars: HashMap<String, String> = std::env::vars().collect();
+                        expand_vars(body, &vars)
+                    } else {
+                        body.clone()
+                    });
+                }
+                Some(Token::HereDoc {
+                    delimiter,
+                    body: None,
+                    ..
+                }) => {
+                    return Err(format!(
+                        "here-document ended before its delimiter `{}'",
+                        delimiter
+                    ));
+                }
+                _ => return Ok(input),
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// Whether `line` opens a here-document whose closing delimiter hasn't been typed yet
+pub fn heredoc_pending(line: &str) -> bool {
+    tokenize(line).is_ok_and(|tokens| {
+        tokens
+            .iter()
+            .any(|t| matches!(t, Token::HereDoc { body: None, .. }))
+    })
+}
+
+/// Parse a command line with `|`, `&&`, `||`, `( )` grouping and `<<<`/`<<` input into an `Ast`
+pub fn parse(line: &str) -> Result<Ast, String> {
+    let mut parser = Parser {
+        tokens: tokenize(line)?,
+        pos: 0,
+    };
+    if parser.peek().is_none() {
+        return Ok(Ast::Command(Vec::new()));
+    }
+
+    let ast = parser.list()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(ast)
+}
+
+/// Where a node reads its stdin from
+enum Input<'a> {
+    /// The terminal's stdin
+    Inherit,
+    /// Bytes from a here-string or here-document
+    Data(&'a [u8]),
+    /// The read end of a pipe from the previous pipeline stage
+    Pipe(io::PipeReader),
+}
+
+impl Input<'_> {
+    /// Another handle on the same input, for the next command of a list
+    fn share(&self) -> io::Result<Self> {
+        Ok(match self {
+            Input::Inherit => Input::Inherit,
+            Input::Data(data) => Input::Data(data),
+            Input::Pipe(reader) => Input::Pipe(reader.try_clone()?),
+        })
+    }
+}
+
+/// Where a node writes its stdout
+enum Output {
+    /// The terminal's stdout
+    Inherit,
+    /// Collected and returned to the caller
+    Capture,
+    /// The write end of a pipe to the next pipeline stage
+    Pipe(io::PipeWriter),
+}
+
+impl Output {
+    /// Another handle on the same output, for the next command of a list
+    fn share(&self) -> io::Result<Self> {
+        Ok(match self {
+            Output::Inherit => Output::Inherit,
+            Output::Capture => Output::Capture,
+            Output::Pipe(writer) => Output::Pipe(writer.try_clone()?),
+        })
+    }
+}
+
+/// Run one external program with the given stdin and stdout
+fn run_program(argv: &[String], input: Input, output: Output) -> io::Result<(i32, Vec<u8>)> {
+    let Some((program, args)) = argv.split_first() else {
+        return Ok((0, Vec::new()));
+    };
+
+    let mut command = Command::new(program);
+    command.args(args);
+    let data = match input {
+        Input::Inherit => None,
+        Input::Data(data) => {
+            command.stdin(Stdio::piped());
+            Some(data)
+        }
+        Input::Pipe(reader) => {
+            command.stdin(reader);
+            None
+        }
+    };
+    match output {
+        Output::Inherit => {}
+        Output::Capture => {
+            command.stdout(Stdio::piped());
+        }
+        Output::Pipe(writer) => {
+            command.stdout(writer);
+        }
+    }
+
+    let spawned = command.spawn();
+    // Close our copies of the pipe ends so the neighbours see EOF or EPIPE when this child exits
+    drop(command);
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!("{}: command not found", program);
+            return Ok((127, Vec::new()));
+        }
+        Err(e) => return Err(e),
+    };
+
+    // Write from another thread so a child filling its stdout can't deadlock us
+    let writer = child.stdin.take().zip(data).map(|(mut stdin, data)| {
+        let data = data.to_vec();
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&data);
+        })
+    });
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+
+    Ok((output.status.code().unwrap_or(1), output.stdout))
+}
+
+/// Run `ast`, returning its exit status and, with `Output::Capture`, its stdout.
+///
+/// Pipeline stages run at the same time, joined by OS pipes, so `yes | head -1` ends
+/// as soon as `head` does. Both sides of `&&`/`||` read from the same input.
+fn run_node(ast: &Ast, input: Input, output: Output) -> io::Result<(i32, Vec<u8>)> {
+    match ast {
+        Ast::Command(argv) => run_program(argv, input, output),
+        Ast::Group(inner) => run_node(inner, input, output),
+        // An explicit redirection replaces whatever was piped in
+        Ast::Here(inner, text) => run_node(inner, Input::Data(text.as_bytes()), output),
+        Ast::Pipe(stages) => {
+            let (last, rest) = stages.split_last().expect("pipeline has stages");
+            std::thread::scope(|scope| {
+                let mut input = input;
+                let mut upstream = Vec::new();
+                for stage in rest {
+                    let (reader, writer) = io::pipe()?;
+                    let stage_input = std::mem::replace(&mut input, Input::Pipe(reader));
+                    upstream.push(
+                        scope.spawn(move || run_node(stage, stage_input, Output::Pipe(writer))),
+                    );
+                }
+                let result = run_node(last, input, output);
+                for stage in upstream {
+                    stage.join().expect("pipeline stage panicked")?;
+                }
+                result
+            })
+        }
+        Ast::And(left, right) | Ast::Or(left, right) => {
+            let (status, mut out) = run_node(left, input.share()?, output.share()?)?;
+            if (status == 0) != matches!(ast, Ast::And(..)) {
+                return Ok((status, out));
+            }
+            let (status, more) = run_node(right, input, output)?;
+            out.extend(more);
+            Ok((status, out))
+        }
+    }
+}
+
+/// Run `ast` with the terminal's stdin and stdout, returning its exit status
+pub fn run_ast(ast: &Ast) -> io::Result<i32> {
+    run_node(ast, Input::Inherit, Output::Inherit).map(|(status, _)| status)
+}
+
+/// Run `ast` and collect what it writes to stdout
+pub fn capture_ast(ast: &Ast) -> io::Result<(i32, Vec<u8>)> {
+    run_node(ast, Input::Inherit, Output::Capture)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([("HOME".to_string(), "/home/me".to_string())])
+    }
+
+    fn command(words: &[&str]) -> Ast {
+        Ast::Command(words.iter().map(|w| w.to_string()).collect())
+    }
+
+    #[test]
+    fn test_parse_nested_groups() {
+        assert_eq!(
+            parse("(false || echo a) | cat").unwrap(),
+            Ast::Pipe(vec![
+                Ast::Group(Box::new(Ast::Or(
+                    Box::new(command(&["false"])),
+                    Box::new(command(&["echo", "a"])),
+                ))),
+                command(&["cat"]),
+            ])
+        );
+        assert_eq!(
+            parse("((a && b)) || c 'x|y'").unwrap(),
+            Ast::Or(
+                Box::new(Ast::Group(Box::new(Ast::Group(Box::new(Ast::And(
+                    Box::new(command(&["a"])),
+                    Box::new(command(&["b"])),
+                )))))),
+                Box::new(command(&["c", "x|y"])),
+            )
+        );
+        assert_eq!(parse("  ").unwrap(), Ast::Command(Vec::new()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("(echo a").is_err());
+        assert!(parse("echo a)").is_err());
+        assert!(parse("&& echo a").is_err());
+        assert!(parse("echo a |").is_err());
+        assert!(parse("(a) b").is_err());
+        assert!(parse("echo 'open").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_group_into_pipe() {
+        let ast = parse("(false || echo a) | cat").unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (0, b"a\n".to_vec()));
+
+        let ast = parse("(echo a && (false || echo b)) | (cat && false)").unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (1, b"a\nb\n".to_vec()));
+    }
+
+    #[test]
+    fn test_parse_here_redirections() {
+        assert_eq!(
+            parse("cat <<< 'hello world' -n").unwrap(),
+            Ast::Here(
+                Box::new(command(&["cat", "-n"])),
+                "hello world\n".to_string()
+            )
+        );
+        assert_eq!(
+            parse("cat <<'EOF' | wc -l\n$HOME | x\nEOF").unwrap(),
+            Ast::Pipe(vec![
+                Ast::Here(Box::new(command(&["cat"])), "$HOME | x\n".to_string()),
+                command(&["wc", "-l"]),
+            ])
+        );
+
+        assert!(heredoc_pending("cat <<EOF"));
+        assert!(heredoc_pending("cat <<EOF\nstill typing"));
+        assert!(!heredoc_pending("cat <<EOF\nline\nEOF"));
+        assert!(!heredoc_pending("cat <<< EOF"));
+        assert!(parse("cat <<EOF\nno end").is_err());
+        assert!(parse("cat <<<").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_stops_infinite_producer() {
+        let (done, finished) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let ast = parse("yes | head -n 2").unwrap();
+            let _ = done.send(capture_ast(&ast).unwrap());
+        });
+        let result = finished
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("`yes | head` should end when head exits");
+        assert_eq!(result, (0, b"y\ny\n".to_vec()));
+
+        let ast = parse("(echo a && echo b) | cat | tr a-z A-Z").unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (0, b"A\nB\n".to_vec()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_here_string_and_heredoc() {
+        let ast = parse("cat <<< hello").unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (0, b"hello\n".to_vec()));
+
+        let body = "  indented | not a pipe\n\t$UNSET_WINIX_VAR 'quotes' \"kept\"\n\n";
+        let ast = parse(&format!("cat <<'EOF'\n{}EOF", body)).unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (0, body.as_bytes().to_vec()));
+
+        let ast = parse("cat <<END | tr a-z A-Z\nabc\nEND").unwrap();
+        assert_eq!(capture_ast(&ast).unwrap(), (0, b"ABC\n".to_vec()));
+    }
+
+    #[test]
+    fn test_tilde_expands_to_home() {
+        assert_eq!(
+            expand_words_with("ls ~ ~/src a~b", &vars(), Some("/home/me")),
+            ["ls", "/home/me", "/home/me/src", "a~b"]
+        );
+    }
+
+    #[test]
+    fn test_variables_expand_outside_single_quotes() {
+        assert_eq!(
+            expand_words_with("echo $HOME ${HOME}/x \"$HOME dir\" '$HOME'", &vars(), None),
+            ["echo", "/home/me", "/home/me/x", "/home/me dir", "$HOME"]
+        );
+        assert_eq!(
+            expand_words_with("echo '~' $UNSET", &vars(), Some("/home/me")),
+            ["echo", "~", "$UNSET"]
+        );
+    }
+
+    #[test]
+    fn test_expand_words_uses_process_home() {
+        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+        assert_eq!(expand_words("cd ~"), ["cd", home.as_str()]);
+    }
+
     #[tokio::test]
     async fn test_cat_grep_pipeline() {
         let file_path = "test_pipeline.txt";
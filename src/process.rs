@@ -153,3 +153,821 @@ mod fallback {
 
 #[cfg(not(windows))]
 pub use fallback::{ProcessError, ProcessHandle, spawn};
+
+/// Whether a process with this PID currently exists and has not exited
+#[cfg(windows)]
+pub fn is_running(pid: u32) -> bool {
+    use winapi::shared::winerror::ERROR_ACCESS_DENIED;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::minwinbase::STILL_ACTIVE;
+    use winapi::um::processthreadsapi::{GetExitCodeProcess, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            // Protected system processes refuse even limited queries but do exist
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+
+        let mut exit_code = 0;
+        let queried = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        queried != 0 && exit_code == STILL_ACTIVE
+    }
+}
+
+/// Executable name of a running process, e.g. `notepad.exe`
+#[cfg(windows)]
+pub fn name_of(pid: u32) -> Option<String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut buffer = [0u16; 1024];
+        let mut size = buffer.len() as u32;
+        let queried = QueryFullProcessImageNameW(handle, 0, buffer.as_mut_ptr(), &mut size);
+        CloseHandle(handle);
+        if queried == 0 {
+            return None;
+        }
+
+        let path = String::from_utf16_lossy(&buffer[..size as usize]);
+        std::path::Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }
+}
+
+/// Whether a process with this PID currently exists and is not a zombie
+#[cfg(target_os = "linux")]
+pub fn is_running(pid: u32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        // The state follows the parenthesised command name, which may itself contain ')'
+        Ok(stat) => stat
+            .rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .is_some_and(|state| state != "Z" && state != "X"),
+        Err(_) => false,
+    }
+}
+
+/// Executable name of a running process, as reported by `/proc/<pid>/comm`
+#[cfg(target_os = "linux")]
+pub fn name_of(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim_end().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Whether a process with this PID currently exists
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn is_running(pid: u32) -> bool {
+    // Signal 0 only checks for existence; EPERM means it exists but isn't ours
+    unsafe {
+        libc::kill(pid as libc::pid_t, 0) == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+/// Executable name of a running process (unsupported without `/proc`)
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn name_of(_pid: u32) -> Option<String> {
+    None
+}
+
+/// Number of open handles held by a process, or `None` if it can't be queried
+#[cfg(windows)]
+pub fn handle_count(pid: u32) -> Option<usize> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetProcessHandleCount, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut count = 0;
+        let queried = GetProcessHandleCount(handle, &mut count);
+        CloseHandle(handle);
+        (queried != 0).then_some(count as usize)
+    }
+}
+
+/// Number of open file descriptors, counted from `/proc/<pid>/fd`
+#[cfg(unix)]
+pub fn handle_count(pid: u32) -> Option<usize> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()
+        .map(|entries| entries.count())
+}
+
+/// Resident memory and recent CPU load of a process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcUsage {
+    pub rss_bytes: u64,
+    /// CPU time over wall time while sampling; above 100 when using several cores
+    pub cpu_percent: f64,
+}
+
+/// How long `usage` waits between its two CPU time samples
+const USAGE_SAMPLE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Sample a process's CPU time twice over a short interval and read its RSS
+pub fn usage(pid: u32) -> Option<ProcUsage> {
+    use std::time::Instant;
+
+    let first = cpu_time(pid)?;
+    let start = Instant::now();
+    std::thread::sleep(USAGE_SAMPLE_INTERVAL);
+    let second = cpu_time(pid)?;
+    let wall = start.elapsed();
+
+    Some(ProcUsage {
+        rss_bytes: rss_bytes(pid)?,
+        cpu_percent: second.saturating_sub(first).as_secs_f64() / wall.as_secs_f64() * 100.0,
+    })
+}
+
+/// Total user plus kernel CPU time a process has used
+#[cfg(windows)]
+fn cpu_time(pid: u32) -> Option<std::time::Duration> {
+    use winapi::shared::minwindef::FILETIME;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetProcessTimes, OpenProcess};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    let ticks = |time: &FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut creation: FILETIME = std::mem::zeroed();
+        let mut exit: FILETIME = std::mem::zeroed();
+        let mut kernel: FILETIME = std::mem::zeroed();
+        let mut user: FILETIME = std::mem::zeroed();
+        let queried = GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user);
+        CloseHandle(handle);
+        if queried == 0 {
+            return None;
+        }
+
+        // FILETIME counts 100ns intervals
+        Some(std::time::Duration::from_nanos(
+            (ticks(&kernel) + ticks(&user)) * 100,
+        ))
+    }
+}
+
+/// Working set size of a process
+#[cfg(windows)]
+fn rss_bytes(pid: u32) -> Option<u64> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let queried = GetProcessMemoryInfo(handle, &mut counters, size);
+        CloseHandle(handle);
+        (queried != 0).then_some(counters.WorkingSetSize as u64)
+    }
+}
+
+/// Total user plus system CPU time, from fields 14 and 15 of `/proc/<pid>/stat`
+#[cfg(target_os = "linux")]
+fn cpu_time(pid: u32) -> Option<std::time::Duration> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // Fields after the parenthesised command name start at field 3 (state)
+    let (_, rest) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_second <= 0 {
+        return None;
+    }
+    Some(std::time::Duration::from_secs_f64(
+        (utime + stime) as f64 / ticks_per_second as f64,
+    ))
+}
+
+/// Resident set size, from the second field of `/proc/<pid>/statm` (in pages)
+#[cfg(target_os = "linux")]
+fn rss_bytes(pid: u32) -> Option<u64> {
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    (page_size > 0).then(|| pages * page_size as u64)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn cpu_time(_pid: u32) -> Option<std::time::Duration> {
+    None
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn rss_bytes(_pid: u32) -> Option<u64> {
+    None
+}
+
+/// PID of the process listening on a local TCP port ("what's using port 8080?")
+pub fn pid_for_port(port: u16) -> Option<u32> {
+    pids_for_port(port).into_iter().next()
+}
+
+/// Every process listening on a local TCP port, in PID order; several processes can
+/// share one through `SO_REUSEPORT` or an inherited socket
+pub fn pids_for_port(port: u16) -> Vec<u32> {
+    #[cfg(windows)]
+    let mut pids: Vec<u32> = tcp_listeners()
+        .into_iter()
+        .filter(|&(listening, _)| listening == port)
+        .map(|(_, pid)| pid)
+        .collect();
+
+    #[cfg(target_os = "linux")]
+    let mut pids: Vec<u32> = {
+        let inodes: Vec<u64> = tcp_listeners()
+            .into_iter()
+            .filter(|&(listening, _)| listening == port)
+            .map(|(_, inode)| inode)
+            .collect();
+        if inodes.is_empty() {
+            return Vec::new();
+        }
+        let Ok(entries) = std::fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter(|&pid| {
+                socket_inodes(pid)
+                    .iter()
+                    .any(|inode| inodes.contains(inode))
+            })
+            .collect()
+    };
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    let mut pids: Vec<u32> = {
+        let _ = port;
+        Vec::new()
+    };
+
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+}
+
+/// Local TCP ports a process is listening on, sorted and without duplicates
+pub fn listening_ports(pid: u32) -> Vec<u16> {
+    #[cfg(windows)]
+    let mut ports: Vec<u16> = tcp_listeners()
+        .into_iter()
+        .filter(|&(_, owner)| owner == pid)
+        .map(|(port, _)| port)
+        .collect();
+
+    #[cfg(target_os = "linux")]
+    let mut ports: Vec<u16> = {
+        let inodes = socket_inodes(pid);
+        tcp_listeners()
+            .into_iter()
+            .filter(|(_, inode)| inodes.contains(inode))
+            .map(|(port, _)| port)
+            .collect()
+    };
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    let mut ports: Vec<u16> = {
+        let _ = pid;
+        Vec::new()
+    };
+
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Port and owning PID of every listening IPv4 and IPv6 TCP socket
+#[cfg(windows)]
+fn tcp_listeners() -> Vec<(u16, u32)> {
+    use winapi::shared::iprtrmib::TCP_TABLE_OWNER_PID_LISTENER;
+    use winapi::shared::tcpmib::{MIB_TCP6ROW_OWNER_PID, MIB_TCPROW_OWNER_PID};
+    use winapi::shared::winerror::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+    use winapi::shared::ws2def::{AF_INET, AF_INET6};
+    use winapi::um::iphlpapi::GetExtendedTcpTable;
+
+    /// The table for one address family: an entry count followed by the rows.
+    /// A `u32` buffer keeps the rows aligned.
+    fn table(family: i32) -> Vec<u32> {
+        let mut buffer: Vec<u32> = Vec::new();
+        let mut size = 0;
+        loop {
+            let status = unsafe {
+                GetExtendedTcpTable(
+                    buffer.as_mut_ptr().cast(),
+                    &mut size,
+                    0,
+                    family as u32,
+                    TCP_TABLE_OWNER_PID_LISTENER,
+                    0,
+                )
+            };
+            match status {
+                NO_ERROR => return buffer,
+                // The table can grow between the size query and the read, so retry
+                ERROR_INSUFFICIENT_BUFFER => buffer = vec![0; (size as usize).div_ceil(4)],
+                _ => return Vec::new(),
+            }
+        }
+    }
+
+    fn rows<T: Copy>(buffer: &[u32]) -> Vec<T> {
+        let Some(&count) = buffer.first() else {
+            return Vec::new();
+        };
+        let first = buffer[1..].as_ptr().cast::<T>();
+        (0..count as usize)
+            .map(|i| unsafe { *first.add(i) })
+            .collect()
+    }
+
+    // Ports are stored in network byte order in the low 16 bits
+    let port = |raw: u32| u16::from_be(raw as u16);
+    let v4 = rows::<MIB_TCPROW_OWNER_PID>(&table(AF_INET))
+        .into_iter()
+        .map(|row| (port(row.dwLocalPort), row.dwOwningPid));
+    let v6 = rows::<MIB_TCP6ROW_OWNER_PID>(&table(AF_INET6))
+        .into_iter()
+        .map(|row| (port(row.dwLocalPort), row.dwOwningPid));
+    v4.chain(v6).collect()
+}
+
+/// Port and socket inode of every listening IPv4 and IPv6 TCP socket
+#[cfg(target_os = "linux")]
+fn tcp_listeners() -> Vec<(u16, u64)> {
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .flat_map(|table| parse_tcp_listeners(&table))
+        .collect()
+}
+
+/// Local port and socket inode of each listening socket in a `/proc/net/tcp` (or `tcp6`) table
+pub fn parse_tcp_listeners(table: &str) -> Vec<(u16, u64)> {
+    // TCP_LISTEN in the kernel's state numbering
+    const LISTEN: &str = "0A";
+
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            // sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.get(3) != Some(&LISTEN) {
+                return None;
+            }
+            let (_, port) = fields.get(1)?.rsplit_once(':')?;
+            let port = u16::from_str_radix(port, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+/// Inodes of the sockets a process has open, from the `socket:[inode]` links in `/proc/<pid>/fd`
+#[cfg(target_os = "linux")]
+fn socket_inodes(pid: u32) -> Vec<u64> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            target
+                .to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// Suspend (or resume) every thread of a process; Windows has no documented
+/// whole-process call, so this walks a Toolhelp thread snapshot
+#[cfg(windows)]
+fn set_threads_suspended(pid: u32, suspend: bool) -> std::io::Result<()> {
+    use std::io;
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::processthreadsapi::{OpenThread, ResumeThread, SuspendThread};
+    use winapi::um::tlhelp32::{
+        CreateToolhelp32Snapshot, TH32CS_SNAPTHREAD, THREADENTRY32, Thread32First, Thread32Next,
+    };
+    use winapi::um::winnt::THREAD_SUSPEND_RESUME;
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut found = false;
+        let mut result = Ok(());
+
+        let mut more = Thread32First(snapshot, &mut entry) != 0;
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                found = true;
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if thread.is_null() {
+                    result = Err(io::Error::last_os_error());
+                } else {
+                    let previous = if suspend {
+                        SuspendThread(thread)
+                    } else {
+                        ResumeThread(thread)
+                    };
+                    if previous == u32::MAX {
+                        result = Err(io::Error::last_os_error());
+                    }
+                    CloseHandle(thread);
+                }
+            }
+            more = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+
+        if !found {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no such process: {}", pid),
+            ));
+        }
+        result
+    }
+}
+
+/// Stop a process from being scheduled until `resume` is called
+#[cfg(windows)]
+pub fn suspend(pid: u32) -> std::io::Result<()> {
+    set_threads_suspended(pid, true)
+}
+
+/// Let a process suspended with `suspend` run again
+#[cfg(windows)]
+pub fn resume(pid: u32) -> std::io::Result<()> {
+    set_threads_suspended(pid, false)
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> std::io::Result<()> {
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Stop a process with `SIGSTOP` until `resume` is called
+#[cfg(unix)]
+pub fn suspend(pid: u32) -> std::io::Result<()> {
+    send_signal(pid, libc::SIGSTOP)
+}
+
+/// Continue a stopped process with `SIGCONT`
+#[cfg(unix)]
+pub fn resume(pid: u32) -> std::io::Result<()> {
+    send_signal(pid, libc::SIGCONT)
+}
+
+/// Wait up to `timeout` for a process to exit, returning whether it did
+pub fn wait_timeout(pid: u32, timeout: std::time::Duration) -> bool {
+    use std::time::{Duration, Instant};
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_running(pid) {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep((deadline - now).min(Duration::from_millis(50)));
+    }
+}
+
+/// Output of a finished command, with ANSI escape sequences stripped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: std::process::ExitStatus,
+}
+
+impl CapturedOutput {
+    /// Stdout followed by stderr, separated by a newline when both are present
+    pub fn combined(&self) -> String {
+        match (self.stdout.is_empty(), self.stderr.is_empty()) {
+            (_, true) => self.stdout.clone(),
+            (true, false) => self.stderr.clone(),
+            (false, false) => format!("{}\n{}", self.stdout.trim_end_matches('\n'), self.stderr),
+        }
+    }
+}
+
+/// Which pipe a streamed line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Run `cmd` to completion and capture both pipes
+pub fn run_capture(cmd: &str, args: &[&str]) -> std::io::Result<CapturedOutput> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()?;
+
+    Ok(CapturedOutput {
+        stdout: crate::ansi::strip_ansi(&String::from_utf8_lossy(&output.stdout)),
+        stderr: crate::ansi::strip_ansi(&String::from_utf8_lossy(&output.stderr)),
+        status: output.status,
+    })
+}
+
+/// Run `cmd`, calling `on_line` with each line of output as soon as it is written
+pub fn run_streaming<F>(
+    cmd: &str,
+    args: &[&str],
+    mut on_line: F,
+) -> std::io::Result<std::process::ExitStatus>
+where
+    F: FnMut(Stream, &str),
+{
+    use std::process::Stdio;
+    use std::sync::mpsc;
+
+    let mut child = std::process::Command::new(cmd)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // One reader per pipe so a chatty stderr can't block stdout
+    let (tx, rx) = mpsc::channel();
+    let mut readers = Vec::new();
+    if let Some(pipe) = child.stdout.take() {
+        readers.push(forward_lines(pipe, Stream::Stdout, tx.clone()));
+    }
+    if let Some(pipe) = child.stderr.take() {
+        readers.push(forward_lines(pipe, Stream::Stderr, tx.clone()));
+    }
+    drop(tx);
+
+    for (stream, line) in rx {
+        on_line(stream, &crate::ansi::strip_ansi(&line));
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+    child.wait()
+}
+
+/// Send each line read from `pipe` down `tx`, tagged with the stream it came from
+fn forward_lines<R: std::io::Read + Send + 'static>(
+    pipe: R,
+    stream: Stream,
+    tx: std::sync::mpsc::Sender<(Stream, String)>,
+) -> std::thread::JoinHandle<()> {
+    use std::io::BufRead;
+
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(pipe).lines().map_while(Result::ok) {
+            if tx.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_process_is_running() {
+        let pid = std::process::id();
+        assert!(is_running(pid));
+        assert!(!name_of(pid).unwrap_or_default().is_empty());
+    }
+
+    #[test]
+    fn test_pid_for_port_finds_own_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let pid = std::process::id();
+
+        assert_eq!(pid_for_port(port), Some(pid));
+        assert!(listening_ports(pid).contains(&port));
+        drop(listener);
+    }
+
+    #[test]
+    fn test_parse_tcp_listeners() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41522 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:A3C2 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 41990 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(parse_tcp_listeners(table), [(8080, 41522)]);
+    }
+
+    #[test]
+    fn test_exited_child_is_not_running() {
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("cmd")
+            .args(["/C", "exit"])
+            .spawn()
+            .unwrap();
+        #[cfg(not(windows))]
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_running(pid));
+    }
+
+    #[test]
+    fn test_current_process_handle_count() {
+        let count = handle_count(std::process::id());
+        // Only Linux-style /proc exposes descriptors on Unix
+        #[cfg(any(windows, target_os = "linux"))]
+        assert!(count.unwrap() > 0);
+        #[cfg(not(any(windows, target_os = "linux")))]
+        let _ = count;
+    }
+
+    #[test]
+    #[cfg(any(windows, target_os = "linux"))]
+    fn test_busy_child_uses_cpu() {
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("powershell")
+            .args(["-Command", "while ($true) {}"])
+            .spawn()
+            .unwrap();
+        #[cfg(not(windows))]
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "while :; do :; done"])
+            .spawn()
+            .unwrap();
+
+        // Give the interpreter time to start spinning
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let sampled = usage(child.id());
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let sampled = sampled.expect("a running child should be measurable");
+        assert!(sampled.cpu_percent > 0.0);
+        assert!(sampled.rss_bytes > 0);
+    }
+
+    #[test]
+    #[cfg(any(windows, target_os = "linux"))]
+    fn test_suspended_child_uses_no_cpu() {
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("powershell")
+            .args(["-Command", "while ($true) {}"])
+            .spawn()
+            .unwrap();
+        #[cfg(not(windows))]
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "while :; do :; done"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+
+        suspend(pid).unwrap();
+        // Let any in-flight time slice finish before sampling
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let paused = usage(pid);
+        let still_alive = is_running(pid);
+        resume(pid).unwrap();
+        let resumed = usage(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(still_alive);
+        let paused = paused.expect("a suspended child should be measurable");
+        assert!(
+            paused.cpu_percent < 5.0,
+            "used {}% while suspended",
+            paused.cpu_percent
+        );
+        assert!(
+            resumed
+                .expect("a resumed child should be measurable")
+                .cpu_percent
+                > 0.0
+        );
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        use std::time::Duration;
+
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("powershell")
+            .args(["-Command", "Start-Sleep -Seconds 30"])
+            .spawn()
+            .unwrap();
+        #[cfg(not(windows))]
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+
+        let pid = child.id();
+        assert!(!wait_timeout(pid, Duration::from_millis(100)));
+
+        child.kill().unwrap();
+        assert!(wait_timeout(pid, Duration::from_secs(5)));
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_run_capture() {
+        #[cfg(windows)]
+        let captured = run_capture("cmd", &["/C", "echo hello& echo oops 1>&2& exit 3"]).unwrap();
+        #[cfg(not(windows))]
+        let captured = run_capture(
+            "sh",
+            &[
+                "-c",
+                "printf '\\033[31mhello\\033[0m\\n'; echo oops >&2; exit 3",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(captured.stdout.trim_end(), "hello");
+        assert_eq!(captured.stderr.trim_end(), "oops");
+        assert_eq!(captured.status.code(), Some(3));
+        assert_eq!(
+            captured.combined().lines().collect::<Vec<_>>(),
+            ["hello", "oops"]
+        );
+    }
+
+    #[test]
+    fn test_run_streaming_sees_each_line() {
+        #[cfg(windows)]
+        let args = ["/C", "echo one& echo two& echo three& echo err 1>&2"];
+        #[cfg(not(windows))]
+        let args = ["-c", "echo one; echo two; echo three; echo err >&2"];
+        let shell = if cfg!(windows) { "cmd" } else { "sh" };
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let status = run_streaming(shell, &args, |stream, line| match stream {
+            Stream::Stdout => stdout.push(line.trim_end().to_string()),
+            Stream::Stderr => stderr.push(line.trim_end().to_string()),
+        })
+        .unwrap();
+
+        assert!(status.success());
+        assert_eq!(stdout, ["one", "two", "three"]);
+        assert_eq!(stderr, ["err"]);
+    }
+}
@@ -1,6 +1,342 @@
+use crate::watch;
+use colored::Colorize;
+use crossterm::{cursor, execute, terminal};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::time::{Duration, SystemTime};
 use sysinfo::System;
 
-pub fn execute() {
+/// A single row of process information, as shown by the TUI process monitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub start_time: Option<SystemTime>,
+}
+
+/// Get all running processes for TUI display, sorted by CPU usage
+pub fn get_processes_for_tui() -> Vec<ProcessEntry> {
+    let mut sys = System::new_all();
+    sys.refresh_all();
+    snapshot(&sys)
+}
+
+/// The processes `sys` last refreshed, sorted by CPU usage
+pub fn snapshot(sys: &System) -> Vec<ProcessEntry> {
+    let mut processes: Vec<ProcessEntry> = sys
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessEntry {
+            pid: pid.as_u32(),
+            parent_pid: process.parent().map(|parent| parent.as_u32()),
+            name: process.name().to_string_lossy().to_string(),
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            // sysinfo already read this while refreshing; 0 means it couldn't
+            start_time: Some(process.start_time())
+                .filter(|&secs| secs > 0)
+                .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        })
+        .collect();
+
+    processes.sort_by(|a, b| {
+        b.cpu_usage
+            .partial_cmp(&a.cpu_usage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    processes
+}
+
+/// Render processes as a forest, children indented under their parents
+pub fn render_tree(processes: &[ProcessEntry]) -> String {
+    let known: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    let mut roots = Vec::new();
+
+    for process in processes {
+        match process.parent_pid {
+            Some(ppid) if ppid != process.pid && known.contains(&ppid) => {
+                children.entry(ppid).or_default().push(process)
+            }
+            _ => roots.push(process),
+        }
+    }
+    for list in children.values_mut() {
+        list.sort_by_key(|p| p.pid);
+    }
+    roots.sort_by_key(|p| p.pid);
+
+    let mut output = String::new();
+    let mut visited = HashSet::new();
+    for root in roots {
+        render_node(root, "", "", &children, &mut visited, &mut output);
+    }
+
+    // Processes whose parents form a cycle are unreachable from any root
+    let mut orphans: Vec<&ProcessEntry> = processes
+        .iter()
+        .filter(|p| !visited.contains(&p.pid))
+        .collect();
+    orphans.sort_by_key(|p| p.pid);
+    for orphan in orphans {
+        render_node(orphan, "", "", &children, &mut visited, &mut output);
+    }
+
+    output
+}
+
+fn render_node(
+    entry: &ProcessEntry,
+    line_prefix: &str,
+    child_prefix: &str,
+    children: &HashMap<u32, Vec<&ProcessEntry>>,
+    visited: &mut HashSet<u32>,
+    output: &mut String,
+) {
+    if !visited.insert(entry.pid) {
+        return;
+    }
+    output.push_str(&format!("{:>7} {}{}\n", entry.pid, line_prefix, entry.name));
+
+    let pending: Vec<&ProcessEntry> = children
+        .get(&entry.pid)
+        .map(|list| {
+            list.iter()
+                .copied()
+                .filter(|child| !visited.contains(&child.pid))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for (i, child) in pending.iter().enumerate() {
+        let is_last = i == pending.len() - 1;
+        let line = format!("{}{}", child_prefix, if is_last { "└── " } else { "├── " });
+        let nested = format!("{}{}", child_prefix, if is_last { "    " } else { "│   " });
+        render_node(child, &line, &nested, children, visited, output);
+    }
+}
+
+/// Format an elapsed time like `ps -o etime`: `MM:SS`, `H:MM:SS` or `D-HH:MM:SS`
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Parse a `--since` duration such as `90s`, `15m`, `2h` or `1d`; a bare number means minutes
+pub fn parse_since(value: &str) -> Result<Duration, String> {
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => value.split_at(split),
+        None => (value, "m"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("invalid duration '{}' (use s, m, h or d)", value)),
+    };
+    number
+        .checked_mul(scale)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration '{}' is too large", value))
+}
+
+/// How long a process has been running at `now`
+fn elapsed_at(start_time: Option<SystemTime>, now: SystemTime) -> Option<Duration> {
+    start_time.map(|start| now.duration_since(start).unwrap_or_default())
+}
+
+/// Processes started within `since` of `now`; ones with an unknown start time are left out
+pub fn filter_since(
+    processes: &[ProcessEntry],
+    since: Duration,
+    now: SystemTime,
+) -> Vec<ProcessEntry> {
+    processes
+        .iter()
+        .filter(|p| elapsed_at(p.start_time, now).is_some_and(|elapsed| elapsed <= since))
+        .cloned()
+        .collect()
+}
+
+/// CPU usage rise, in percentage points, that `--watch` highlights
+pub const CPU_JUMP_THRESHOLD: f32 = 10.0;
+
+/// What changed between two `--watch` refreshes
+#[derive(Debug, Default, PartialEq)]
+pub struct SnapshotDiff {
+    /// PIDs absent from the previous snapshot
+    pub new: HashSet<u32>,
+    /// PIDs whose CPU usage rose by more than the threshold
+    pub cpu_jumped: HashSet<u32>,
+}
+
+/// Compare two snapshots by PID
+pub fn diff_snapshots(
+    previous: &[ProcessEntry],
+    current: &[ProcessEntry],
+    threshold: f32,
+) -> SnapshotDiff {
+    let before: HashMap<u32, f32> = previous.iter().map(|p| (p.pid, p.cpu_usage)).collect();
+    let mut diff = SnapshotDiff::default();
+    for process in current {
+        match before.get(&process.pid) {
+            None => {
+                diff.new.insert(process.pid);
+            }
+            Some(&cpu) if process.cpu_usage - cpu > threshold => {
+                diff.cpu_jumped.insert(process.pid);
+            }
+            Some(_) => {}
+        }
+    }
+    diff
+}
+
+/// One `--watch` redraw: the busiest processes, new ones in green and CPU jumps in yellow
+pub fn render_watch_frame(
+    processes: &[ProcessEntry],
+    diff: &SnapshotDiff,
+    interval: Duration,
+) -> String {
+    let mut frame = format!(
+        " Every {:.1}s: ps ({} processes, {} new)\n\n",
+        interval.as_secs_f64(),
+        processes.len(),
+        diff.new.len()
+    );
+    frame.push_str(&format!(
+        "{:<8} {:<25} {:>7} {:>10}\n",
+        "PID", "NAME", "CPU%", "MEMORY"
+    ));
+    for process in processes.iter().take(25) {
+        let row = format!(
+            "{:<8} {:<25} {:>7.1} {:>10}",
+            process.pid,
+            truncate_string(&process.name, 24),
+            process.cpu_usage,
+            format_bytes(process.memory)
+        );
+        let row = if diff.new.contains(&process.pid) {
+            row.green().bold().to_string()
+        } else if diff.cpu_jumped.contains(&process.pid) {
+            row.yellow().bold().to_string()
+        } else {
+            row
+        };
+        frame.push_str(&row);
+        frame.push('\n');
+    }
+    frame
+}
+
+/// Redraw the process table every `interval` until Ctrl+C or `q`.
+/// One `System` lives across refreshes so CPU usage covers each interval.
+fn watch(interval: Duration) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _ = execute!(stdout, cursor::Hide);
+
+    let mut sys = System::new_all();
+    let mut previous: Option<Vec<ProcessEntry>> = None;
+    let result = loop {
+        sys.refresh_all();
+        let current = snapshot(&sys);
+        // The first frame has nothing to compare against
+        let diff = previous
+            .as_deref()
+            .map_or_else(SnapshotDiff::default, |previous| {
+                diff_snapshots(previous, &current, CPU_JUMP_THRESHOLD)
+            });
+        let frame = render_watch_frame(&current, &diff, interval);
+        if let Err(e) = watch::draw_frame(&mut stdout, &frame) {
+            break Err(e);
+        }
+        previous = Some(current);
+        match watch::wait_for_quit(interval) {
+            Ok(false) => continue,
+            Ok(true) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+
+    let _ = execute!(stdout, cursor::Show);
+    let _ = terminal::disable_raw_mode();
+    result
+}
+
+pub fn execute(args: &[String]) {
+    if let Some(i) = args
+        .iter()
+        .position(|a| a == "--watch" || a.starts_with("--watch="))
+    {
+        let value = match args[i].strip_prefix("--watch=") {
+            Some(value) => Some(value),
+            None => args.get(i + 1).map(String::as_str),
+        };
+        let interval = match value.map(watch::parse_interval) {
+            Some(Ok(interval)) => interval,
+            Some(Err(e)) => {
+                eprintln!("ps: {}", e);
+                return;
+            }
+            None => {
+                eprintln!("ps: option '--watch' requires an argument");
+                return;
+            }
+        };
+        if let Err(e) = watch(interval) {
+            eprintln!("ps: {}", e);
+        }
+        return;
+    }
+
+    if args
+        .iter()
+        .any(|a| a == "--tree" || a == "-H" || a == "--forest")
+    {
+        println!("{:>7} NAME", "PID");
+        print!("{}", render_tree(&get_processes_for_tui()));
+        return;
+    }
+
+    let show_handles = args.iter().any(|a| a == "--handles");
+    let since = match args.iter().position(|a| a == "--since") {
+        Some(i) => match args.get(i + 1).map(|value| parse_since(value)) {
+            Some(Ok(since)) => Some(since),
+            Some(Err(e)) => {
+                eprintln!("ps: {}", e);
+                return;
+            }
+            None => {
+                eprintln!("ps: option '--since' requires an argument");
+                return;
+            }
+        },
+        None => None,
+    };
+    let show_etime = since.is_some() || args.iter().any(|a| a == "--etime");
+    let now = SystemTime::now();
+
     let mut sys = System::new_all();
     sys.refresh_all();
 
@@ -10,22 +346,31 @@ pub fn execute() {
     println!("{}", "=".repeat(90));
 
     // Column headers
-    println!(
+    let mut header = format!(
         "{:<8} {:<25} {:<8} {:<10} {:<12} {:<15}",
         "PID", "NAME", "CPU%", "MEMORY", "DISK R/W", "STATUS"
     );
+    if show_handles {
+        header.push_str(&format!(" {:>7}", "HANDLES"));
+    }
+    if show_etime {
+        header.push_str(&format!(" {:>11}", "ELAPSED"));
+    }
+    println!("{}", header);
     println!("{}", "-".repeat(90));
 
-    // Get processes and sort by CPU usage
-    let mut processes: Vec<_> = sys.processes().iter().collect();
-    processes.sort_by(|a, b| {
-        b.1.cpu_usage()
-            .partial_cmp(&a.1.cpu_usage())
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    // Processes sorted by CPU usage
+    let mut entries = snapshot(&sys);
+    if let Some(since) = since {
+        entries = filter_since(&entries, since, now);
+    }
 
     // Display top 25 processes
-    for (pid, process) in processes.iter().take(25) {
+    for entry in entries.iter().take(25) {
+        let pid = entry.pid;
+        let Some(process) = sys.process(sysinfo::Pid::from_u32(pid)) else {
+            continue;
+        };
         let name = truncate_string(&process.name().to_string_lossy(), 24);
         let cpu = format!("{:.1}", process.cpu_usage());
         let memory = format_bytes(process.memory());
@@ -40,10 +385,18 @@ pub fn execute() {
 
         let status = format!("{:?}", process.status());
 
-        println!(
+        let mut row = format!(
             "{:<8} {:<25} {:<8} {:<10} {:<12} {:<15}",
             pid, name, cpu, memory, disk_info, status
         );
+        if show_handles {
+            row.push_str(&format!(" {:>7}", format_handles(pid)));
+        }
+        if show_etime {
+            let elapsed = elapsed_at(entry.start_time, now).map_or("-".to_string(), format_elapsed);
+            row.push_str(&format!(" {:>11}", elapsed));
+        }
+        println!("{}", row);
     }
 
     println!("{}", "-".repeat(90));
@@ -60,6 +413,11 @@ pub fn execute() {
     println!("Used swap: {}", format_bytes(sys.used_swap()));
 }
 
+/// Open handle (Windows) or file descriptor (Unix) count, `-` when it can't be read
+fn format_handles(pid: u32) -> String {
+    crate::process::handle_count(pid).map_or("-".to_string(), |count| count.to_string())
+}
+
 // Helper function to format bytes
 fn format_bytes(bytes: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
@@ -91,3 +449,111 @@ fn truncate_string(s: &str, max_len: usize) -> String {
         format!("{}...", &s[..max_len.saturating_sub(3)])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, parent_pid: Option<u32>, name: &str) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            parent_pid,
+            name: name.to_string(),
+            cpu_usage: 0.0,
+            memory: 0,
+            start_time: None,
+        }
+    }
+
+    #[test]
+    fn test_render_tree_indents_children() {
+        let processes = vec![
+            entry(12, Some(1), "shell"),
+            entry(1, None, "init"),
+            entry(10, Some(1), "service"),
+            entry(11, Some(10), "worker"),
+        ];
+
+        let expected = concat!(
+            "      1 init\n",
+            "     10 ├── service\n",
+            "     11 │   └── worker\n",
+            "     12 └── shell\n",
+        );
+        assert_eq!(render_tree(&processes), expected);
+    }
+
+    #[test]
+    fn test_render_tree_survives_cycles() {
+        let processes = vec![
+            entry(20, Some(21), "a"),
+            entry(21, Some(20), "b"),
+            entry(30, Some(30), "self"),
+        ];
+
+        let expected = concat!("     30 self\n", "     20 a\n", "     21 └── b\n");
+        assert_eq!(render_tree(&processes), expected);
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let with_cpu = |pid, cpu| ProcessEntry {
+            cpu_usage: cpu,
+            ..entry(pid, None, "proc")
+        };
+        let previous = vec![with_cpu(1, 0.0), with_cpu(2, 5.0), with_cpu(3, 50.0)];
+        let current = vec![
+            with_cpu(1, 2.0),
+            with_cpu(2, 40.0),
+            with_cpu(3, 10.0),
+            with_cpu(4, 0.0),
+        ];
+
+        let diff = diff_snapshots(&previous, &current, CPU_JUMP_THRESHOLD);
+        assert_eq!(diff.new, HashSet::from([4]));
+        assert_eq!(diff.cpu_jumped, HashSet::from([2]));
+
+        assert_eq!(
+            diff_snapshots(&current, &current, CPU_JUMP_THRESHOLD),
+            SnapshotDiff::default()
+        );
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(3723)), "1:02:03");
+        assert_eq!(format_elapsed(Duration::from_secs(65)), "01:05");
+        assert_eq!(
+            format_elapsed(Duration::from_secs(2 * 86400 + 3723)),
+            "2-01:02:03"
+        );
+    }
+
+    #[test]
+    fn test_since_filters_by_start_time() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let started = |pid, secs_ago| ProcessEntry {
+            start_time: Some(now - Duration::from_secs(secs_ago)),
+            ..entry(pid, None, "proc")
+        };
+        let processes = vec![
+            started(1, 30),
+            started(2, 10 * 60),
+            started(3, 3 * 3600),
+            entry(4, None, "unknown"),
+        ];
+
+        let since = parse_since("15m").unwrap();
+        let pids: Vec<u32> = filter_since(&processes, since, now)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, [1, 2]);
+
+        assert_eq!(parse_since("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_since("5").unwrap(), Duration::from_secs(300));
+        assert!(parse_since("soon").is_err());
+        assert!(parse_since("5w").is_err());
+        assert!(parse_since("99999999999999999d").is_err());
+    }
+}
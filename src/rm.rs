@@ -1,6 +1,8 @@
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, Write};
 use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
 
 pub fn rm<S: AsRef<Path>>(files: Vec<S>) -> io::Result<()> {
     for file_path in files {
@@ -19,3 +21,617 @@ pub fn rm<S: AsRef<Path>>(files: Vec<S>) -> io::Result<()> {
     }
     Ok(())
 }
+
+/// When `rm` asks before removing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Interactive {
+    #[default]
+    Never,
+    /// `-I`: once, before removing more than three operands or recursing
+    Once,
+    /// `-i`: before every operand
+    Always,
+}
+
+/// Parse the WHEN of `--interactive[=WHEN]`; a bare `--interactive` means `always`
+pub fn parse_interactive(value: Option<&str>) -> Result<Interactive, String> {
+    match value {
+        None | Some("always") | Some("yes") => Ok(Interactive::Always),
+        Some("once") => Ok(Interactive::Once),
+        Some("never") | Some("no") | Some("none") => Ok(Interactive::Never),
+        Some(other) => Err(format!("invalid argument '{}' for '--interactive'", other)),
+    }
+}
+
+/// Options for the `rm` command
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RmOptions {
+    pub recursive: bool,
+    pub one_file_system: bool,
+    /// `-d`: remove empty directories without `-r`
+    pub dir: bool,
+    /// `--trash`/`--recycle`: move to the trash instead of deleting
+    pub trash: bool,
+    /// `-f`: ignore operands that don't exist
+    pub force: bool,
+    pub interactive: Interactive,
+}
+
+/// Reports which device (filesystem or volume) a path lives on
+pub trait DeviceIdProvider {
+    fn device_id(&self, path: &Path) -> io::Result<u64>;
+}
+
+/// Device ids from the real filesystem
+pub struct FsDeviceIds;
+
+impl DeviceIdProvider for FsDeviceIds {
+    #[cfg(unix)]
+    fn device_id(&self, path: &Path) -> io::Result<u64> {
+        use std::os::unix::fs::MetadataExt;
+        Ok(fs::symlink_metadata(path)?.dev())
+    }
+
+    #[cfg(windows)]
+    fn device_id(&self, path: &Path) -> io::Result<u64> {
+        use std::os::windows::fs::OpenOptionsExt;
+        use std::os::windows::io::AsRawHandle;
+        use winapi::um::fileapi::{BY_HANDLE_FILE_INFORMATION, GetFileInformationByHandle};
+        use winapi::um::winbase::FILE_FLAG_BACKUP_SEMANTICS;
+
+        // Directories can only be opened with backup semantics
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(path)?;
+
+        let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+        if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(info.dwVolumeSerialNumber as u64)
+    }
+}
+
+/// Remove a directory tree, leaving subtrees on other devices in place when
+/// `one_file_system` is set. Returns whether `path` itself was removed.
+pub fn remove_recursive(
+    path: &Path,
+    options: &RmOptions,
+    devices: &dyn DeviceIdProvider,
+) -> io::Result<bool> {
+    let root_device = devices.device_id(path)?;
+    remove_tree(path, root_device, options, devices)
+}
+
+fn remove_tree(
+    path: &Path,
+    root_device: u64,
+    options: &RmOptions,
+    devices: &dyn DeviceIdProvider,
+) -> io::Result<bool> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        fs::remove_file(path)?;
+        return Ok(true);
+    }
+
+    if options.one_file_system && devices.device_id(path)? != root_device {
+        eprintln!(
+            "rm: skipping '{}', since it's on a different device",
+            path.display()
+        );
+        return Ok(false);
+    }
+
+    let mut removed_all = true;
+    for entry in fs::read_dir(path)? {
+        removed_all &= remove_tree(&entry?.path(), root_device, options, devices)?;
+    }
+
+    // A skipped mount point keeps its parents non-empty
+    if removed_all {
+        fs::remove_dir(path)?;
+    }
+    Ok(removed_all)
+}
+
+fn ensure_empty(path: &Path) -> io::Result<()> {
+    if fs::read_dir(path)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::DirectoryNotEmpty,
+            "Directory not empty",
+        ));
+    }
+    Ok(())
+}
+
+/// Remove a directory only if it is empty, as `rm -d` and `rmdir` do
+pub fn remove_empty_dir(path: &Path) -> io::Result<()> {
+    ensure_empty(path)?;
+    fs::remove_dir(path)
+}
+
+/// Move `path` into the XDG trash rooted at `trash` (its `files` and `info` directories),
+/// returning where it landed. Fails rather than copying when `trash` is on another device.
+#[cfg(unix)]
+pub fn trash_to(path: &Path, trash: &Path) -> io::Result<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let absolute = std::path::absolute(path)?;
+    let Some(name) = absolute.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot trash a root directory",
+        ));
+    };
+    let files = trash.join("files");
+    let info = trash.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+
+    // The spec wants the original path percent-encoded like a URL path
+    let original: String = absolute
+        .as_os_str()
+        .as_bytes()
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_alphanumeric() || b"/-_.~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect();
+
+    // Creating the .trashinfo first claims the name against other trashers
+    let mut n = 1;
+    loop {
+        let mut entry = name.to_os_string();
+        if n > 1 {
+            entry.push(format!(".{}", n));
+        }
+        let mut info_name = entry.clone();
+        info_name.push(".trashinfo");
+        let info_path = info.join(info_name);
+
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(mut file) => {
+                write!(
+                    file,
+                    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                    original,
+                    deletion_date()
+                )?;
+                let dest = files.join(&entry);
+                if let Err(e) = fs::rename(&absolute, &dest) {
+                    let _ = fs::remove_file(&info_path);
+                    return Err(e);
+                }
+                return Ok(dest);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Local time as `YYYY-MM-DDThh:mm:ss`, the `.trashinfo` format
+#[cfg(unix)]
+fn deletion_date() -> String {
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe { libc::localtime_r(&now, &mut tm) };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+/// Pick the trash for `path`: `home_trash` when it is on the same device, otherwise
+/// `$topdir/.Trash-$uid` at the top of the device `path` lives on, as the FreeDesktop
+/// spec has it. `None` when there is no home trash and so nowhere to go.
+#[cfg(unix)]
+pub fn trash_dir_for(
+    path: &Path,
+    home_trash: Option<&Path>,
+    devices: &dyn DeviceIdProvider,
+) -> io::Result<Option<PathBuf>> {
+    let Some(home_trash) = home_trash else {
+        return Ok(None);
+    };
+    let device = devices.device_id(path)?;
+    // The home trash may not exist yet; its nearest existing ancestor is on the same device
+    let home_device = home_trash
+        .ancestors()
+        .find_map(|dir| devices.device_id(dir).ok());
+    if home_device == Some(device) {
+        return Ok(Some(home_trash.to_path_buf()));
+    }
+
+    let absolute = std::path::absolute(path)?;
+    let mut top = absolute.parent().unwrap_or(&absolute);
+    while let Some(parent) = top.parent() {
+        if devices.device_id(parent)? != device {
+            break;
+        }
+        top = parent;
+    }
+    let uid = unsafe { libc::getuid() };
+    Ok(Some(top.join(format!(".Trash-{}", uid))))
+}
+
+/// Move `path` to its trash (see `trash_dir_for`), with `$XDG_DATA_HOME/Trash` or
+/// `~/.local/share/Trash` as the home trash. Returns `false`, leaving `path` alone,
+/// when there is no home directory to hold a trash.
+#[cfg(unix)]
+pub fn move_to_trash(path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let home_trash = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local/share")))
+        .map(|data_home| data_home.join("Trash"));
+    let Some(trash) = trash_dir_for(path, home_trash.as_deref(), &FsDeviceIds)? else {
+        return Ok(false);
+    };
+    // Trash directories hold other people's deleted files; keep them private
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(&trash)?;
+    trash_to(path, &trash).map(|_| true)
+}
+
+/// Move `path` to the Recycle Bin
+#[cfg(windows)]
+pub fn move_to_trash(path: &Path) -> io::Result<bool> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::shellapi::{
+        FO_DELETE, FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT, SHFILEOPSTRUCTW,
+        SHFileOperationW,
+    };
+
+    // The shell wants a full path, in a list ended by an extra NUL
+    let absolute = std::path::absolute(path)?;
+    let mut from: Vec<u16> = absolute.as_os_str().encode_wide().collect();
+    from.extend([0, 0]);
+
+    let mut op: SHFILEOPSTRUCTW = unsafe { std::mem::zeroed() };
+    op.wFunc = FO_DELETE as _;
+    op.pFrom = from.as_ptr();
+    op.fFlags = (FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT) as _;
+    let code = unsafe { SHFileOperationW(&mut op) };
+    if code != 0 {
+        return Err(io::Error::other(format!(
+            "SHFileOperationW failed with code {:#x}",
+            code
+        )));
+    }
+    if op.fAnyOperationsAborted != 0 {
+        return Err(io::Error::other("recycling was cancelled"));
+    }
+    Ok(true)
+}
+
+/// Remove one operand per `options`, asking `devices` where `--one-file-system`
+/// stops. Returns whether it was removed.
+pub fn remove_path(
+    path: &Path,
+    options: &RmOptions,
+    devices: &dyn DeviceIdProvider,
+) -> io::Result<bool> {
+    let is_dir = fs::symlink_metadata(path)?.is_dir();
+    if is_dir && !options.recursive {
+        if !options.dir {
+            return Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                "Is a directory",
+            ));
+        }
+        ensure_empty(path)?;
+    }
+
+    // A failed move must not fall back to deleting what was meant to stay recoverable
+    if options.trash {
+        if move_to_trash(path)? {
+            return Ok(true);
+        }
+        eprintln!(
+            "rm: no trash directory for '{}' (no home directory); deleting it permanently",
+            path.display()
+        );
+    }
+
+    if !is_dir {
+        fs::remove_file(path).map(|_| true)
+    } else if options.recursive {
+        remove_recursive(path, options, devices)
+    } else {
+        remove_empty_dir(path).map(|_| true)
+    }
+}
+
+/// Ask `question` on stderr; only an answer starting with `y` agrees
+fn confirm(question: &str) -> bool {
+    eprint!("rm: {} ", question);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer).is_ok() && answer.trim_start().starts_with(['y', 'Y'])
+}
+
+/// Entry point for the `rm` shell command.
+/// Returns 1 if any operand could not be removed.
+pub fn run(args: &[String]) -> i32 {
+    run_with_devices(args, &FsDeviceIds)
+}
+
+fn run_with_devices(args: &[String], devices: &dyn DeviceIdProvider) -> i32 {
+    let mut options = RmOptions::default();
+    let mut files = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "-r" | "-R" | "--recursive" => options.recursive = true,
+            "-d" | "--dir" => options.dir = true,
+            "--one-file-system" => options.one_file_system = true,
+            "--trash" | "--recycle" => options.trash = true,
+            "-i" => options.interactive = Interactive::Always,
+            "-I" => options.interactive = Interactive::Once,
+            "-f" | "--force" => {
+                options.interactive = Interactive::Never;
+                options.force = true;
+            }
+            arg if arg == "--interactive" || arg.starts_with("--interactive=") => {
+                match parse_interactive(arg.strip_prefix("--interactive=")) {
+                    Ok(when) => options.interactive = when,
+                    Err(e) => {
+                        eprintln!("rm: {}", e);
+                        return 1;
+                    }
+                }
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!(
+            "Usage: rm [-r] [-d] [-i|-I|--interactive[=WHEN]] [--trash] [--one-file-system] \
+             <file1> [file2] ..."
+        );
+        return 1;
+    }
+
+    if options.interactive == Interactive::Once
+        && (files.len() > 3 || options.recursive)
+        && !confirm(&format!(
+            "remove {} argument{}{}?",
+            files.len(),
+            if files.len() == 1 { "" } else { "s" },
+            if options.recursive {
+                " recursively"
+            } else {
+                ""
+            }
+        ))
+    {
+        return 0;
+    }
+
+    let mut code = 0;
+    for file in files {
+        let missing =
+            fs::symlink_metadata(file).is_err_and(|e| e.kind() == io::ErrorKind::NotFound);
+        if options.force && missing {
+            continue;
+        }
+        if options.interactive == Interactive::Always && !confirm(&format!("remove '{}'?", file)) {
+            continue;
+        }
+        match remove_path(Path::new(file), &options, devices) {
+            Ok(true) => println!("Deleted {}", file),
+            // A skipped mount point leaves the operand in place, as GNU rm reports
+            Ok(false) => code = 1,
+            Err(e) => {
+                eprintln!("Failed to delete {}: {}", file, e);
+                code = 1;
+            }
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Anything under a directory named `mnt` is on device 2
+    struct MockDevices;
+
+    impl DeviceIdProvider for MockDevices {
+        fn device_id(&self, path: &Path) -> io::Result<u64> {
+            let mounted = path.components().any(|c| c.as_os_str() == "mnt");
+            Ok(if mounted { 2 } else { 1 })
+        }
+    }
+
+    fn build_tree(root: &Path) {
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(root.join("mnt/data")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("sub/b.txt"), "b").unwrap();
+        fs::write(root.join("mnt/data/c.txt"), "c").unwrap();
+    }
+
+    #[test]
+    fn test_one_file_system_skips_other_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tree");
+        build_tree(&root);
+
+        let options = RmOptions {
+            recursive: true,
+            one_file_system: true,
+            ..Default::default()
+        };
+        assert!(!remove_recursive(&root, &options, &MockDevices).unwrap());
+
+        assert!(!root.join("a.txt").exists());
+        assert!(!root.join("sub").exists());
+        assert!(root.join("mnt/data/c.txt").exists());
+
+        // The operand is still there, so the command fails like GNU rm
+        let args = [
+            "-r".to_string(),
+            "--one-file-system".to_string(),
+            root.to_string_lossy().to_string(),
+        ];
+        assert_eq!(run_with_devices(&args, &MockDevices), 1);
+        assert!(root.exists());
+    }
+
+    #[test]
+    fn test_recursive_crosses_devices_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tree");
+        build_tree(&root);
+
+        let options = RmOptions {
+            recursive: true,
+            ..Default::default()
+        };
+        assert!(remove_recursive(&root, &options, &MockDevices).unwrap());
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_dir_removes_only_empty_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty");
+        let full = dir.path().join("full");
+        fs::create_dir(&empty).unwrap();
+        fs::create_dir(&full).unwrap();
+        fs::write(full.join("keep.txt"), "keep").unwrap();
+
+        let options = RmOptions {
+            dir: true,
+            ..Default::default()
+        };
+        assert!(remove_path(&empty, &options, &FsDeviceIds).unwrap());
+        assert!(!empty.exists());
+
+        let err = remove_path(&full, &options, &FsDeviceIds).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::DirectoryNotEmpty);
+        assert_eq!(err.to_string(), "Directory not empty");
+        assert!(full.join("keep.txt").exists());
+
+        // Without -d, directories are refused outright
+        assert!(remove_path(&full, &RmOptions::default(), &FsDeviceIds).is_err());
+
+        let args = vec!["-d".to_string(), full.to_string_lossy().to_string()];
+        assert_eq!(run(&args), 1);
+        let args = vec!["--dir".to_string(), empty.to_string_lossy().to_string()];
+        assert_eq!(run(&args), 1);
+        fs::create_dir(&empty).unwrap();
+        assert_eq!(run(&args), 0);
+    }
+
+    #[test]
+    fn test_parse_interactive() {
+        assert_eq!(parse_interactive(None), Ok(Interactive::Always));
+        assert_eq!(parse_interactive(Some("once")), Ok(Interactive::Once));
+        assert_eq!(parse_interactive(Some("never")), Ok(Interactive::Never));
+        assert!(parse_interactive(Some("sometimes")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_to_keeps_file_and_origin() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash = dir.path().join("Trash");
+        let doomed = dir.path().join("my notes.txt");
+        fs::write(&doomed, "first").unwrap();
+
+        let landed = trash_to(&doomed, &trash).unwrap();
+        assert!(!doomed.exists());
+        assert_eq!(landed, trash.join("files/my notes.txt"));
+        assert_eq!(fs::read_to_string(&landed).unwrap(), "first");
+        let info = fs::read_to_string(trash.join("info/my notes.txt.trashinfo")).unwrap();
+        let expected = format!(
+            "Path={}/my%20notes.txt",
+            std::path::absolute(dir.path()).unwrap().display()
+        );
+        assert!(info.starts_with("[Trash Info]\n"));
+        assert!(info.lines().any(|line| line == expected));
+        assert!(info.lines().any(|line| line.starts_with("DeletionDate=")));
+
+        // A second file with the same name doesn't clobber the first
+        fs::write(&doomed, "second").unwrap();
+        let landed = trash_to(&doomed, &trash).unwrap();
+        assert_eq!(landed, trash.join("files/my notes.txt.2"));
+        assert!(trash.join("info/my notes.txt.2.trashinfo").exists());
+        assert_eq!(
+            fs::read_to_string(trash.join("files/my notes.txt")).unwrap(),
+            "first"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_dir_for_other_device_uses_its_top_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("tree");
+        build_tree(&root);
+        let home_trash = root.join("home/.local/share/Trash");
+
+        let same = trash_dir_for(&root.join("a.txt"), Some(&home_trash), &MockDevices).unwrap();
+        assert_eq!(same, Some(home_trash.clone()));
+
+        let other = root.join("mnt/data/c.txt");
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(
+            trash_dir_for(&other, Some(&home_trash), &MockDevices).unwrap(),
+            Some(std::path::absolute(root.join(format!("mnt/.Trash-{}", uid))).unwrap())
+        );
+        assert_eq!(trash_dir_for(&other, None, &MockDevices).unwrap(), None);
+    }
+
+    #[test]
+    fn test_force_ignores_missing_operands() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+
+        assert_eq!(run(&["--trash".to_string(), missing.clone()]), 1);
+        assert_eq!(
+            run(&["-f".to_string(), "--trash".to_string(), missing.clone()]),
+            0
+        );
+        assert_eq!(run(&["-f".to_string(), missing]), 0);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_recycled_file_leaves_original_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let doomed = dir.path().join("recycle-me.txt");
+        fs::write(&doomed, "bye").unwrap();
+
+        let options = RmOptions {
+            trash: true,
+            ..Default::default()
+        };
+        assert!(remove_path(&doomed, &options, &FsDeviceIds).unwrap());
+        assert!(!doomed.exists());
+    }
+}
@@ -1,7 +1,658 @@
 use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
 use sysinfo::Components;
 
-pub fn execute() {
+/// A single temperature reading in degrees Celsius
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SensorReading {
+    pub label: String,
+    pub temperature: f32,
+    pub max: Option<f32>,
+    pub critical: Option<f32>,
+}
+
+/// Get valid temperature readings for TUI display
+pub fn get_sensors_for_tui() -> Vec<SensorReading> {
+    component_readings(&Components::new_with_refreshed_list())
+}
+
+fn component_readings(components: &Components) -> Vec<SensorReading> {
+    components
+        .iter()
+        .filter_map(|component| {
+            let temperature = component.temperature()?;
+            if temperature <= 0.0 {
+                return None;
+            }
+            Some(SensorReading {
+                label: component.label().to_string(),
+                temperature,
+                max: component.max(),
+                critical: component.critical(),
+            })
+        })
+        .collect()
+}
+
+/// One fan's speed; `rpm` is `None` when the sensor can't be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FanReading {
+    pub label: String,
+    pub rpm: Option<u32>,
+}
+
+/// Where Linux exposes hardware monitoring chips
+const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChannelKind {
+    Temperature,
+    Fan,
+}
+
+impl ChannelKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            ChannelKind::Temperature => "temp",
+            ChannelKind::Fan => "fan",
+        }
+    }
+}
+
+/// One hwmon sensor; everything but `input` is read once when it is discovered
+#[derive(Debug, Clone, PartialEq)]
+struct Channel {
+    kind: ChannelKind,
+    chip: String,
+    label: String,
+    input: PathBuf,
+    max: Option<f32>,
+    critical: Option<f32>,
+}
+
+impl Channel {
+    fn read(&self) -> Option<String> {
+        fs::read_to_string(&self.input)
+            .ok()
+            .map(|value| value.trim().to_string())
+    }
+
+    fn fan_reading(&self) -> FanReading {
+        FanReading {
+            label: self.label.clone(),
+            rpm: self.read().and_then(|value| value.parse().ok()),
+        }
+    }
+}
+
+/// The `temp*`/`fan*` channels of one `/sys/class/hwmon/hwmon*` directory, by index
+fn discover_channels(dir: &Path, kind: ChannelKind) -> Vec<Channel> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let prefix = kind.prefix();
+    let mut indices: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix(prefix)?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    indices.sort_unstable();
+
+    let chip = read_value(dir, "name");
+    let celsius = |index: u32, limit: &str| {
+        if kind != ChannelKind::Temperature {
+            return None;
+        }
+        read_number(dir, &format!("{}{}_{}", prefix, index, limit)).map(|m| (m / 1000.0) as f32)
+    };
+    indices
+        .into_iter()
+        .map(|index| Channel {
+            kind,
+            chip: chip.clone().unwrap_or_else(|| {
+                dir.file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().to_string())
+            }),
+            label: read_value(dir, &format!("{}{}_label", prefix, index))
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| match &chip {
+                    Some(chip) => format!("{} {}{}", chip, prefix, index),
+                    None => format!("{}{}", prefix, index),
+                }),
+            input: dir.join(format!("{}{}_input", prefix, index)),
+            max: celsius(index, "max"),
+            critical: celsius(index, "crit"),
+        })
+        .collect()
+}
+
+/// The `hwmon*` directories of a hwmon class directory, sorted
+fn hwmon_dirs(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("hwmon"))
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Parse the `fan*_input` files of one `/sys/class/hwmon/hwmon*` directory
+pub fn parse_hwmon_fans(dir: &Path) -> Vec<FanReading> {
+    discover_channels(dir, ChannelKind::Fan)
+        .iter()
+        .map(Channel::fan_reading)
+        .collect()
+}
+
+/// Read every fan under a hwmon class directory
+pub fn read_hwmon_fans(root: &Path) -> Vec<FanReading> {
+    hwmon_dirs(root)
+        .iter()
+        .flat_map(|dir| parse_hwmon_fans(dir))
+        .collect()
+}
+
+/// Temperatures and fan speeds for repeated polling, as the TUI dashboard does.
+/// Chips and labels are discovered once; `refresh` only re-reads the `*_input` files.
+// The binary compiles this module too but only the TUI holds a reader
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct SensorReader {
+    channels: Vec<Channel>,
+    /// Temperatures come from `sysinfo` when hwmon has none, as on Windows
+    components: Option<Components>,
+    temperatures: Vec<SensorReading>,
+    fans: Vec<FanReading>,
+}
+
+#[allow(dead_code)]
+impl SensorReader {
+    /// Discover this machine's sensors and take a first reading
+    pub fn new() -> Self {
+        Self::from_hwmon(Path::new(HWMON_ROOT))
+    }
+
+    /// Discover the chips under a hwmon class directory and take a first reading
+    pub fn from_hwmon(root: &Path) -> Self {
+        let channels: Vec<Channel> = hwmon_dirs(root)
+            .iter()
+            .flat_map(|dir| {
+                let mut channels = discover_channels(dir, ChannelKind::Temperature);
+                channels.extend(discover_channels(dir, ChannelKind::Fan));
+                channels
+            })
+            .collect();
+        let components = channels
+            .iter()
+            .all(|channel| channel.kind != ChannelKind::Temperature)
+            .then(Components::new_with_refreshed_list);
+
+        let mut reader = SensorReader {
+            channels,
+            components,
+            temperatures: Vec::new(),
+            fans: Vec::new(),
+        };
+        reader.refresh();
+        reader
+    }
+
+    /// Re-read every value; chips and labels stay as discovered
+    pub fn refresh(&mut self) {
+        self.temperatures.clear();
+        self.fans.clear();
+        for channel in &self.channels {
+            match channel.kind {
+                ChannelKind::Temperature => {
+                    let temperature = channel
+                        .read()
+                        .and_then(|value| value.parse::<f64>().ok())
+                        .map(|millidegrees| (millidegrees / 1000.0) as f32)
+                        .filter(|temperature| *temperature > 0.0);
+                    if let Some(temperature) = temperature {
+                        self.temperatures.push(SensorReading {
+                            label: channel.label.clone(),
+                            temperature,
+                            max: channel.max,
+                            critical: channel.critical,
+                        });
+                    }
+                }
+                ChannelKind::Fan => self.fans.push(channel.fan_reading()),
+            }
+        }
+
+        if let Some(components) = &mut self.components {
+            components.refresh(false);
+            self.temperatures = component_readings(components);
+        }
+    }
+
+    pub fn temperatures(&self) -> &[SensorReading] {
+        &self.temperatures
+    }
+
+    pub fn fans(&self) -> &[FanReading] {
+        &self.fans
+    }
+
+    /// Chip names in discovery order, each listed once
+    pub fn chips(&self) -> Vec<&str> {
+        let mut chips: Vec<&str> = Vec::new();
+        for channel in &self.channels {
+            if !chips.contains(&channel.chip.as_str()) {
+                chips.push(&channel.chip);
+            }
+        }
+        chips
+    }
+}
+
+impl Default for SensorReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse `label|value` lines; an empty or non-numeric value means unavailable
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_fan_lines(output: &str) -> Vec<FanReading> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (label, value) = line.trim().rsplit_once('|')?;
+            Some(FanReading {
+                label: label.trim().to_string(),
+                rpm: value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .map(|rpm| rpm.max(0.0).round() as u32),
+            })
+        })
+        .collect()
+}
+
+/// Fans on this machine, from OpenHardwareMonitor/LibreHardwareMonitor when
+/// one is running, otherwise the (usually speedless) `Win32_Fan` devices
+#[cfg(windows)]
+pub fn fan_speeds() -> Vec<FanReading> {
+    use std::process::Command;
+
+    const SCRIPT: &str = "foreach ($ns in 'root/OpenHardwareMonitor','root/LibreHardwareMonitor') { \
+        $s = Get-CimInstance -Namespace $ns -ClassName Sensor -Filter \"SensorType='Fan'\" \
+        -ErrorAction SilentlyContinue; \
+        if ($s) { $s | ForEach-Object { \"$($_.Name)|$($_.Value)\" }; exit } }; \
+        Get-CimInstance Win32_Fan -ErrorAction SilentlyContinue | \
+        ForEach-Object { \"$($_.Name)|$($_.DesiredSpeed)\" }";
+
+    Command::new("powershell")
+        .args(["-NoProfile", "-Command", SCRIPT])
+        .output()
+        .map(|output| parse_fan_lines(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Fans on this machine; empty when none are exposed
+#[cfg(not(windows))]
+pub fn fan_speeds() -> Vec<FanReading> {
+    read_hwmon_fans(Path::new(HWMON_ROOT))
+}
+
+/// Human-readable fan line, e.g. `CPU Fan: 1200 RPM`
+pub fn format_fan(fan: &FanReading) -> String {
+    match fan.rpm {
+        Some(0) => format!("{}: 0 RPM (stopped)", fan.label),
+        Some(rpm) => format!("{}: {} RPM", fan.label, rpm),
+        None => format!("{}: unavailable", fan.label),
+    }
+}
+
+/// The CPU's package temperature and the per-core readings it summarizes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuTemperatures {
+    pub package: SensorReading,
+    /// No package sensor was found, so `package` is the hottest core
+    pub from_cores: bool,
+    /// Sorted by core number
+    pub cores: Vec<SensorReading>,
+}
+
+/// Labels of a dedicated package sensor, best first: Intel `coretemp`, then AMD `k10temp`
+/// (whose `Tctl` may carry a fan-curve offset)
+const PACKAGE_LABELS: [&str; 3] = ["Package id", "Tdie", "Tctl"];
+
+/// `3` for labels like `Core 3` or `coretemp Core 3`
+fn core_number(label: &str) -> Option<u32> {
+    label.rsplit_once("Core ")?.1.trim().parse().ok()
+}
+
+/// Pick out the package and `Core N` readings; without a package sensor
+/// the hottest core stands in for it
+pub fn cpu_temperatures(readings: &[SensorReading]) -> Option<CpuTemperatures> {
+    let mut cores: Vec<(u32, &SensorReading)> = readings
+        .iter()
+        .filter_map(|reading| Some((core_number(&reading.label)?, reading)))
+        .collect();
+    cores.sort_by_key(|(number, _)| *number);
+    let cores: Vec<SensorReading> = cores
+        .into_iter()
+        .map(|(_, reading)| reading.clone())
+        .collect();
+
+    let dedicated = PACKAGE_LABELS.iter().find_map(|wanted| {
+        readings
+            .iter()
+            .filter(|reading| reading.label.contains(wanted))
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+    });
+    let (package, from_cores) = match dedicated {
+        Some(package) => (package.clone(), false),
+        None => (
+            cores
+                .iter()
+                .max_by(|a, b| a.temperature.total_cmp(&b.temperature))?
+                .clone(),
+            true,
+        ),
+    };
+
+    Some(CpuTemperatures {
+        package,
+        from_cores,
+        cores,
+    })
+}
+
+/// Human-readable CPU lines: the package, then each core indented when `per_core` is set
+pub fn format_cpu(cpu: &CpuTemperatures, per_core: bool) -> String {
+    let mut out = format!("CPU Package: {:.1}°C", cpu.package.temperature);
+    if cpu.from_cores {
+        out.push_str(&format!(" (hottest core: {})", cpu.package.label));
+    }
+    if per_core {
+        for core in &cpu.cores {
+            out.push_str(&format!("\n  {}: {:.1}°C", core.label, core.temperature));
+        }
+    }
+    out
+}
+
+/// Whether the battery is being charged from AC power
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChargeState {
+    Charging,
+    Discharging,
+    Full,
+    Unknown,
+}
+
+impl ChargeState {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChargeState::Charging => "charging",
+            ChargeState::Discharging => "discharging",
+            ChargeState::Full => "full",
+            ChargeState::Unknown => "unknown",
+        }
+    }
+}
+
+/// Charge level and power state of one battery
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatteryStatus {
+    pub name: String,
+    pub percent: u8,
+    pub state: ChargeState,
+    /// Seconds until empty when discharging, or until full when charging
+    #[serde(rename = "time_remaining_secs")]
+    pub time_remaining: Option<u64>,
+}
+
+fn read_value(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn read_number(dir: &Path, file: &str) -> Option<f64> {
+    read_value(dir, file)?.parse().ok()
+}
+
+/// Parse one `/sys/class/power_supply/BAT*` directory
+pub fn parse_power_supply(dir: &Path) -> Option<BatteryStatus> {
+    if read_value(dir, "type").is_some_and(|kind| kind != "Battery") {
+        return None;
+    }
+
+    let state = match read_value(dir, "status")?.as_str() {
+        "Charging" => ChargeState::Charging,
+        "Discharging" => ChargeState::Discharging,
+        "Full" => ChargeState::Full,
+        _ => ChargeState::Unknown,
+    };
+
+    // Drivers report either energy (µWh, µW) or charge (µAh, µA) counters
+    let (now, full, rate) = match read_number(dir, "energy_now") {
+        Some(now) => (
+            Some(now),
+            read_number(dir, "energy_full"),
+            read_number(dir, "power_now"),
+        ),
+        None => (
+            read_number(dir, "charge_now"),
+            read_number(dir, "charge_full"),
+            read_number(dir, "current_now"),
+        ),
+    };
+
+    let percent = match (read_number(dir, "capacity"), now, full) {
+        (Some(capacity), _, _) => capacity,
+        (None, Some(now), Some(full)) if full > 0.0 => now / full * 100.0,
+        _ => return None,
+    };
+
+    let hours = match (state, now, full, rate) {
+        (_, _, _, Some(rate)) if rate <= 0.0 => None,
+        (ChargeState::Discharging, Some(now), _, Some(rate)) => Some(now / rate),
+        (ChargeState::Charging, Some(now), Some(full), Some(rate)) => {
+            Some((full - now).max(0.0) / rate)
+        }
+        _ => None,
+    };
+
+    Some(BatteryStatus {
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        percent: percent.clamp(0.0, 100.0).round() as u8,
+        state,
+        time_remaining: hours.map(|hours| (hours * 3600.0).round() as u64),
+    })
+}
+
+/// Read every `BAT*` entry under a power_supply class directory
+pub fn read_power_supplies(root: &Path) -> Vec<BatteryStatus> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("BAT"))
+        })
+        .collect();
+    dirs.sort();
+
+    dirs.iter()
+        .filter_map(|dir| parse_power_supply(dir))
+        .collect()
+}
+
+/// Batteries on this machine; empty when there is none
+#[cfg(windows)]
+pub fn battery_status() -> Vec<BatteryStatus> {
+    use winapi::um::winbase::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    const BATTERY_CHARGING: u8 = 8;
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    const UNKNOWN: u8 = 255;
+    const UNKNOWN_LIFETIME: u32 = u32::MAX;
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return Vec::new();
+    }
+    if status.BatteryFlag == UNKNOWN
+        || status.BatteryFlag & NO_SYSTEM_BATTERY != 0
+        || status.BatteryLifePercent == UNKNOWN
+    {
+        return Vec::new();
+    }
+
+    let state = if status.BatteryFlag & BATTERY_CHARGING != 0 {
+        ChargeState::Charging
+    } else if status.ACLineStatus == 0 {
+        ChargeState::Discharging
+    } else if status.BatteryLifePercent >= 100 {
+        ChargeState::Full
+    } else {
+        ChargeState::Unknown
+    };
+
+    // Windows only estimates the time left while running on battery
+    let time_remaining = (state == ChargeState::Discharging
+        && status.BatteryLifeTime != UNKNOWN_LIFETIME)
+        .then_some(status.BatteryLifeTime as u64);
+
+    vec![BatteryStatus {
+        name: "Battery".to_string(),
+        percent: status.BatteryLifePercent.min(100),
+        state,
+        time_remaining,
+    }]
+}
+
+/// Batteries on this machine; empty when there is none
+#[cfg(not(windows))]
+pub fn battery_status() -> Vec<BatteryStatus> {
+    read_power_supplies(Path::new("/sys/class/power_supply"))
+}
+
+/// Human-readable battery line, e.g. `BAT0: 80% (discharging, 2:05 remaining)`
+pub fn format_battery(battery: &BatteryStatus) -> String {
+    let mut line = format!(
+        "{}: {}% ({}",
+        battery.name,
+        battery.percent,
+        battery.state.as_str()
+    );
+    if let Some(secs) = battery.time_remaining {
+        let suffix = if battery.state == ChargeState::Charging {
+            "until full"
+        } else {
+            "remaining"
+        };
+        line.push_str(&format!(
+            ", {}:{:02} {}",
+            secs / 3600,
+            (secs % 3600) / 60,
+            suffix
+        ));
+    }
+    line.push(')');
+    line
+}
+
+/// Everything `sensors --json` prints, as one object
+#[derive(Serialize)]
+struct SensorsReport<'a> {
+    temperatures: &'a [SensorReading],
+    fans: &'a [FanReading],
+    batteries: &'a [BatteryStatus],
+}
+
+/// Render temperatures, fans and batteries as a single JSON object.
+/// NaN and infinite readings become `null`, since JSON has no literal for them.
+pub fn render_json(
+    readings: &[SensorReading],
+    fans: &[FanReading],
+    batteries: &[BatteryStatus],
+) -> String {
+    let report = SensorsReport {
+        temperatures: readings,
+        fans,
+        batteries,
+    };
+    serde_json::to_string(&report).unwrap_or_default()
+}
+
+fn print_fans() {
+    println!();
+    println!("{}", "Fans:".bold().blue());
+    let fans = fan_speeds();
+    if fans.is_empty() {
+        println!("{}", "no fan sensors".dimmed());
+    }
+    for fan in &fans {
+        let line = format_fan(fan);
+        match fan.rpm {
+            Some(0) | None => println!("{}", line.yellow()),
+            Some(_) => println!("{}", line),
+        }
+    }
+}
+
+fn print_cpu(per_core: bool) {
+    if let Some(cpu) = cpu_temperatures(&get_sensors_for_tui()) {
+        println!();
+        println!("{}", format_cpu(&cpu, per_core));
+    }
+}
+
+fn print_batteries() {
+    println!();
+    println!("{}", "Battery:".bold().blue());
+    let batteries = battery_status();
+    if batteries.is_empty() {
+        println!("{}", "no battery".dimmed());
+    }
+    for battery in &batteries {
+        println!("{}", format_battery(battery));
+    }
+}
+
+pub fn execute(args: &[String]) {
+    if args.iter().any(|arg| arg == "--json") {
+        println!(
+            "{}",
+            render_json(&get_sensors_for_tui(), &fan_speeds(), &battery_status())
+        );
+        return;
+    }
+    let per_core = args.iter().any(|arg| arg == "--per-core");
+
     println!("{}", "System Component Temperatures:".bold().blue());
     println!("{}", "=".repeat(50));
 
@@ -21,6 +672,8 @@ pub fn execute() {
             "  - Hardware that supports temperature monitoring".dimmed()
         );
         println!("{}", "  - Proper drivers installed".dimmed());
+        print_fans();
+        print_batteries();
         return;
     }
 
@@ -31,38 +684,38 @@ pub fn execute() {
         let max_temp = component.max();
         let critical_temp = component.critical();
 
-        if let Some(temp) = temperature {
-            if temp > 0.0 {
-                sensor_count += 1;
-                print!("{}: ", label.bold());
-
-                let temp_str = format!("{:.1}°C", temp);
-                if let Some(crit) = critical_temp {
-                    if temp >= crit {
-                        print!("{}", temp_str.red().bold());
-                    } else if temp >= crit * 0.8 {
-                        print!("{}", temp_str.yellow());
-                    } else {
-                        print!("{}", temp_str.green());
-                    }
-                } else {
-                    print!("{}", temp_str.cyan());
-                }
+        if let Some(temp) = temperature
+            && temp > 0.0
+        {
+            sensor_count += 1;
+            print!("{}: ", label.bold());
 
-                if let Some(max) = max_temp {
-                    if max > 0.0 {
-                        print!(" {}", format!("(Max: {:.1}°C)", max).dimmed());
-                    }
+            let temp_str = format!("{:.1}°C", temp);
+            if let Some(crit) = critical_temp {
+                if temp >= crit {
+                    print!("{}", temp_str.red().bold());
+                } else if temp >= crit * 0.8 {
+                    print!("{}", temp_str.yellow());
+                } else {
+                    print!("{}", temp_str.green());
                 }
+            } else {
+                print!("{}", temp_str.cyan());
+            }
 
-                if let Some(crit) = critical_temp {
-                    if crit > 0.0 {
-                        print!(" {}", format!("[Critical: {:.1}°C]", crit).red().dimmed());
-                    }
-                }
+            if let Some(max) = max_temp
+                && max > 0.0
+            {
+                print!(" {}", format!("(Max: {:.1}°C)", max).dimmed());
+            }
 
-                println!();
+            if let Some(crit) = critical_temp
+                && crit > 0.0
+            {
+                print!(" {}", format!("[Critical: {:.1}°C]", crit).red().dimmed());
             }
+
+            println!();
         }
     }
 
@@ -78,5 +731,316 @@ pub fn execute() {
             "{}",
             format!("Found {} temperature sensor(s)", sensor_count).green()
         );
+        print_cpu(per_core);
+    }
+
+    print_fans();
+    print_batteries();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, value) in files {
+            fs::write(dir.join(file), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_read_power_supplies_sample() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(
+            root.path(),
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "80"),
+                ("energy_now", "40000000"),
+                ("energy_full", "50000000"),
+                ("power_now", "10000000"),
+            ],
+        );
+        write_supply(
+            root.path(),
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("charge_now", "1500000"),
+                ("charge_full", "3000000"),
+                ("current_now", "1000000"),
+            ],
+        );
+        write_supply(root.path(), "AC", &[("type", "Mains"), ("online", "1")]);
+
+        let batteries = read_power_supplies(root.path());
+        assert_eq!(
+            batteries,
+            vec![
+                BatteryStatus {
+                    name: "BAT0".to_string(),
+                    percent: 80,
+                    state: ChargeState::Discharging,
+                    time_remaining: Some(4 * 3600),
+                },
+                BatteryStatus {
+                    name: "BAT1".to_string(),
+                    percent: 50,
+                    state: ChargeState::Charging,
+                    time_remaining: Some(5400),
+                },
+            ]
+        );
+        assert_eq!(
+            format_battery(&batteries[0]),
+            "BAT0: 80% (discharging, 4:00 remaining)"
+        );
+        assert_eq!(
+            format_battery(&batteries[1]),
+            "BAT1: 50% (charging, 1:30 until full)"
+        );
+    }
+
+    #[test]
+    fn test_no_battery() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(root.path(), "AC", &[("type", "Mains"), ("online", "1")]);
+        assert!(read_power_supplies(root.path()).is_empty());
+        assert!(read_power_supplies(&root.path().join("missing")).is_empty());
+
+        assert_eq!(
+            render_json(&[], &[], &[]),
+            r#"{"temperatures":[],"fans":[],"batteries":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let readings = [SensorReading {
+            label: "CPU \"pkg\"".to_string(),
+            temperature: 45.5,
+            max: Some(60.0),
+            critical: None,
+        }];
+        let fans = [
+            FanReading {
+                label: "cpu_fan".to_string(),
+                rpm: Some(1200),
+            },
+            FanReading {
+                label: "case".to_string(),
+                rpm: None,
+            },
+        ];
+        let batteries = [BatteryStatus {
+            name: "BAT0".to_string(),
+            percent: 100,
+            state: ChargeState::Full,
+            time_remaining: None,
+        }];
+        assert_eq!(
+            render_json(&readings, &fans, &batteries),
+            r#"{"temperatures":[{"label":"CPU \"pkg\"","temperature":45.5,"max":60.0,"critical":null}],"fans":[{"label":"cpu_fan","rpm":1200},{"label":"case","rpm":null}],"batteries":[{"name":"BAT0","percent":100,"state":"full","time_remaining_secs":null}]}"#
+        );
+
+        // A chip that reports garbage must still produce parseable JSON
+        let broken = [SensorReading {
+            label: "acpitz".to_string(),
+            temperature: f32::NAN,
+            max: Some(f32::INFINITY),
+            critical: Some(f32::NAN),
+        }];
+        let json = render_json(&broken, &[], &[]);
+        assert_eq!(
+            json,
+            r#"{"temperatures":[{"label":"acpitz","temperature":null,"max":null,"critical":null}],"fans":[],"batteries":[]}"#
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_read_hwmon_fans_sample() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(
+            root.path(),
+            "hwmon0",
+            &[("name", "acpitz"), ("temp1_input", "45000")],
+        );
+        write_supply(
+            root.path(),
+            "hwmon1",
+            &[
+                ("name", "nct6775"),
+                ("fan1_input", "1187"),
+                ("fan1_label", "CPU Fan"),
+                ("fan2_input", "0"),
+                ("fan10_input", "garbage"),
+            ],
+        );
+
+        let fans = read_hwmon_fans(root.path());
+        assert_eq!(
+            fans,
+            vec![
+                FanReading {
+                    label: "CPU Fan".to_string(),
+                    rpm: Some(1187),
+                },
+                FanReading {
+                    label: "nct6775 fan2".to_string(),
+                    rpm: Some(0),
+                },
+                FanReading {
+                    label: "nct6775 fan10".to_string(),
+                    rpm: None,
+                },
+            ]
+        );
+        assert_eq!(format_fan(&fans[0]), "CPU Fan: 1187 RPM");
+        assert_eq!(format_fan(&fans[1]), "nct6775 fan2: 0 RPM (stopped)");
+        assert_eq!(format_fan(&fans[2]), "nct6775 fan10: unavailable");
+        assert!(read_hwmon_fans(&root.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_sensor_reader_refresh_keeps_chips() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(
+            root.path(),
+            "hwmon0",
+            &[
+                ("name", "acpitz"),
+                ("temp1_input", "45000"),
+                ("temp1_crit", "95000"),
+            ],
+        );
+        write_supply(
+            root.path(),
+            "hwmon1",
+            &[
+                ("name", "nct6775"),
+                ("temp1_input", "38500"),
+                ("temp1_label", "CPU"),
+                ("fan1_input", "1187"),
+            ],
+        );
+
+        let mut reader = SensorReader::from_hwmon(root.path());
+        assert_eq!(reader.chips(), ["acpitz", "nct6775"]);
+        assert_eq!(
+            reader.temperatures()[0],
+            SensorReading {
+                label: "acpitz temp1".to_string(),
+                temperature: 45.0,
+                max: None,
+                critical: Some(95.0),
+            }
+        );
+        assert_eq!(reader.temperatures()[1].label, "CPU");
+        assert_eq!(reader.fans()[0].rpm, Some(1187));
+
+        // New values are picked up; the label was cached at discovery
+        let hwmon1 = root.path().join("hwmon1");
+        fs::write(hwmon1.join("temp1_input"), "52000\n").unwrap();
+        fs::write(hwmon1.join("temp1_label"), "Renamed\n").unwrap();
+        fs::write(hwmon1.join("fan1_input"), "0\n").unwrap();
+        reader.refresh();
+        let chips: Vec<String> = reader.chips().iter().map(|c| c.to_string()).collect();
+        reader.refresh();
+        assert_eq!(reader.chips(), chips);
+        assert_eq!(reader.chips(), ["acpitz", "nct6775"]);
+        assert_eq!(reader.temperatures()[1].label, "CPU");
+        assert_eq!(reader.temperatures()[1].temperature, 52.0);
+        assert_eq!(reader.fans()[0].rpm, Some(0));
+    }
+
+    #[test]
+    fn test_cpu_package_from_coretemp() {
+        let root = tempfile::tempdir().unwrap();
+        write_supply(
+            root.path(),
+            "hwmon0",
+            &[("name", "acpitz"), ("temp1_input", "27800")],
+        );
+        write_supply(
+            root.path(),
+            "hwmon1",
+            &[
+                ("name", "coretemp"),
+                ("temp1_input", "58000"),
+                ("temp1_label", "Package id 0"),
+                ("temp1_crit", "100000"),
+                ("temp2_input", "51000"),
+                ("temp2_label", "Core 0"),
+                ("temp3_input", "61000"),
+                ("temp3_label", "Core 4"),
+                ("temp10_input", "49000"),
+                ("temp10_label", "Core 12"),
+                ("temp6_input", "55000"),
+                ("temp6_label", "Core 8"),
+            ],
+        );
+
+        let reader = SensorReader::from_hwmon(root.path());
+        let cpu = cpu_temperatures(reader.temperatures()).unwrap();
+        assert_eq!(cpu.package.label, "Package id 0");
+        assert_eq!(cpu.package.temperature, 58.0);
+        assert_eq!(cpu.package.critical, Some(100.0));
+        assert!(!cpu.from_cores);
+        let cores: Vec<(&str, f32)> = cpu
+            .cores
+            .iter()
+            .map(|core| (core.label.as_str(), core.temperature))
+            .collect();
+        assert_eq!(
+            cores,
+            [
+                ("Core 0", 51.0),
+                ("Core 4", 61.0),
+                ("Core 8", 55.0),
+                ("Core 12", 49.0)
+            ]
+        );
+        assert_eq!(format_cpu(&cpu, false), "CPU Package: 58.0°C");
+        assert_eq!(
+            format_cpu(&cpu, true),
+            "CPU Package: 58.0°C\n  Core 0: 51.0°C\n  Core 4: 61.0°C\n  Core 8: 55.0°C\n  Core 12: 49.0°C"
+        );
+
+        // No package sensor: the hottest core stands in
+        fs::remove_file(root.path().join("hwmon1/temp1_input")).unwrap();
+        let reader = SensorReader::from_hwmon(root.path());
+        let cpu = cpu_temperatures(reader.temperatures()).unwrap();
+        assert!(cpu.from_cores);
+        assert_eq!(cpu.package.temperature, 61.0);
+        assert_eq!(
+            format_cpu(&cpu, false),
+            "CPU Package: 61.0°C (hottest core: Core 4)"
+        );
+
+        let acpi_only = &reader.temperatures()[..1];
+        assert_eq!(cpu_temperatures(acpi_only), None);
+    }
+
+    #[test]
+    fn test_parse_fan_lines() {
+        assert_eq!(
+            parse_fan_lines("Fan #1|1534.5\r\nCooling Device|\r\n"),
+            vec![
+                FanReading {
+                    label: "Fan #1".to_string(),
+                    rpm: Some(1535),
+                },
+                FanReading {
+                    label: "Cooling Device".to_string(),
+                    rpm: None,
+                },
+            ]
+        );
     }
 }
@@ -1,57 +1,387 @@
-#[cfg(target_family = "unix")]
+use std::collections::HashMap;
 use std::process::Command;
 
-#[allow(dead_code)]
-fn main() {
-    let args: Vec<String> = std::env::args().skip(1).collect();
+use crate::env;
 
-    if args.is_empty() {
-        eprintln!("Usage: sudo <command> [args]");
-        std::process::exit(1);
+/// Variables every elevated command gets so it can find programs and its profile
+#[cfg(windows)]
+const ESSENTIAL_VARS: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SystemRoot",
+    "SystemDrive",
+    "windir",
+    "ComSpec",
+    "TEMP",
+    "TMP",
+    "USERNAME",
+    "USERPROFILE",
+];
+// HOME, USER and the like are left to sudo, which sets them for the target user
+#[cfg(not(windows))]
+const ESSENTIAL_VARS: &[&str] = &["PATH", "TERM", "LANG"];
+
+/// Which of the caller's environment variables reach the elevated command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreserveEnv {
+    /// Only the essentials
+    Clean,
+    /// Everything (`-E`/`--preserve-env`)
+    All,
+    /// The essentials plus the listed variables (`--preserve-env=LIST`)
+    Only(Vec<String>),
+}
+
+/// Parsed `sudo` command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SudoOptions {
+    pub preserve: PreserveEnv,
+    pub command: Vec<String>,
+}
+
+/// Parse `[-E | --preserve-env[=LIST]] [--] <command> [args]`
+pub fn parse_args(args: &[String]) -> Result<SudoOptions, String> {
+    let mut preserve = PreserveEnv::Clean;
+    let mut i = 0;
+
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "-E" || arg == "--preserve-env" {
+            preserve = PreserveEnv::All;
+        } else if let Some(list) = arg.strip_prefix("--preserve-env=") {
+            let names = list
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string);
+            match &mut preserve {
+                PreserveEnv::Only(kept) => kept.extend(names),
+                PreserveEnv::Clean => preserve = PreserveEnv::Only(names.collect()),
+                PreserveEnv::All => {}
+            }
+        } else if arg == "--" {
+            i += 1;
+            break;
+        } else if arg.starts_with('-') {
+            return Err(format!("invalid option '{}'", arg));
+        } else {
+            break;
+        }
+        i += 1;
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        let status = Command::new("sudo")
-            .args(&args)
-            .status()
-            .expect("Failed to execute sudo command");
+    if i >= args.len() {
+        return Err("Usage: sudo [-E | --preserve-env[=LIST]] <command> [args]".to_string());
+    }
 
-        std::process::exit(status.code().unwrap_or(1));
+    Ok(SudoOptions {
+        preserve,
+        command: args[i..].to_vec(),
+    })
+}
+
+/// The environment the elevated command starts with
+pub fn child_environment(preserve: &PreserveEnv) -> HashMap<String, String> {
+    let mut keep: Vec<String> = ESSENTIAL_VARS.iter().map(|name| name.to_string()).collect();
+    match preserve {
+        PreserveEnv::All => return env::inherited_environment(None),
+        PreserveEnv::Clean => {}
+        PreserveEnv::Only(names) => keep.extend(names.iter().cloned()),
     }
+    env::inherited_environment(Some(&keep))
+}
+
+/// Replace `cmd`'s environment with exactly `vars`
+pub fn apply_environment(cmd: &mut Command, vars: &HashMap<String, String>) {
+    cmd.env_clear().envs(vars);
+}
+
+/// Relaunch through the system `sudo`, which gets exactly `vars` and is asked to keep the
+/// non-essential ones by name; values stay out of the command line, where `ps` would show
+/// them to every user. The essentials are sudo's own to reset, so a plain `sudo` needs no
+/// `SETENV` permission.
+#[cfg(unix)]
+fn elevated_command(command: &[String], vars: &HashMap<String, String>) -> Command {
+    elevated_command_via("sudo", command, vars)
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let status = Command::new("sudo")
-            .args(&args)
-            .status()
-            .expect("Failed to execute sudo command");
+#[cfg(unix)]
+fn elevated_command_via(sudo: &str, command: &[String], vars: &HashMap<String, String>) -> Command {
+    let mut names: Vec<&str> = vars
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !ESSENTIAL_VARS.contains(name))
+        .collect();
+    names.sort();
 
-        std::process::exit(status.code().unwrap_or(1));
+    let mut cmd = Command::new(sudo);
+    apply_environment(&mut cmd, vars);
+    if !names.is_empty() {
+        cmd.arg(format!("--preserve-env={}", names.join(",")));
     }
+    cmd.arg("--").args(command);
+    cmd
+}
+
+/// Quote a string for a PowerShell single-quoted literal
+#[cfg(windows)]
+fn ps_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Base64 of the UTF-16LE text, as `powershell -EncodedCommand` expects
+#[cfg(windows)]
+fn encode_command(script: &str) -> String {
+    use base64::Engine;
+
+    let bytes: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
 
-    #[cfg(target_family = "windows")]
-    {
-        use std::process::Command;
+/// Owner, Administrators and SYSTEM only, with nothing inherited from the temp directory
+#[cfg(windows)]
+const ENV_FILE_SDDL: &str = "D:P(A;;FA;;;OW)(A;;FA;;;BA)(A;;FA;;;SY)";
 
-        let command = &args[0];
-        let cmd_args = &args[1..];
+/// Write `vars` as JSON to a temp file that only its owner and administrators can read.
+/// The file is removed when the returned handle is dropped.
+#[cfg(windows)]
+fn write_env_file(vars: &HashMap<String, String>) -> std::io::Result<tempfile::NamedTempFile> {
+    use std::io::{self, Write};
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::sddl::{
+        ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+    };
+    use winapi::shared::winerror::ERROR_SUCCESS;
+    use winapi::um::accctrl::SE_FILE_OBJECT;
+    use winapi::um::aclapi::SetNamedSecurityInfoW;
+    use winapi::um::securitybaseapi::GetSecurityDescriptorDacl;
+    use winapi::um::winbase::LocalFree;
+    use winapi::um::winnt::{
+        DACL_SECURITY_INFORMATION, PACL, PROTECTED_DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR,
+    };
 
-        let joined_args = cmd_args
+    let mut file = tempfile::Builder::new()
+        .prefix("winix-sudo-")
+        .suffix(".json")
+        .tempfile()?;
+    let sddl: Vec<u16> = ENV_FILE_SDDL.encode_utf16().chain([0]).collect();
+    let path: Vec<u16> = file.path().as_os_str().encode_wide().chain([0]).collect();
+
+    // Lock the file down before anything is written to it
+    unsafe {
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl.as_ptr(),
+            SDDL_REVISION_1 as DWORD,
+            &mut descriptor,
+            ptr::null_mut(),
+        ) == 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        let (mut present, mut defaulted) = (0, 0);
+        let mut dacl: PACL = ptr::null_mut();
+        let result = if GetSecurityDescriptorDacl(
+            descriptor,
+            &mut present,
+            &mut dacl,
+            &mut defaulted,
+        ) == 0
+        {
+            Err(io::Error::last_os_error())
+        } else {
+            // SetNamedSecurityInfoW returns the error code rather than setting the last error
+            match SetNamedSecurityInfoW(
+                path.as_ptr() as *mut u16,
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                dacl,
+                ptr::null_mut(),
+            ) {
+                ERROR_SUCCESS => Ok(()),
+                code => Err(io::Error::from_raw_os_error(code as i32)),
+            }
+        };
+        LocalFree(descriptor as *mut _);
+        result?;
+    }
+
+    file.write_all(serde_json::to_string(vars)?.as_bytes())?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// Relaunch elevated with UAC; the elevated PowerShell rebuilds the environment
+/// since `runAs` starts from the administrator's profile instead of ours.
+/// The values travel in a locked-down temp file, never on a command line other
+/// processes can read; keep the returned file alive until the command exits.
+#[cfg(windows)]
+fn elevated_command(
+    command: &[String],
+    vars: &HashMap<String, String>,
+) -> std::io::Result<(Command, tempfile::NamedTempFile)> {
+    let env_file = write_env_file(vars)?;
+    let file = ps_quote(&env_file.path().to_string_lossy());
+    let mut script = format!(
+        "$vars = Get-Content -Raw -Encoding UTF8 -LiteralPath {0} | ConvertFrom-Json; \
+         Remove-Item -LiteralPath {0}; \
+         Remove-Item Env:* -ErrorAction SilentlyContinue; \
+         foreach ($var in $vars.PSObject.Properties) {{ \
+         [Environment]::SetEnvironmentVariable($var.Name, $var.Value) }}; ",
+        file
+    );
+    script.push_str("& ");
+    script.push_str(
+        &command
             .iter()
-            .map(|s| format!("'{}'", s))
-            .collect::<Vec<String>>()
-            .join(",");
-
-        let status = Command::new("powershell")
-            .arg("-Command")
-            .arg(format!(
-                "Start-Process '{}' -ArgumentList {} -Verb runAs",
-                command, joined_args
-            ))
-            .status()
-            .expect("Failed to launch command as admin");
-
-        std::process::exit(status.code().unwrap_or(1));
+            .map(|arg| ps_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    script.push_str("; exit $LASTEXITCODE");
+
+    let mut cmd = Command::new("powershell");
+    cmd.arg("-NoProfile").arg("-Command").arg(format!(
+        "$p = Start-Process powershell -Verb runAs -Wait -PassThru -ArgumentList \
+         '-NoProfile','-EncodedCommand','{}'; exit $p.ExitCode",
+        encode_command(&script)
+    ));
+    Ok((cmd, env_file))
+}
+
+/// Run the `sudo` command, returning the elevated command's exit code
+pub fn execute(args: &[String]) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("sudo: {}", e);
+            return 1;
+        }
+    };
+
+    let vars = child_environment(&options.preserve);
+    #[cfg(windows)]
+    let (mut cmd, _env_file) = match elevated_command(&options.command, &vars) {
+        Ok(elevated) => elevated,
+        Err(e) => {
+            eprintln!("sudo: cannot pass the environment on: {}", e);
+            return 1;
+        }
+    };
+    #[cfg(unix)]
+    let mut cmd = elevated_command(&options.command, &vars);
+    match cmd.status() {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("sudo: failed to launch '{}': {}", options.command[0], e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_preserve_env() {
+        let parsed = parse_args(&args(&["whoami"])).unwrap();
+        assert_eq!(parsed.preserve, PreserveEnv::Clean);
+        assert_eq!(parsed.command, args(&["whoami"]));
+
+        let parsed = parse_args(&args(&["-E", "make", "-j4"])).unwrap();
+        assert_eq!(parsed.preserve, PreserveEnv::All);
+        assert_eq!(parsed.command, args(&["make", "-j4"]));
+
+        let parsed = parse_args(&args(&["--preserve-env=A,B", "--", "-x"])).unwrap();
+        assert_eq!(parsed.preserve, PreserveEnv::Only(args(&["A", "B"])));
+        assert_eq!(parsed.command, args(&["-x"]));
+
+        assert!(parse_args(&args(&["-E"])).is_err());
+        assert!(parse_args(&args(&["--bogus", "ls"])).is_err());
+    }
+
+    #[test]
+    fn test_preserve_env_list_reaches_child() {
+        unsafe {
+            std::env::set_var("WINIX_SUDO_FOO", "kept");
+            std::env::set_var("WINIX_SUDO_OTHER", "dropped");
+        }
+
+        let parsed = parse_args(&args(&["--preserve-env=WINIX_SUDO_FOO", "env"])).unwrap();
+        let vars = child_environment(&parsed.preserve);
+
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", "set"]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut cmd = Command::new("env");
+
+        apply_environment(&mut cmd, &vars);
+        let output = cmd.output().expect("failed to run child");
+        let output = String::from_utf8_lossy(&output.stdout);
+
+        assert!(output.lines().any(|line| line == "WINIX_SUDO_FOO=kept"));
+        assert!(!output.contains("WINIX_SUDO_OTHER"));
+        if std::env::var("PATH").is_ok() {
+            assert!(output.lines().any(|line| line.starts_with("PATH=")));
+        }
+        for (key, _) in std::env::vars() {
+            if !key.starts_with("WINIX_SUDO_") && !ESSENTIAL_VARS.contains(&key.as_str()) {
+                assert!(!vars.contains_key(&key), "{} leaked into the child", key);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_elevated_command_keeps_values_out_of_argv() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Stands in for sudo: drops its own options and runs the command in the environment it got
+        let dir = tempfile::tempdir().unwrap();
+        let stub = dir.path().join("sudo");
+        std::fs::write(
+            &stub,
+            "#!/bin/sh\nwhile [ \"$1\" != -- ]; do shift; done\nshift\nexec \"$@\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut vars = HashMap::new();
+        vars.insert("WINIX_SUDO_TOKEN".to_string(), "s3cret".to_string());
+        vars.insert(
+            "PATH".to_string(),
+            std::env::var("PATH").unwrap_or_default(),
+        );
+        let mut cmd = elevated_command_via(stub.to_str().unwrap(), &args(&["env"]), &vars);
+
+        let argv: Vec<_> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(argv, ["--preserve-env=WINIX_SUDO_TOKEN", "--", "env"]);
+
+        let output = cmd.output().expect("failed to run the sudo stub");
+        let output = String::from_utf8_lossy(&output.stdout);
+        assert!(output.lines().any(|line| line == "WINIX_SUDO_TOKEN=s3cret"));
+        assert!(!output.lines().any(|line| line.starts_with("HOME=")));
+
+        // A plain sudo asks to keep nothing, so sudo sets the target user's HOME and USER
+        let clean = child_environment(&PreserveEnv::Clean);
+        assert!(!clean.contains_key("HOME") && !clean.contains_key("USER"));
+        let cmd = elevated_command_via(stub.to_str().unwrap(), &args(&["id"]), &clean);
+        let argv: Vec<_> = cmd.get_args().collect();
+        assert_eq!(argv, ["--", "id"]);
     }
 }
@@ -1,14 +1,517 @@
 use sys_info;
 use std::env;
 use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 use std::process; // to exit the process without panic
 use std::error::Error;
+use serde::Serialize;
 
-pub fn run() {
-    println!("OS: {}", sys_info::os_type().unwrap());
-    println!("OS release: {}", sys_info::os_release().unwrap());
-    println!("Hostname: {}", sys_info::hostname().unwrap());
-    println!("CPU cores: {}", sys_info::cpu_num().unwrap());
-    println!("CPU speed (MHz): {}", sys_info::cpu_speed().unwrap());
-    println!("Total RAM: {} MB", sys_info::mem_info().unwrap().total / 1024);
+/// What `sysinfo` reports; anything that can't be determined is left empty
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SystemInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_release: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_cores: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_speed_mhz: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_ram_mb: Option<u64>,
+    pub gpus: Vec<String>,
+    pub interfaces: Vec<NetworkInterface>,
+}
+
+/// A network interface and the addresses assigned to it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    /// Colon-separated hardware address; `None` for interfaces without one, like loopback
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    pub ipv4: Vec<Ipv4Addr>,
+    pub ipv6: Vec<Ipv6Addr>,
+    pub loopback: bool,
+}
+
+impl SystemInfo {
+    pub fn collect() -> Self {
+        SystemInfo {
+            os: sys_info::os_type().ok(),
+            os_release: sys_info::os_release().ok(),
+            hostname: sys_info::hostname().ok(),
+            cpu_model: cpu_model(),
+            cpu_cores: sys_info::cpu_num().ok(),
+            cpu_speed_mhz: sys_info::cpu_speed().ok(),
+            total_ram_mb: sys_info::mem_info().ok().map(|mem| mem.total / 1024),
+            gpus: gpu_names(),
+            interfaces: network(),
+        }
+    }
+
+    /// Render as a JSON object, leaving out fields that are unknown
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// CPU brand string from the extended CPUID leaves, e.g. "Intel(R) Core(TM) i7-9700K CPU @ 3.60GHz"
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn cpuid_brand() -> Option<String> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    if __cpuid(0x8000_0000).eax < 0x8000_0004 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(48);
+    for leaf in 0x8000_0002..=0x8000_0004 {
+        let regs = __cpuid(leaf);
+        for reg in [regs.eax, regs.ebx, regs.ecx, regs.edx] {
+            bytes.extend_from_slice(&reg.to_le_bytes());
+        }
+    }
+
+    let brand = String::from_utf8_lossy(&bytes);
+    let brand = brand.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!brand.is_empty()).then(|| brand.to_string())
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn cpuid_brand() -> Option<String> {
+    None
+}
+
+/// The "model name" (or ARM "Processor"/"Hardware") line of `/proc/cpuinfo`
+pub fn parse_cpuinfo_model(cpuinfo: &str) -> Option<String> {
+    ["model name", "Processor", "Hardware"]
+        .iter()
+        .find_map(|key| {
+            cpuinfo.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                let value = value.trim();
+                (name.trim() == *key && !value.is_empty()).then(|| value.to_string())
+            })
+        })
+}
+
+pub fn cpu_model() -> Option<String> {
+    cpuid_brand().or_else(|| {
+        fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|cpuinfo| parse_cpuinfo_model(&cpuinfo))
+    })
+}
+
+/// Display adapters listed in `lspci` output
+pub fn parse_lspci(output: &str) -> Vec<String> {
+    const CLASSES: [&str; 3] = [
+        "VGA compatible controller",
+        "3D controller",
+        "Display controller",
+    ];
+
+    output
+        .lines()
+        .filter_map(|line| {
+            // "01:00.0 VGA compatible controller: NVIDIA Corporation GP104 [GeForce GTX 1080]"
+            let (_, rest) = line.split_once(' ')?;
+            let (class, name) = rest.split_once(": ")?;
+            CLASSES
+                .iter()
+                .any(|c| class.starts_with(c))
+                .then(|| name.trim().to_string())
+        })
+        .collect()
+}
+
+/// GPUs from `/sys/class/drm/cardN/device`, named by vendor and PCI device id
+pub fn drm_gpus(root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut cards: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        // card0-HDMI-A-1 and friends are connectors of card0
+        .filter(|name| name.starts_with("card") && !name.contains('-'))
+        .collect();
+    cards.sort();
+
+    cards
+        .iter()
+        .filter_map(|card| {
+            let device = root.join(card).join("device");
+            let read = |file: &str| fs::read_to_string(device.join(file)).ok();
+            let vendor = read("vendor")?.trim().to_lowercase();
+            let id = read("device")?.trim().to_lowercase();
+            let vendor_name = match vendor.as_str() {
+                "0x10de" => "NVIDIA",
+                "0x1002" => "AMD",
+                "0x8086" => "Intel",
+                other => other,
+            };
+            Some(format!("{} GPU [{}]", vendor_name, id))
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+pub fn gpu_names() -> Vec<String> {
+    // WMI through PowerShell saves binding COM for a single query
+    std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_VideoController | Select-Object -ExpandProperty Name",
+        ])
+        .output()
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(windows))]
+pub fn gpu_names() -> Vec<String> {
+    let from_lspci = std::process::Command::new("lspci")
+        .output()
+        .map(|output| parse_lspci(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+
+    if from_lspci.is_empty() {
+        drm_gpus(Path::new("/sys/class/drm"))
+    } else {
+        from_lspci
+    }
+}
+
+/// `aa:bb:cc:dd:ee:ff` for a hardware address, or `None` when it is empty or all zeros
+pub fn format_mac(bytes: &[u8]) -> Option<String> {
+    if bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+    let octets: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Some(octets.join(":"))
+}
+
+/// The entry for `name`, added at the end of `interfaces` if it is new
+#[cfg(unix)]
+fn interface_named<'a>(
+    interfaces: &'a mut Vec<NetworkInterface>,
+    name: &str,
+) -> &'a mut NetworkInterface {
+    match interfaces.iter().position(|nic| nic.name == name) {
+        Some(i) => &mut interfaces[i],
+        None => {
+            interfaces.push(NetworkInterface {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            interfaces.last_mut().unwrap()
+        }
+    }
+}
+
+/// Network interfaces with their hardware and IP addresses, loopback included
+#[cfg(windows)]
+pub fn network() -> Vec<NetworkInterface> {
+    use winapi::shared::ifdef::IfOperStatusUp;
+    use winapi::shared::ipifcons::IF_TYPE_SOFTWARE_LOOPBACK;
+    use winapi::shared::winerror::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use winapi::shared::ws2def::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN};
+    use winapi::shared::ws2ipdef::SOCKADDR_IN6;
+    use winapi::um::iphlpapi::GetAdaptersAddresses;
+    use winapi::um::iptypes::{
+        GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        IP_ADAPTER_ADDRESSES,
+    };
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    // A `u64` buffer keeps the adapter records aligned
+    let mut buffer: Vec<u64> = Vec::new();
+    let mut size = 0;
+    loop {
+        let status = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as u32,
+                flags,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        match status {
+            NO_ERROR => break,
+            // Adapters can appear between the size query and the read, so retry
+            ERROR_BUFFER_OVERFLOW => buffer = vec![0; (size as usize).div_ceil(8)],
+            _ => return Vec::new(),
+        }
+    }
+    if buffer.is_empty() {
+        return Vec::new();
+    }
+
+    let mut interfaces = Vec::new();
+    let mut adapter = buffer.as_ptr().cast::<IP_ADAPTER_ADDRESSES>();
+    while let Some(current) = unsafe { adapter.as_ref() } {
+        let name = unsafe {
+            let mut len = 0;
+            while *current.FriendlyName.add(len) != 0 {
+                len += 1;
+            }
+            String::from_utf16_lossy(std::slice::from_raw_parts(current.FriendlyName, len))
+        };
+        let mac_len = (current.PhysicalAddressLength as usize).min(current.PhysicalAddress.len());
+        let mut nic = NetworkInterface {
+            name,
+            mac: format_mac(&current.PhysicalAddress[..mac_len]),
+            loopback: current.IfType == IF_TYPE_SOFTWARE_LOOPBACK,
+            ..Default::default()
+        };
+
+        let mut unicast = current.FirstUnicastAddress;
+        while let Some(address) = unsafe { unicast.as_ref() } {
+            let sockaddr = address.Address.lpSockaddr;
+            match unsafe { (*sockaddr).sa_family } as i32 {
+                AF_INET => {
+                    let v4 = unsafe { &*sockaddr.cast::<SOCKADDR_IN>() };
+                    let raw = unsafe { *v4.sin_addr.S_un.S_addr() };
+                    nic.ipv4.push(Ipv4Addr::from(u32::from_be(raw)));
+                }
+                AF_INET6 => {
+                    let v6 = unsafe { &*sockaddr.cast::<SOCKADDR_IN6>() };
+                    nic.ipv6
+                        .push(Ipv6Addr::from(unsafe { *v6.sin6_addr.u.Byte() }));
+                }
+                _ => {}
+            }
+            unicast = address.Next;
+        }
+
+        // Disconnected virtual adapters clutter the list without carrying any address
+        if current.OperStatus == IfOperStatusUp || !nic.ipv4.is_empty() || !nic.ipv6.is_empty() {
+            interfaces.push(nic);
+        }
+        adapter = current.Next;
+    }
+    interfaces
+}
+
+/// Network interfaces with their hardware and IP addresses, loopback included
+#[cfg(unix)]
+pub fn network() -> Vec<NetworkInterface> {
+    use std::ffi::CStr;
+
+    let mut interfaces = Vec::new();
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut list) } != 0 {
+        return interfaces;
+    }
+
+    let mut entry = list;
+    while let Some(current) = unsafe { entry.as_ref() } {
+        let name = unsafe { CStr::from_ptr(current.ifa_name) }.to_string_lossy();
+        let nic = interface_named(&mut interfaces, &name);
+        nic.loopback |= current.ifa_flags & libc::IFF_LOOPBACK as u32 != 0;
+
+        if let Some(addr) = unsafe { current.ifa_addr.as_ref() } {
+            match addr.sa_family as i32 {
+                libc::AF_INET => {
+                    let v4 = unsafe { &*current.ifa_addr.cast::<libc::sockaddr_in>() };
+                    nic.ipv4
+                        .push(Ipv4Addr::from(u32::from_be(v4.sin_addr.s_addr)));
+                }
+                libc::AF_INET6 => {
+                    let v6 = unsafe { &*current.ifa_addr.cast::<libc::sockaddr_in6>() };
+                    nic.ipv6.push(Ipv6Addr::from(v6.sin6_addr.s6_addr));
+                }
+                _ => {}
+            }
+        }
+        entry = current.ifa_next;
+    }
+    unsafe { libc::freeifaddrs(list) };
+
+    for nic in &mut interfaces {
+        nic.mac = sys_class_net_mac(Path::new("/sys/class/net"), &nic.name);
+    }
+    interfaces
+}
+
+/// Hardware address of `name` from a `/sys/class/net` tree
+pub fn sys_class_net_mac(root: &Path, name: &str) -> Option<String> {
+    let address = fs::read_to_string(root.join(name).join("address")).ok()?;
+    let bytes = address
+        .trim()
+        .split(':')
+        .map(|octet| u8::from_str_radix(octet, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    format_mac(&bytes)
+}
+
+fn or_unknown<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or("unknown".to_string(), |v| v.to_string())
+}
+
+pub fn run(args: &[String]) {
+    let info = SystemInfo::collect();
+
+    if args.iter().any(|arg| arg == "--json") {
+        println!("{}", info.to_json());
+        return;
+    }
+
+    println!("OS: {}", or_unknown(&info.os));
+    println!("OS release: {}", or_unknown(&info.os_release));
+    println!("Hostname: {}", or_unknown(&info.hostname));
+    println!("CPU model: {}", or_unknown(&info.cpu_model));
+    println!("CPU cores: {}", or_unknown(&info.cpu_cores));
+    println!("CPU speed (MHz): {}", or_unknown(&info.cpu_speed_mhz));
+    println!("Total RAM: {} MB", or_unknown(&info.total_ram_mb));
+    if info.gpus.is_empty() {
+        println!("GPU: unknown");
+    }
+    for gpu in &info.gpus {
+        println!("GPU: {}", gpu);
+    }
+    for nic in &info.interfaces {
+        let mut line = format!("Network: {}", nic.name);
+        if nic.loopback {
+            line.push_str(" (loopback)");
+        }
+        if let Some(mac) = &nic.mac {
+            line.push_str(&format!(" [{}]", mac));
+        }
+        let addresses: Vec<String> = nic
+            .ipv4
+            .iter()
+            .map(ToString::to_string)
+            .chain(nic.ipv6.iter().map(ToString::to_string))
+            .collect();
+        if !addresses.is_empty() {
+            line.push_str(&format!(" {}", addresses.join(", ")));
+        }
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64", target_os = "linux"))]
+    fn test_cpu_model_is_reported() {
+        let model = cpu_model().expect("CPU model should be known on this platform");
+        assert!(!model.trim().is_empty());
+    }
+
+    #[test]
+    fn test_parse_cpuinfo_model() {
+        let x86 = "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel name\t: Intel(R) Xeon(R) CPU\n";
+        assert_eq!(
+            parse_cpuinfo_model(x86).as_deref(),
+            Some("Intel(R) Xeon(R) CPU")
+        );
+        let arm = "processor\t: 0\nBogoMIPS\t: 38.40\nHardware\t: BCM2835\n";
+        assert_eq!(parse_cpuinfo_model(arm).as_deref(), Some("BCM2835"));
+        assert_eq!(parse_cpuinfo_model("processor\t: 0\n"), None);
+    }
+
+    #[test]
+    fn test_parse_lspci() {
+        let output = "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 630 (rev 02)\n\
+                      00:14.0 USB controller: Intel Corporation Cannon Lake PCH USB 3.1 xHCI\n\
+                      01:00.0 3D controller: NVIDIA Corporation TU117M [GeForce GTX 1650 Mobile]\n";
+        assert_eq!(
+            parse_lspci(output),
+            [
+                "Intel Corporation UHD Graphics 630 (rev 02)",
+                "NVIDIA Corporation TU117M [GeForce GTX 1650 Mobile]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drm_gpus() {
+        let dir = tempfile::tempdir().unwrap();
+        let device = dir.path().join("card0").join("device");
+        fs::create_dir_all(&device).unwrap();
+        fs::create_dir_all(dir.path().join("card0-HDMI-A-1")).unwrap();
+        fs::write(device.join("vendor"), "0x10de\n").unwrap();
+        fs::write(device.join("device"), "0x1b80\n").unwrap();
+
+        assert_eq!(drm_gpus(dir.path()), ["NVIDIA GPU [0x1b80]"]);
+        assert!(drm_gpus(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_to_json_omits_unknown_fields() {
+        let info = SystemInfo {
+            os: Some("Linux".to_string()),
+            cpu_model: Some("Test \"CPU\"".to_string()),
+            cpu_cores: Some(8),
+            gpus: vec!["GPU A".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            info.to_json(),
+            r#"{"os":"Linux","cpu_model":"Test \"CPU\"","cpu_cores":8,"gpus":["GPU A"],"interfaces":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_network_includes_loopback() {
+        let interfaces = network();
+        assert!(!interfaces.is_empty());
+        let loopback = interfaces
+            .iter()
+            .find(|nic| nic.loopback)
+            .expect("a loopback interface should be listed");
+        assert!(loopback.ipv4.iter().any(Ipv4Addr::is_loopback) || !loopback.ipv6.is_empty());
+        assert_eq!(loopback.mac, None);
+
+        let json = SystemInfo {
+            interfaces,
+            ..Default::default()
+        }
+        .to_json();
+        assert!(json.contains("\"loopback\":true"));
+    }
+
+    #[test]
+    fn test_sys_class_net_mac() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, address) in [
+            ("eth0", "52:54:00:AB:cd:01\n"),
+            ("lo", "00:00:00:00:00:00\n"),
+        ] {
+            fs::create_dir_all(dir.path().join(name)).unwrap();
+            fs::write(dir.path().join(name).join("address"), address).unwrap();
+        }
+        assert_eq!(
+            sys_class_net_mac(dir.path(), "eth0").as_deref(),
+            Some("52:54:00:ab:cd:01")
+        );
+        assert_eq!(sys_class_net_mac(dir.path(), "lo"), None);
+        assert_eq!(sys_class_net_mac(dir.path(), "missing"), None);
+    }
 }
@@ -1,7 +1,8 @@
 use bytes::Bytes;
 use futures::stream::{self, Stream, StreamExt};
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::time::Duration;
 use tokio::fs::File as TokioFile;
 use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 
@@ -10,27 +11,9 @@ pub fn tail_sync<S: AsRef<Path>>(files: Vec<S>, lines: usize) -> io::Result<Stri
     let mut result = String::new();
 
     for file_path in files {
-        let file = std::fs::File::open(&file_path)?;
-        let reader = std::io::BufReader::new(file);
-        let all_lines: Vec<String> = reader
-            .lines()
-            .map(|line| {
-                let mut line = line?;
-                // Normalize Windows-style line endings (\r\n) to Unix-style (\n)
-                if line.ends_with('\r') {
-                    line.pop(); // Remove '\r'
-                }
-                Ok(line)
-            })
-            .collect::<io::Result<Vec<String>>>()?;
-
-        let start = if all_lines.len() > lines {
-            all_lines.len() - lines
-        } else {
-            0
-        };
-
-        for line in &all_lines[start..] {
+        let data = tail_file(file_path.as_ref(), lines, b'\n')?;
+        // Normalize Windows-style line endings (\r\n) to Unix-style (\n)
+        for line in String::from_utf8_lossy(&data).lines() {
             result.push_str(line);
             result.push('\n');
         }
@@ -39,6 +22,404 @@ pub fn tail_sync<S: AsRef<Path>>(files: Vec<S>, lines: usize) -> io::Result<Stri
     Ok(result)
 }
 
+/// Like `tail_sync`, but with `zero_terminated` records are NUL-delimited on input and output
+pub fn tail_sync_with<S: AsRef<Path>>(
+    files: Vec<S>,
+    lines: usize,
+    zero_terminated: bool,
+) -> io::Result<String> {
+    if !zero_terminated {
+        return tail_sync(files, lines);
+    }
+
+    let mut result = String::new();
+
+    for file_path in files {
+        let data = tail_file(file_path.as_ref(), lines, b'\0')?;
+        if data.is_empty() {
+            continue;
+        }
+        let body = data.strip_suffix(b"\0").unwrap_or(&data);
+        for record in body.split(|&b| b == b'\0') {
+            result.push_str(&String::from_utf8_lossy(record));
+            result.push('\0');
+        }
+    }
+
+    Ok(result)
+}
+
+/// The last `lines` records of `data`, split on the byte `delimiter`, left untouched
+pub fn last_records(data: &[u8], lines: usize, delimiter: u8) -> &[u8] {
+    if lines == 0 {
+        return &[];
+    }
+
+    // A trailing delimiter ends the last record rather than starting a new one
+    let body = data.strip_suffix(&[delimiter]).unwrap_or(data);
+    let mut start = body.len();
+    for _ in 0..lines {
+        match body[..start].iter().rposition(|&b| b == delimiter) {
+            Some(pos) => start = pos,
+            None => return data,
+        }
+    }
+    &data[start + 1..]
+}
+
+/// How much `last_records_seek` reads at a time while scanning backwards
+const SEEK_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// The last `lines` records of a seekable input, found by reading chunks backwards
+/// from the end, so a huge log costs no more than its tail plus one chunk
+pub fn last_records_seek<R: Read + Seek>(
+    reader: &mut R,
+    lines: usize,
+    delimiter: u8,
+) -> io::Result<Vec<u8>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let end = reader.seek(SeekFrom::End(0))?;
+    let mut start = end;
+    let mut found = 0;
+    // Chunks from the end backwards; joined in reverse once enough records are seen
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+
+    while start > 0 {
+        let size = SEEK_CHUNK_SIZE.min(start);
+        start -= size;
+        reader.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0; size as usize];
+        reader.read_exact(&mut chunk)?;
+
+        for i in (0..chunk.len()).rev() {
+            // A trailing delimiter ends the last record rather than starting a new one
+            if chunk[i] != delimiter || start + i as u64 == end - 1 {
+                continue;
+            }
+            found += 1;
+            if found == lines {
+                chunk.drain(..=i);
+                chunks.push(chunk);
+                return Ok(chunks.into_iter().rev().flatten().collect());
+            }
+        }
+        chunks.push(chunk);
+    }
+
+    // Fewer records than asked for: the whole input
+    Ok(chunks.into_iter().rev().flatten().collect())
+}
+
+/// The last `lines` records of an input that can only be read once, such as a pipe,
+/// holding no more than those records at a time
+pub fn last_records_stream<R: Read>(reader: R, lines: usize, delimiter: u8) -> io::Result<Vec<u8>> {
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut reader = io::BufReader::new(reader);
+    let mut records = std::collections::VecDeque::new();
+    loop {
+        let mut record = Vec::new();
+        if reader.read_until(delimiter, &mut record)? == 0 {
+            break;
+        }
+        if records.len() == lines {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+    Ok(records.into_iter().flatten().collect())
+}
+
+/// The last `lines` records of the file at `path`, or of standard input for `-`
+fn tail_file(path: &Path, lines: usize, delimiter: u8) -> io::Result<Vec<u8>> {
+    if path == Path::new("-") {
+        return last_records_stream(io::stdin().lock(), lines, delimiter);
+    }
+
+    let mut file = std::fs::File::open(path)?;
+    let metadata = file.metadata()?;
+    // Pipes can't seek, and files in /proc report no size until read
+    if metadata.is_file() && metadata.len() > 0 {
+        last_records_seek(&mut file, lines, delimiter)
+    } else {
+        last_records_stream(file, lines, delimiter)
+    }
+}
+
+/// Tail each file as raw bytes, splitting on `\n` (or NUL with `zero_terminated`)
+pub fn tail_bytes<S: AsRef<Path>>(
+    files: &[S],
+    lines: usize,
+    zero_terminated: bool,
+) -> io::Result<Vec<u8>> {
+    let delimiter = if zero_terminated { b'\0' } else { b'\n' };
+    let mut result = Vec::new();
+
+    for file_path in files {
+        result.extend(tail_file(file_path.as_ref(), lines, delimiter)?);
+    }
+
+    Ok(result)
+}
+
+fn header(name: &str, first: bool) -> String {
+    format!("{}==> {} <==\n", if first { "" } else { "\n" }, name)
+}
+
+/// Tail each file, preceded by a `==> file <==` header with `show_headers`
+pub fn tail_with_headers(
+    files: &[String],
+    lines: usize,
+    zero_terminated: bool,
+    show_headers: bool,
+) -> io::Result<Vec<u8>> {
+    if !show_headers {
+        return tail_bytes(files, lines, zero_terminated);
+    }
+
+    let mut result = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        result.extend_from_slice(header(file, i == 0).as_bytes());
+        result.extend(tail_bytes(&[file], lines, zero_terminated)?);
+    }
+    Ok(result)
+}
+
+/// What tells a rotated file apart from the one that was being read
+#[cfg(unix)]
+type FileId = (u64, u64);
+#[cfg(windows)]
+type FileId = (u32, u64);
+#[cfg(not(any(unix, windows)))]
+type FileId = std::time::SystemTime;
+
+#[cfg(unix)]
+fn file_id(_name: &str, metadata: &std::fs::Metadata) -> io::Result<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+/// Volume serial number and file index; creation times can't be used since
+/// NTFS tunneling hands a recreated name its predecessor's
+#[cfg(windows)]
+fn file_id(name: &str, _metadata: &std::fs::Metadata) -> io::Result<FileId> {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::fileapi::{BY_HANDLE_FILE_INFORMATION, GetFileInformationByHandle};
+
+    let file = std::fs::File::open(name)?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Ok((info.dwVolumeSerialNumber, index))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_id(_name: &str, metadata: &std::fs::Metadata) -> io::Result<FileId> {
+    metadata.created()
+}
+
+/// A followed file; `id` is `None` while it doesn't exist
+struct Followed {
+    name: String,
+    offset: u64,
+    id: Option<FileId>,
+}
+
+/// Tracks read offsets for `tail -f` across one or more files
+pub struct Follower {
+    files: Vec<Followed>,
+    active: Option<usize>,
+    show_headers: bool,
+    retry: bool,
+    by_name: bool,
+}
+
+impl Follower {
+    /// Start following from the current end of each file
+    pub fn new(files: &[String]) -> io::Result<Self> {
+        Self::open(files, false, false)
+    }
+
+    /// Like `new`; with `retry` missing files are waited for instead of an error,
+    /// and with `by_name` a file replaced under the same name is read from its start
+    pub fn open(files: &[String], retry: bool, by_name: bool) -> io::Result<Self> {
+        let files = files
+            .iter()
+            .map(|file| match std::fs::metadata(file) {
+                Ok(metadata) => Ok(Followed {
+                    name: file.clone(),
+                    offset: metadata.len(),
+                    id: Some(file_id(file, &metadata)?),
+                }),
+                Err(e) if retry && e.kind() == io::ErrorKind::NotFound => Ok(Followed {
+                    name: file.clone(),
+                    offset: 0,
+                    id: None,
+                }),
+                Err(e) => Err(e),
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Follower {
+            // The initial tail output ends with the last file's lines
+            active: files.len().checked_sub(1),
+            show_headers: files.len() > 1,
+            files,
+            retry,
+            by_name,
+        })
+    }
+
+    /// Read whatever was appended since the last poll, with a header
+    /// whenever the output switches to a different file
+    pub fn poll(&mut self) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+
+        for i in 0..self.files.len() {
+            let Followed { name, offset, id } = &mut self.files[i];
+            let metadata = match std::fs::metadata(&*name) {
+                Ok(metadata) => metadata,
+                // Mid-rotation the name may briefly point at nothing
+                Err(e) if (self.retry || self.by_name) && e.kind() == io::ErrorKind::NotFound => {
+                    if id.take().is_some() {
+                        eprintln!("tail: '{}' has become inaccessible", name);
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            // Only a missing file counts as gone, and the metadata check above covers that;
+            // anything else, like a sharing violation on a locked log, is retried next poll
+            let Ok(current) = file_id(name, &metadata) else {
+                continue;
+            };
+            let current = Some(current);
+            if id.is_none() {
+                eprintln!("tail: '{}' has appeared;  following new file", name);
+                *offset = 0;
+            } else if self.by_name && current != *id {
+                eprintln!("tail: '{}' has been replaced;  following new file", name);
+                *offset = 0;
+            }
+            *id = current;
+
+            let len = metadata.len();
+            if len < *offset {
+                eprintln!("tail: {}: file truncated", name);
+                *offset = 0;
+            }
+            if len == *offset {
+                continue;
+            }
+
+            let Ok(mut file) = std::fs::File::open(&*name) else {
+                continue;
+            };
+            file.seek(SeekFrom::Start(*offset))?;
+            let mut appended = Vec::new();
+            file.read_to_end(&mut appended)?;
+            *offset += appended.len() as u64;
+
+            if self.show_headers && self.active != Some(i) {
+                output.extend_from_slice(header(name, false).as_bytes());
+            }
+            self.active = Some(i);
+            output.extend(appended);
+        }
+
+        Ok(output)
+    }
+}
+
+/// Print appended data from every file until the process is interrupted
+pub fn follow(mut follower: Follower) -> io::Result<()> {
+    loop {
+        let output = follower.poll()?;
+        if !output.is_empty() {
+            let mut stdout = io::stdout();
+            stdout.write_all(&output)?;
+            stdout.flush()?;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Entry point for the `tail` shell command
+pub fn run(args: &[String]) {
+    let parsed = match crate::head::parse_line_args(args) {
+        Ok(parsed) if !parsed.files.is_empty() => parsed,
+        Ok(_) => {
+            eprintln!(
+                "Usage: tail [-n NUM] [-q | -v] [-z] [-f | -F | --follow=name] [--retry] <file>..."
+            );
+            return;
+        }
+        Err(e) => {
+            eprintln!("tail: {}", e);
+            return;
+        }
+    };
+    if parsed.bytes.is_some() {
+        eprintln!("tail: counting bytes with -c is not supported");
+        return;
+    }
+
+    let retry = parsed.retry && parsed.follow;
+    if parsed.retry && !parsed.follow {
+        eprintln!("tail: warning: --retry ignored; --retry is useful mainly when following");
+    }
+
+    // Files that don't exist yet get no initial output when they will be waited for
+    let existing: Vec<String> = parsed
+        .files
+        .iter()
+        .filter(|file| {
+            let exists = Path::new(file).exists();
+            if retry && !exists {
+                eprintln!(
+                    "tail: cannot open '{}' for reading: No such file or directory",
+                    file
+                );
+            }
+            !retry || exists
+        })
+        .cloned()
+        .collect();
+
+    match tail_with_headers(
+        &existing,
+        parsed.lines,
+        parsed.zero_terminated,
+        parsed.show_headers(),
+    ) {
+        Ok(output) => {
+            let mut stdout = io::stdout();
+            if let Err(e) = stdout.write_all(&output).and_then(|_| stdout.flush()) {
+                eprintln!("tail: {}", e);
+                return;
+            }
+        }
+        Err(e) => {
+            eprintln!("tail: {}", e);
+            return;
+        }
+    }
+
+    if parsed.follow
+        && let Err(e) = Follower::open(&parsed.files, retry, parsed.follow_name).and_then(follow)
+    {
+        eprintln!("tail: {}", e);
+    }
+}
+
 // Async version that returns a Stream<Bytes>
 pub async fn tail_async<S: AsRef<Path> + Send + 'static>(
     files: Vec<S>,
@@ -148,4 +529,200 @@ mod tests {
 
         tokio::fs::remove_file(file_path).await.unwrap();
     }
+
+    #[test]
+    fn test_tail_zero_terminated() {
+        let file_path = "test_tail_zero.txt";
+        std::fs::write(file_path, "a\0b\nc\0d\0").unwrap();
+
+        let result = tail_sync_with(vec![file_path], 2, true).unwrap();
+        assert_eq!(result, "b\nc\0d\0");
+
+        std::fs::remove_file(file_path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_multiple_files_with_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.log").to_string_lossy().to_string();
+        let b = dir.path().join("b.log").to_string_lossy().to_string();
+        std::fs::write(&a, "a0\n").unwrap();
+        std::fs::write(&b, "b0\n").unwrap();
+
+        let files = vec![a.clone(), b.clone()];
+        assert_eq!(
+            tail_with_headers(&files, 10, false, true).unwrap(),
+            format!("==> {} <==\na0\n\n==> {} <==\nb0\n", a, b).into_bytes()
+        );
+
+        let append = |path: &str, text: &str| {
+            let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+
+        let mut follower = Follower::new(&files).unwrap();
+        assert_eq!(follower.poll().unwrap(), b"");
+
+        // b was printed last, so more b output needs no header
+        append(&b, "b1\n");
+        assert_eq!(follower.poll().unwrap(), b"b1\n");
+
+        append(&a, "a1\n");
+        assert_eq!(
+            follower.poll().unwrap(),
+            format!("\n==> {} <==\na1\n", a).into_bytes()
+        );
+
+        append(&a, "a2\n");
+        assert_eq!(follower.poll().unwrap(), b"a2\n");
+
+        append(&b, "b2\n");
+        append(&a, "a3\n");
+        assert_eq!(
+            follower.poll().unwrap(),
+            format!("a3\n\n==> {} <==\nb2\n", b).into_bytes()
+        );
+    }
+
+    #[test]
+    fn test_retry_waits_for_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("late.log");
+        let file = path.to_string_lossy().to_string();
+
+        let args: Vec<String> = ["-F", &file].iter().map(|s| s.to_string()).collect();
+        let parsed = crate::head::parse_line_args(&args).unwrap();
+        assert!(parsed.follow && parsed.follow_name && parsed.retry);
+
+        assert!(Follower::new(std::slice::from_ref(&file)).is_err());
+        let mut follower = Follower::open(std::slice::from_ref(&file), true, true).unwrap();
+        assert_eq!(follower.poll().unwrap(), b"");
+
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            std::fs::write(&path, "first\nsecond\n").unwrap();
+        });
+        let mut output = Vec::new();
+        for _ in 0..50 {
+            output.extend(follower.poll().unwrap());
+            if !output.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        writer.join().unwrap();
+        assert_eq!(output, b"first\nsecond\n");
+
+        // A rotated log is read from its start, even when it is already longer
+        std::fs::rename(&file, dir.path().join("late.log.1")).unwrap();
+        std::fs::write(&file, "rotated log line\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), b"rotated log line\n");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_locked_file_is_not_reread() {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("locked.log").to_string_lossy().to_string();
+        std::fs::write(&file, "old line\n").unwrap();
+        let mut follower = Follower::open(std::slice::from_ref(&file), true, true).unwrap();
+
+        // No sharing at all, so opening it again fails with a sharing violation
+        let lock = std::fs::OpenOptions::new()
+            .append(true)
+            .share_mode(0)
+            .open(&file)
+            .unwrap();
+        assert_eq!(follower.poll().unwrap(), b"");
+        drop(lock);
+
+        let mut log = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&file)
+            .unwrap();
+        log.write_all(b"new line\n").unwrap();
+        assert_eq!(follower.poll().unwrap(), b"new line\n");
+    }
+
+    #[test]
+    fn test_tail_large_file_reads_backwards() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.log");
+        // Several chunks of lines, with the interesting ones straddling a chunk boundary
+        let mut content = Vec::new();
+        for i in 0..200_000 {
+            writeln!(content, "line {:06}", i).unwrap();
+        }
+        std::fs::write(&path, &content).unwrap();
+
+        let tail = tail_bytes(&[&path], 3, false).unwrap();
+        assert_eq!(tail, b"line 199997\nline 199998\nline 199999\n");
+        assert_eq!(
+            tail_bytes(&[&path], 10_000, false).unwrap(),
+            last_records(&content, 10_000, b'\n')
+        );
+        assert_eq!(tail_bytes(&[&path], 300_000, false).unwrap(), content);
+
+        // Same answers as scanning everything, with and without a final newline
+        for data in [&b"a\nb\n\nc"[..], b"a\nb\n", b"\n\n", b"single"] {
+            for lines in 0..5 {
+                let mut cursor = io::Cursor::new(data);
+                assert_eq!(
+                    last_records_seek(&mut cursor, lines, b'\n').unwrap(),
+                    last_records(data, lines, b'\n'),
+                    "{:?} -n {}",
+                    String::from_utf8_lossy(data),
+                    lines
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_tail_pipe_falls_back_to_streaming() {
+        let (reader, mut writer) = io::pipe().unwrap();
+        let producer = std::thread::spawn(move || {
+            for i in 0..100_000 {
+                writeln!(writer, "piped {}", i).unwrap();
+            }
+        });
+
+        let tail = last_records_stream(reader, 2, b'\n').unwrap();
+        producer.join().unwrap();
+        assert_eq!(tail, b"piped 99998\npiped 99999\n");
+
+        assert_eq!(
+            last_records_stream(&b"x\0y\0z"[..], 2, b'\0').unwrap(),
+            b"y\0z"
+        );
+        assert!(
+            last_records_stream(&b"x\ny\n"[..], 0, b'\n')
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_tail_invalid_utf8() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mixed.log");
+        std::fs::write(
+            &path,
+            b"first\n\xff\xfe latin1 \xe9t\xe9\r\nlast \xc3\x28\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            tail_bytes(&[&path], 2, false).unwrap(),
+            b"\xff\xfe latin1 \xe9t\xe9\r\nlast \xc3\x28\n"
+        );
+        assert_eq!(tail_bytes(&[&path], 1, false).unwrap(), b"last \xc3\x28\n");
+        assert_eq!(
+            tail_bytes(&[&path], 10, false).unwrap(),
+            std::fs::read(&path).unwrap()
+        );
+        assert!(tail_bytes(&[&path], 0, false).unwrap().is_empty());
+    }
 }
@@ -1,13 +1,33 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::path::Path;
 
 #[cfg(unix)]
 use filetime::{FileTime, set_file_times};
 
+/// Entry point for the `touch` shell command; `-p`/`--parents` creates missing
+/// parent directories first
 pub fn run(args: &[String]) {
-    for file_name in args {
+    let parents = args.iter().any(|arg| arg == "-p" || arg == "--parents");
+    let files = args
+        .iter()
+        .filter(|arg| *arg != "-p" && *arg != "--parents");
+
+    for file_name in files {
         let path = Path::new(file_name);
 
+        if parents
+            && let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+            && let Err(e) = fs::create_dir_all(parent)
+        {
+            eprintln!(
+                "touch: cannot create directory '{}': {}",
+                parent.display(),
+                e
+            );
+            continue;
+        }
+
         // If the file doesn't exist, create it
         if !path.exists() {
             match File::create(&path) {
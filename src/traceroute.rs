@@ -1,44 +1,286 @@
+use serde::{Deserialize, Serialize};
 use std::env;
-use std::net::{SocketAddr, ToSocketAddrs, UdpSocket, IpAddr, Ipv4Addr};
-use std::time::{Duration, Instant};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
 use std::process::Command;
+use std::time::{Duration, Instant};
 
 #[cfg(not(target_os = "windows"))]
-use socket2::{Socket, Domain, Type, Protocol, SockAddr};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 #[cfg(not(target_os = "windows"))]
 use std::mem::MaybeUninit;
 
 pub fn print_usage(prog: &str) {
-    eprintln!("Usage: {} <host> [max_hops] [probes_per_hop] [timeout_ms] [start_port]", prog);
+    eprintln!(
+        "Usage: {} [-n] [-A] [--json] <host> [max_hops] [probes_per_hop] [timeout_ms] [start_port]",
+        prog
+    );
+    eprintln!("  -n      do not resolve hop addresses to hostnames");
+    eprintln!("  -A      look up the origin AS number of each hop");
+    eprintln!("  --json  print the whole run as one JSON object");
     eprintln!("Example: {} google.com 30 3 2000 33434", prog);
 }
 
+/// How each hop address is annotated, and whether to report in JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceOptions {
+    pub resolve_names: bool,
+    pub lookup_asn: bool,
+    pub json: bool,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            resolve_names: true,
+            lookup_asn: false,
+            json: false,
+        }
+    }
+}
+
+/// One probe: who answered and after how long; `None` for no reply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub address: Option<IpAddr>,
+    pub rtt_ms: Option<f64>,
+}
+
+/// Every probe sent with one TTL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HopResult {
+    pub ttl: u32,
+    pub probes: Vec<ProbeResult>,
+    /// Reverse DNS name of the answering address; `null` when unresolved or not looked up
+    pub hostname: Option<String>,
+    /// Origin AS of the answering address, looked up with `-A`
+    pub asn: Option<u32>,
+}
+
+impl HopResult {
+    /// The first address that answered, if any did
+    pub fn address(&self) -> Option<IpAddr> {
+        self.probes.iter().find_map(|probe| probe.address)
+    }
+
+    /// Look up the hostname and ASN of the answering address, as the options ask
+    pub fn annotate(&mut self, options: &TraceOptions) {
+        let Some(ip) = self.address() else {
+            return;
+        };
+        if options.resolve_names && self.hostname.is_none() {
+            self.hostname = reverse_dns(ip);
+        }
+        if options.lookup_asn {
+            self.asn = lookup_asn(ip);
+        }
+    }
+}
+
+/// A whole traceroute run, as printed by `--json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceReport {
+    pub destination: String,
+    pub resolved_ip: Option<IpAddr>,
+    pub hops: Vec<HopResult>,
+}
+
+impl TraceReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Parse the hop lines of Windows `tracert` output, e.g.
+/// `  2     9 ms    <1 ms     *     router.lan [10.0.0.1]`
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_tracert(output: &str) -> Vec<HopResult> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace().peekable();
+            let ttl = tokens.next()?.parse().ok()?;
+
+            let mut rtts = Vec::new();
+            while let Some(&token) = tokens.peek() {
+                if token == "*" {
+                    rtts.push(None);
+                    tokens.next();
+                } else if let Ok(ms) = token.trim_start_matches('<').parse::<f64>() {
+                    // `<1 ms` is only an upper bound; report it as 1
+                    rtts.push(Some(ms));
+                    tokens.next();
+                    tokens.next_if_eq(&"ms");
+                } else {
+                    break;
+                }
+            }
+            if rtts.is_empty() {
+                return None;
+            }
+
+            // What's left is "a.b.c.d", "name [a.b.c.d]" or "Request timed out."
+            let rest: Vec<&str> = tokens.collect();
+            let address = rest.last().and_then(|last| {
+                last.trim_matches(|c| c == '[' || c == ']')
+                    .parse::<IpAddr>()
+                    .ok()
+            });
+            let hostname = match rest[..] {
+                [name, last] if address.is_some() && last.starts_with('[') => {
+                    Some(name.to_string())
+                }
+                _ => None,
+            };
+            let probes = rtts
+                .into_iter()
+                .map(|rtt_ms| ProbeResult {
+                    address: rtt_ms.and(address),
+                    rtt_ms,
+                })
+                .collect();
+            Some(HopResult {
+                ttl,
+                probes,
+                hostname,
+                asn: None,
+            })
+        })
+        .collect()
+}
+
+/// Split `-n`/`-A`/`--json` flags from the positional arguments
+pub fn parse_options(args: &[String]) -> (TraceOptions, Vec<String>) {
+    let mut options = TraceOptions::default();
+    let mut positional = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-n" => options.resolve_names = false,
+            "-A" | "--as-path-lookups" => options.lookup_asn = true,
+            "--json" => options.json = true,
+            _ => positional.push(arg.clone()),
+        }
+    }
+    (options, positional)
+}
+
+/// Format a hop as `12.34.56.78 (host.example.com) [AS1234]`
+pub fn format_hop(ip: IpAddr, hostname: Option<&str>, asn: Option<u32>) -> String {
+    let mut line = ip.to_string();
+    if let Some(name) = hostname {
+        line.push_str(&format!(" ({})", name));
+    }
+    if let Some(asn) = asn {
+        line.push_str(&format!(" [AS{}]", asn));
+    }
+    line
+}
+
+/// Reverse DNS name for a hop, if it has one
+fn reverse_dns(ip: IpAddr) -> Option<String> {
+    dns_lookup::lookup_addr(&ip)
+        .ok()
+        .filter(|name| *name != ip.to_string())
+}
+
+/// Extract the AS number from a Team Cymru whois reply
+pub fn parse_cymru_whois(reply: &str) -> Option<u32> {
+    // "AS      | IP               | AS Name" followed by "15169   | 8.8.8.8 | GOOGLE, US"
+    reply
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("AS "))
+        .find_map(|line| line.split('|').next()?.trim().parse().ok())
+}
+
+/// Origin AS number of an address, via the Team Cymru whois service
+fn lookup_asn(ip: IpAddr) -> Option<u32> {
+    let addr = ("whois.cymru.com", 43).to_socket_addrs().ok()?.next()?;
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(3)).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(3))).ok()?;
+    stream.write_all(format!("{}\r\n", ip).as_bytes()).ok()?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).ok()?;
+    parse_cymru_whois(&reply)
+}
+
 fn resolve_host(host: &str) -> Option<IpAddr> {
     // prefer IPv4 for this traceroute
     match (host, 0).to_socket_addrs() {
-        Ok(mut iter) => iter.find_map(|s| match s.ip() { IpAddr::V4(v4) => Some(IpAddr::V4(v4)), _ => None }),
+        Ok(mut iter) => iter.find_map(|s| match s.ip() {
+            IpAddr::V4(v4) => Some(IpAddr::V4(v4)),
+            _ => None,
+        }),
         Err(_) => None,
     }
 }
 
 #[cfg(target_os = "windows")]
-pub fn windows_traceroute(host: &str, max_hops: u32, probes: u32, timeout_ms: u64) {
+pub fn windows_traceroute(
+    host: &str,
+    max_hops: u32,
+    probes: u32,
+    timeout_ms: u64,
+    options: &TraceOptions,
+) {
     // Use system tracert for Windows; build command with count and timeout approximations
     // tracert doesn't allow probes count directly, but this is a pragmatic fallback.
-    // We'll call tracert -d (no DNS) -h max_hops host
+    // tracert does its own reverse DNS unless given -d
     let mut cmd = Command::new("tracert");
-    cmd.arg("-d").arg("-h").arg(max_hops.to_string()).arg(host);
+    if !options.resolve_names {
+        cmd.arg("-d");
+    }
+    cmd.arg("-h").arg(max_hops.to_string()).arg(host);
 
     match cmd.output() {
         Ok(out) => {
-            println!("{}", String::from_utf8_lossy(&out.stdout));
+            let output = String::from_utf8_lossy(&out.stdout);
+            if options.json {
+                let mut report = TraceReport {
+                    destination: host.to_string(),
+                    resolved_ip: resolve_host(host),
+                    hops: parse_tracert(&output),
+                };
+                // tracert has already resolved the names it could
+                let asn_only = TraceOptions {
+                    resolve_names: false,
+                    ..*options
+                };
+                for hop in &mut report.hops {
+                    hop.annotate(&asn_only);
+                }
+                println!("{}", report.to_json());
+                return;
+            }
+            for line in output.lines() {
+                // Hop lines end with "a.b.c.d" or "name [a.b.c.d]"
+                let hop = line.split_whitespace().last().and_then(|last| {
+                    last.trim_matches(|c| c == '[' || c == ']')
+                        .parse::<Ipv4Addr>()
+                        .ok()
+                });
+                let asn = hop
+                    .filter(|_| options.lookup_asn)
+                    .and_then(|ip| lookup_asn(IpAddr::V4(ip)));
+                match asn {
+                    Some(asn) => println!("{} [AS{}]", line, asn),
+                    None => println!("{}", line),
+                }
+            }
         }
         Err(e) => eprintln!("Failed to run tracert: {}", e),
     }
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn run_traceroute_unix(host: &str, max_hops: u32, probes: u32, timeout_ms: u64, start_port: u16) -> std::io::Result<()> {
+pub fn run_traceroute_unix(
+    host: &str,
+    max_hops: u32,
+    probes: u32,
+    timeout_ms: u64,
+    start_port: u16,
+    options: &TraceOptions,
+) -> std::io::Result<()> {
     // Resolve host IPv4
     let ip = match resolve_host(host) {
         Some(IpAddr::V4(v4)) => v4,
@@ -52,7 +294,17 @@ pub fn run_traceroute_unix(host: &str, max_hops: u32, probes: u32, timeout_ms: u
         }
     };
 
-    println!("traceroute to {} ({}), {} hops max, {} probes per hop", host, ip, max_hops, probes);
+    if !options.json {
+        println!(
+            "traceroute to {} ({}), {} hops max, {} probes per hop",
+            host, ip, max_hops, probes
+        );
+    }
+    let mut report = TraceReport {
+        destination: host.to_string(),
+        resolved_ip: Some(IpAddr::V4(ip)),
+        hops: Vec::new(),
+    };
 
     // Raw socket to receive ICMP replies (needs root)
     let recv_sock = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4))?;
@@ -68,7 +320,6 @@ pub fn run_traceroute_unix(host: &str, max_hops: u32, probes: u32, timeout_ms: u
     for ttl in 1..=max_hops {
         // set TTL on UDP socket
         send_sock.set_ttl(ttl)?;
-        print!("{:2}  ", ttl);
         let mut hop_ips: Vec<Option<IpAddr>> = Vec::new();
         let mut rtts: Vec<Option<u128>> = Vec::new();
 
@@ -138,37 +389,145 @@ pub fn run_traceroute_unix(host: &str, max_hops: u32, probes: u32, timeout_ms: u
             }
         }
 
+        let mut hop = HopResult {
+            ttl,
+            probes: hop_ips
+                .iter()
+                .zip(&rtts)
+                .map(|(&address, &rtt)| ProbeResult {
+                    address,
+                    rtt_ms: rtt.filter(|_| address.is_some()).map(|ms| ms as f64),
+                })
+                .collect(),
+            hostname: None,
+            asn: None,
+        };
+        hop.annotate(options);
+
         // print results for this ttl
         // If any ip present, print first unique ip and times
-        let mut printed_addr: Option<IpAddr> = None;
-        for i in 0..(hop_ips.len()) {
-            if let Some(ipaddr) = hop_ips[i] {
-                if printed_addr.is_none() {
-                    printed_addr = Some(ipaddr);
-                    print!("{}  ", ipaddr);
-                }
-                if let Some(ms) = rtts[i] {
-                    print!("{:>4} ms  ", ms);
-                } else {
-                    print!("  *    ");
+        if !options.json {
+            print!("{:2}  ", ttl);
+            if let Some(ipaddr) = hop.address() {
+                print!("{}  ", format_hop(ipaddr, hop.hostname.as_deref(), hop.asn));
+            }
+            for probe in &hop.probes {
+                match probe.rtt_ms {
+                    Some(ms) => print!("{:>4} ms  ", ms),
+                    None => print!("  *    "),
                 }
-            } else {
-                print!("  *    ");
             }
+            println!();
         }
-        println!();
 
         // If any rtt corresponds to destination (ICMP type 3 code 3 port unreachable), we should stop.
-        // Simpler heuristic: if printed_addr is destination IP then stop
-        if let Some(a) = printed_addr {
-            if a == IpAddr::V4(ip) {
+        // Simpler heuristic: if the hop's address is the destination IP then stop
+        let reached = hop.address() == Some(IpAddr::V4(ip));
+        report.hops.push(hop);
+        if reached {
+            if !options.json {
                 println!("Reached destination.");
-                break;
             }
+            break;
         }
 
         dst_port = dst_port.wrapping_add(probes as u16); // advance ports
     }
 
+    if options.json {
+        println!("{}", report.to_json());
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_hop_annotations() {
+        let ip = IpAddr::V4(Ipv4Addr::new(12, 34, 56, 78));
+        assert_eq!(
+            format_hop(ip, Some("host.example.com"), Some(1234)),
+            "12.34.56.78 (host.example.com) [AS1234]"
+        );
+        assert_eq!(format_hop(ip, None, Some(1234)), "12.34.56.78 [AS1234]");
+        assert_eq!(format_hop(ip, None, None), "12.34.56.78");
+    }
+
+    #[test]
+    fn test_parse_cymru_whois() {
+        let reply = "AS      | IP               | AS Name\n\
+                     15169   | 8.8.8.8          | GOOGLE, US\n";
+        assert_eq!(parse_cymru_whois(reply), Some(15169));
+        assert_eq!(
+            parse_cymru_whois("AS | IP | AS Name\nNA | 10.0.0.1 | NA\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let args: Vec<String> = ["-n", "example.com", "-A", "--json", "20"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (options, positional) = parse_options(&args);
+        assert!(!options.resolve_names);
+        assert!(options.lookup_asn);
+        assert!(options.json);
+        assert_eq!(positional, ["example.com", "20"]);
+    }
+
+    #[test]
+    fn test_tracert_json_report() {
+        let output = "\
+Tracing route to dns.google [8.8.8.8]
+over a maximum of 30 hops:
+
+  1    <1 ms    <1 ms    <1 ms  192.168.1.1
+  2     9 ms     *       8 ms  10.0.0.1
+  3     *        *        *     Request timed out.
+  4    12 ms    11 ms    12 ms  dns.google [8.8.8.8]
+
+Trace complete.
+";
+        let report = TraceReport {
+            destination: "dns.google".to_string(),
+            resolved_ip: Some(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8))),
+            hops: parse_tracert(output),
+        };
+
+        let parsed: TraceReport = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(parsed, report);
+        assert_eq!(parsed.hops.len(), 4);
+        assert_eq!(
+            parsed.hops[1].probes,
+            vec![
+                ProbeResult {
+                    address: Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+                    rtt_ms: Some(9.0)
+                },
+                ProbeResult {
+                    address: None,
+                    rtt_ms: None
+                },
+                ProbeResult {
+                    address: Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+                    rtt_ms: Some(8.0)
+                },
+            ]
+        );
+        assert_eq!(parsed.hops[2].address(), None);
+        assert_eq!(parsed.hops[3].address(), parsed.resolved_ip);
+        assert_eq!(parsed.hops[3].hostname.as_deref(), Some("dns.google"));
+        assert_eq!(parsed.hops[1].hostname, None);
+
+        // Lookups that weren't made or found are still present, as null
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["hops"][1]["hostname"], serde_json::Value::Null);
+        assert_eq!(json["hops"][3]["hostname"], "dns.google");
+        assert!(json["hops"][3].as_object().unwrap().contains_key("asn"));
+        assert_eq!(json["hops"][3]["asn"], serde_json::Value::Null);
+    }
+}
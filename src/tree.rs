@@ -3,35 +3,229 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-/// Print the tree structure of a directory
-fn print_tree(path: &Path, prefix: &str, is_last: bool) {
-    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+/// Output controls for `render`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// `--filelimit N`: don't descend into directories with more than N entries
+    pub filelimit: Option<usize>,
+    /// `-f`: print each entry's full path instead of its name
+    pub full_path: bool,
+    /// `--noreport`: leave out the trailing directory and file count
+    pub noreport: bool,
+}
+
+/// Directories and files listed below the root
+#[derive(Debug, Default)]
+struct Counts {
+    dirs: usize,
+    files: usize,
+}
+
+/// Append the tree structure of a directory to `out`, not descending into
+/// directories with more than `filelimit` entries
+fn render_tree(
+    path: &Path,
+    prefix: &str,
+    is_last: bool,
+    options: &TreeOptions,
+    counts: &mut Counts,
+    out: &mut String,
+) {
+    let file_name = if options.full_path {
+        path.display().to_string()
+    } else {
+        path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+    let entries: Option<Vec<_>> = fs::read_dir(path)
+        .ok()
+        .map(|entries| entries.filter_map(|e| e.ok()).collect());
 
-    println!("{}{}{}", prefix, if is_last { "└── " } else { "├── " }, file_name);
+    let over_limit = match (&entries, options.filelimit) {
+        (Some(entries), Some(limit)) if entries.len() > limit => Some(entries.len()),
+        _ => None,
+    };
 
-    if let Ok(entries) = fs::read_dir(path) {
-        let entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+    out.push_str(&format!(
+        "{}{}{}",
+        prefix,
+        if is_last { "└── " } else { "├── " },
+        file_name
+    ));
+    if let Some(count) = over_limit {
+        out.push_str(&format!(
+            "  [{} entries exceeds filelimit, not opening dir]\n",
+            count
+        ));
+        return;
+    }
+    out.push('\n');
+
+    if let Some(entries) = entries {
         let count = entries.len();
 
         for (i, entry) in entries.into_iter().enumerate() {
+            let path = entry.path();
+            if path.is_dir() {
+                counts.dirs += 1;
+            } else {
+                counts.files += 1;
+            }
+
             let is_last_entry = i == count - 1;
             let new_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
-            print_tree(&entry.path(), &new_prefix, is_last_entry);
+            render_tree(&path, &new_prefix, is_last_entry, options, counts, out);
         }
     }
 }
 
+/// The `tree` output for `root`, ending with a `N directories, M files` report
+/// unless `noreport` is set
+pub fn render(root: &Path, options: &TreeOptions) -> String {
+    let mut out = format!("{}\n", root.display());
+    let mut counts = Counts::default();
+    render_tree(root, "", true, options, &mut counts, &mut out);
+
+    if !options.noreport {
+        out.push_str(&format!(
+            "\n{} director{}, {} file{}\n",
+            counts.dirs,
+            if counts.dirs == 1 { "y" } else { "ies" },
+            counts.files,
+            if counts.files == 1 { "" } else { "s" }
+        ));
+    }
+    out
+}
+
+/// A directory entry as listed by `list_dir`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// List a directory with subdirectories first, each group sorted by name
+pub fn list_dir(path: &Path) -> io::Result<Vec<TreeEntry>> {
+    let mut entries: Vec<TreeEntry> = fs::read_dir(path)?
+        .filter_map(|e| e.ok())
+        .map(|entry| TreeEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            is_dir: entry.path().is_dir(),
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
 /// Run the `tree` command
-/// `args` can contain optional directory path to start from
+/// `args` can contain optional directory path to start from, `--filelimit N`,
+/// `-f` and `--noreport`
 pub fn run(args: &[String]) -> io::Result<()> {
-    let root: PathBuf = if !args.is_empty() {
-        PathBuf::from(&args[0])
-    } else {
-        env::current_dir()?
+    let mut options = TreeOptions::default();
+    let mut root = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "-f" {
+            options.full_path = true;
+            continue;
+        } else if arg == "--noreport" {
+            options.noreport = true;
+            continue;
+        }
+
+        let value = if arg == "--filelimit" {
+            iter.next().map(String::as_str)
+        } else if let Some(value) = arg.strip_prefix("--filelimit=") {
+            Some(value)
+        } else {
+            root = Some(PathBuf::from(arg));
+            continue;
+        };
+
+        match value.map(|v| v.parse::<usize>()) {
+            Some(Ok(limit)) => options.filelimit = Some(limit),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "--filelimit requires a number of entries",
+                ));
+            }
+        }
+    }
+
+    let root = match root {
+        Some(root) => root,
+        None => env::current_dir()?,
     };
 
-    println!("{}", root.display());
-    print_tree(&root, "", true);
+    print!("{}", render(&root, &options));
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filelimit_skips_large_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = dir.path().join("node_modules");
+        fs::create_dir(&big).unwrap();
+        for i in 0..20 {
+            fs::write(big.join(format!("pkg{}.js", i)), "").unwrap();
+        }
+        fs::write(dir.path().join("index.js"), "").unwrap();
+
+        let limited = render(
+            dir.path(),
+            &TreeOptions {
+                filelimit: Some(10),
+                ..Default::default()
+            },
+        );
+        assert!(limited.contains("node_modules  [20 entries exceeds filelimit, not opening dir]"));
+        assert!(limited.contains("index.js"));
+        assert!(!limited.contains("pkg0.js"));
+
+        let full = render(dir.path(), &TreeOptions::default());
+        assert!(full.contains("pkg0.js"));
+        assert!(!full.contains("exceeds filelimit"));
+    }
+
+    #[test]
+    fn test_full_path_and_noreport() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src").join("bin");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("main.rs"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let plain = render(dir.path(), &TreeOptions::default());
+        assert!(plain.contains("└── main.rs\n"));
+        assert!(plain.ends_with("\n2 directories, 2 files\n"));
+
+        let full = render(
+            dir.path(),
+            &TreeOptions {
+                full_path: true,
+                noreport: true,
+                ..Default::default()
+            },
+        );
+        let expected = dir.path().join("src").join("bin").join("main.rs");
+        assert!(full.contains(&format!("└── {}\n", expected.display())));
+        assert!(full.contains(&format!("── {}\n", dir.path().join("README.md").display())));
+        assert!(!full.contains("directories"));
+        assert!(!full.contains("files"));
+    }
+}
@@ -1,5 +1,7 @@
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -11,89 +13,718 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{
         Block, BorderType, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row,
-        Table, Tabs, Wrap,
+        Table, TableState, Tabs, Wrap,
     },
 };
-use std::io;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 // Remove the conflicting mod declarations and use imports
 // Import functions from the root-level modules instead
-use crate::{env, nproc};
+use crate::df::{self, DiskStats};
+use crate::free::{self, MemoryStats};
+use crate::nproc::CpuInfo;
+use crate::ps::{self, ProcessEntry};
+use crate::sensors::{SensorReader, SensorReading};
+use crate::tree::{self, TreeEntry};
+use crate::{cat, env, nproc, process, uname};
+
+const TAB_TITLES: [&str; 9] = [
+    "System",
+    "Processes",
+    "Memory",
+    "Disks",
+    "Sensors",
+    "Files",
+    "Git",
+    "Dashboard",
+    "Environment",
+];
+const PROCESSES_TAB: usize = 1;
+const FILES_TAB: usize = 5;
+const DASHBOARD_TAB: usize = 7;
+const ENV_TAB: usize = 8;
+
+/// How often the process monitor and dashboard re-read the system, unless configured
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Bounds for the refresh interval, whether configured or changed with `+`/`-`
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const MAX_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// How much of a file the Files tab preview reads
+const PREVIEW_BYTES: u64 = 64 * 1024;
+/// Scrollback kept by the command output pane before the oldest lines are dropped
+pub const DEFAULT_OUTPUT_LINES: usize = 1000;
+
+/// Something a key can be bound to outside of text input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ToggleHelp,
+    CommandMode,
+    PreviousTab,
+    NextTab,
+    Refresh,
+    SelectPrevious,
+    SelectNext,
+    PagePrevious,
+    PageNext,
+    Filter,
+    ClearFilter,
+    Kill,
+    Descend,
+    GoUp,
+    Open,
+    Edit,
+    Add,
+    Delete,
+    RefreshFaster,
+    RefreshSlower,
+}
+
+impl Action {
+    /// Every action, in the order the help overlay lists them
+    pub const ALL: [Action; 21] = [
+        Action::SelectPrevious,
+        Action::SelectNext,
+        Action::PagePrevious,
+        Action::PageNext,
+        Action::Filter,
+        Action::ClearFilter,
+        Action::Kill,
+        Action::Descend,
+        Action::GoUp,
+        Action::Open,
+        Action::Edit,
+        Action::Add,
+        Action::Delete,
+        Action::Refresh,
+        Action::RefreshFaster,
+        Action::RefreshSlower,
+        Action::PreviousTab,
+        Action::NextTab,
+        Action::CommandMode,
+        Action::ToggleHelp,
+        Action::Quit,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ToggleHelp => "Toggle this help",
+            Action::CommandMode => "Open command mode",
+            Action::PreviousTab => "Previous tab",
+            Action::NextTab => "Next tab",
+            Action::Refresh => "Refresh",
+            Action::SelectPrevious => "Move up",
+            Action::SelectNext => "Move down",
+            Action::PagePrevious => "Page up",
+            Action::PageNext => "Page down",
+            Action::Filter => "Filter processes",
+            Action::ClearFilter => "Clear the filter",
+            Action::Kill => "Kill the selected process",
+            Action::Descend => "Enter directory / preview file",
+            Action::GoUp => "Parent directory",
+            Action::Open => "Open with the default program",
+            Action::Edit => "Edit the selected variable",
+            Action::Add => "Add a variable",
+            Action::Delete => "Delete the selected variable",
+            Action::RefreshFaster => "Refresh more often",
+            Action::RefreshSlower => "Refresh less often",
+        }
+    }
+
+    /// Whether the action does anything on `tab`
+    pub fn applies_to(self, tab: usize) -> bool {
+        match self {
+            Action::SelectPrevious
+            | Action::SelectNext
+            | Action::PagePrevious
+            | Action::PageNext => matches!(tab, PROCESSES_TAB | FILES_TAB | ENV_TAB),
+            Action::Filter | Action::ClearFilter | Action::Kill => tab == PROCESSES_TAB,
+            Action::Descend | Action::GoUp | Action::Open => tab == FILES_TAB,
+            Action::Edit | Action::Add | Action::Delete => tab == ENV_TAB,
+            _ => true,
+        }
+    }
+}
+
+/// Display name of a key, as shown in the help overlay and footer
+pub fn key_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+/// One line of the help overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub action: Action,
+    pub keys: String,
+    pub description: &'static str,
+}
+
+/// Key bindings used outside of text input. A key may be bound to several
+/// actions as long as they apply to different tabs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+
+        let bindings = vec![
+            (Up, Action::SelectPrevious),
+            (Down, Action::SelectNext),
+            (PageUp, Action::PagePrevious),
+            (PageDown, Action::PageNext),
+            (Char('/'), Action::Filter),
+            (Esc, Action::ClearFilter),
+            (Char('k'), Action::Kill),
+            (Char('K'), Action::Kill),
+            (Delete, Action::Kill),
+            (Enter, Action::Descend),
+            (Backspace, Action::GoUp),
+            (Char('o'), Action::Open),
+            (Char('O'), Action::Open),
+            (Enter, Action::Edit),
+            (Char('e'), Action::Edit),
+            (Char('a'), Action::Add),
+            (Char('d'), Action::Delete),
+            (Delete, Action::Delete),
+            (Char('r'), Action::Refresh),
+            (Char('R'), Action::Refresh),
+            (Char('+'), Action::RefreshFaster),
+            (Char('-'), Action::RefreshSlower),
+            (Left, Action::PreviousTab),
+            (Right, Action::NextTab),
+            (Tab, Action::NextTab),
+            (Char('c'), Action::CommandMode),
+            (Char('C'), Action::CommandMode),
+            (Char('?'), Action::ToggleHelp),
+            (Char('h'), Action::ToggleHelp),
+            (Char('H'), Action::ToggleHelp),
+            (Char('q'), Action::Quit),
+            (Char('Q'), Action::Quit),
+        ];
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    /// The action `code` triggers on `tab`, if any
+    pub fn action(&self, code: KeyCode, tab: usize) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(key, action)| *key == code && action.applies_to(tab))
+            .map(|(_, action)| *action)
+    }
+
+    /// Keys bound to `action`, in binding order
+    pub fn keys_for(&self, action: Action) -> Vec<KeyCode> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// Replace the keys bound to `action`. A key that already triggers another action
+    /// on a tab where `action` applies is refused, leaving the keymap unchanged.
+    pub fn remap(&mut self, action: Action, keys: &[KeyCode]) -> Result<(), String> {
+        let shares_tab = |other: Action| {
+            (0..TAB_TITLES.len()).any(|tab| action.applies_to(tab) && other.applies_to(tab))
+        };
+        if let Some((key, other)) = self
+            .bindings
+            .iter()
+            .find(|(key, bound)| *bound != action && keys.contains(key) && shares_tab(*bound))
+        {
+            return Err(format!(
+                "{} is already bound to \"{}\"",
+                key_label(*key),
+                other.description()
+            ));
+        }
+
+        self.bindings.retain(|(_, bound)| *bound != action);
+        self.bindings.extend(keys.iter().map(|key| (*key, action)));
+        Ok(())
+    }
+
+    /// `?` / `h`-style label for the keys bound to `action`
+    pub fn label(&self, action: Action) -> String {
+        self.keys_for(action)
+            .into_iter()
+            .map(key_label)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Every key bound to any of `actions`, joined compactly as in `↑/↓`
+    pub fn keys_label(&self, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .flat_map(|&action| self.keys_for(action))
+            .map(key_label)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Panel title hints such as `/: Filter | ↑/↓: Scroll`, one per group of actions;
+    /// groups with no bound keys are left out
+    pub fn hints(&self, groups: &[(&[Action], &str)]) -> String {
+        groups
+            .iter()
+            .filter_map(|(actions, text)| {
+                let keys = self.keys_label(actions);
+                (!keys.is_empty()).then(|| format!("{}: {}", keys, text))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// The bound actions that apply to `tab`, for the help overlay
+    pub fn help(&self, tab: usize) -> Vec<HelpEntry> {
+        Action::ALL
+            .iter()
+            .filter(|action| action.applies_to(tab))
+            .map(|&action| HelpEntry {
+                action,
+                keys: self.label(action),
+                description: action.description(),
+            })
+            .filter(|entry| !entry.keys.is_empty())
+            .collect()
+    }
+}
+
+/// Key hints in the title of the process filter line
+const PROCESS_HINTS: &[(&[Action], &str)] = &[
+    (&[Action::Filter], "Filter"),
+    (
+        &[
+            Action::SelectPrevious,
+            Action::SelectNext,
+            Action::PagePrevious,
+            Action::PageNext,
+        ],
+        "Scroll",
+    ),
+    (&[Action::Kill], "Kill"),
+    (&[Action::ClearFilter], "Clear"),
+];
+
+/// Key hints in the title of the file browser's directory line
+const FILE_HINTS: &[(&[Action], &str)] = &[
+    (&[Action::Descend], "open dir"),
+    (&[Action::GoUp], "up"),
+    (&[Action::Open], "open file"),
+];
+
+/// Key hints in the title of the environment editor's input line
+const ENV_HINTS: &[(&[Action], &str)] = &[
+    (&[Action::Edit], "Edit"),
+    (&[Action::Add], "Add"),
+    (&[Action::Delete], "Delete"),
+    (&[Action::SelectPrevious, Action::SelectNext], "Scroll"),
+];
+
+/// Colors for every panel, so a theme or `NO_COLOR` applies everywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Titles, table headers, key names in the footer and the selected tab
+    pub accent: Color,
+    pub text: Color,
+    /// Unselected tabs and secondary text
+    pub muted: Color,
+    /// Separators
+    pub dim: Color,
+    /// Key names in the help overlay, text being typed and commit hashes
+    pub key: Color,
+    /// Second accent, e.g. the current branch and the swap gauge
+    pub highlight: Color,
+    /// Healthy levels and a clean working tree
+    pub good: Color,
+    /// Levels getting high and modified files
+    pub warn: Color,
+    /// Critical levels and errors
+    pub bad: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            accent: Color::Cyan,
+            text: Color::White,
+            muted: Color::Gray,
+            dim: Color::DarkGray,
+            key: Color::Yellow,
+            highlight: Color::Magenta,
+            good: Color::Green,
+            warn: Color::Yellow,
+            bad: Color::Red,
+        }
+    }
+}
+
+impl Palette {
+    /// The terminal's own colors everywhere, for `NO_COLOR`
+    pub fn monochrome() -> Self {
+        Palette {
+            accent: Color::Reset,
+            text: Color::Reset,
+            muted: Color::Reset,
+            dim: Color::Reset,
+            key: Color::Reset,
+            highlight: Color::Reset,
+            good: Color::Reset,
+            warn: Color::Reset,
+            bad: Color::Reset,
+        }
+    }
+
+    /// `good`, `warn` or `bad` for a usage ratio such as a disk's
+    pub fn level(&self, ratio: f64) -> Color {
+        if ratio >= 0.9 {
+            self.bad
+        } else if ratio >= 0.75 {
+            self.warn
+        } else {
+            self.good
+        }
+    }
+}
+
+/// User settings for the TUI, read from `tui.toml` in the winix config directory:
+///
+/// ```toml
+/// refresh_ms = 2000      # process monitor and dashboard, 250..=60000
+///
+/// [theme]                # any ratatui color: "cyan", "light-blue", "#ff8800", "208"
+/// accent = "cyan"
+/// text = "white"
+/// muted = "gray"
+/// dim = "dark-gray"
+/// key = "yellow"
+/// highlight = "magenta"
+/// good = "green"
+/// warn = "yellow"
+/// bad = "red"
+///
+/// [panels]               # every tab is enabled unless set to false
+/// sensors = false
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuiConfig {
+    pub refresh_interval: Duration,
+    pub theme: Palette,
+    /// Indexed like `TAB_TITLES`
+    pub panels: [bool; TAB_TITLES.len()],
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        TuiConfig {
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            theme: Palette::default(),
+            panels: [true; TAB_TITLES.len()],
+        }
+    }
+}
+
+impl TuiConfig {
+    /// Where `load` looks for the config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("winix").join("tui.toml"))
+    }
+
+    /// Read the config file, or the defaults when there is none.
+    /// A non-empty `NO_COLOR` overrides the theme with the terminal's own colors.
+    pub fn load() -> Result<TuiConfig, String> {
+        let config = match Self::path() {
+            None => TuiConfig::default(),
+            Some(path) => match std::fs::read_to_string(&path) {
+                Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => TuiConfig::default(),
+                Err(e) => return Err(format!("{}: {}", path.display(), e)),
+            },
+        };
+        Ok(config.with_no_color(std::env::var_os("NO_COLOR").as_deref()))
+    }
+
+    /// Switch to `Palette::monochrome` when `no_color`, the value of `NO_COLOR`, is non-empty
+    fn with_no_color(mut self, no_color: Option<&std::ffi::OsStr>) -> Self {
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            self.theme = Palette::monochrome();
+        }
+        self
+    }
+
+    /// Parse the TOML subset shown above: `[section]` headers, `key = value` lines and
+    /// `#` comments, with strings in double quotes and no escapes. Anything unset keeps
+    /// its default.
+    pub fn parse(text: &str) -> Result<TuiConfig, String> {
+        let mut config = TuiConfig::default();
+        let mut section = String::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let err = |message: String| format!("line {}: {}", number + 1, message);
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.trim().to_string();
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| err(format!("expected `key = value`, found `{}`", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+            let string = || {
+                value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .ok_or_else(|| err(format!("`{}` must be a quoted string", key)))
+            };
+            let boolean = || {
+                value
+                    .parse::<bool>()
+                    .map_err(|_| err(format!("`{}` must be true or false", key)))
+            };
+
+            match (section.as_str(), key) {
+                ("", "refresh_ms") => {
+                    let ms: u64 = value
+                        .parse()
+                        .map_err(|_| err(format!("invalid refresh_ms `{}`", value)))?;
+                    config.refresh_interval =
+                        Duration::from_millis(ms).clamp(MIN_REFRESH_INTERVAL, MAX_REFRESH_INTERVAL);
+                }
+                ("theme", _) => {
+                    let color = string()?
+                        .parse::<Color>()
+                        .map_err(|_| err(format!("unknown color `{}`", value)))?;
+                    let slot = match key {
+                        "accent" => &mut config.theme.accent,
+                        "text" => &mut config.theme.text,
+                        "muted" => &mut config.theme.muted,
+                        "dim" => &mut config.theme.dim,
+                        "key" => &mut config.theme.key,
+                        "highlight" => &mut config.theme.highlight,
+                        "good" => &mut config.theme.good,
+                        "warn" => &mut config.theme.warn,
+                        "bad" => &mut config.theme.bad,
+                        _ => return Err(err(format!("unknown theme color `{}`", key))),
+                    };
+                    *slot = color;
+                }
+                ("panels", _) => {
+                    let tab = TAB_TITLES
+                        .iter()
+                        .position(|title| title.eq_ignore_ascii_case(key))
+                        .ok_or_else(|| err(format!("unknown panel `{}`", key)))?;
+                    config.panels[tab] = boolean()?;
+                }
+                _ => return Err(err(format!("unknown setting `{}`", key))),
+            }
+        }
+
+        if !config.panels.contains(&true) {
+            return Err("at least one panel must be enabled".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Halve the refresh interval, down to the minimum
+    pub fn refresh_faster(&mut self) {
+        self.refresh_interval = (self.refresh_interval / 2).max(MIN_REFRESH_INTERVAL);
+    }
+
+    /// Double the refresh interval, up to the maximum
+    pub fn refresh_slower(&mut self) {
+        self.refresh_interval = (self.refresh_interval * 2).min(MAX_REFRESH_INTERVAL);
+    }
+}
+
+/// `line` up to its `#` comment; a `#` inside a quoted string, like a hex color, is kept
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
 
 #[derive(Debug)]
 pub struct App {
     pub selected_tab: usize,
     pub should_quit: bool,
-    #[allow(dead_code)]
-    pub process_list_state: ListState,
+    pub process_view: ProcessView,
+    pub file_browser: FileBrowser,
+    pub dashboard: Option<DashboardModel>,
+    /// Created with the first dashboard refresh and reused by the ones after
+    pub sensor_reader: Option<SensorReader>,
+    pub env_editor: EnvEditor,
     pub last_update: Instant,
     pub show_help: bool,
+    pub keymap: Keymap,
     pub current_dir: String,
     pub ls_items: Vec<String>,
-    pub ls_state: ListState,
     pub command_input: String,
-    pub command_output: Vec<String>,
+    pub command_output: OutputPane,
     pub show_command_mode: bool,
+    pub config: TuiConfig,
 }
 
 impl Default for App {
     fn default() -> App {
+        App::with_config(TuiConfig::default())
+    }
+}
+
+impl App {
+    pub fn with_config(config: TuiConfig) -> App {
         let mut app = App {
-            selected_tab: 0,
+            selected_tab: config.panels.iter().position(|&on| on).unwrap_or(0),
             should_quit: false,
-            process_list_state: ListState::default(),
+            process_view: ProcessView::default(),
+            file_browser: FileBrowser::new(std::env::current_dir().unwrap_or_else(|_| ".".into())),
+            dashboard: None,
+            sensor_reader: None,
+            env_editor: EnvEditor::default(),
             last_update: Instant::now(),
             show_help: false,
+            keymap: Keymap::default(),
             current_dir: std::env::current_dir()
                 .unwrap_or_else(|_| "?".into())
                 .display()
                 .to_string(),
             ls_items: Vec::new(),
-            ls_state: ListState::default(),
             command_input: String::new(),
-            command_output: Vec::new(),
+            command_output: OutputPane::new(DEFAULT_OUTPUT_LINES),
             show_command_mode: false,
+            config,
         };
         app.refresh_ls();
         app
     }
-}
 
-impl App {
+    pub fn refresh_dashboard(&mut self) {
+        let sensors = self.sensor_reader.get_or_insert_with(SensorReader::new);
+        self.dashboard = Some(DashboardModel::collect_with(sensors));
+    }
+
     pub fn refresh_ls(&mut self) {
         self.ls_items.clear();
         if let Ok(entries) = std::fs::read_dir(&self.current_dir) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let name = entry.file_name().to_string_lossy().to_string();
-                    if entry.file_type().is_ok() && entry.file_type().unwrap().is_dir() {
-                        self.ls_items.push(format!("📁 {}", name));
-                    } else {
-                        self.ls_items.push(format!("📄 {}", name));
-                    }
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.file_type().is_ok() && entry.file_type().unwrap().is_dir() {
+                    self.ls_items.push(format!("📁 {}", name));
+                } else {
+                    self.ls_items.push(format!("📄 {}", name));
                 }
             }
         }
         self.ls_items.sort();
     }
 
+    /// Move `step` enabled tabs to the right (or left when negative), wrapping around
+    fn step_tab(&mut self, step: isize) {
+        let count = TAB_TITLES.len() as isize;
+        let mut tab = self.selected_tab as isize;
+        for _ in 0..count {
+            tab = (tab + step).rem_euclid(count);
+            if self.config.panels[tab as usize] {
+                self.selected_tab = tab as usize;
+                return;
+            }
+        }
+    }
+
     pub fn next_tab(&mut self) {
-        self.selected_tab = (self.selected_tab + 1) % 7;
+        self.step_tab(1);
     }
 
     pub fn previous_tab(&mut self) {
-        if self.selected_tab > 0 {
-            self.selected_tab -= 1;
-        } else {
-            self.selected_tab = 6;
-        }
+        self.step_tab(-1);
     }
 
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
 
+    /// Carry out a key-bound action on the current tab
+    pub fn perform(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::ToggleHelp => self.toggle_help(),
+            Action::CommandMode => self.toggle_command_mode(),
+            Action::PreviousTab => self.previous_tab(),
+            Action::NextTab => self.next_tab(),
+            Action::Refresh => {
+                self.last_update = Instant::now();
+                match self.selected_tab {
+                    PROCESSES_TAB => self.process_view.refresh(),
+                    DASHBOARD_TAB => self.refresh_dashboard(),
+                    ENV_TAB => self.env_editor.reload(),
+                    FILES_TAB => self.file_browser.refresh(),
+                    _ => {}
+                }
+            }
+            Action::SelectPrevious
+            | Action::SelectNext
+            | Action::PagePrevious
+            | Action::PageNext => {
+                let step = match action {
+                    Action::PagePrevious | Action::PageNext => 10,
+                    _ => 1,
+                };
+                let up = matches!(action, Action::SelectPrevious | Action::PagePrevious);
+                match (self.selected_tab, up) {
+                    (PROCESSES_TAB, true) => self.process_view.select_previous(step),
+                    (PROCESSES_TAB, false) => self.process_view.select_next(step),
+                    (FILES_TAB, true) => self.file_browser.select_previous(step),
+                    (FILES_TAB, false) => self.file_browser.select_next(step),
+                    (ENV_TAB, true) => self.env_editor.select_previous(step),
+                    (ENV_TAB, false) => self.env_editor.select_next(step),
+                    _ => {}
+                }
+            }
+            Action::Filter => self.process_view.filter_mode = true,
+            Action::ClearFilter => self.process_view.clear_filter(),
+            Action::Kill => self.process_view.request_kill(),
+            Action::Descend => self.file_browser.enter(),
+            Action::GoUp => self.file_browser.go_up(),
+            Action::Open => self.file_browser.open_selected(),
+            Action::Edit => self.env_editor.start_edit(),
+            Action::Add => self.env_editor.start_add(),
+            Action::Delete => self.env_editor.delete_selected(),
+            Action::RefreshFaster => self.config.refresh_faster(),
+            Action::RefreshSlower => self.config.refresh_slower(),
+        }
+    }
+
     pub fn toggle_command_mode(&mut self) {
         self.show_command_mode = !self.show_command_mode;
         if !self.show_command_mode {
@@ -105,21 +736,25 @@ impl App {
         if self.command_input.trim().is_empty() {
             return;
         }
-        let parts: Vec<&str> = self.command_input.trim().split_whitespace().collect();
+        let parts: Vec<&str> = self.command_input.split_whitespace().collect();
         let command = parts[0].to_lowercase();
-        self.command_output.clear();
+        self.command_output.push_line(
+            OutputKind::Command,
+            format!("$ {}", self.command_input.trim()),
+        );
 
         match command.as_str() {
             "cd" => {
                 if parts.len() > 1 {
                     if let Err(e) = std::env::set_current_dir(parts[1]) {
-                        self.command_output.push(format!("cd: {}", e));
+                        self.command_output.push_err(format!("cd: {}", e));
                     } else {
                         self.current_dir = std::env::current_dir()
                             .unwrap_or_else(|_| "?".into())
                             .display()
                             .to_string();
                         self.refresh_ls();
+                        self.file_browser = FileBrowser::new(PathBuf::from(&self.current_dir));
                         self.command_output
                             .push(format!("Changed directory to: {}", self.current_dir));
                     }
@@ -207,10 +842,11 @@ impl App {
                     self.command_output
                         .push("  git commit -m \"message\"".to_string());
                 } else {
-                    let args: Vec<&str> = parts[1..].to_vec();
-                    let output = capture_git_output(&args);
-                    for line in output.lines() {
-                        self.command_output.push(line.to_string());
+                    match process::run_capture("git", &parts[1..]) {
+                        Ok(output) => self.command_output.push_captured(&output),
+                        Err(e) => self
+                            .command_output
+                            .push_err(format!("Failed to execute git command: {}", e)),
                     }
                 }
             }
@@ -271,7 +907,7 @@ impl App {
                                     self.command_output.push(format!("Set {}={}", name, value));
                                 }
                                 Err(e) => {
-                                    self.command_output.push(format!("Error: {}", e));
+                                    self.command_output.push_err(format!("Error: {}", e));
                                 }
                             }
                         } else {
@@ -296,7 +932,7 @@ impl App {
                             self.command_output.push(format!("Unset {}", name));
                         }
                         Err(e) => {
-                            self.command_output.push(format!("Error: {}", e));
+                            self.command_output.push_err(format!("Error: {}", e));
                         }
                     }
                 } else {
@@ -352,7 +988,7 @@ impl App {
                 let output = capture_powershell_output(&parts);
                 if output.trim().is_empty() {
                     self.command_output
-                        .push(format!("Unknown command: '{}'", command));
+                        .push_err(format!("Unknown command: '{}'", command));
                     self.command_output
                         .push("Type 'help' for built-in commands".to_string());
                 } else {
@@ -366,212 +1002,949 @@ impl App {
     }
 }
 
-pub fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// Where a line in the output pane came from, which decides its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// The command line being run, echoed before its output
+    Command,
+    Stdout,
+    Stderr,
+}
 
-    // Create app state
-    let mut app = App::default();
+/// Scrollback of command results. Holds at most `capacity` lines, dropping the
+/// oldest, and scrolls in lines back from the newest output.
+#[derive(Debug)]
+pub struct OutputPane {
+    lines: VecDeque<(OutputKind, String)>,
+    capacity: usize,
+    /// Lines scrolled back from the bottom; 0 follows new output
+    scroll: usize,
+    /// Rows the pane had when last drawn, used for paging and clamping
+    viewport: usize,
+}
 
-    // Main loop
-    let result = run_app(&mut terminal, &mut app);
+impl OutputPane {
+    pub fn new(capacity: usize) -> Self {
+        OutputPane {
+            lines: VecDeque::new(),
+            capacity,
+            scroll: 0,
+            viewport: 10,
+        }
+    }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    pub fn push(&mut self, line: impl Into<String>) {
+        self.push_line(OutputKind::Stdout, line);
+    }
 
-    Ok(result?)
-}
+    pub fn push_err(&mut self, line: impl Into<String>) {
+        self.push_line(OutputKind::Stderr, line);
+    }
 
-fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-    app: &mut App,
-) -> io::Result<()> {
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+    pub fn push_line(&mut self, kind: OutputKind, line: impl Into<String>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back((kind, line.into()));
+        // Stay on the same lines while scrolled back
+        if self.scroll > 0 {
+            self.scroll_up(1);
+        }
+    }
 
-        // Use slightly longer polling for better performance while maintaining responsiveness
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    if app.show_command_mode {
-                        match key.code {
-                            KeyCode::Char(c) => {
-                                app.command_input.push(c);
-                            }
-                            KeyCode::Backspace => {
-                                app.command_input.pop();
-                            }
-                            KeyCode::Enter => {
-                                app.execute_command();
-                            }
-                            KeyCode::Esc => {
-                                app.toggle_command_mode();
-                            }
-                            _ => {}
-                        }
-                    } else {
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                                app.should_quit = true;
-                            }
-                            KeyCode::Char('h') | KeyCode::Char('H') => {
-                                app.toggle_help();
-                            }
-                            KeyCode::Char('c') | KeyCode::Char('C') => {
-                                app.toggle_command_mode();
-                            }
-                            KeyCode::Left => {
-                                app.previous_tab();
-                            }
-                            KeyCode::Right => {
-                                app.next_tab();
-                            }
-                            KeyCode::Tab => {
-                                app.next_tab();
-                            }
-                            KeyCode::Char('r') | KeyCode::Char('R') => {
-                                app.last_update = Instant::now();
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+    /// Both streams of a finished command, stdout first
+    pub fn push_captured(&mut self, output: &process::CapturedOutput) {
+        for line in output.stdout.lines() {
+            self.push(line);
+        }
+        for line in output.stderr.lines() {
+            self.push_err(line);
+        }
+        if !output.status.success() && output.stdout.is_empty() && output.stderr.is_empty() {
+            match output.status.code() {
+                Some(code) => self.push_err(format!("exited with code {}", code)),
+                None => self.push_err("terminated by a signal"),
             }
         }
+    }
 
-        if app.should_quit {
-            break;
-        }
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+    }
 
-        // Auto-refresh every 10 seconds to reduce system calls
-        if app.last_update.elapsed() >= Duration::from_secs(10) {
-            app.last_update = Instant::now();
-        }
+    pub fn len(&self) -> usize {
+        self.lines.len()
     }
 
-    Ok(())
-}
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
 
-fn ui(f: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(0),
-            Constraint::Length(3),
-        ])
-        .split(f.area());
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
 
-    // Header
-    let header = create_header();
-    f.render_widget(header, chunks[0]);
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.viewport)
+    }
 
-    // Main content
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
-        .split(chunks[1]);
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.max_scroll());
+    }
 
-    // Tab bar
-    let tab_titles = vec![
-        "System",
-        "Processes",
-        "Memory",
-        "Disks",
-        "Sensors",
-        "Files",
-        "Git",
-    ];
-    let tabs = Tabs::new(tab_titles)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Dashboard")
-                .border_type(BorderType::Plain),
-        )
-        .style(Style::default().fg(Color::Gray))
-        .highlight_style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .select(app.selected_tab);
-    f.render_widget(tabs, main_chunks[0]);
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
 
-    // Tab content
-    match app.selected_tab {
-        0 => render_system_info(f, main_chunks[1]),
-        1 => render_processes(f, main_chunks[1]),
-        2 => render_memory(f, main_chunks[1]),
-        3 => render_disk_usage(f, main_chunks[1]),
-        4 => render_sensors(f, main_chunks[1]),
-        5 => render_file_browser(f, main_chunks[1], app),
-        6 => render_git_info(f, main_chunks[1]),
-        _ => {}
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.viewport.max(1));
     }
 
-    // Footer
-    let footer = create_footer();
-    f.render_widget(footer, chunks[2]);
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.viewport.max(1));
+    }
 
-    // Help popup
-    if app.show_help {
-        render_help_popup(f);
+    pub fn set_viewport(&mut self, rows: usize) {
+        self.viewport = rows;
+        self.scroll = self.scroll.min(self.max_scroll());
     }
 
-    // Command mode popup
-    if app.show_command_mode {
-        render_command_popup(f, app);
+    /// The lines that fit in the pane at the current scroll position
+    pub fn visible(&self) -> impl Iterator<Item = &(OutputKind, String)> {
+        let end = self.lines.len() - self.scroll;
+        let start = end.saturating_sub(self.viewport);
+        self.lines.range(start..end)
     }
 }
 
-fn create_header() -> Paragraph<'static> {
-    let header_text = vec![Line::from(vec![
-        Span::styled(
-            "WINIX",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-        Span::styled(
-            "Linux Commands on Windows",
-            Style::default().fg(Color::White),
-        ),
-    ])];
+/// State for the Processes tab: a filterable, scrollable process table
+#[derive(Debug, Default)]
+pub struct ProcessView {
+    pub processes: Vec<ProcessEntry>,
+    pub filter: String,
+    pub filter_mode: bool,
+    pub table_state: TableState,
+    pub status: Option<String>,
+    pub last_refresh: Option<Instant>,
+    /// Kept between refreshes, since CPU usage is measured across two samples
+    pub system: sysinfo::System,
+    /// Process waiting on a y/n answer before it is killed
+    pub pending_kill: Option<u32>,
+}
 
-    Paragraph::new(header_text)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
+impl ProcessView {
+    /// Re-read the process table, keeping the selection on the same PID if it still exists
+    pub fn refresh(&mut self) {
+        let selected_pid = self.selected_pid();
+        self.system.refresh_all();
+        self.processes = ps::snapshot(&self.system);
+        self.last_refresh = Some(Instant::now());
+
+        let visible = self.visible();
+        let index = selected_pid
+            .and_then(|pid| visible.iter().position(|p| p.pid == pid))
+            .or(if visible.is_empty() { None } else { Some(0) });
+        self.table_state.select(index);
+    }
+
+    pub fn needs_refresh(&self, interval: Duration) -> bool {
+        self.last_refresh.is_none_or(|t| t.elapsed() >= interval)
+    }
+
+    /// Processes matching the current filter, in display order
+    pub fn visible(&self) -> Vec<&ProcessEntry> {
+        filter_processes(&self.processes, &self.filter)
+    }
+
+    pub fn selected_pid(&self) -> Option<u32> {
+        let index = self.table_state.selected()?;
+        self.visible().get(index).map(|p| p.pid)
+    }
+
+    pub fn select_next(&mut self, step: usize) {
+        let len = self.visible().len();
+        if len == 0 {
+            self.table_state.select(None);
+            return;
+        }
+        let next = self
+            .table_state
+            .selected()
+            .map_or(0, |i| (i + step).min(len - 1));
+        self.table_state.select(Some(next));
+    }
+
+    pub fn select_previous(&mut self, step: usize) {
+        if self.visible().is_empty() {
+            self.table_state.select(None);
+            return;
+        }
+        let previous = self
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(step));
+        self.table_state.select(Some(previous));
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.reset_selection();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.reset_selection();
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filter_mode = false;
+        self.reset_selection();
+    }
+
+    fn reset_selection(&mut self) {
+        let index = if self.visible().is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.table_state.select(index);
+    }
+
+    /// Ask for confirmation before killing the selected process
+    pub fn request_kill(&mut self) {
+        let Some(pid) = self.selected_pid() else {
+            self.status = Some("No process selected".to_string());
+            return;
+        };
+        let name = self
+            .processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map_or("", |p| p.name.as_str());
+        self.status = Some(format!("Kill {} ({})? y/n", pid, name));
+        self.pending_kill = Some(pid);
+    }
+
+    /// Answer the prompt from `request_kill`
+    pub fn confirm_kill(&mut self, confirmed: bool) {
+        let Some(pid) = self.pending_kill.take() else {
+            return;
+        };
+        if confirmed {
+            self.kill(pid);
+        } else {
+            self.status = Some(format!("Kill {} cancelled", pid));
+        }
+    }
+
+    /// Terminate `pid` and report the outcome in the status line
+    fn kill(&mut self, pid: u32) {
+        // `kill::execute` prints to stdout, which would scribble over the screen
+        self.status = Some(match crate::kill::terminate(pid) {
+            Ok(()) => format!("Sent kill signal to process {}", pid),
+            Err(e) => format!("kill {}: {}", pid, e),
+        });
+        self.refresh();
+    }
+}
+
+/// What the environment editor is currently doing with its input line
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum EnvEditMode {
+    #[default]
+    Browse,
+    EditValue {
+        name: String,
+        value: String,
+    },
+    AddName {
+        name: String,
+    },
+    AddValue {
+        name: String,
+        value: String,
+    },
+}
+
+/// State for the Environment tab, built on the env module's TUI helpers
+#[derive(Debug, Default)]
+pub struct EnvEditor {
+    pub vars: Vec<(String, String)>,
+    pub table_state: TableState,
+    pub mode: EnvEditMode,
+    pub status: Option<String>,
+    pub error: Option<String>,
+}
+
+impl EnvEditor {
+    /// Re-read the environment, keeping the selection on the same variable if possible
+    pub fn reload(&mut self) {
+        let selected = self.selected_name();
+        self.vars = env::get_env_for_tui();
+        match selected {
+            Some(name) => self.select_name(&name),
+            None => self.select_index(0),
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.mode != EnvEditMode::Browse
+    }
+
+    pub fn selected_name(&self) -> Option<String> {
+        let index = self.table_state.selected()?;
+        self.vars.get(index).map(|(name, _)| name.clone())
+    }
+
+    pub fn select_name(&mut self, name: &str) {
+        match self.vars.iter().position(|(n, _)| n == name) {
+            Some(index) => self.table_state.select(Some(index)),
+            None => self.select_index(0),
+        }
+    }
+
+    fn select_index(&mut self, index: usize) {
+        if self.vars.is_empty() {
+            self.table_state.select(None);
+        } else {
+            self.table_state
+                .select(Some(index.min(self.vars.len() - 1)));
+        }
+    }
+
+    pub fn select_next(&mut self, step: usize) {
+        let next = self.table_state.selected().map_or(0, |i| i + step);
+        self.select_index(next);
+    }
+
+    pub fn select_previous(&mut self, step: usize) {
+        let previous = self
+            .table_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(step));
+        self.select_index(previous);
+    }
+
+    /// Start editing the value of the selected variable
+    pub fn start_edit(&mut self) {
+        let Some(index) = self.table_state.selected() else {
+            return;
+        };
+        if let Some((name, value)) = self.vars.get(index) {
+            self.mode = EnvEditMode::EditValue {
+                name: name.clone(),
+                value: value.clone(),
+            };
+            self.error = None;
+        }
+    }
+
+    /// Start adding a new variable, beginning with its name
+    pub fn start_add(&mut self) {
+        self.mode = EnvEditMode::AddName {
+            name: String::new(),
+        };
+        self.error = None;
+    }
+
+    pub fn input_char(&mut self, c: char) {
+        match &mut self.mode {
+            EnvEditMode::Browse => {}
+            EnvEditMode::AddName { name } => name.push(c),
+            EnvEditMode::EditValue { value, .. } | EnvEditMode::AddValue { value, .. } => {
+                value.push(c)
+            }
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        match &mut self.mode {
+            EnvEditMode::Browse => {}
+            EnvEditMode::AddName { name } => {
+                name.pop();
+            }
+            EnvEditMode::EditValue { value, .. } | EnvEditMode::AddValue { value, .. } => {
+                value.pop();
+            }
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.mode = EnvEditMode::Browse;
+        self.error = None;
+    }
+
+    /// Confirm the current input; invalid names keep the editor open with an error
+    pub fn confirm(&mut self) {
+        match std::mem::take(&mut self.mode) {
+            EnvEditMode::Browse => {}
+            EnvEditMode::AddName { name } => {
+                if env::is_valid_var_name(&name) {
+                    self.error = None;
+                    self.mode = EnvEditMode::AddValue {
+                        name,
+                        value: String::new(),
+                    };
+                } else {
+                    self.error = Some(format!("Invalid variable name: {}", name));
+                    self.mode = EnvEditMode::AddName { name };
+                }
+            }
+            EnvEditMode::EditValue { name, value } => {
+                if self.apply(&name, &value) {
+                    self.status = Some(format!("Set {}={}", name, value));
+                } else {
+                    self.mode = EnvEditMode::EditValue { name, value };
+                }
+            }
+            EnvEditMode::AddValue { name, value } => {
+                if self.apply(&name, &value) {
+                    self.status = Some(format!("Added {}={}", name, value));
+                } else {
+                    self.mode = EnvEditMode::AddValue { name, value };
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, name: &str, value: &str) -> bool {
+        match env::set_env_var(name, value) {
+            Ok(_) => {
+                self.error = None;
+                self.reload();
+                self.select_name(name);
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    /// Remove the selected variable from the environment
+    pub fn delete_selected(&mut self) {
+        let Some(name) = self.selected_name() else {
+            return;
+        };
+        match env::remove_env_var(&name) {
+            Ok(_) => {
+                self.error = None;
+                self.status = Some(format!("Unset {}", name));
+                self.reload();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// State for the Files tab: a directory listing with a preview of the selected file
+#[derive(Debug, Default)]
+pub struct FileBrowser {
+    pub current_dir: PathBuf,
+    pub entries: Vec<TreeEntry>,
+    pub list_state: ListState,
+    pub preview: String,
+    pub status: Option<String>,
+}
+
+impl FileBrowser {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut browser = FileBrowser {
+            current_dir: dir,
+            ..Default::default()
+        };
+        browser.refresh();
+        browser
+    }
+
+    /// Re-read the current directory, keeping the selection on the same name if possible
+    pub fn refresh(&mut self) {
+        let selected = self.selected_entry().map(|entry| entry.name.clone());
+        match tree::list_dir(&self.current_dir) {
+            Ok(entries) => {
+                self.entries = entries;
+                self.status = None;
+            }
+            Err(e) => {
+                self.entries.clear();
+                self.status = Some(format!("{}: {}", self.current_dir.display(), e));
+            }
+        }
+        let index = selected
+            .and_then(|name| self.entries.iter().position(|entry| entry.name == name))
+            .unwrap_or(0);
+        self.select_index(index);
+    }
+
+    pub fn selected_entry(&self) -> Option<&TreeEntry> {
+        self.entries.get(self.list_state.selected()?)
+    }
+
+    pub fn selected_path(&self) -> Option<PathBuf> {
+        self.selected_entry()
+            .map(|entry| self.current_dir.join(&entry.name))
+    }
+
+    fn select_index(&mut self, index: usize) {
+        if self.entries.is_empty() {
+            self.list_state.select(None);
+        } else {
+            self.list_state
+                .select(Some(index.min(self.entries.len() - 1)));
+        }
+        self.update_preview();
+    }
+
+    pub fn select_next(&mut self, step: usize) {
+        let next = self.list_state.selected().map_or(0, |i| i + step);
+        self.select_index(next);
+    }
+
+    pub fn select_previous(&mut self, step: usize) {
+        let previous = self
+            .list_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(step));
+        self.select_index(previous);
+    }
+
+    /// Descend into the selected directory
+    pub fn enter(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        if entry.is_dir {
+            self.current_dir = self.current_dir.join(&entry.name);
+            self.list_state.select(None);
+            self.refresh();
+        }
+    }
+
+    /// Move to the parent directory, selecting the directory we came from
+    pub fn go_up(&mut self) {
+        let Some(parent) = self.current_dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let child = self
+            .current_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+
+        self.current_dir = parent;
+        self.list_state.select(None);
+        self.refresh();
+        if let Some(index) =
+            child.and_then(|name| self.entries.iter().position(|entry| entry.name == name))
+        {
+            self.select_index(index);
+        }
+    }
+
+    /// Open the selected file with the system's default application
+    pub fn open_selected(&mut self) {
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+
+        #[cfg(windows)]
+        let result = std::process::Command::new("cmd")
+            .args(["/C", "start", ""])
+            .arg(&path)
+            .spawn();
+        #[cfg(target_os = "macos")]
+        let result = std::process::Command::new("open").arg(&path).spawn();
+        #[cfg(all(unix, not(target_os = "macos")))]
+        let result = std::process::Command::new("xdg-open")
+            .arg(&path)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+
+        self.status = Some(match result {
+            Ok(_) => format!("Opened {}", path.display()),
+            Err(e) => format!("Cannot open {}: {}", path.display(), e),
+        });
+    }
+
+    fn update_preview(&mut self) {
+        self.preview = match (self.selected_entry(), self.selected_path()) {
+            (Some(entry), _) if entry.is_dir => {
+                match tree::list_dir(&self.current_dir.join(&entry.name)) {
+                    Ok(children) => children
+                        .iter()
+                        .map(|child| {
+                            format!("{}{}", child.name, if child.is_dir { "/" } else { "" })
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    Err(e) => e.to_string(),
+                }
+            }
+            (Some(_), Some(path)) => preview_file(&path),
+            _ => String::new(),
+        };
+    }
+}
+
+/// The start of a file for the preview pane; binary content is rendered like `cat -v`
+pub fn preview_file(path: &Path) -> String {
+    let mut bytes = Vec::new();
+    let read =
+        std::fs::File::open(path).and_then(|file| file.take(PREVIEW_BYTES).read_to_end(&mut bytes));
+    if let Err(e) = read {
+        return e.to_string();
+    }
+
+    let text_len = match std::str::from_utf8(&bytes) {
+        Ok(_) => Some(bytes.len()),
+        // A multi-byte character cut off at the preview limit is still text
+        Err(e) if e.error_len().is_none() => Some(e.valid_up_to()),
+        Err(_) => None,
+    };
+
+    match text_len {
+        Some(len) if !bytes.contains(&0) => String::from_utf8_lossy(&bytes[..len])
+            .lines()
+            .map(|line| cat::expand_tabs(line, 4))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => cat::show_nonprinting(&bytes),
+    }
+}
+
+/// Snapshot behind the Dashboard tab, collected from the free, df, nproc and sensors modules
+#[derive(Debug, Clone)]
+pub struct DashboardModel {
+    pub memory: MemoryStats,
+    pub disks: Vec<DiskStats>,
+    pub cpu: CpuInfo,
+    pub sensors: Vec<SensorReading>,
+    pub collected_at: Instant,
+}
+
+impl DashboardModel {
+    pub fn collect() -> Self {
+        Self::collect_with(&mut SensorReader::new())
+    }
+
+    /// Collect with a long-lived sensor reader, which only re-reads the sensor values
+    pub fn collect_with(sensors: &mut SensorReader) -> Self {
+        sensors.refresh();
+        DashboardModel {
+            memory: free::get_memory_for_tui(),
+            disks: df::get_disks_for_tui(),
+            cpu: nproc::get_cpu_info(),
+            sensors: sensors.temperatures().to_vec(),
+            collected_at: Instant::now(),
+        }
+    }
+}
+
+/// Keep the processes whose name (case-insensitive) or PID contains `query`
+pub fn filter_processes<'a>(processes: &'a [ProcessEntry], query: &str) -> Vec<&'a ProcessEntry> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return processes.iter().collect();
+    }
+
+    processes
+        .iter()
+        .filter(|p| p.name.to_lowercase().contains(&query) || p.pid.to_string().contains(&query))
+        .collect()
+}
+
+pub fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
+    // Create app state; a broken config file shouldn't keep the TUI from starting.
+    // Loaded before the alternate screen so the warning stays visible.
+    let config = TuiConfig::load().unwrap_or_else(|e| {
+        eprintln!("winix: ignoring TUI config: {}", e);
+        TuiConfig::default()
+    });
+    let mut app = App::with_config(config);
+
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Main loop
+    let result = run_app(&mut terminal, &mut app);
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    Ok(result?)
+}
+
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    app: &mut App,
+) -> io::Result<()> {
+    loop {
+        terminal.draw(|f| ui(f, app))?;
+
+        // Use slightly longer polling for better performance while maintaining responsiveness
+        if event::poll(Duration::from_millis(50))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            if app.show_command_mode {
+                match key.code {
+                    KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.command_output.clear();
+                    }
+                    KeyCode::Char(c) => {
+                        app.command_input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        app.command_input.pop();
+                    }
+                    KeyCode::Enter => {
+                        app.execute_command();
+                    }
+                    KeyCode::Esc => {
+                        app.toggle_command_mode();
+                    }
+                    KeyCode::PageUp => app.command_output.page_up(),
+                    KeyCode::PageDown => app.command_output.page_down(),
+                    _ => {}
+                }
+            } else if app.selected_tab == ENV_TAB && app.env_editor.is_editing() {
+                match key.code {
+                    KeyCode::Char(c) => app.env_editor.input_char(c),
+                    KeyCode::Backspace => app.env_editor.backspace(),
+                    KeyCode::Enter => app.env_editor.confirm(),
+                    KeyCode::Esc => app.env_editor.cancel(),
+                    _ => {}
+                }
+            } else if app.selected_tab == PROCESSES_TAB && app.process_view.filter_mode {
+                match key.code {
+                    KeyCode::Char(c) => {
+                        app.process_view.push_filter_char(c);
+                    }
+                    KeyCode::Backspace => {
+                        app.process_view.pop_filter_char();
+                    }
+                    KeyCode::Enter => {
+                        app.process_view.filter_mode = false;
+                    }
+                    KeyCode::Esc => {
+                        app.process_view.clear_filter();
+                    }
+                    KeyCode::Up => app.process_view.select_previous(1),
+                    KeyCode::Down => app.process_view.select_next(1),
+                    _ => {}
+                }
+            } else if app.selected_tab == PROCESSES_TAB && app.process_view.pending_kill.is_some() {
+                let confirmed = matches!(key.code, KeyCode::Char('y' | 'Y'));
+                app.process_view.confirm_kill(confirmed);
+                if confirmed {
+                    // kill reports to stdout, so repaint over anything it printed
+                    terminal.clear()?;
+                }
+            } else if let Some(action) = app.keymap.action(key.code, app.selected_tab) {
+                app.perform(action);
+            }
+        }
+
+        if app.should_quit {
+            break;
+        }
+
+        // Auto-refresh every 10 seconds to reduce system calls
+        if app.last_update.elapsed() >= Duration::from_secs(10) {
+            app.last_update = Instant::now();
+        }
+
+        // The process monitor keeps its own, faster refresh cycle while visible
+        if app.selected_tab == PROCESSES_TAB
+            && app.process_view.needs_refresh(app.config.refresh_interval)
+        {
+            app.process_view.refresh();
+        }
+
+        if app.selected_tab == ENV_TAB && app.env_editor.vars.is_empty() {
+            app.env_editor.reload();
+        }
+
+        if app.selected_tab == DASHBOARD_TAB
+            && app
+                .dashboard
+                .as_ref()
+                .is_none_or(|d| d.collected_at.elapsed() >= app.config.refresh_interval)
+        {
+            app.refresh_dashboard();
+        }
+    }
+
+    Ok(())
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(f.area());
+
+    let theme = app.config.theme;
+
+    // Header
+    let header = create_header(&theme);
+    f.render_widget(header, chunks[0]);
+
+    // Main content
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(chunks[1]);
+
+    // Tab bar, showing only the enabled panels
+    let enabled: Vec<usize> = (0..TAB_TITLES.len())
+        .filter(|&tab| app.config.panels[tab])
+        .collect();
+    let tabs = Tabs::new(
+        enabled
+            .iter()
+            .map(|&tab| TAB_TITLES[tab])
+            .collect::<Vec<_>>(),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Dashboard")
+            .border_type(BorderType::Plain),
+    )
+    .style(Style::default().fg(theme.muted))
+    .highlight_style(
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    )
+    .select(enabled.iter().position(|&tab| tab == app.selected_tab));
+    f.render_widget(tabs, main_chunks[0]);
+
+    // Tab content
+    match app.selected_tab {
+        0 => render_system_info(f, main_chunks[1]),
+        PROCESSES_TAB => render_processes(f, main_chunks[1], app),
+        2 => render_memory(f, main_chunks[1], &theme),
+        3 => render_disk_usage(f, main_chunks[1]),
+        4 => render_sensors(f, main_chunks[1]),
+        FILES_TAB => render_file_browser(
+            f,
+            main_chunks[1],
+            &mut app.file_browser,
+            &app.keymap,
+            &theme,
+        ),
+        6 => render_git_info(f, main_chunks[1], &app.keymap, &theme),
+        DASHBOARD_TAB => render_dashboard(f, main_chunks[1], app.dashboard.as_ref(), &theme),
+        ENV_TAB => render_env_editor(f, main_chunks[1], &mut app.env_editor, &app.keymap, &theme),
+        _ => {}
+    }
+
+    // Footer
+    let footer = create_footer(&app.keymap, &app.config);
+    f.render_widget(footer, chunks[2]);
+
+    // Help popup
+    if app.show_help {
+        render_help_popup(f, app);
+    }
+
+    // Command mode popup
+    if app.show_command_mode {
+        render_command_popup(f, app);
+    }
+}
+
+fn create_header(theme: &Palette) -> Paragraph<'static> {
+    let header_text = vec![Line::from(vec![
+        Span::styled(
+            "WINIX",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" | ", Style::default().fg(theme.dim)),
+        Span::styled("Linux Commands on Windows", Style::default().fg(theme.text)),
+    ])];
+
+    Paragraph::new(header_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
                 .border_type(BorderType::Plain),
         )
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
 }
 
-fn create_footer() -> Paragraph<'static> {
+fn create_footer(keymap: &Keymap, config: &TuiConfig) -> Paragraph<'static> {
+    let theme = &config.theme;
+    let first_key = |action| {
+        keymap
+            .keys_for(action)
+            .first()
+            .map_or("-".to_string(), |key| key_label(*key))
+    };
     let footer_text = vec![Line::from(vec![
-        Span::styled("Tab/Arrow Keys: ", Style::default().fg(Color::Cyan)),
-        Span::styled("Navigate", Style::default().fg(Color::White)),
-        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-        Span::styled("H: ", Style::default().fg(Color::Cyan)),
-        Span::styled("Help", Style::default().fg(Color::White)),
-        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-        Span::styled("C: ", Style::default().fg(Color::Cyan)),
-        Span::styled("Command", Style::default().fg(Color::White)),
-        Span::styled(" | ", Style::default().fg(Color::DarkGray)),
-        Span::styled("Q: ", Style::default().fg(Color::Cyan)),
-        Span::styled("Quit", Style::default().fg(Color::White)),
+        Span::styled(
+            format!(
+                "{}: ",
+                keymap.keys_label(&[Action::PreviousTab, Action::NextTab])
+            ),
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled("Navigate", Style::default().fg(theme.text)),
+        Span::styled(" | ", Style::default().fg(theme.dim)),
+        Span::styled(
+            format!("{}: ", first_key(Action::ToggleHelp)),
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled("Help", Style::default().fg(theme.text)),
+        Span::styled(" | ", Style::default().fg(theme.dim)),
+        Span::styled(
+            format!("{}: ", first_key(Action::CommandMode)),
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled("Command", Style::default().fg(theme.text)),
+        Span::styled(" | ", Style::default().fg(theme.dim)),
+        Span::styled(
+            format!("{}: ", first_key(Action::Quit)),
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled("Quit", Style::default().fg(theme.text)),
+        Span::styled(" | ", Style::default().fg(theme.dim)),
+        Span::styled(
+            format!(
+                "{}/{}: ",
+                first_key(Action::RefreshFaster),
+                first_key(Action::RefreshSlower)
+            ),
+            Style::default().fg(theme.accent),
+        ),
+        Span::styled(
+            format!("Refresh {:.2}s", config.refresh_interval.as_secs_f64()),
+            Style::default().fg(theme.text),
+        ),
     ])];
 
     Paragraph::new(footer_text)
@@ -580,7 +1953,7 @@ fn create_footer() -> Paragraph<'static> {
                 .borders(Borders::ALL)
                 .border_type(BorderType::Plain),
         )
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
 }
 
 fn render_system_info(f: &mut Frame, area: Rect) {
@@ -614,23 +1987,32 @@ fn render_system_info(f: &mut Frame, area: Rect) {
     f.render_widget(uptime_paragraph, chunks[1]);
 }
 
-fn render_processes(f: &mut Frame, area: Rect) {
-    let processes = get_process_list();
+fn render_processes(f: &mut Frame, area: Rect, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(area);
+
+    let hints = app.keymap.hints(PROCESS_HINTS);
+    let theme = app.config.theme;
+    let view = &mut app.process_view;
+    let visible = view.visible();
     let header =
-        Row::new(vec!["PID", "Name", "CPU%", "Memory"]).style(Style::default().fg(Color::Cyan));
+        Row::new(vec!["PID", "Name", "CPU%", "Memory"]).style(Style::default().fg(theme.accent));
 
-    let rows: Vec<Row> = processes
-        .into_iter()
+    let rows: Vec<Row> = visible
+        .iter()
         .map(|p| {
             Row::new(vec![
-                Cell::from(p.0),
-                Cell::from(p.1),
-                Cell::from(p.2),
-                Cell::from(p.3),
+                Cell::from(p.pid.to_string()),
+                Cell::from(p.name.clone()),
+                Cell::from(format!("{:.1}%", p.cpu_usage)),
+                Cell::from(format_bytes(p.memory)),
             ])
         })
         .collect();
 
+    let title = format!("Processes ({}/{})", visible.len(), view.processes.len());
     let table = Table::new(
         rows,
         &[
@@ -644,14 +2026,45 @@ fn render_processes(f: &mut Frame, area: Rect) {
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Top Processes")
+            .title(title)
             .border_type(BorderType::Plain),
+    )
+    .row_highlight_style(
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD | Modifier::REVERSED),
     );
 
-    f.render_widget(table, area);
+    f.render_stateful_widget(table, chunks[0], &mut view.table_state);
+
+    // Filter line, doubling as the status bar for kill results
+    let filter_style = if view.filter_mode {
+        Style::default().fg(theme.key)
+    } else {
+        Style::default().fg(theme.text)
+    };
+    let mut filter_line = vec![
+        Span::styled("Filter: ", Style::default().fg(theme.accent)),
+        Span::styled(view.filter.clone(), filter_style),
+    ];
+    if let Some(status) = &view.status {
+        filter_line.push(Span::styled(" | ", Style::default().fg(theme.dim)));
+        filter_line.push(Span::styled(
+            status.clone(),
+            Style::default().fg(theme.muted),
+        ));
+    }
+
+    let filter = Paragraph::new(Line::from(filter_line)).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(hints)
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(filter, chunks[1]);
 }
 
-fn render_memory(f: &mut Frame, area: Rect) {
+fn render_memory(f: &mut Frame, area: Rect, theme: &Palette) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(0)])
@@ -667,7 +2080,7 @@ fn render_memory(f: &mut Frame, area: Rect) {
                 .title("Memory Usage")
                 .border_type(BorderType::Plain),
         )
-        .gauge_style(Style::default().fg(Color::Cyan))
+        .gauge_style(Style::default().fg(theme.accent))
         .ratio(memory_info.usage_ratio)
         .label(format!("{:.1}%", memory_info.usage_ratio * 100.0));
     f.render_widget(memory_gauge, chunks[0]);
@@ -697,84 +2110,334 @@ fn render_disk_usage(f: &mut Frame, area: Rect) {
     f.render_widget(disk_paragraph, area);
 }
 
-fn render_sensors(f: &mut Frame, area: Rect) {
-    let sensor_info = get_sensor_info();
-    let sensor_paragraph = Paragraph::new(sensor_info)
+fn render_sensors(f: &mut Frame, area: Rect) {
+    let sensor_info = get_sensor_info();
+    let sensor_paragraph = Paragraph::new(sensor_info)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Temperature Sensors")
+                .border_type(BorderType::Plain),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(sensor_paragraph, area);
+}
+
+fn render_file_browser(
+    f: &mut Frame,
+    area: Rect,
+    browser: &mut FileBrowser,
+    keymap: &Keymap,
+    theme: &Palette,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    // Current directory, or the last error/status
+    let header_text = match &browser.status {
+        Some(status) => format!("📁 {} | {}", browser.current_dir.display(), status),
+        None => format!("📁 {}", browser.current_dir.display()),
+    };
+    let current_dir = Paragraph::new(header_text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Current Directory ({})", keymap.hints(FILE_HINTS)))
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(current_dir, chunks[0]);
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(chunks[1]);
+
+    // File list
+    let items: Vec<ListItem> = browser
+        .entries
+        .iter()
+        .map(|entry| {
+            let icon = if entry.is_dir { "📁" } else { "📄" };
+            ListItem::new(format!("{} {}", icon, entry.name))
+        })
+        .collect();
+
+    let file_list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Files & Directories ({})", browser.entries.len()))
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+        );
+    f.render_stateful_widget(file_list, panes[0], &mut browser.list_state);
+
+    // Preview of the selected entry
+    let title = browser
+        .selected_entry()
+        .map_or("Preview".to_string(), |entry| {
+            format!("Preview: {}", entry.name)
+        });
+    let preview = Paragraph::new(browser.preview.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(preview, panes[1]);
+}
+
+fn render_dashboard(f: &mut Frame, area: Rect, model: Option<&DashboardModel>, theme: &Palette) {
+    let Some(model) = model else {
+        let loading = Paragraph::new("Collecting system information...").block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Dashboard")
+                .border_type(BorderType::Plain),
+        );
+        f.render_widget(loading, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0]);
+
+    let cpu = Paragraph::new(model.cpu.to_string()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("CPUs")
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(cpu, top[0]);
+
+    let swap_gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Swap")
+                .border_type(BorderType::Plain),
+        )
+        .gauge_style(Style::default().fg(theme.highlight))
+        .ratio(model.memory.swap_ratio())
+        .label(format!(
+            "{} / {}",
+            format_bytes(model.memory.used_swap),
+            format_bytes(model.memory.total_swap)
+        ));
+    f.render_widget(swap_gauge, top[1]);
+
+    let memory_gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Memory")
+                .border_type(BorderType::Plain),
+        )
+        .gauge_style(Style::default().fg(theme.accent))
+        .ratio(model.memory.usage_ratio())
+        .label(format!(
+            "{} / {} ({:.1}%)",
+            format_bytes(model.memory.used),
+            format_bytes(model.memory.total),
+            model.memory.usage_ratio() * 100.0
+        ));
+    f.render_widget(memory_gauge, chunks[1]);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[2]);
+
+    // One single-line gauge per disk inside a shared block
+    let disk_block = Block::default()
+        .borders(Borders::ALL)
+        .title("Disks")
+        .border_type(BorderType::Plain);
+    let disk_area = disk_block.inner(bottom[0]);
+    f.render_widget(disk_block, bottom[0]);
+
+    let disk_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); model.disks.len()])
+        .split(disk_area);
+    for (disk, row) in model.disks.iter().zip(disk_rows.iter()) {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(theme.level(disk.usage_ratio())))
+            .ratio(disk.usage_ratio())
+            .label(format!(
+                "{} {} / {}",
+                disk.mount_point,
+                format_bytes(disk.used()),
+                format_bytes(disk.total)
+            ));
+        f.render_widget(gauge, *row);
+    }
+
+    let sensor_lines: Vec<Line> = if model.sensors.is_empty() {
+        vec![Line::from(Span::styled(
+            "No temperature sensors found or accessible.",
+            Style::default().fg(theme.warn),
+        ))]
+    } else {
+        model
+            .sensors
+            .iter()
+            .map(|reading| {
+                let color = match reading.critical {
+                    Some(crit) if reading.temperature >= crit => theme.bad,
+                    Some(crit) if reading.temperature >= crit * 0.8 => theme.warn,
+                    _ => theme.good,
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}: ", reading.label),
+                        Style::default().fg(theme.text),
+                    ),
+                    Span::styled(
+                        format!("{:.1}°C", reading.temperature),
+                        Style::default().fg(color),
+                    ),
+                ])
+            })
+            .collect()
+    };
+
+    let sensors = Paragraph::new(sensor_lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Temperature Sensors")
+                .title("Temperatures")
                 .border_type(BorderType::Plain),
         )
         .wrap(Wrap { trim: true });
-    f.render_widget(sensor_paragraph, area);
+    f.render_widget(sensors, bottom[1]);
 }
 
-fn render_file_browser(f: &mut Frame, area: Rect, app: &App) {
+fn render_env_editor(
+    f: &mut Frame,
+    area: Rect,
+    editor: &mut EnvEditor,
+    keymap: &Keymap,
+    theme: &Palette,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
         .split(area);
 
-    // Current directory
-    let current_dir = Paragraph::new(format!("📁 {}", app.current_dir)).block(
+    let header = Row::new(vec!["Name", "Value"]).style(Style::default().fg(theme.accent));
+    let rows: Vec<Row> = editor
+        .vars
+        .iter()
+        .map(|(name, value)| Row::new(vec![Cell::from(name.clone()), Cell::from(value.clone())]))
+        .collect();
+
+    let table = Table::new(
+        rows,
+        &[Constraint::Percentage(30), Constraint::Percentage(70)],
+    )
+    .header(header)
+    .block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Current Directory")
+            .title(format!("Environment Variables ({})", editor.vars.len()))
             .border_type(BorderType::Plain),
+    )
+    .row_highlight_style(
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD | Modifier::REVERSED),
     );
-    f.render_widget(current_dir, chunks[0]);
+    f.render_stateful_widget(table, chunks[0], &mut editor.table_state);
 
-    // File list
-    let items: Vec<ListItem> = app
-        .ls_items
-        .iter()
-        .map(|item| ListItem::new(item.as_str()))
-        .collect();
+    let (title, input) = match &editor.mode {
+        EnvEditMode::Browse => (
+            keymap.hints(ENV_HINTS),
+            editor.status.clone().unwrap_or_default(),
+        ),
+        EnvEditMode::EditValue { name, value } => (
+            format!("Edit {} (Enter to save, Esc to cancel)", name),
+            value.clone(),
+        ),
+        EnvEditMode::AddName { name } => (
+            "New variable name (Enter to continue, Esc to cancel)".to_string(),
+            name.clone(),
+        ),
+        EnvEditMode::AddValue { name, value } => (
+            format!("Value for {} (Enter to save, Esc to cancel)", name),
+            value.clone(),
+        ),
+    };
 
-    let file_list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Files & Directories")
-                .border_type(BorderType::Plain),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        );
-    f.render_stateful_widget(file_list, chunks[1], &mut app.ls_state.clone());
+    let line = match &editor.error {
+        Some(error) => Line::from(vec![
+            Span::styled(input, Style::default().fg(theme.key)),
+            Span::styled(" | ", Style::default().fg(theme.dim)),
+            Span::styled(error.clone(), Style::default().fg(theme.bad)),
+        ]),
+        None => Line::from(Span::styled(input, Style::default().fg(theme.key))),
+    };
+
+    let input_box = Paragraph::new(line).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_type(BorderType::Plain),
+    );
+    f.render_widget(input_box, chunks[1]);
 }
 
-fn render_help_popup(f: &mut Frame) {
+fn render_help_popup(f: &mut Frame, app: &App) {
     let area = centered_rect(70, 80, f.area());
     f.render_widget(Clear, area);
 
-    let help_text = vec![
+    let entries = app.keymap.help(app.selected_tab);
+    let theme = &app.config.theme;
+    let width = entries
+        .iter()
+        .map(|e| e.keys.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut help_text = vec![
         Line::from(Span::styled(
-            "Winix Help",
+            format!("Keys for {}", TAB_TITLES[app.selected_tab]),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from("Navigation:"),
-        Line::from("  Tab / ← → : Switch between tabs"),
-        Line::from("  H         : Toggle help"),
-        Line::from("  C         : Open command mode"),
-        Line::from("  Q         : Quit"),
-        Line::from(""),
-        Line::from("Tabs:"),
-        Line::from("  System    : OS information"),
-        Line::from("  Processes : Running processes"),
-        Line::from("  Memory    : Memory usage"),
-        Line::from("  Disks     : Disk usage"),
-        Line::from("  Sensors   : Temperature sensors"),
-        Line::from("  Files     : File browser"),
-        Line::from(""),
-        Line::from("Press H to close"),
     ];
+    help_text.extend(entries.iter().map(|entry| {
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<width$}", entry.keys, width = width),
+                Style::default().fg(theme.key),
+            ),
+            Span::raw(format!(" : {}", entry.description)),
+        ])
+    }));
+    help_text.push(Line::from(""));
+    help_text.push(Line::from(format!(
+        "Press {} to close",
+        app.keymap.label(Action::ToggleHelp)
+    )));
 
     let help_popup = Paragraph::new(help_text)
         .block(
@@ -783,13 +2446,14 @@ fn render_help_popup(f: &mut Frame) {
                 .title("Help")
                 .border_type(BorderType::Plain),
         )
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     f.render_widget(help_popup, area);
 }
 
-fn render_command_popup(f: &mut Frame, app: &App) {
+fn render_command_popup(f: &mut Frame, app: &mut App) {
     let area = centered_rect(80, 60, f.area());
     f.render_widget(Clear, area);
+    let theme = app.config.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -804,24 +2468,35 @@ fn render_command_popup(f: &mut Frame, app: &App) {
                 .title("Command (ESC to close)")
                 .border_type(BorderType::Plain),
         )
-        .style(Style::default().fg(Color::Cyan));
+        .style(Style::default().fg(theme.accent));
     f.render_widget(input, chunks[0]);
 
-    // Command output
+    // Command output, one row per line so scrolling stays exact
+    app.command_output
+        .set_viewport(chunks[1].height.saturating_sub(2) as usize);
     let output: Vec<Line> = app
         .command_output
-        .iter()
-        .map(|line| Line::from(line.as_str()))
+        .visible()
+        .map(|(kind, line)| {
+            let style = match kind {
+                OutputKind::Command => Style::default().fg(theme.accent),
+                OutputKind::Stdout => Style::default(),
+                OutputKind::Stderr => Style::default().fg(theme.bad),
+            };
+            Line::styled(line.as_str(), style)
+        })
         .collect();
 
-    let output_paragraph = Paragraph::new(output)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Output")
-                .border_type(BorderType::Plain),
-        )
-        .wrap(Wrap { trim: true });
+    let title = match app.command_output.scroll() {
+        0 => "Output (PgUp/PgDn scroll, Ctrl+L clear)".to_string(),
+        back => format!("Output ({} lines back, PgDn for newer)", back),
+    };
+    let output_paragraph = Paragraph::new(output).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_type(BorderType::Plain),
+    );
     f.render_widget(output_paragraph, chunks[1]);
 }
 
@@ -887,38 +2562,6 @@ fn get_uptime_info() -> Text<'static> {
     Text::from(uptime_text)
 }
 
-fn get_process_list() -> Vec<(String, String, String, String)> {
-    use sysinfo::System;
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    let mut processes: Vec<_> = sys.processes().iter().collect();
-    processes.sort_by(|a, b| {
-        b.1.cpu_usage()
-            .partial_cmp(&a.1.cpu_usage())
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    processes
-        .iter()
-        .take(15)
-        .map(|(pid, process)| {
-            let name = process.name().to_string_lossy().to_string();
-            let name = if name.len() > 20 {
-                format!("{}...", &name[..17])
-            } else {
-                name
-            };
-            (
-                pid.to_string(),
-                name,
-                format!("{:.1}%", process.cpu_usage()),
-                format_bytes(process.memory()),
-            )
-        })
-        .collect()
-}
-
 fn format_bytes(bytes: u64) -> String {
     let gb = bytes as f64 / (1024.0 * 1024.0 * 1024.0);
     let mb = bytes as f64 / (1024.0 * 1024.0);
@@ -1056,13 +2699,16 @@ fn capture_uname_output() -> String {
     if let Some(version) = sysinfo::System::os_version() {
         info.push_str(&format!("OS Version: {}\n", version));
     }
-    if let Some(kernel) = sysinfo::System::kernel_version() {
-        info.push_str(&format!("Kernel: {}\n", kernel));
+    let uname = uname::collect();
+    info.push_str(&format!("Kernel: {} {}\n", uname.sysname, uname.release));
+    if let Some(wsl) = &uname.wsl {
+        info.push_str(&format!("Runtime: {}\n", wsl));
     }
-    info.push_str(&format!("Architecture: {}\n", std::env::consts::ARCH));
-    if let Some(hostname) = sysinfo::System::host_name() {
-        info.push_str(&format!("Hostname: {}\n", hostname));
+    info.push_str(&format!("Architecture: {}\n", uname.machine));
+    if let Some(platform) = &uname.hardware_platform {
+        info.push_str(&format!("Hardware: {}\n", platform));
     }
+    info.push_str(&format!("Hostname: {}\n", uname.nodename));
     info
 }
 
@@ -1219,48 +2865,6 @@ fn capture_chown_output(args: &[&str]) -> String {
     }
 }
 
-fn capture_git_output(args: &[&str]) -> String {
-    use std::process::Command;
-
-    // Check if git is available
-    if !is_command_available("git") {
-        return "Error: Git is not installed or not in PATH".to_string();
-    }
-
-    match Command::new("git").args(args).output() {
-        Ok(output) => {
-            let mut result = String::new();
-
-            // Add stdout
-            if !output.stdout.is_empty() {
-                result.push_str(&String::from_utf8_lossy(&output.stdout));
-            }
-
-            // Add stderr
-            if !output.stderr.is_empty() {
-                if !result.is_empty() {
-                    result.push('\n');
-                }
-                result.push_str(&String::from_utf8_lossy(&output.stderr));
-            }
-
-            // If command failed and no output, add error message
-            if !output.status.success() && result.is_empty() {
-                if let Some(code) = output.status.code() {
-                    result = format!("Git command failed with exit code: {}", code);
-                } else {
-                    result = "Git command failed".to_string();
-                }
-            }
-
-            result
-        }
-        Err(e) => {
-            format!("Failed to execute git command: {}", e)
-        }
-    }
-}
-
 fn capture_powershell_output(args: &[&str]) -> String {
     use std::process::Command;
 
@@ -1276,7 +2880,7 @@ fn capture_powershell_output(args: &[&str]) -> String {
     let command_string = args.join(" ");
 
     match Command::new(ps_exe)
-        .args(&["-Command", &command_string])
+        .args(["-Command", &command_string])
         .output()
     {
         Ok(output) => {
@@ -1356,7 +2960,7 @@ fn is_command_available(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn render_git_info(f: &mut Frame, area: Rect) {
+fn render_git_info(f: &mut Frame, area: Rect, keymap: &Keymap, theme: &Palette) {
     // Check if we're in a git repository
     let is_git_repo = crate::git::is_git_repo();
 
@@ -1364,26 +2968,26 @@ fn render_git_info(f: &mut Frame, area: Rect) {
         let no_git_text = vec![
             Line::from(vec![Span::styled(
                 "Not a Git Repository",
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.bad).add_modifier(Modifier::BOLD),
             )]),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Navigate to a Git repository or initialize one with:",
-                Style::default().fg(Color::Gray),
+                Style::default().fg(theme.muted),
             )]),
             Line::from(""),
             Line::from(vec![
-                Span::styled("git init", Style::default().fg(Color::Cyan)),
+                Span::styled("git init", Style::default().fg(theme.accent)),
                 Span::styled(
                     " - Initialize a new Git repository",
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(theme.muted),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("git clone <url>", Style::default().fg(Color::Cyan)),
+                Span::styled("git clone <url>", Style::default().fg(theme.accent)),
                 Span::styled(
                     " - Clone an existing repository",
-                    Style::default().fg(Color::Gray),
+                    Style::default().fg(theme.muted),
                 ),
             ]),
         ];
@@ -1412,16 +3016,16 @@ fn render_git_info(f: &mut Frame, area: Rect) {
         .split(area);
 
     // Repository Information
-    render_git_repo_info(f, layout[0]);
+    render_git_repo_info(f, layout[0], theme);
 
     // Branch Information
-    render_git_branch_info(f, layout[1]);
+    render_git_branch_info(f, layout[1], keymap, theme);
 
     // Status and recent commits
-    render_git_status_and_log(f, layout[2]);
+    render_git_status_and_log(f, layout[2], theme);
 }
 
-fn render_git_repo_info(f: &mut Frame, area: Rect) {
+fn render_git_repo_info(f: &mut Frame, area: Rect, theme: &Palette) {
     let current_dir = std::env::current_dir()
         .unwrap_or_else(|_| "?".into())
         .display()
@@ -1429,20 +3033,20 @@ fn render_git_repo_info(f: &mut Frame, area: Rect) {
 
     let repo_info = vec![
         Line::from(vec![
-            Span::styled("Repository: ", Style::default().fg(Color::Cyan)),
-            Span::styled(current_dir, Style::default().fg(Color::White)),
+            Span::styled("Repository: ", Style::default().fg(theme.accent)),
+            Span::styled(current_dir, Style::default().fg(theme.text)),
         ]),
         Line::from(""),
         Line::from(vec![
-            Span::styled("Git Status: ", Style::default().fg(Color::Cyan)),
+            Span::styled("Git Status: ", Style::default().fg(theme.accent)),
             if let Some(status) = crate::git::get_repo_status() {
                 match status.as_str() {
-                    "clean" => Span::styled("Clean ✓", Style::default().fg(Color::Green)),
-                    "dirty" => Span::styled("Modified ✗", Style::default().fg(Color::Red)),
-                    _ => Span::styled("Unknown", Style::default().fg(Color::Yellow)),
+                    "clean" => Span::styled("Clean ✓", Style::default().fg(theme.good)),
+                    "dirty" => Span::styled("Modified ✗", Style::default().fg(theme.bad)),
+                    _ => Span::styled("Unknown", Style::default().fg(theme.warn)),
                 }
             } else {
-                Span::styled("Error getting status", Style::default().fg(Color::Red))
+                Span::styled("Error getting status", Style::default().fg(theme.bad))
             },
         ]),
     ];
@@ -1459,27 +3063,30 @@ fn render_git_repo_info(f: &mut Frame, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn render_git_branch_info(f: &mut Frame, area: Rect) {
+fn render_git_branch_info(f: &mut Frame, area: Rect, keymap: &Keymap, theme: &Palette) {
     let current_branch = crate::git::get_current_branch().unwrap_or_else(|| "HEAD".to_string());
 
     let branch_info = vec![
         Line::from(vec![
-            Span::styled("Current Branch: ", Style::default().fg(Color::Cyan)),
+            Span::styled("Current Branch: ", Style::default().fg(theme.accent)),
             Span::styled(
                 current_branch,
                 Style::default()
-                    .fg(Color::Magenta)
+                    .fg(theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
         ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Quick Commands:",
-            Style::default().fg(Color::Yellow),
+            Style::default().fg(theme.key),
         )]),
         Line::from(vec![Span::styled(
-            "  Press 'c' to run git commands",
-            Style::default().fg(Color::Gray),
+            format!(
+                "  Press {} to run git commands",
+                keymap.keys_label(&[Action::CommandMode])
+            ),
+            Style::default().fg(theme.muted),
         )]),
     ];
 
@@ -1495,24 +3102,24 @@ fn render_git_branch_info(f: &mut Frame, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn render_git_status_and_log(f: &mut Frame, area: Rect) {
+fn render_git_status_and_log(f: &mut Frame, area: Rect, theme: &Palette) {
     let layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(area);
 
     // Status information
-    render_git_status_detailed(f, layout[0]);
+    render_git_status_detailed(f, layout[0], theme);
 
     // Recent commits
-    render_git_recent_commits(f, layout[1]);
+    render_git_recent_commits(f, layout[1], theme);
 }
 
-fn render_git_status_detailed(f: &mut Frame, area: Rect) {
+fn render_git_status_detailed(f: &mut Frame, area: Rect, theme: &Palette) {
     use std::process::Command;
 
     let status_output = Command::new("git")
-        .args(&["status", "--porcelain"])
+        .args(["status", "--porcelain"])
         .output()
         .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
         .unwrap_or_else(|_| "Error getting status".to_string());
@@ -1520,7 +3127,7 @@ fn render_git_status_detailed(f: &mut Frame, area: Rect) {
     let status_lines: Vec<Line> = if status_output.trim().is_empty() {
         vec![Line::from(vec![Span::styled(
             "Working tree clean",
-            Style::default().fg(Color::Green),
+            Style::default().fg(theme.good),
         )])]
     } else {
         status_output
@@ -1534,18 +3141,18 @@ fn render_git_status_detailed(f: &mut Frame, area: Rect) {
                 };
 
                 let color = match status.trim() {
-                    "M" | "AM" => Color::Yellow,
-                    "A" | "AA" => Color::Green,
-                    "D" | "AD" => Color::Red,
-                    "R" | "AR" => Color::Blue,
-                    "C" | "AC" => Color::Cyan,
-                    "??" => Color::Gray,
-                    _ => Color::White,
+                    "M" | "AM" => theme.warn,
+                    "A" | "AA" => theme.good,
+                    "D" | "AD" => theme.bad,
+                    "R" | "AR" => theme.highlight,
+                    "C" | "AC" => theme.accent,
+                    "??" => theme.muted,
+                    _ => theme.text,
                 };
 
                 Line::from(vec![
                     Span::styled(format!("{} ", status), Style::default().fg(color)),
-                    Span::styled(filename, Style::default().fg(Color::White)),
+                    Span::styled(filename, Style::default().fg(theme.text)),
                 ])
             })
             .collect()
@@ -1563,11 +3170,11 @@ fn render_git_status_detailed(f: &mut Frame, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn render_git_recent_commits(f: &mut Frame, area: Rect) {
+fn render_git_recent_commits(f: &mut Frame, area: Rect, theme: &Palette) {
     use std::process::Command;
 
     let log_output = Command::new("git")
-        .args(&["log", "--oneline", "-10"])
+        .args(["log", "--oneline", "-10"])
         .output()
         .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
         .unwrap_or_else(|_| "Error getting log".to_string());
@@ -1575,7 +3182,7 @@ fn render_git_recent_commits(f: &mut Frame, area: Rect) {
     let log_lines: Vec<Line> = if log_output.trim().is_empty() {
         vec![Line::from(vec![Span::styled(
             "No commits yet",
-            Style::default().fg(Color::Gray),
+            Style::default().fg(theme.muted),
         )])]
     } else {
         log_output
@@ -1584,12 +3191,12 @@ fn render_git_recent_commits(f: &mut Frame, area: Rect) {
                 let parts: Vec<&str> = line.splitn(2, ' ').collect();
                 if parts.len() == 2 {
                     Line::from(vec![
-                        Span::styled(parts[0], Style::default().fg(Color::Yellow)),
+                        Span::styled(parts[0], Style::default().fg(theme.key)),
                         Span::styled(" ", Style::default()),
-                        Span::styled(parts[1], Style::default().fg(Color::White)),
+                        Span::styled(parts[1], Style::default().fg(theme.text)),
                     ])
                 } else {
-                    Line::from(vec![Span::styled(line, Style::default().fg(Color::White))])
+                    Line::from(vec![Span::styled(line, Style::default().fg(theme.text))])
                 }
             })
             .collect()
@@ -1605,3 +3212,574 @@ fn render_git_recent_commits(f: &mut Frame, area: Rect) {
         .wrap(Wrap { trim: true });
     f.render_widget(paragraph, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(pane: &OutputPane) -> Vec<&str> {
+        pane.visible().map(|(_, line)| line.as_str()).collect()
+    }
+
+    #[test]
+    fn test_output_pane_drops_oldest_beyond_capacity() {
+        let mut pane = OutputPane::new(3);
+        for i in 1..=5 {
+            pane.push(format!("line {}", i));
+        }
+        pane.push_err("oops");
+
+        assert_eq!(pane.len(), 3);
+        assert_eq!(texts(&pane), ["line 4", "line 5", "oops"]);
+        assert_eq!(pane.visible().last().unwrap().0, OutputKind::Stderr);
+
+        pane.clear();
+        assert!(pane.is_empty());
+        assert!(OutputPane::new(0).is_empty());
+    }
+
+    #[test]
+    fn test_output_pane_scroll_clamps() {
+        let mut pane = OutputPane::new(DEFAULT_OUTPUT_LINES);
+        for i in 1..=10 {
+            pane.push(i.to_string());
+        }
+        pane.set_viewport(4);
+        assert_eq!(texts(&pane), ["7", "8", "9", "10"]);
+
+        pane.page_up();
+        assert_eq!(texts(&pane), ["3", "4", "5", "6"]);
+        pane.page_up();
+        assert_eq!(pane.scroll(), 6);
+        assert_eq!(texts(&pane), ["1", "2", "3", "4"]);
+
+        // New output doesn't move a scrolled-back view
+        pane.push("11");
+        assert_eq!(texts(&pane), ["1", "2", "3", "4"]);
+
+        pane.scroll_down(100);
+        assert_eq!(pane.scroll(), 0);
+        assert_eq!(texts(&pane), ["8", "9", "10", "11"]);
+
+        // A pane taller than its contents can't scroll at all
+        pane.set_viewport(20);
+        pane.page_up();
+        assert_eq!(pane.scroll(), 0);
+        assert_eq!(pane.visible().count(), 11);
+    }
+
+    fn entry(pid: u32, name: &str) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            parent_pid: None,
+            name: name.to_string(),
+            cpu_usage: 0.0,
+            memory: 0,
+            start_time: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_processes() {
+        let processes = vec![
+            entry(4, "System"),
+            entry(1200, "notepad.exe"),
+            entry(3400, "Code.exe"),
+            entry(5120, "NOTEPAD++.exe"),
+        ];
+
+        // Empty query keeps everything in order
+        let all = filter_processes(&processes, "");
+        assert_eq!(all.len(), 4);
+
+        // Name match is case-insensitive
+        let pids: Vec<u32> = filter_processes(&processes, "Notepad")
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![1200, 5120]);
+
+        // PIDs can be searched too
+        let pids: Vec<u32> = filter_processes(&processes, "340")
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![3400]);
+
+        assert!(filter_processes(&processes, "chrome").is_empty());
+    }
+
+    #[test]
+    fn test_process_view_selection_follows_filter() {
+        let mut view = ProcessView {
+            processes: vec![entry(1, "alpha"), entry(2, "beta"), entry(3, "alphabet")],
+            ..ProcessView::default()
+        };
+
+        view.select_next(1);
+        view.select_next(1);
+        assert_eq!(view.selected_pid(), Some(2));
+
+        // Typing resets the selection to the first matching row
+        view.push_filter_char('a');
+        view.push_filter_char('l');
+        assert_eq!(view.selected_pid(), Some(1));
+        view.select_next(5);
+        assert_eq!(view.selected_pid(), Some(3));
+
+        view.clear_filter();
+        assert_eq!(view.visible().len(), 3);
+        assert!(!view.filter_mode);
+    }
+
+    #[test]
+    fn test_kill_asks_for_confirmation() {
+        let keymap = Keymap::default();
+        for key in ['k', 'K'] {
+            assert_eq!(
+                keymap.action(KeyCode::Char(key), PROCESSES_TAB),
+                Some(Action::Kill)
+            );
+        }
+
+        let mut app = App::default();
+        app.process_view.processes = vec![entry(42, "victim")];
+        app.process_view.table_state.select(Some(0));
+        app.perform(Action::Kill);
+        assert_eq!(app.process_view.pending_kill, Some(42));
+        assert_eq!(
+            app.process_view.status.as_deref(),
+            Some("Kill 42 (victim)? y/n")
+        );
+
+        app.process_view.confirm_kill(false);
+        assert_eq!(app.process_view.pending_kill, None);
+        assert_eq!(
+            app.process_view.status.as_deref(),
+            Some("Kill 42 cancelled")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_confirmed_kill_reports_in_status_line() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        let mut view = ProcessView {
+            processes: vec![entry(pid, "sleep")],
+            ..Default::default()
+        };
+        view.table_state.select(Some(0));
+        view.request_kill();
+        view.confirm_kill(true);
+        assert_eq!(
+            view.status,
+            Some(format!("Sent kill signal to process {}", pid))
+        );
+        assert!(!child.wait().unwrap().success());
+
+        view.pending_kill = Some(1);
+        view.confirm_kill(true);
+        assert!(view.status.unwrap().starts_with("kill 1: "));
+    }
+
+    #[test]
+    fn test_dashboard_model_collects_from_modules() {
+        let model = DashboardModel::collect();
+
+        // CPU counts come straight from nproc
+        assert_eq!(model.cpu.total, nproc::get_total_cpus());
+        assert_eq!(model.cpu.available, nproc::get_available_cpus());
+
+        // Memory comes from free
+        let memory = free::get_memory_for_tui();
+        assert_eq!(model.memory.total, memory.total);
+        assert!(model.memory.total > 0);
+        assert!((0.0..=1.0).contains(&model.memory.usage_ratio()));
+
+        // Disks and sensors mirror the df and sensors collectors
+        assert_eq!(model.disks.len(), df::get_disks_for_tui().len());
+        for disk in &model.disks {
+            assert!(disk.used() <= disk.total);
+        }
+        for reading in &model.sensors {
+            assert!(reading.temperature > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_dashboard_ratios() {
+        let memory = MemoryStats {
+            total: 8,
+            used: 2,
+            total_swap: 0,
+            used_swap: 0,
+        };
+        assert_eq!(memory.usage_ratio(), 0.25);
+        assert_eq!(memory.swap_ratio(), 0.0);
+
+        let disk = DiskStats {
+            name: "disk0".to_string(),
+            file_system: "NTFS".to_string(),
+            mount_point: "C:\\".to_string(),
+            total: 100,
+            available: 40,
+            remote: false,
+            read_only: false,
+            mount_options: Vec::new(),
+        };
+        assert_eq!(disk.used(), 60);
+        assert_eq!(disk.usage_ratio(), 0.6);
+    }
+
+    #[test]
+    fn test_env_editor_edit_then_confirm() {
+        env::set_env_var("WINIX_TUI_EDIT_TEST", "old").unwrap();
+        let mut editor = EnvEditor::default();
+        editor.reload();
+        editor.select_name("WINIX_TUI_EDIT_TEST");
+
+        editor.start_edit();
+        assert_eq!(
+            editor.mode,
+            EnvEditMode::EditValue {
+                name: "WINIX_TUI_EDIT_TEST".to_string(),
+                value: "old".to_string(),
+            }
+        );
+
+        for _ in 0..3 {
+            editor.backspace();
+        }
+        for c in "new".chars() {
+            editor.input_char(c);
+        }
+        editor.confirm();
+
+        assert!(!editor.is_editing());
+        assert_eq!(
+            editor.selected_name().as_deref(),
+            Some("WINIX_TUI_EDIT_TEST")
+        );
+        assert!(
+            editor
+                .vars
+                .contains(&("WINIX_TUI_EDIT_TEST".to_string(), "new".to_string()))
+        );
+        assert_eq!(
+            env::get_env_var("WINIX_TUI_EDIT_TEST").as_deref(),
+            Some("new")
+        );
+
+        editor.delete_selected();
+        assert!(env::get_env_var("WINIX_TUI_EDIT_TEST").is_none());
+        assert!(!editor.vars.iter().any(|(n, _)| n == "WINIX_TUI_EDIT_TEST"));
+    }
+
+    #[test]
+    fn test_env_editor_add_validates_name() {
+        let mut editor = EnvEditor::default();
+        editor.reload();
+
+        editor.start_add();
+        for c in "1BAD".chars() {
+            editor.input_char(c);
+        }
+        editor.confirm();
+        assert!(
+            editor
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("Invalid variable name")
+        );
+        assert_eq!(
+            editor.mode,
+            EnvEditMode::AddName {
+                name: "1BAD".to_string()
+            }
+        );
+
+        editor.cancel();
+        assert!(!editor.is_editing());
+        assert!(editor.error.is_none());
+
+        editor.start_add();
+        for c in "WINIX_TUI_ADD_TEST".chars() {
+            editor.input_char(c);
+        }
+        editor.confirm();
+        assert!(matches!(editor.mode, EnvEditMode::AddValue { .. }));
+        for c in "value".chars() {
+            editor.input_char(c);
+        }
+        editor.confirm();
+
+        assert!(!editor.is_editing());
+        assert_eq!(
+            env::get_env_var("WINIX_TUI_ADD_TEST").as_deref(),
+            Some("value")
+        );
+        assert_eq!(
+            editor.selected_name().as_deref(),
+            Some("WINIX_TUI_ADD_TEST")
+        );
+
+        env::remove_env_var("WINIX_TUI_ADD_TEST").unwrap();
+    }
+
+    fn names(browser: &FileBrowser) -> Vec<&str> {
+        browser.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_file_browser_navigation() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/guide.txt"), "hello\tworld\n").unwrap();
+        std::fs::write(root.join("b.txt"), "b").unwrap();
+        std::fs::write(root.join("a.bin"), [0u8, 1, 200]).unwrap();
+
+        let mut browser = FileBrowser::new(root.clone());
+        assert_eq!(names(&browser), ["docs", "a.bin", "b.txt"]);
+        assert_eq!(browser.list_state.selected(), Some(0));
+        assert_eq!(browser.preview, "guide.txt");
+
+        browser.select_next(1);
+        assert_eq!(browser.selected_path(), Some(root.join("a.bin")));
+        assert_eq!(browser.preview, "^@^AM-H");
+
+        // Enter on a file does not change directory
+        browser.enter();
+        assert_eq!(browser.current_dir, root);
+
+        browser.select_previous(5);
+        browser.enter();
+        assert_eq!(browser.current_dir, root.join("docs"));
+        assert_eq!(names(&browser), ["guide.txt"]);
+        assert_eq!(browser.preview, "hello   world");
+
+        browser.go_up();
+        assert_eq!(browser.current_dir, root);
+        assert_eq!(browser.selected_entry().unwrap().name, "docs");
+    }
+
+    #[test]
+    fn test_file_browser_empty_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut browser = FileBrowser::new(dir.path().to_path_buf());
+        assert!(browser.entries.is_empty());
+        assert_eq!(browser.list_state.selected(), None);
+
+        browser.select_next(1);
+        browser.enter();
+        assert_eq!(browser.current_dir, dir.path());
+        assert!(browser.preview.is_empty());
+    }
+
+    #[test]
+    fn test_help_lists_every_bound_action() {
+        let keymap = Keymap::default();
+
+        for tab in 0..TAB_TITLES.len() {
+            let help = keymap.help(tab);
+            for action in Action::ALL {
+                let listed = help.iter().any(|entry| entry.action == action);
+                let bound = !keymap.keys_for(action).is_empty();
+                assert_eq!(
+                    listed,
+                    bound && action.applies_to(tab),
+                    "{:?} on tab {}",
+                    action,
+                    tab
+                );
+            }
+        }
+
+        let files = keymap.help(FILES_TAB);
+        let help = files
+            .iter()
+            .find(|e| e.action == Action::ToggleHelp)
+            .unwrap();
+        assert_eq!(help.keys, "? / h / H");
+        assert!(!files.iter().any(|e| e.action == Action::Kill));
+        assert_eq!(
+            keymap.action(KeyCode::Enter, FILES_TAB),
+            Some(Action::Descend)
+        );
+        assert_eq!(keymap.action(KeyCode::Enter, ENV_TAB), Some(Action::Edit));
+        assert_eq!(keymap.action(KeyCode::Enter, 0), None);
+    }
+
+    #[test]
+    fn test_panel_hints_follow_the_keymap() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.hints(FILE_HINTS),
+            "Enter: open dir | Backspace: up | o/O: open file"
+        );
+        assert_eq!(
+            keymap.hints(ENV_HINTS),
+            "Enter/e: Edit | a: Add | d/Del: Delete | ↑/↓: Scroll"
+        );
+        assert_eq!(
+            keymap.keys_label(&[Action::PreviousTab, Action::NextTab]),
+            "←/→/Tab"
+        );
+    }
+
+    #[test]
+    fn test_remap_updates_help() {
+        let mut keymap = Keymap::default();
+        keymap.remap(Action::Kill, &[KeyCode::Char('x')]).unwrap();
+
+        let help = keymap.help(PROCESSES_TAB);
+        let kill = help.iter().find(|e| e.action == Action::Kill).unwrap();
+        assert_eq!(kill.keys, "x");
+        assert_eq!(
+            keymap.action(KeyCode::Char('x'), PROCESSES_TAB),
+            Some(Action::Kill)
+        );
+        assert_eq!(keymap.action(KeyCode::Char('k'), PROCESSES_TAB), None);
+        // Delete still deletes variables on the Environment tab
+        assert_eq!(
+            keymap.action(KeyCode::Delete, ENV_TAB),
+            Some(Action::Delete)
+        );
+
+        assert_eq!(
+            keymap.hints(PROCESS_HINTS),
+            "/: Filter | ↑/↓/PgUp/PgDn: Scroll | x: Kill | Esc: Clear"
+        );
+
+        keymap.remap(Action::Kill, &[]).unwrap();
+        assert_eq!(
+            keymap.hints(PROCESS_HINTS),
+            "/: Filter | ↑/↓/PgUp/PgDn: Scroll | Esc: Clear"
+        );
+        assert!(
+            !keymap
+                .help(PROCESSES_TAB)
+                .iter()
+                .any(|e| e.action == Action::Kill)
+        );
+    }
+
+    #[test]
+    fn test_remap_rejects_conflicts() {
+        let mut keymap = Keymap::default();
+
+        // `q` quits everywhere, so it can't also kill on the Processes tab
+        let err = keymap
+            .remap(Action::Kill, &[KeyCode::Char('q')])
+            .unwrap_err();
+        assert!(err.contains("Quit"), "{}", err);
+        assert_eq!(keymap, Keymap::default());
+
+        // `o` only opens files, so the Processes tab is free to use it
+        keymap.remap(Action::Kill, &[KeyCode::Char('o')]).unwrap();
+        assert_eq!(
+            keymap.action(KeyCode::Char('o'), PROCESSES_TAB),
+            Some(Action::Kill)
+        );
+        assert_eq!(
+            keymap.action(KeyCode::Char('o'), FILES_TAB),
+            Some(Action::Open)
+        );
+
+        // Rebinding an action to a key it already has is not a conflict
+        keymap.remap(Action::Quit, &[KeyCode::Char('q')]).unwrap();
+    }
+
+    #[test]
+    fn test_config_file_overrides_defaults() {
+        let config = TuiConfig::parse(
+            "# winix tui\n\
+             refresh_ms = 500\n\
+             \n\
+             [theme]\n\
+             accent = \"magenta\"  # highlight\n\
+             dim = \"#303030\"\n\
+             \n\
+             [panels]\n\
+             Sensors = false\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.refresh_interval, Duration::from_millis(500));
+        assert_eq!(config.theme.accent, Color::Magenta);
+        assert_eq!(config.theme.dim, Color::Rgb(0x30, 0x30, 0x30));
+        assert_eq!(config.theme.text, Palette::default().text);
+        let sensors = TAB_TITLES.iter().position(|t| *t == "Sensors").unwrap();
+        assert!(!config.panels[sensors]);
+        assert_eq!(config.panels.iter().filter(|&&on| !on).count(), 1);
+
+        // Disabled panels are skipped when cycling tabs
+        let mut app = App::with_config(config);
+        app.selected_tab = sensors - 1;
+        app.next_tab();
+        assert_eq!(app.selected_tab, (sensors + 1) % TAB_TITLES.len());
+        app.previous_tab();
+        assert_eq!(app.selected_tab, sensors - 1);
+
+        // The interval can still be changed at runtime, within bounds
+        app.perform(Action::RefreshSlower);
+        assert_eq!(app.config.refresh_interval, Duration::from_secs(1));
+        for _ in 0..10 {
+            app.perform(Action::RefreshFaster);
+        }
+        assert_eq!(app.config.refresh_interval, MIN_REFRESH_INTERVAL);
+
+        // `#` starts a comment only outside quotes
+        let config = TuiConfig::parse(
+            "[theme] # colors\n\
+             accent = \"#ff8800\" # orange, not \"#00ff00\"\n\
+             key = \"#00ff00\"# no space before the comment\n\
+             # a comment with a \" quote\n\
+             text = \"208\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.theme.accent, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(config.theme.key, Color::Rgb(0x00, 0xff, 0x00));
+        assert_eq!(config.theme.text, Color::Indexed(208));
+
+        let err = TuiConfig::parse("[theme]\naccent = \"chartreuse-ish\"").unwrap_err();
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(TuiConfig::parse("refresh = 5").is_err());
+        assert!(TuiConfig::parse("[panels]\nfiles = maybe").is_err());
+    }
+
+    #[test]
+    fn test_no_color_and_status_colors() {
+        let config = TuiConfig::parse("[theme]\ngood = \"blue\"\nbad = \"#ff0000\"\n").unwrap();
+        assert_eq!(config.theme.good, Color::Blue);
+        assert_eq!(config.theme.bad, Color::Rgb(0xff, 0, 0));
+        assert_eq!(config.theme.level(0.5), Color::Blue);
+        assert_eq!(config.theme.level(0.8), Color::Yellow);
+        assert_eq!(config.theme.level(0.95), Color::Rgb(0xff, 0, 0));
+
+        let themed = config.clone().with_no_color(None);
+        assert_eq!(themed.theme, config.theme);
+        // An empty NO_COLOR doesn't count, as the convention says
+        let themed = config.clone().with_no_color(Some("".as_ref()));
+        assert_eq!(themed.theme, config.theme);
+        let plain = config.with_no_color(Some("1".as_ref()));
+        assert_eq!(plain.theme, Palette::monochrome());
+        assert_eq!(plain.theme.level(0.95), Color::Reset);
+    }
+
+    #[test]
+    fn test_absent_config_uses_defaults() {
+        let config = TuiConfig::parse("").unwrap();
+        assert_eq!(config, TuiConfig::default());
+        assert_eq!(config.refresh_interval, DEFAULT_REFRESH_INTERVAL);
+        assert_eq!(config.theme.accent, Color::Cyan);
+        assert!(config.panels.iter().all(|&on| on));
+        assert_eq!(App::with_config(config).selected_tab, 0);
+    }
+}
@@ -1,6 +1,260 @@
+use serde::Serialize;
 use sysinfo::{Networks, System};
 
-pub fn execute() {
+/// The fields printed by POSIX `uname -a`, plus processor and hardware platform
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UnameInfo {
+    pub sysname: String,
+    pub nodename: String,
+    pub release: String,
+    pub version: String,
+    pub machine: String,
+    pub processor: Option<String>,
+    pub hardware_platform: Option<String>,
+    /// `WSL1`/`WSL2` when this Linux is running under the Windows Subsystem for Linux
+    pub wsl: Option<String>,
+}
+
+impl UnameInfo {
+    /// `uname -o`: GNU's name for the system, tagged with the WSL generation when there is one
+    pub fn operating_system(&self) -> String {
+        let name = match self.sysname.as_str() {
+            "Linux" => "GNU/Linux",
+            "Windows_NT" => "Windows",
+            other => other,
+        };
+        match &self.wsl {
+            Some(wsl) => format!("{} {}", name, wsl),
+            None => name.to_string(),
+        }
+    }
+
+    /// Render as a JSON object; unknown processor/platform become `null`
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Which WSL generation a `/proc/version` line comes from, if any.
+/// WSL2 kernels are built as `microsoft-standard-WSL2`; WSL1 reports a `Microsoft` build.
+pub fn wsl_from_proc_version(version: &str) -> Option<&'static str> {
+    let version = version.to_lowercase();
+    if version.contains("wsl2") || version.contains("microsoft-standard") {
+        Some("WSL2")
+    } else if version.contains("microsoft") || version.contains("wsl") {
+        Some("WSL1")
+    } else {
+        None
+    }
+}
+
+/// The kernel's own word first; `WSL_DISTRO_NAME` catches kernels built without the tag
+#[cfg(target_os = "linux")]
+fn detect_wsl() -> Option<String> {
+    let from_kernel = std::fs::read_to_string("/proc/version")
+        .ok()
+        .and_then(|version| wsl_from_proc_version(&version));
+    match from_kernel {
+        Some(wsl) => Some(wsl.to_string()),
+        None => std::env::var_os("WSL_DISTRO_NAME").map(|_| "WSL".to_string()),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn detect_wsl() -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+fn c_field(field: &[libc::c_char]) -> String {
+    let bytes: Vec<u8> = field
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Board or product name, from the device tree on ARM boards or DMI elsewhere
+#[cfg(target_os = "linux")]
+fn hardware_platform() -> Option<String> {
+    [
+        "/sys/firmware/devicetree/base/model",
+        "/sys/class/dmi/id/product_name",
+    ]
+    .iter()
+    .filter_map(|path| std::fs::read(path).ok())
+    .map(|bytes| {
+        String::from_utf8_lossy(&bytes)
+            .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+            .to_string()
+    })
+    .find(|name| !name.is_empty())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn hardware_platform() -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+pub fn collect() -> UnameInfo {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return UnameInfo {
+            sysname: std::env::consts::OS.to_string(),
+            nodename: System::host_name().unwrap_or_default(),
+            machine: std::env::consts::ARCH.to_string(),
+            wsl: detect_wsl(),
+            ..Default::default()
+        };
+    }
+
+    UnameInfo {
+        sysname: c_field(&uts.sysname),
+        nodename: c_field(&uts.nodename),
+        release: c_field(&uts.release),
+        version: c_field(&uts.version),
+        machine: c_field(&uts.machine),
+        processor: Some(System::cpu_arch()),
+        hardware_platform: hardware_platform(),
+        wsl: detect_wsl(),
+    }
+}
+
+/// `uname -m` style name for a `PROCESSOR_ARCHITECTURE` value
+#[cfg(windows)]
+fn windows_machine(architecture: &str) -> String {
+    match architecture.to_uppercase().as_str() {
+        "AMD64" => "x86_64".to_string(),
+        "ARM64" => "aarch64".to_string(),
+        "X86" => "i686".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+#[cfg(windows)]
+pub fn collect() -> UnameInfo {
+    use winapi::shared::ntdef::NTSTATUS;
+    use winapi::um::winnt::RTL_OSVERSIONINFOW;
+
+    // Unlike GetVersionEx, RtlGetVersion is not subject to manifest-based version lies
+    #[link(name = "ntdll")]
+    unsafe extern "system" {
+        fn RtlGetVersion(info: *mut RTL_OSVERSIONINFOW) -> NTSTATUS;
+    }
+
+    let mut info: RTL_OSVERSIONINFOW = unsafe { std::mem::zeroed() };
+    info.dwOSVersionInfoSize = std::mem::size_of::<RTL_OSVERSIONINFOW>() as u32;
+    let (release, version) = if unsafe { RtlGetVersion(&mut info) } == 0 {
+        (
+            format!("{}.{}", info.dwMajorVersion, info.dwMinorVersion),
+            info.dwBuildNumber.to_string(),
+        )
+    } else {
+        (String::new(), String::new())
+    };
+
+    // A 32-bit process on 64-bit Windows sees the native architecture in PROCESSOR_ARCHITEW6432
+    let machine = std::env::var("PROCESSOR_ARCHITEW6432")
+        .or_else(|_| std::env::var("PROCESSOR_ARCHITECTURE"))
+        .map(|arch| windows_machine(&arch))
+        .unwrap_or_else(|_| std::env::consts::ARCH.to_string());
+
+    UnameInfo {
+        sysname: "Windows_NT".to_string(),
+        nodename: std::env::var("COMPUTERNAME")
+            .ok()
+            .or_else(System::host_name)
+            .unwrap_or_default(),
+        release,
+        version,
+        machine,
+        processor: std::env::var("PROCESSOR_IDENTIFIER").ok(),
+        hardware_platform: None,
+        wsl: None,
+    }
+}
+
+/// The fields picked by `uname`'s short options (`-a` for all), space-separated in
+/// POSIX order; `-a` leaves out a processor or hardware platform nobody knows
+pub fn format_selected(info: &UnameInfo, flags: &str) -> Result<String, String> {
+    if let Some(bad) = flags.chars().find(|c| !"asnrvmpio".contains(*c)) {
+        return Err(format!("invalid option -- '{}'", bad));
+    }
+    let all = flags.contains('a');
+    let wanted = |flag: char| all || flags.contains(flag);
+    let unknown = "unknown".to_string();
+
+    let mut fields = Vec::new();
+    if wanted('s') || flags.is_empty() {
+        fields.push(info.sysname.clone());
+    }
+    if wanted('n') {
+        fields.push(info.nodename.clone());
+    }
+    if wanted('r') {
+        fields.push(info.release.clone());
+    }
+    if wanted('v') {
+        fields.push(info.version.clone());
+    }
+    if wanted('m') {
+        fields.push(info.machine.clone());
+    }
+    for (flag, value) in [('p', &info.processor), ('i', &info.hardware_platform)] {
+        match value {
+            Some(value) if wanted(flag) => fields.push(value.clone()),
+            None if flags.contains(flag) => fields.push(unknown.clone()),
+            _ => {}
+        }
+    }
+    if wanted('o') {
+        fields.push(info.operating_system());
+    }
+    Ok(fields.join(" "))
+}
+
+/// Turn `-s -nr --all ...` into the short flags they stand for
+fn short_flags(args: &[String]) -> Result<String, String> {
+    let mut flags = String::new();
+    for arg in args {
+        match arg.as_str() {
+            "--all" => flags.push('a'),
+            "--kernel-name" => flags.push('s'),
+            "--nodename" => flags.push('n'),
+            "--kernel-release" => flags.push('r'),
+            "--kernel-version" => flags.push('v'),
+            "--machine" => flags.push('m'),
+            "--processor" => flags.push('p'),
+            "--hardware-platform" => flags.push('i'),
+            "--operating-system" => flags.push('o'),
+            other => match other.strip_prefix('-') {
+                Some(short) if !short.is_empty() && !short.starts_with('-') => {
+                    flags.push_str(short)
+                }
+                _ => return Err(format!("extra operand '{}'", other)),
+            },
+        }
+    }
+    Ok(flags)
+}
+
+pub fn execute(args: &[String]) {
+    if args.iter().any(|arg| arg == "--json") {
+        println!("{}", collect().to_json());
+        return;
+    }
+
+    // With options, print the POSIX one-liner; without, the detailed report below
+    if !args.is_empty() {
+        match short_flags(args).and_then(|flags| format_selected(&collect(), &flags)) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("uname: {}", e),
+        }
+        return;
+    }
+
     let mut sys = System::new_all();
 
     sys.refresh_all();
@@ -58,3 +312,69 @@ fn format_memory(bytes: u64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_reports_machine() {
+        let info = collect();
+        assert!(!info.machine.is_empty());
+        assert!(!info.sysname.is_empty());
+
+        let json: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(json["sysname"], info.sysname.as_str());
+        assert_eq!(json["machine"], info.machine.as_str());
+    }
+
+    #[test]
+    fn test_to_json_escapes_and_nulls() {
+        let info = UnameInfo {
+            sysname: "Linux".to_string(),
+            nodename: "host".to_string(),
+            release: "6.1.0".to_string(),
+            version: "#1 SMP \"test\"".to_string(),
+            machine: "x86_64".to_string(),
+            processor: Some("x86_64".to_string()),
+            hardware_platform: None,
+            wsl: None,
+        };
+        assert_eq!(
+            info.to_json(),
+            r##"{"sysname":"Linux","nodename":"host","release":"6.1.0","version":"#1 SMP \"test\"","machine":"x86_64","processor":"x86_64","hardware_platform":null,"wsl":null}"##
+        );
+    }
+
+    #[test]
+    fn test_wsl_detected_from_proc_version() {
+        let wsl2 = "Linux version 5.15.153.1-microsoft-standard-WSL2 (root@941d701f84f1) \
+                    (gcc (GCC) 11.2.0) #1 SMP Fri Mar 29 23:14:13 UTC 2024";
+        let wsl1 = "Linux version 4.4.0-19041-Microsoft (Microsoft@Microsoft.com) \
+                    (gcc version 5.4.0 (GCC) ) #1237-Microsoft Sat Sep 11 14:32:00 PST 2021";
+        let native = "Linux version 6.1.0-18-amd64 (debian-kernel@lists.debian.org) \
+                      (gcc-12 (Debian 12.2.0-14) 12.2.0) #1 SMP PREEMPT_DYNAMIC Debian 6.1.76-1";
+        assert_eq!(wsl_from_proc_version(wsl2), Some("WSL2"));
+        assert_eq!(wsl_from_proc_version(wsl1), Some("WSL1"));
+        assert_eq!(wsl_from_proc_version(native), None);
+
+        let info = UnameInfo {
+            sysname: "Linux".to_string(),
+            nodename: "host".to_string(),
+            release: "5.15.153.1-microsoft-standard-WSL2".to_string(),
+            machine: "x86_64".to_string(),
+            wsl: wsl_from_proc_version(wsl2).map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(format_selected(&info, "o").unwrap(), "GNU/Linux WSL2");
+        assert_eq!(
+            format_selected(&info, "a").unwrap(),
+            "Linux host 5.15.153.1-microsoft-standard-WSL2  x86_64 GNU/Linux WSL2"
+        );
+        assert_eq!(format_selected(&info, "sp").unwrap(), "Linux unknown");
+        assert!(format_selected(&info, "x").is_err());
+
+        let json: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(json["wsl"], "WSL2");
+    }
+}
@@ -1,14 +1,234 @@
+use serde::Serialize;
 use sysinfo::System;
 
-pub fn execute() {
-    let mut sys = System::new_all();
-    let load_avg = System::load_average();
-    sys.refresh_all();
-
-    println!("System booted at {} seconds", System::boot_time());
-    println!("System running since {} seconds", System::uptime());
-    println!(
-        "one minute: {}%, five minutes: {}%, fifteen minutes: {}%",
-        load_avg.one, load_avg.five, load_avg.fifteen,
-    );
+/// Everything `uptime` reports, gathered at one instant
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UptimeInfo {
+    /// Seconds since the Unix epoch when the system booted
+    pub boot_time: u64,
+    pub uptime_secs: u64,
+    pub users: usize,
+    /// 1, 5 and 15 minute load averages
+    #[serde(rename = "load_average")]
+    pub load: (f64, f64, f64),
+}
+
+impl UptimeInfo {
+    /// Render as a JSON object with the load averages as a three-element array
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Read boot time, uptime, logged-in users and load averages
+pub fn collect() -> UptimeInfo {
+    let load = System::load_average();
+    UptimeInfo {
+        boot_time: System::boot_time(),
+        uptime_secs: System::uptime(),
+        users: logged_in_users(),
+        load: (load.one, load.five, load.fifteen),
+    }
+}
+
+/// Render `info` as `uptime [--json | -l | -p]` would print it
+pub fn render(info: &UptimeInfo, args: &[String]) -> Result<String, String> {
+    match args {
+        [] => Ok(format_uptime_line(info.uptime_secs, info.users, info.load)),
+        [flag] if flag == "--json" => Ok(info.to_json()),
+        [flag] if flag == "-l" || flag == "--load" => Ok(format!(
+            "{:.2} {:.2} {:.2}",
+            info.load.0, info.load.1, info.load.2
+        )),
+        [flag] if flag == "-p" || flag == "--pretty" => Ok(format_pretty(info.uptime_secs)),
+        [flag] if flag.starts_with('-') => Err(format!("invalid option '{}'", flag)),
+        _ => Err("Usage: uptime [--json | -l | -p]".to_string()),
+    }
+}
+
+pub fn execute(args: &[String]) {
+    match render(&collect(), args) {
+        Ok(output) => println!("{}", output),
+        Err(e) => eprintln!("uptime: {}", e),
+    }
+}
+
+/// Format uptime like `uptime -p`, e.g. `up 2 days, 3 hours, 1 minute`
+pub fn format_pretty(uptime_secs: u64) -> String {
+    let parts: Vec<String> = [
+        (uptime_secs / 86400, "day"),
+        ((uptime_secs % 86400) / 3600, "hour"),
+        ((uptime_secs % 3600) / 60, "minute"),
+    ]
+    .iter()
+    .filter(|(value, _)| *value > 0)
+    .map(|(value, unit)| format!("{} {}{}", value, unit, if *value == 1 { "" } else { "s" }))
+    .collect();
+
+    if parts.is_empty() {
+        "up 0 minutes".to_string()
+    } else {
+        format!("up {}", parts.join(", "))
+    }
+}
+
+/// Format the standard `up ..., N users, load average: ...` line
+pub fn format_uptime_line(uptime_secs: u64, users: usize, load: (f64, f64, f64)) -> String {
+    let days = uptime_secs / 86400;
+    let hours = (uptime_secs % 86400) / 3600;
+    let minutes = (uptime_secs % 3600) / 60;
+
+    let mut up = String::new();
+    if days > 0 {
+        up.push_str(&format!(
+            "{} day{}, ",
+            days,
+            if days == 1 { "" } else { "s" }
+        ));
+    }
+    if hours > 0 {
+        up.push_str(&format!("{:2}:{:02}", hours, minutes));
+    } else {
+        up.push_str(&format!("{} min", minutes));
+    }
+
+    format!(
+        "up {},  {} user{},  load average: {:.2}, {:.2}, {:.2}",
+        up,
+        users,
+        if users == 1 { "" } else { "s" },
+        load.0,
+        load.1,
+        load.2
+    )
+}
+
+/// Number of logged-in users, counted from active interactive sessions
+#[cfg(windows)]
+pub fn logged_in_users() -> usize {
+    use std::ptr;
+    use winapi::shared::minwindef::{BOOL, DWORD};
+    use winapi::shared::ntdef::{HANDLE, LPWSTR};
+
+    // wtsapi32 is not bound by winapi 0.3, so declare the two calls we need
+    #[repr(C)]
+    #[allow(non_snake_case)]
+    struct WTS_SESSION_INFOW {
+        SessionId: DWORD,
+        pWinStationName: LPWSTR,
+        State: i32,
+    }
+    const WTS_ACTIVE: i32 = 0;
+
+    #[link(name = "wtsapi32")]
+    unsafe extern "system" {
+        fn WTSEnumerateSessionsW(
+            server: HANDLE,
+            reserved: DWORD,
+            version: DWORD,
+            sessions: *mut *mut WTS_SESSION_INFOW,
+            count: *mut DWORD,
+        ) -> BOOL;
+        fn WTSFreeMemory(memory: *mut std::ffi::c_void);
+    }
+
+    unsafe {
+        let mut sessions: *mut WTS_SESSION_INFOW = ptr::null_mut();
+        let mut count: DWORD = 0;
+        if WTSEnumerateSessionsW(ptr::null_mut(), 0, 1, &mut sessions, &mut count) == 0 {
+            return 1;
+        }
+
+        let active = std::slice::from_raw_parts(sessions, count as usize)
+            .iter()
+            .filter(|session| session.State == WTS_ACTIVE)
+            .count();
+        WTSFreeMemory(sessions as *mut _);
+
+        // Services-only machines still have whoever is running this
+        active.max(1)
+    }
+}
+
+/// Number of logged-in users, counted from utmp login records
+#[cfg(unix)]
+pub fn logged_in_users() -> usize {
+    let mut users = 0;
+    unsafe {
+        libc::setutxent();
+        loop {
+            let entry = libc::getutxent();
+            if entry.is_null() {
+                break;
+            }
+            if (*entry).ut_type == libc::USER_PROCESS {
+                users += 1;
+            }
+        }
+        libc::endutxent();
+    }
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_line_reports_users() {
+        let users = logged_in_users();
+        let line = format_uptime_line(System::uptime(), users, (0.0, 0.0, 0.0));
+        assert!(line.contains("user"));
+        assert!(line.contains(&format!(" {} user", users)));
+    }
+
+    #[test]
+    fn test_json_and_load_outputs() {
+        let info = collect();
+        let json = render(&info, &["--json".to_string()]).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let uptime = parsed["uptime_secs"]
+            .as_u64()
+            .expect("uptime_secs should be a number");
+        assert!(uptime > 0);
+        assert_eq!(uptime, info.uptime_secs);
+        assert_eq!(parsed["boot_time"].as_u64(), Some(info.boot_time));
+        assert_eq!(parsed["users"].as_u64(), Some(info.users as u64));
+        let loads = parsed["load_average"]
+            .as_array()
+            .expect("load_average should be an array");
+        assert_eq!(loads.len(), 3);
+        assert!(loads.iter().all(|value| value.is_f64()));
+
+        let load = render(&info, &["-l".to_string()]).unwrap();
+        let numbers: Vec<f64> = load
+            .split_whitespace()
+            .map(|value| value.parse().unwrap())
+            .collect();
+        assert_eq!(numbers.len(), 3);
+
+        assert!(render(&info, &["-x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_format_pretty() {
+        assert_eq!(
+            format_pretty(86400 + 3 * 3600 + 60),
+            "up 1 day, 3 hours, 1 minute"
+        );
+        assert_eq!(format_pretty(2 * 3600), "up 2 hours");
+        assert_eq!(format_pretty(30), "up 0 minutes");
+    }
+
+    #[test]
+    fn test_format_uptime_line() {
+        assert_eq!(
+            format_uptime_line(3 * 86400 + 2 * 3600 + 5 * 60, 2, (0.15, 0.1, 0.05)),
+            "up 3 days,  2:05,  2 users,  load average: 0.15, 0.10, 0.05"
+        );
+        assert_eq!(
+            format_uptime_line(5 * 60, 1, (0.0, 0.0, 0.0)),
+            "up 5 min,  1 user,  load average: 0.00, 0.00, 0.00"
+        );
+    }
 }
@@ -0,0 +1,78 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Parse the SECONDS argument of `--watch`, which may be fractional but must be positive
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    // `try_from_secs_f64` refuses NaN, negatives and values too large for a Duration
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| *secs > 0.0)
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("invalid --watch interval '{}'", value))
+}
+
+/// Longest single wait for a key, so huge intervals never reach the event backend
+const MAX_POLL: Duration = Duration::from_secs(60);
+
+/// Sleep for `interval`, returning early with `true` on Ctrl+C or `q`
+pub fn wait_for_quit(interval: Duration) -> io::Result<bool> {
+    // A deadline further out than `Instant` can hold never arrives; only a key ends the wait
+    let deadline = Instant::now().checked_add(interval);
+    loop {
+        let remaining = deadline.map_or(Duration::MAX, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        });
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        if !event::poll(remaining.min(MAX_POLL))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && (key.code == KeyCode::Char('q')
+                || (key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL)))
+        {
+            return Ok(true);
+        }
+    }
+}
+
+/// Clear the screen and draw `frame` from the top-left corner
+pub fn draw_frame<W: Write>(out: &mut W, frame: &str) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    // Raw mode doesn't return the carriage on a bare newline
+    write!(out, "{}", frame.replace('\n', "\r\n"))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_interval("2"), Ok(Duration::from_secs(2)));
+        assert!(parse_interval("0").is_err());
+        assert!(parse_interval("-1").is_err());
+        assert!(parse_interval("inf").is_err());
+        assert!(parse_interval("NaN").is_err());
+        assert!(parse_interval("1e300").is_err());
+        assert!(parse_interval("soon").is_err());
+    }
+
+    #[test]
+    fn test_draw_frame_clears_before_drawing() {
+        let mut out = Vec::new();
+        draw_frame(&mut out, "header\nrow\n").unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1b[2J"));
+        assert!(out.ends_with("header\r\nrow\r\n"));
+    }
+}
@@ -1,4 +1,4 @@
-use winix::ansi::{AnsiEvent, AnsiParser}; // Adjust based on your module path
+use winix::ansi::{AnsiEvent, AnsiParser, strip_ansi, visible_width}; // Adjust based on your module path
 
 #[test]
 fn test_ls_color_output_parses_color_events() {
@@ -16,3 +16,28 @@ fn test_ls_color_output_parses_color_events() {
         "Expected at least one SetColor event in ANSI output"
     );
 }
+
+#[test]
+fn test_strip_ansi_removes_color_codes() {
+    let colored = "\x1b[1;31mError:\x1b[0m file \x1b[32mnot\x1b[39m found\x1b[K";
+    assert_eq!(strip_ansi(colored), "Error: file not found");
+
+    let titled = "\x1b]0;winix\x07prompt\x1b]8;;http://x\x1b\\link";
+    assert_eq!(strip_ansi(titled), "promptlink");
+}
+
+#[test]
+fn test_visible_width_ignores_escapes() {
+    let colored = "\x1b[31mHello\x1b[0m, \x1b[1mwörld\x1b[0m";
+    assert_eq!(visible_width(colored), "Hello, wörld".chars().count());
+    assert_eq!(visible_width("plain"), 5);
+}
+
+#[cfg(windows)]
+#[test]
+fn test_enable_vt_mode_is_idempotent() {
+    // Without an attached console (e.g. redirected CI output) both calls fail the same way
+    let first = winix::ansi::enable_vt_mode();
+    let second = winix::ansi::enable_vt_mode();
+    assert_eq!(first, second);
+}
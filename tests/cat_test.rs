@@ -1,6 +1,10 @@
-use std::io::Write;
+use std::io::{self, Read, Write};
 use tempfile::NamedTempFile;
-use winix::cat::{cat, cat_async_to_string}; // <- Adjust path if not in `lib.rs`
+use winix::cat::{
+    CHUNK_SIZE, CatOptions, TacOptions, byte_lines, cat, cat_async_to_string, cat_to_writer,
+    cat_with_options, copy_stream, expand_tabs, parse_args, parse_tac_args, show_nonprinting, tac,
+    tac_str,
+}; // <- Adjust path if not in `lib.rs`
 
 /// Create a temporary file with given content
 fn create_temp_file(content: &str) -> NamedTempFile {
@@ -28,3 +32,212 @@ async fn test_cat_async_to_string() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap().trim(), "async hello");
 }
+
+#[test]
+fn test_cat_expands_leading_tab() {
+    let file = create_temp_file("\tindented\nab\tc\n");
+    let options = CatOptions {
+        tabs: Some(4),
+        ..CatOptions::default()
+    };
+
+    let result = cat_with_options(vec![file.path()], &options).unwrap();
+    assert_eq!(result, "    indented\nab  c\n");
+    assert_eq!(expand_tabs("abcd\te", 4), "abcd    e");
+
+    let (options, _) = parse_args(&["--tabs=8".to_string()]).unwrap();
+    assert_eq!(options.tabs, Some(8));
+    assert!(parse_args(&["--tabs=0".to_string()]).is_err());
+    assert!(parse_args(&["--tabs=wide".to_string()]).is_err());
+}
+
+#[test]
+fn test_cat_number_width_grows_with_line_count() {
+    let options = CatOptions {
+        number: true,
+        ..CatOptions::default()
+    };
+
+    let small = create_temp_file(&"x\n".repeat(9999));
+    let result = cat_with_options(vec![small.path()], &options).unwrap();
+    assert!(result.starts_with("   1\tx\n"));
+    assert!(result.ends_with("9999\tx\n"));
+
+    let large = create_temp_file(&"x\n".repeat(10000));
+    let result = cat_with_options(vec![large.path()], &options).unwrap();
+    assert!(result.starts_with("    1\tx\n"));
+    assert!(result.ends_with("10000\tx\n"));
+}
+
+#[test]
+fn test_show_nonprinting() {
+    assert_eq!(show_nonprinting(b"a\tb\n"), "a\tb\n");
+    assert_eq!(show_nonprinting(&[0, 7, 27, 127]), "^@^G^[^?");
+    assert_eq!(
+        show_nonprinting(&[0xC3, 0xA9, 0x80, 0xFF]),
+        "M-CM-)M-^@M-^?"
+    );
+}
+
+#[test]
+fn test_cat_shortcut_flags() {
+    let args = |list: &[&str]| -> Vec<String> { list.iter().map(|s| s.to_string()).collect() };
+    let file = create_temp_file("a\tb\x07\nc\x1b\n");
+    let path = file.path().to_string_lossy().to_string();
+
+    let (options, files) = parse_args(&args(&["-e", &path])).unwrap();
+    assert!(options.show_nonprinting && options.show_ends && !options.show_tabs);
+    assert_eq!(files, [path.as_str()]);
+    let shown = cat_with_options(files, &options).unwrap();
+    assert_eq!(shown, "a\tb^G$\nc^[$\n");
+
+    let (options, files) = parse_args(&args(&["-t", &path])).unwrap();
+    assert!(options.show_nonprinting && options.show_tabs && !options.show_ends);
+    assert_eq!(cat_with_options(files, &options).unwrap(), "a^Ib^G\nc^[\n");
+
+    for all in ["-A", "--show-all", "-vET", "-et"] {
+        let (options, files) = parse_args(&args(&[all, &path])).unwrap();
+        assert_eq!(
+            cat_with_options(files, &options).unwrap(),
+            "a^Ib^G$\nc^[$\n",
+            "{}",
+            all
+        );
+    }
+
+    let (options, _) = parse_args(&args(&["-nE", &path])).unwrap();
+    assert!(options.number && options.show_ends && !options.show_nonprinting);
+    assert!(parse_args(&args(&["-x", &path])).is_err());
+    assert!(parse_args(&args(&["--show-everything"])).is_err());
+}
+
+#[test]
+fn test_tac_reverses_lines() {
+    let options = TacOptions::default();
+    assert_eq!(tac_str("one\ntwo\nthree\n", &options), "three\ntwo\none\n");
+    // Like GNU tac, a final unterminated line is emitted without a separator
+    assert_eq!(tac_str("one\ntwo", &options), "twoone\n");
+
+    let file = create_temp_file("a\nb\nc\n");
+    assert_eq!(tac(vec![file.path()], &options).unwrap(), "c\nb\na\n");
+}
+
+#[test]
+fn test_tac_custom_separator() {
+    let args: Vec<String> = ["-s", ",", "list.txt"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let (options, files) = parse_tac_args(&args).unwrap();
+    assert_eq!(files, ["list.txt"]);
+    assert_eq!(tac_str("x,y,z,", &options), "z,y,x,");
+
+    let before = TacOptions {
+        separator: ",".to_string(),
+        before: true,
+    };
+    assert_eq!(tac_str("x,y,z", &before), ",z,yx");
+
+    assert!(parse_tac_args(&["-s".to_string()]).is_err());
+}
+
+#[test]
+fn test_cat_large_file_is_byte_identical() {
+    let mut file = NamedTempFile::new().unwrap();
+    let mut expected = Vec::new();
+    for i in 0..400_000u32 {
+        // Invalid UTF-8 and lines straddling chunk boundaries must pass through untouched
+        writeln!(
+            expected,
+            "line {} \u{e9}{}",
+            i,
+            "x".repeat((i % 97) as usize)
+        )
+        .unwrap();
+        if i % 1000 == 0 {
+            expected.extend_from_slice(&[0xFF, 0xFE, b'\n']);
+        }
+    }
+    file.write_all(&expected).unwrap();
+    assert!(expected.len() > 8 * CHUNK_SIZE);
+
+    let mut out = Vec::new();
+    cat_to_writer(&[file.path()], &CatOptions::default(), &mut out).unwrap();
+    assert!(out == expected, "output differs from the input file");
+}
+
+#[test]
+fn test_cat_crlf_split_across_chunks() {
+    let mut input = vec![b'a'; CHUNK_SIZE - 1];
+    input.extend_from_slice(b"\r\nb\r\r\nc\r");
+
+    let mut out = Vec::new();
+    copy_stream(&input[..], &mut out).unwrap();
+    let mut expected = vec![b'a'; CHUNK_SIZE - 1];
+    expected.extend_from_slice(b"\nb\r\nc\n");
+    assert_eq!(out, expected);
+
+    let lines: Vec<Vec<u8>> = byte_lines(&b"one\r\ntwo\nthree"[..])
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(lines, [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]);
+}
+
+/// Produces `remaining` bytes of text on demand, remembering the largest read
+struct GeneratedInput {
+    remaining: usize,
+    largest_read: usize,
+}
+
+impl Read for GeneratedInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.largest_read = self.largest_read.max(buf.len());
+        let n = buf.len().min(self.remaining);
+        for (i, byte) in buf[..n].iter_mut().enumerate() {
+            *byte = if (self.remaining - i) % 80 == 1 {
+                b'\n'
+            } else {
+                b'x'
+            };
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// Discards output, remembering the total and the largest single write
+#[derive(Default)]
+struct CountingSink {
+    total: u64,
+    largest_write: usize,
+}
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.total += buf.len() as u64;
+        self.largest_write = self.largest_write.max(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_cat_streams_in_bounded_chunks() {
+    const SIZE: usize = 32 * 1024 * 1024;
+    let mut input = GeneratedInput {
+        remaining: SIZE,
+        largest_read: 0,
+    };
+    let mut sink = CountingSink::default();
+
+    let written = copy_stream(&mut input, &mut sink).unwrap();
+
+    // Nothing near the input size is ever buffered at once
+    assert_eq!(written, SIZE as u64);
+    assert_eq!(sink.total, SIZE as u64);
+    assert!(input.largest_read <= CHUNK_SIZE);
+    assert!(sink.largest_write <= CHUNK_SIZE);
+}
@@ -11,3 +11,51 @@ fn test_echo_output() {
     // If we reach here, the test passes (no panic occurred)
     assert!(true, "Echo command should execute successfully");
 }
+
+#[test]
+fn test_echo_to_writer() {
+    let mut sink = Vec::new();
+    let args = vec!["Hello,".to_string(), "Rust!".to_string()];
+
+    echo::echo_to(&mut sink, &args).unwrap();
+    echo::echo_to(&mut sink, &[]).unwrap();
+
+    assert_eq!(String::from_utf8(sink).unwrap(), "Hello, Rust!\n\n");
+}
+
+#[test]
+fn test_repeat() {
+    assert_eq!(echo::repeat("hello", 3, " "), "hello hello hello");
+    assert_eq!(
+        echo::repeat("hello", 5, ","),
+        "hello,hello,hello,hello,hello"
+    );
+    assert_eq!(echo::repeat("x", 1, ","), "x");
+    assert_eq!(echo::repeat("hello", 0, ","), "");
+}
+
+#[test]
+fn test_echo_repeat_options() {
+    let echo = |args: &[&str]| {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut sink = Vec::new();
+        echo::echo_with_options(&mut sink, &args).map(|_| String::from_utf8(sink).unwrap())
+    };
+
+    assert_eq!(
+        echo(&["--repeat", "5", "--sep=,", "hello"]).unwrap(),
+        "hello,hello,hello,hello,hello\n"
+    );
+    assert_eq!(echo(&["--repeat=2", "a", "b"]).unwrap(), "a b a b\n");
+    assert_eq!(
+        echo(&["--sep", "-", "--repeat", "3", "ab"]).unwrap(),
+        "ab-ab-ab\n"
+    );
+    assert_eq!(echo(&["--repeat", "0", "hello"]).unwrap(), "");
+    assert_eq!(
+        echo(&["plain", "--repeat", "2"]).unwrap(),
+        "plain --repeat 2\n"
+    );
+    assert!(echo(&["--repeat", "many", "x"]).is_err());
+    assert!(echo(&["--repeat"]).is_err());
+}
@@ -447,6 +447,24 @@ mod tests {
         let _ = child.kill();
     }
 
+    #[test]
+    fn test_timeout_escalates_when_term_is_ignored() {
+        // A windowless console process has nothing to receive a graceful close
+        let mut child = create_test_process();
+        let pid = child.id();
+        thread::sleep(Duration::from_millis(100));
+
+        let start = std::time::Instant::now();
+        let result = winix::kill::execute(&["-TERM", "--timeout", "500", "KILL", &pid.to_string()]);
+        assert!(result.is_ok(), "Escalating kill should succeed");
+
+        // TERM alone must not have ended it; KILL only comes after the timeout
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        let status = child.wait().expect("Failed to wait for child");
+        assert!(!status.success());
+        assert!(!is_process_running(pid));
+    }
+
     #[test]
     fn test_invalid_a_flag_with_pid() {
         // Test that -a flag fails when used with numeric PID
@@ -574,6 +592,34 @@ mod tests {
         let _ = child.kill();
     }
 
+    #[test]
+    fn test_kill_port_terminates_listener() {
+        use std::io::{BufRead, BufReader};
+
+        // PowerShell prints the ephemeral port once it is listening
+        let mut child = Command::new("powershell")
+            .args([
+                "-Command",
+                "$l = [System.Net.Sockets.TcpListener]::new([System.Net.IPAddress]::Loopback, 0); \
+                 $l.Start(); $l.LocalEndpoint.Port; Start-Sleep 30",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start test process");
+        let mut port = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut port)
+            .unwrap();
+        let port = port.trim();
+
+        assert_eq!(winix::kill::dry_run(&["--port", port]), Ok(1));
+        winix::kill::execute(&["--port", port]).expect("kill --port failed");
+        child.wait().unwrap();
+        assert!(!is_process_running(child.id()));
+        assert!(winix::kill::execute(&["--port", port]).is_err());
+    }
+
     // Helper function to check if a process is running
     fn is_process_running(pid: u32) -> bool {
         unsafe {
@@ -594,3 +640,82 @@ mod tests {
         }
     }
 }
+
+#[cfg(unix)]
+mod unix_tests {
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::{Child, Command};
+
+    fn sleeper(group: i32) -> Child {
+        Command::new("sleep")
+            .arg("30")
+            .process_group(group)
+            .spawn()
+            .expect("Failed to start test process")
+    }
+
+    #[test]
+    fn test_signal_group_terminates_all_members() {
+        let leader = sleeper(0);
+        let pgid = leader.id() as i32;
+        let mut members = vec![sleeper(pgid), sleeper(pgid)];
+
+        let group = format!("-{}", pgid);
+        winix::kill::execute(&["-TERM", "--", &group]).expect("kill failed");
+
+        members.insert(0, leader);
+        for child in &mut members {
+            let status = child.wait().unwrap();
+            assert_eq!(status.signal(), Some(libc::SIGTERM));
+        }
+    }
+
+    #[test]
+    fn test_kill_port_terminates_listener() {
+        use std::os::fd::AsRawFd;
+
+        // Hand a listening socket to a child, then close ours so only the child owns the port
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port().to_string();
+        let fd = listener.as_raw_fd();
+        let mut command = Command::new("sleep");
+        command.arg("30");
+        unsafe {
+            command.pre_exec(move || {
+                let flags = libc::fcntl(fd, libc::F_GETFD);
+                libc::fcntl(fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC);
+                Ok(())
+            });
+        }
+        let mut child = command.spawn().expect("Failed to start test process");
+        drop(listener);
+
+        assert_eq!(winix::kill::dry_run(&["--port", &port]), Ok(1));
+        winix::kill::execute(&["--port", &port]).expect("kill --port failed");
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+
+        assert!(winix::kill::execute(&["--port", &port]).is_err());
+        assert!(winix::kill::execute(&["--port", "0"]).is_err());
+        assert!(winix::kill::execute(&["--port=http"]).is_err());
+    }
+
+    #[test]
+    fn test_dry_run_leaves_process_running() {
+        let mut child = sleeper(0);
+        let pid = child.id().to_string();
+
+        assert_eq!(winix::kill::dry_run(&["-9", &pid]), Ok(1));
+        winix::kill::execute(&["-n", "-KILL", &pid]).expect("dry run failed");
+        let group = format!("-{}", pid);
+        assert_eq!(winix::kill::dry_run(&["--", &group]), Ok(1));
+        assert!(winix::kill::dry_run(&["-n", "-p", &pid]).is_err());
+
+        assert!(
+            child.try_wait().unwrap().is_none(),
+            "dry run signaled the process"
+        );
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -20,3 +20,18 @@ fn test_touch_creates_file() {
     // Cleanup after
     fs::remove_file(filename).unwrap();
 }
+
+#[test]
+fn test_touch_parents_creates_directories() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("a").join("b").join("c.txt");
+    let target_name = target.to_string_lossy().to_string();
+
+    // Without -p the missing parent is an error and nothing is created
+    touch::run(std::slice::from_ref(&target_name));
+    assert!(!dir.path().join("a").exists());
+
+    touch::run(&["-p".to_string(), target_name]);
+    assert!(dir.path().join("a").join("b").is_dir());
+    assert!(target.is_file());
+}